@@ -16,6 +16,7 @@ fn main() {
             ("PanId<[u8; 2]>", "PanId::new_owned([0xed, 0xfe])"),
         ),
         ("MAC_IMPLICIT_BROADCAST", ("bool", "false")),
+        ("MAC_MAX_PENDING_TRANSACTIONS", ("usize", "2")),
     ]);
 
     // Make sure we get rerun if needed
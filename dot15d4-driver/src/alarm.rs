@@ -0,0 +1,260 @@
+//! Sharing of the radio timer alarm.
+
+use core::{
+    array,
+    cell::{Cell, RefCell},
+    future::poll_fn,
+    marker::PhantomData,
+    task::{Poll, Waker},
+};
+
+use dot15d4_util::sync::{select, CancellationGuard};
+
+use crate::{time::Instant, RadioTimerApi};
+
+/// Multiplexes the single alarm of the radio timer between a fixed number of
+/// clients, e.g. the MAC service and the driver service.
+///
+/// The radio timer has a single alarm and wakes a single waiter. The
+/// multiplexer is therefore its only user: [`AlarmMultiplexer::run()`] keeps
+/// the alarm programmed to the earliest deadline requested by any client and
+/// re-arms it as soon as an earlier deadline is requested. Clients wait for
+/// their deadlines through the [`Alarm`] handles returned by
+/// [`AlarmMultiplexer::alarms()`].
+pub struct AlarmMultiplexer<Timer: RadioTimerApi, const NUM_CLIENTS: usize> {
+    /// The deadline (in radio timer ticks) each client waits for, if any.
+    deadlines: [Cell<Option<u64>>; NUM_CLIENTS],
+    /// The wakers of the waiting clients.
+    client_wakers: [RefCell<Option<Waker>>; NUM_CLIENTS],
+    /// The waker of [`AlarmMultiplexer::run()`].
+    runner_waker: RefCell<Option<Waker>>,
+    timer: PhantomData<Timer>,
+}
+
+impl<Timer: RadioTimerApi, const NUM_CLIENTS: usize> AlarmMultiplexer<Timer, NUM_CLIENTS> {
+    pub fn new() -> Self {
+        Self {
+            deadlines: [const { Cell::new(None) }; NUM_CLIENTS],
+            client_wakers: [const { RefCell::new(None) }; NUM_CLIENTS],
+            runner_waker: RefCell::new(None),
+            timer: PhantomData,
+        }
+    }
+
+    /// Returns one alarm handle per client. Each client may only wait for a
+    /// single deadline at a time.
+    pub fn alarms(&self) -> [Alarm<'_, Timer>; NUM_CLIENTS] {
+        array::from_fn(|client| Alarm {
+            deadline: &self.deadlines[client],
+            waker: &self.client_wakers[client],
+            runner_waker: &self.runner_waker,
+            timer: PhantomData,
+        })
+    }
+
+    /// The earliest deadline after the given tick. Earlier deadlines have
+    /// passed and are only waiting to be withdrawn by their clients.
+    fn next_deadline_after(&self, now: u64) -> Option<u64> {
+        self.deadlines
+            .iter()
+            .filter_map(|deadline| deadline.get())
+            .filter(|deadline| *deadline > now)
+            .min()
+    }
+
+    fn register_runner(&self, waker: &Waker) {
+        let mut runner_waker = self.runner_waker.borrow_mut();
+        if !runner_waker
+            .as_ref()
+            .is_some_and(|runner_waker| runner_waker.will_wake(waker))
+        {
+            *runner_waker = Some(waker.clone());
+        }
+    }
+
+    fn wake_expired_clients(&self, now: u64) {
+        for (deadline, waker) in self.deadlines.iter().zip(&self.client_wakers) {
+            if deadline.get().is_some_and(|deadline| deadline <= now) {
+                if let Some(waker) = waker.borrow().as_ref() {
+                    waker.wake_by_ref();
+                }
+            }
+        }
+    }
+
+    /// Programs the radio timer alarm and wakes clients once their deadlines
+    /// have passed. Must be polled for as long as clients wait for alarms.
+    pub async fn run(&self) -> ! {
+        loop {
+            let armed_deadline = poll_fn(|cx| {
+                self.register_runner(cx.waker());
+                let now = Timer::now().tick();
+                self.wake_expired_clients(now);
+                match self.next_deadline_after(now) {
+                    Some(next_deadline) => Poll::Ready(next_deadline),
+                    None => Poll::Pending,
+                }
+            })
+            .await;
+            Timer::schedule_alarm(Instant::new(armed_deadline));
+
+            let earlier_deadline = poll_fn(|cx| {
+                self.register_runner(cx.waker());
+                if self
+                    .next_deadline_after(Timer::now().tick())
+                    .is_some_and(|next_deadline| next_deadline < armed_deadline)
+                {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            });
+
+            // Expired clients are woken and an earlier deadline re-arms the
+            // alarm on the next iteration.
+            select(Timer::wait_for_alarm(), earlier_deadline).await;
+        }
+    }
+}
+
+impl<Timer: RadioTimerApi, const NUM_CLIENTS: usize> Default
+    for AlarmMultiplexer<Timer, NUM_CLIENTS>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A client's handle to the alarm of the radio timer, see
+/// [`AlarmMultiplexer`].
+pub struct Alarm<'alarms, Timer: RadioTimerApi> {
+    deadline: &'alarms Cell<Option<u64>>,
+    waker: &'alarms RefCell<Option<Waker>>,
+    runner_waker: &'alarms RefCell<Option<Waker>>,
+    timer: PhantomData<Timer>,
+}
+
+impl<Timer: RadioTimerApi> Alarm<'_, Timer> {
+    /// Waits until the given instant has passed.
+    ///
+    /// Cancellable: The deadline is withdrawn when the future is dropped.
+    pub async fn wait_until(&self, at: Instant<Timer>) {
+        let at = at.tick();
+        let cleanup_on_drop = CancellationGuard::new(|| self.withdraw());
+
+        poll_fn(|cx| {
+            if Timer::now().tick() >= at {
+                return Poll::Ready(());
+            }
+
+            *self.waker.borrow_mut() = Some(cx.waker().clone());
+            if self.deadline.replace(Some(at)) != Some(at) {
+                if let Some(runner_waker) = self.runner_waker.borrow().as_ref() {
+                    runner_waker.wake_by_ref();
+                }
+            }
+            Poll::Pending
+        })
+        .await;
+
+        cleanup_on_drop.inactivate();
+        self.withdraw();
+    }
+
+    fn withdraw(&self) {
+        self.deadline.set(None);
+        self.waker.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::{future::Future, pin::pin, task::Context};
+
+    use super::*;
+    use crate::time::Frequency;
+
+    std::thread_local! {
+        static NOW: Cell<u64> = const { Cell::new(0) };
+        static ALARM: Cell<Option<u64>> = const { Cell::new(None) };
+    }
+
+    /// A radio timer that only advances when told to.
+    struct FakeTimer;
+
+    impl Frequency for FakeTimer {
+        const FREQUENCY: u32 = 1_000_000;
+    }
+
+    impl RadioTimerApi for FakeTimer {
+        fn now() -> Instant<Self> {
+            Instant::new(NOW.get())
+        }
+
+        fn schedule_alarm(at: Instant<Self>) {
+            ALARM.set(Some(at.tick()));
+        }
+
+        async fn wait_for_alarm() -> Instant<Self> {
+            poll_fn(|_| match ALARM.get() {
+                Some(alarm) if alarm <= NOW.get() => {
+                    ALARM.set(None);
+                    Poll::Ready(Instant::new(alarm))
+                }
+                _ => Poll::Pending,
+            })
+            .await
+        }
+    }
+
+    fn poll<F: Future>(future: core::pin::Pin<&mut F>) -> Poll<F::Output> {
+        future.poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn earliest_deadline_is_armed() {
+        let multiplexer = AlarmMultiplexer::<FakeTimer, 2>::new();
+        let [first, second] = multiplexer.alarms();
+        let mut runner = pin!(multiplexer.run());
+        let mut late = pin!(first.wait_until(Instant::new(1000)));
+        let mut early = pin!(second.wait_until(Instant::new(100)));
+
+        assert!(poll(late.as_mut()).is_pending());
+        assert!(poll(runner.as_mut()).is_pending());
+        assert_eq!(ALARM.get(), Some(1000));
+
+        // An earlier deadline re-arms the alarm.
+        assert!(poll(early.as_mut()).is_pending());
+        assert!(poll(runner.as_mut()).is_pending());
+        assert_eq!(ALARM.get(), Some(100));
+
+        NOW.set(100);
+        assert!(poll(runner.as_mut()).is_pending());
+        assert_eq!(ALARM.get(), Some(1000));
+        assert!(poll(early.as_mut()).is_ready());
+        assert!(poll(late.as_mut()).is_pending());
+
+        NOW.set(1000);
+        assert!(poll(runner.as_mut()).is_pending());
+        assert!(poll(late.as_mut()).is_ready());
+        assert_eq!(multiplexer.next_deadline_after(0), None);
+    }
+
+    #[test]
+    fn cancelled_deadline_is_withdrawn() {
+        let multiplexer = AlarmMultiplexer::<FakeTimer, 1>::new();
+        let [alarm] = multiplexer.alarms();
+        {
+            let mut wait = pin!(alarm.wait_until(Instant::new(500)));
+            assert!(poll(wait.as_mut()).is_pending());
+            assert_eq!(multiplexer.next_deadline_after(0), Some(500));
+        }
+        assert_eq!(multiplexer.next_deadline_after(0), None);
+
+        // Deadlines that have already passed don't involve the alarm.
+        let mut wait = pin!(alarm.wait_until(Instant::new(0)));
+        assert!(poll(wait.as_mut()).is_ready());
+    }
+}
@@ -14,6 +14,7 @@ mod customizable {
     pub const MAC_MAX_FRAME_RETRIES: u8 = 3; // 0-7
     pub const MAC_PAN_ID: PanId<[u8; 2]> = PanId::new_owned([0xff, 0xff]); // PAN Id
     pub const MAC_IMPLICIT_BROADCAST: bool = false;
+    pub const MAC_MAX_PENDING_TRANSACTIONS: usize = 2;
 }
 
 #[cfg(not(test))]
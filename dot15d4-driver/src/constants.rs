@@ -44,6 +44,20 @@ pub const PHY_MAX_PACKET_SIZE_127: usize = 127; // all other PHYs
 pub const A_TURNAROUND_TIME: Duration<SymbolsOQpsk250kB> = Duration::new(12);
 /// The time required to perform CCA detection in symbol periods.
 pub const PHY_CCA_DURATION: Duration<SymbolsOQpsk250kB> = Duration::new(8);
+/// The duration of a single energy detection measurement in symbol periods,
+/// see IEEE 802.15.4-2024, section 10.2.5.
+pub const PHY_ED_DURATION: Duration<SymbolsOQpsk250kB> = Duration::new(8);
+/// The maximum number of symbols in a frame (phyMaxFrameDuration), i.e. the
+/// synchronization header (phySHRDuration) and the PHY header plus the max PSDU
+/// at two symbols per octet, see IEEE 802.15.4-2024, section 11.3.
+pub const PHY_MAX_FRAME_DURATION: Duration<SymbolsOQpsk250kB> =
+    Duration::new(10 + 2 * (1 + PHY_MAX_PACKET_SIZE_127 as i64));
+/// The duration of the synchronization header (phySHRDuration), i.e. 8 symbols
+/// preamble and 2 symbols SFD, see IEEE 802.15.4-2024, section 11.3.
+pub const PHY_SHR_DURATION: Duration<SymbolsOQpsk250kB> = Duration::new(10);
+/// The duration of the PHY header, i.e. a single octet at two symbols per
+/// octet.
+pub const PHY_HDR_DURATION: Duration<SymbolsOQpsk250kB> = Duration::new(2);
 /// The number of symbols forming the basic time period used by the CSMA-CA
 /// algorithm.
 pub const MAC_UNIT_BACKOFF_PERIOD: Duration<SymbolsOQpsk250kB> =
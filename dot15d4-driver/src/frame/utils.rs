@@ -1,18 +1,67 @@
-use crate::{
-    const_config::{MAC_IMPLICIT_BROADCAST, MAC_PAN_ID},
-    tasks::PreliminaryFrameInfo,
+use crate::tasks::PreliminaryFrameInfo;
+
+use super::{
+    Address, AddressingFields, FrameControl, FrameType, PanId, ShortAddress, BROADCAST_PAN_ID,
 };
 
-use super::{Address, ShortAddress, BROADCAST_PAN_ID};
+/// The MAC PIB attributes and MAC state required to filter incoming frames,
+/// see IEEE 802.15.4-2020, section 6.7.2.
+///
+/// The MAC service owns the PIB and keeps the frame filter of the driver
+/// service in sync whenever the PIB changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameFilter {
+    /// macPanId
+    pub pan_id: PanId<[u8; 2]>,
+    /// macShortAddress
+    pub short_address: ShortAddress<[u8; 2]>,
+    /// macExtendedAddress as a big-endian 64-bit address. If not set, the
+    /// hardware address of the radio will be used.
+    pub extended_address: Option<[u8; 8]>,
+    /// macImplicitBroadcast
+    pub implicit_broadcast: bool,
+    /// macPromiscuousMode
+    pub promiscuous_mode: bool,
+    /// Whether frames with an invalid FCS are to be received, too. Only
+    /// relevant in promiscuous mode.
+    pub accept_crc_errors: bool,
+    /// Whether the device has been started as PAN coordinator.
+    pub pan_coordinator: bool,
+}
+
+/// The filter of a device that has not joined a PAN. The MAC service replaces
+/// it with the filter derived from its PIB on startup.
+impl Default for FrameFilter {
+    fn default() -> Self {
+        Self {
+            pan_id: PanId::new_owned([0xff, 0xff]),
+            short_address: ShortAddress::new_owned([0xff, 0xff]),
+            extended_address: None,
+            implicit_broadcast: false,
+            promiscuous_mode: false,
+            accept_crc_errors: false,
+            pan_coordinator: false,
+        }
+    }
+}
 
 /// Checks if the given MPDU is valid and intended for us. For the hardware
 /// address, the full big-endian 64-bit address should be provided.
 ///
-/// TODO: Implement the full incoming frame procedure here.
+/// Implements the third-level filtering procedure based on the frame control
+/// field and the addressing fields, including the source PAN ID, so that
+/// frames are filtered before they are acknowledged.
+///
+/// In promiscuous mode all frames are accepted.
 pub fn is_frame_valid_and_for_us(
     hardware_addr: &[u8; 8],
+    frame_filter: &FrameFilter,
     preliminary_frame_info: &PreliminaryFrameInfo,
 ) -> bool {
+    if frame_filter.promiscuous_mode {
+        return true;
+    }
+
     let PreliminaryFrameInfo {
         frame_control,
         addressing_fields,
@@ -23,6 +72,7 @@ pub fn is_frame_valid_and_for_us(
         return false;
     }
 
+    // Check frame type and frame version.
     let frame_control = frame_control.as_ref().unwrap();
     if !frame_control.is_valid() {
         return false;
@@ -31,35 +81,255 @@ pub fn is_frame_valid_and_for_us(
     let addressing_fields = addressing_fields.as_ref().unwrap();
 
     // Check destination PAN id.
-    let dst_pan_id = addressing_fields.dst_pan_id().unwrap_or(BROADCAST_PAN_ID);
-    if *dst_pan_id.as_ref() != *MAC_PAN_ID.as_ref() && dst_pan_id != BROADCAST_PAN_ID {
-        return false;
+    let dst_pan_id = addressing_fields.dst_pan_id();
+    if let Some(dst_pan_id) = dst_pan_id {
+        if *dst_pan_id.as_ref() != *frame_filter.pan_id.as_ref() && dst_pan_id != BROADCAST_PAN_ID {
+            return false;
+        }
     }
 
     // Check destination address.
-    let dst_addr = addressing_fields.dst_address();
-    match dst_addr {
-        Some(dst_addr) => {
-            if dst_addr == Address::<&[u8]>::BROADCAST_ADDR {
-                return true;
-            }
-
-            match dst_addr {
-                Address::Absent if MAC_IMPLICIT_BROADCAST => true,
-                Address::Short(addr) => {
-                    // Convert a little-endian short address from the big-endian
-                    // hardware address.
-                    // TODO: This is not a valid method to generate short addresses.
-                    let mut derived_short_address =
-                        <[u8; 2]>::try_from(&hardware_addr[6..]).unwrap();
-                    derived_short_address.reverse();
-                    let derived_short_addr = ShortAddress::new_owned(derived_short_address);
-                    *derived_short_addr.as_ref() == *addr.as_ref()
+    let dst_address_matches = match addressing_fields.dst_address() {
+        Some(Address::Absent) if frame_filter.implicit_broadcast && dst_pan_id.is_none() => true,
+        Some(Address::Absent) => {
+            match frame_control.frame_type() {
+                // Beacons are subject to the source PAN ID check.
+                FrameType::Beacon => true,
+                // Frames with source addressing fields only are accepted by
+                // the PAN coordinator, subject to the source PAN ID check.
+                FrameType::Data | FrameType::MacCommand => {
+                    frame_filter.pan_coordinator && addressing_fields.src_address().is_some()
                 }
-                Address::Extended(addr) => *hardware_addr == addr.into_be_bytes(),
                 _ => false,
             }
         }
+        Some(dst_addr) if dst_addr == Address::<&[u8]>::BROADCAST_ADDR => true,
+        Some(Address::Short(addr)) => {
+            // 0xfffe and 0xffff indicate that no short address was allocated.
+            frame_filter.short_address.into_u16() < 0xfffe
+                && *frame_filter.short_address.as_ref() == *addr.as_ref()
+        }
+        Some(Address::Extended(addr)) => {
+            *frame_filter
+                .extended_address
+                .as_ref()
+                .unwrap_or(hardware_addr)
+                == addr.into_be_bytes()
+        }
         _ => false,
+    };
+
+    dst_address_matches && is_frame_source_valid(frame_filter, frame_control, addressing_fields)
+}
+
+/// Checks the source PAN ID of a frame whose destination passed
+/// [`is_frame_valid_and_for_us`].
+///
+/// - Beacons are accepted if their source PAN ID matches macPanId or if
+///   macPanId is the broadcast PAN ID.
+/// - Data and MAC command frames with source addressing fields only are
+///   accepted if the source PAN ID matches macPanId.
+///
+/// In promiscuous mode all frames are accepted.
+fn is_frame_source_valid<Bytes: AsRef<[u8]>>(
+    frame_filter: &FrameFilter,
+    frame_control: &FrameControl<Bytes>,
+    addressing_fields: &AddressingFields<&[u8]>,
+) -> bool {
+    if frame_filter.promiscuous_mode {
+        return true;
+    }
+
+    // The source PAN ID may have been elided if it is equal to the destination
+    // PAN ID.
+    let src_pan_id = addressing_fields
+        .src_pan_id()
+        .or(addressing_fields.dst_pan_id());
+    let src_pan_id_matches =
+        |src_pan_id: PanId<&[u8]>| *src_pan_id.as_ref() == *frame_filter.pan_id.as_ref();
+
+    match frame_control.frame_type() {
+        FrameType::Beacon => {
+            frame_filter.pan_id.into_u16() == 0xffff || src_pan_id.is_none_or(src_pan_id_matches)
+        }
+        FrameType::Data | FrameType::MacCommand
+            if matches!(addressing_fields.dst_address(), Some(Address::Absent))
+                && !(frame_filter.implicit_broadcast
+                    && addressing_fields.dst_pan_id().is_none()) =>
+        {
+            src_pan_id.is_some_and(src_pan_id_matches)
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::frame::AddressingRepr;
+
+    const HARDWARE_ADDR: [u8; 8] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+
+    /// 2006 data frame, short destination and source addresses, PAN ID
+    /// compression.
+    const DATA_FC_SHORT: [u8; 2] = [0x41, 0x98];
+    /// 2006 data frame, extended destination and source addresses, PAN ID
+    /// compression.
+    const DATA_FC_EXTENDED: [u8; 2] = [0x41, 0xdc];
+    /// 2006 data frame, short source address only.
+    const DATA_FC_SRC_ONLY: [u8; 2] = [0x01, 0x90];
+    /// 2006 beacon frame, short source address.
+    const BEACON_FC: [u8; 2] = [0x00, 0x90];
+
+    fn frame_filter() -> FrameFilter {
+        FrameFilter {
+            pan_id: PanId::new_owned([0xcd, 0xab]),
+            short_address: ShortAddress::new_owned([0x34, 0x12]),
+            ..Default::default()
+        }
+    }
+
+    fn addressing_fields(fc: [u8; 2], addressing: &[u8]) -> AddressingFields<&[u8]> {
+        let addressing_repr = AddressingRepr::from_frame_control(FrameControl::new_unchecked(fc))
+            .unwrap()
+            .unwrap();
+        AddressingFields::new(addressing, addressing_repr).unwrap()
+    }
+
+    fn frame_info(fc: [u8; 2], addressing: &[u8]) -> PreliminaryFrameInfo<'_> {
+        PreliminaryFrameInfo {
+            mpdu_length: 0,
+            frame_control: Some(FrameControl::new_unchecked(fc)),
+            seq_nr: Some(0),
+            addressing_fields: Some(addressing_fields(fc, addressing)),
+        }
+    }
+
+    fn accepts(frame_filter: &FrameFilter, fc: [u8; 2], addressing: &[u8]) -> bool {
+        is_frame_valid_and_for_us(&HARDWARE_ADDR, frame_filter, &frame_info(fc, addressing))
+    }
+
+    #[test]
+    fn short_destination() {
+        let frame_filter = frame_filter();
+
+        assert!(accepts(
+            &frame_filter,
+            DATA_FC_SHORT,
+            &[0xcd, 0xab, 0x34, 0x12, 0x01, 0x00]
+        ));
+        // Broadcast address and broadcast PAN ID.
+        assert!(accepts(
+            &frame_filter,
+            DATA_FC_SHORT,
+            &[0xff, 0xff, 0xff, 0xff, 0x01, 0x00]
+        ));
+        // Wrong PAN ID.
+        assert!(!accepts(
+            &frame_filter,
+            DATA_FC_SHORT,
+            &[0xce, 0xab, 0x34, 0x12, 0x01, 0x00]
+        ));
+        // Wrong short address.
+        assert!(!accepts(
+            &frame_filter,
+            DATA_FC_SHORT,
+            &[0xcd, 0xab, 0x35, 0x12, 0x01, 0x00]
+        ));
+
+        // No short address allocated.
+        let frame_filter = FrameFilter {
+            short_address: ShortAddress::new_owned([0xfe, 0xff]),
+            ..frame_filter
+        };
+        assert!(!accepts(
+            &frame_filter,
+            DATA_FC_SHORT,
+            &[0xcd, 0xab, 0xfe, 0xff, 0x01, 0x00]
+        ));
+    }
+
+    #[test]
+    fn extended_destination() {
+        let mut addressing = [0; 18];
+        addressing[..2].copy_from_slice(&[0xcd, 0xab]);
+        addressing[2..10].copy_from_slice(&[0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00]);
+
+        // Falls back to the hardware address.
+        let frame_filter = frame_filter();
+        assert!(accepts(&frame_filter, DATA_FC_EXTENDED, &addressing));
+
+        let frame_filter = FrameFilter {
+            extended_address: Some([0; 8]),
+            ..frame_filter
+        };
+        assert!(!accepts(&frame_filter, DATA_FC_EXTENDED, &addressing));
+    }
+
+    #[test]
+    fn beacon() {
+        let frame_filter = frame_filter();
+        assert!(accepts(&frame_filter, BEACON_FC, &[0xcd, 0xab, 0x01, 0x00]));
+        assert!(!accepts(
+            &frame_filter,
+            BEACON_FC,
+            &[0xce, 0xab, 0x01, 0x00]
+        ));
+
+        // Unassociated devices accept beacons from all PANs.
+        let frame_filter = FrameFilter {
+            pan_id: PanId::new_owned([0xff, 0xff]),
+            ..frame_filter
+        };
+        assert!(accepts(&frame_filter, BEACON_FC, &[0xce, 0xab, 0x01, 0x00]));
+    }
+
+    #[test]
+    fn source_addressing_only() {
+        let frame_filter = frame_filter();
+        assert!(!accepts(
+            &frame_filter,
+            DATA_FC_SRC_ONLY,
+            &[0xcd, 0xab, 0x01, 0x00]
+        ));
+
+        let frame_filter = FrameFilter {
+            pan_coordinator: true,
+            ..frame_filter
+        };
+        assert!(accepts(
+            &frame_filter,
+            DATA_FC_SRC_ONLY,
+            &[0xcd, 0xab, 0x01, 0x00]
+        ));
+        assert!(!accepts(
+            &frame_filter,
+            DATA_FC_SRC_ONLY,
+            &[0xce, 0xab, 0x01, 0x00]
+        ));
+    }
+
+    #[test]
+    fn promiscuous_mode() {
+        let frame_filter = FrameFilter {
+            promiscuous_mode: true,
+            ..frame_filter()
+        };
+        assert!(accepts(
+            &frame_filter,
+            DATA_FC_SHORT,
+            &[0xce, 0xab, 0x35, 0x12, 0x01, 0x00]
+        ));
+        assert!(is_frame_valid_and_for_us(
+            &HARDWARE_ADDR,
+            &frame_filter,
+            &PreliminaryFrameInfo {
+                mpdu_length: 0,
+                frame_control: None,
+                seq_nr: None,
+                addressing_fields: None,
+            }
+        ));
     }
 }
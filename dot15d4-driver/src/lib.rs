@@ -6,12 +6,13 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use core::{fmt::Debug, future::Future};
+use core::{fmt::Debug, future::Future, ops::RangeInclusive};
 
 use generic_array::ArrayLength;
 
 use crate::time::{Frequency, Instant};
 
+pub mod alarm;
 pub mod config;
 pub mod const_config;
 pub mod constants;
@@ -73,6 +74,16 @@ pub trait DriverConfig {
 
     /// The radio timer implementation.
     type Timer: RadioTimerApi;
+
+    /// The TX power levels in dBm supported by the radio, see
+    /// [`tasks::OffState::set_tx_power()`].
+    const TX_POWER_RANGE: RangeInclusive<i8>;
+
+    /// Whether RX timestamps are accurate enough to synchronize TSCH time,
+    /// i.e. their error stays well within the minimum guard time of the MAC.
+    /// If not, then the MAC neither synchronizes to the timestamps of received
+    /// frames nor reports time corrections in Enh-Acks.
+    const ACCURATE_RX_TIMESTAMPS: bool;
 }
 
 /// Basic features to be implemented by all radio drivers, independent of driver
@@ -84,6 +95,11 @@ pub trait RadioDriverApi {
 pub trait RadioTimerApi: Frequency + Sized {
     fn now() -> Instant<Self>;
 
+    /// Programs the alarm. Replaces an alarm that was programmed earlier and
+    /// has not yet been waited for.
+    ///
+    /// The timer has a single alarm, see [`alarm::AlarmMultiplexer`] to share
+    /// it.
     fn schedule_alarm(at: Instant<Self>);
 
     /// Waits for the alarm programmed by [`RadioTimerApi::schedule_alarm()`].
@@ -120,6 +136,10 @@ pub(crate) mod trace {
 
     pub const TASK_FALL_BACK: u32 = 209;
 
+    pub const TASK_ED_SCHEDULE: u32 = 210;
+    pub const TASK_TRANSITION_TO_ED: u32 = 211;
+    pub const TASK_ED_RUN: u32 = 212;
+
     // Markers
     pub const MISSED_ISR: u32 = 200;
     pub const TASK_RX_FRAME_STARTED: u32 = 201;
@@ -137,6 +157,9 @@ pub(crate) mod trace {
         rtos_trace::trace::task_new_stackless(TASK_TRANSITION_TO_TX, "Transition to TX\0", 0);
         rtos_trace::trace::task_new_stackless(TASK_TX_RUN, "Tx\0", 0);
         rtos_trace::trace::task_new_stackless(TASK_FALL_BACK, "Off (fallback)\0", 0);
+        rtos_trace::trace::task_new_stackless(TASK_ED_SCHEDULE, "Schedule ED\0", 0);
+        rtos_trace::trace::task_new_stackless(TASK_TRANSITION_TO_ED, "Transition to ED\0", 0);
+        rtos_trace::trace::task_new_stackless(TASK_ED_RUN, "ED\0", 0);
         rtos_trace::trace::name_marker(MISSED_ISR, "Missed ISR\0");
         rtos_trace::trace::name_marker(TASK_RX_FRAME_STARTED, "Frame Started\0");
         rtos_trace::trace::name_marker(TASK_RX_FRAME_INFO, "Preliminary Frame Info\0");
@@ -6,10 +6,10 @@
 //      enabling the interrupt.
 
 use core::{
-    cell::{RefCell, RefMut},
+    cell::{Cell, RefCell, RefMut},
     num::NonZero,
-    ops::Deref,
-    sync::atomic::{compiler_fence, Ordering},
+    ops::{Deref, RangeInclusive},
+    sync::atomic::{compiler_fence, AtomicU8, Ordering},
     task::{Context, Poll, Waker},
 };
 
@@ -29,23 +29,26 @@ use typenum::U;
 
 #[cfg(feature = "rtos-trace")]
 use crate::trace::{
-    MISSED_ISR, TASK_FALL_BACK, TASK_OFF_RUN, TASK_OFF_SCHEDULE, TASK_RX_FRAME_INFO,
-    TASK_RX_FRAME_STARTED, TASK_RX_RUN, TASK_RX_SCHEDULE, TASK_TRANSITION_TO_OFF,
-    TASK_TRANSITION_TO_RX, TASK_TRANSITION_TO_TX, TASK_TX_RUN, TASK_TX_SCHEDULE,
+    MISSED_ISR, TASK_ED_RUN, TASK_ED_SCHEDULE, TASK_FALL_BACK, TASK_OFF_RUN, TASK_OFF_SCHEDULE,
+    TASK_RX_FRAME_INFO, TASK_RX_FRAME_STARTED, TASK_RX_RUN, TASK_RX_SCHEDULE,
+    TASK_TRANSITION_TO_ED, TASK_TRANSITION_TO_OFF, TASK_TRANSITION_TO_RX, TASK_TRANSITION_TO_TX,
+    TASK_TX_RUN, TASK_TX_SCHEDULE,
 };
 use crate::{
     config::{CcaMode, Channel},
     constants::{
-        DEFAULT_SFD, FCS_LEN, MAC_AIFS, MAC_LIFS, MAC_SIFS, PHY_HDR_LEN, PHY_MAX_PACKET_SIZE_127,
+        DEFAULT_SFD, FCS_LEN, MAC_AIFS, MAC_LIFS, MAC_SIFS, PHY_ED_DURATION, PHY_HDR_LEN,
+        PHY_MAX_PACKET_SIZE_127,
     },
-    frame::{AddressingFields, RadioFrame, RadioFrameSized},
+    frame::{AddressingFields, RadioFrame, RadioFrameSized, RadioFrameUnsized},
     tasks::{
-        ExternalRadioTransition, Ifs, OffResult, OffState, PreliminaryFrameInfo, RadioDriver,
-        RadioState, RadioTaskError, RadioTransition, RxError, RxResult, RxState, SchedulingError,
-        SelfRadioTransition, TaskOff, TaskRx, TaskTx, Timestamp, TxError, TxResult, TxState,
+        EdResult, EdState, ExternalRadioTransition, Ifs, OffResult, OffState, PreliminaryFrameInfo,
+        RadioDriver, RadioState, RadioTaskError, RadioTransition, RxError, RxInfo, RxResult,
+        RxState, SchedulingError, SelfRadioTransition, TaskEd, TaskOff, TaskRx, TaskTx, Timestamp,
+        TxError, TxInfo, TxResult, TxState,
     },
-    time::{Duration, Microseconds},
-    DriverConfig, FcsNone, RadioDriverApi,
+    time::{Duration, Instant, Microseconds, SymbolsOQpsk250kB},
+    DriverConfig, FcsNone, RadioDriverApi, RadioTimerApi,
 };
 
 use super::NrfRadioTimer;
@@ -72,6 +75,33 @@ impl RadioInterruptHandler {
         WAKER.borrow_ref_mut(cs)
     }
 
+    /// Private function not to be called from outside this struct.
+    ///
+    /// The radio timer tick at which the interrupt handler observed the END
+    /// event.
+    fn end_timestamp(cs: CriticalSection) -> &Cell<Option<u64>> {
+        static END_TIMESTAMP: Mutex<Cell<Option<u64>>> = Mutex::new(Cell::new(None));
+        END_TIMESTAMP.borrow(cs)
+    }
+
+    /// Resets the END event if it was raised.
+    ///
+    /// Returns `None` if the END event was not raised. Otherwise returns the
+    /// time at which the interrupt handler observed the END event, if the END
+    /// interrupt was armed when the event was raised.
+    fn take_end_event() -> Option<Option<Instant<NrfRadioTimer>>> {
+        // The critical section keeps the interrupt handler from timestamping
+        // an END event that is being reset.
+        with_cs(|cs| {
+            let r = NrfRadioDriver::radio();
+            if r.events_end.read().events_end().bit_is_clear() {
+                return None;
+            }
+            r.events_end.reset();
+            Some(Self::end_timestamp(cs).take().map(Instant::new))
+        })
+    }
+
     /// Checks or sets the waker in the given context to be woken when the radio
     /// interrupt fires.
     fn arm<F: FnOnce(&mut <INTENSET_SPEC as Writable>::Writer) -> &mut W<INTENSET_SPEC>>(
@@ -93,6 +123,14 @@ impl RadioInterruptHandler {
     /// To be called from a radio interrupt.
     fn radio_interrupt() {
         with_cs(|cs| {
+            // Timestamp the END event as early as possible, so that the
+            // timestamp does not include the latency of the executor.
+            let r = NrfRadioDriver::radio();
+            if r.intenset.read().end().bit_is_set() && r.events_end.read().events_end().bit_is_set()
+            {
+                Self::end_timestamp(cs).set(Some(NrfRadioTimer::now().tick()));
+            }
+
             let waker = Self::waker(cs).take();
             if let Some(waker) = waker {
                 waker.wake();
@@ -133,8 +171,24 @@ impl DriverConfig for NrfRadioDriver {
     type MaxSduLength = U<{ PHY_MAX_PACKET_SIZE_127 - FCS_LEN }>; // The FCS is handled by the driver and must not be part of the MAC's MPDU.
     type Fcs = FcsNone; // Assuming automatic FCS handling.
     type Timer = NrfRadioTimer;
+    const TX_POWER_RANGE: RangeInclusive<i8> =
+        TX_POWER_LEVELS[TX_POWER_LEVELS.len() - 1]..=TX_POWER_LEVELS[0];
+    // The interrupt latency and RTC resolution of software timestamps stay
+    // within the guard time, see `rmarker_from_end()`.
+    const ACCURATE_RX_TIMESTAMPS: bool = true;
 }
 
+/// The channel used by RX and TX tasks that don't specify their own channel.
+static DEFAULT_CHANNEL: AtomicU8 = AtomicU8::new(11);
+
+/// The TX power levels supported by the radio in dBm, in descending order.
+#[cfg(not(any(feature = "nrf52811", feature = "nrf5340-net")))]
+const TX_POWER_LEVELS: [i8; 14] = [8, 7, 6, 5, 4, 3, 2, 0, -4, -8, -12, -16, -20, -40];
+#[cfg(feature = "nrf52811")]
+const TX_POWER_LEVELS: [i8; 9] = [4, 3, 0, -4, -8, -12, -16, -20, -40];
+#[cfg(feature = "nrf5340-net")]
+const TX_POWER_LEVELS: [i8; 13] = [0, -1, -2, -3, -4, -5, -6, -7, -8, -12, -16, -20, -40];
+
 impl NrfRadioDriver {
     fn radio() -> pac::RADIO {
         // Safety: We let clients prove unique ownership of the peripheral by
@@ -142,6 +196,20 @@ impl NrfRadioDriver {
         // TODO: Check whether this results in efficient assembly.
         unsafe { pac::Peripherals::steal() }.RADIO
     }
+
+    /// Tunes the radio to the given channel or to the default channel.
+    ///
+    /// Must only be called while the radio is disabled.
+    fn set_frequency(channel: Option<Channel>) {
+        let channel: u8 = match channel {
+            Some(channel) => channel.into(),
+            None => DEFAULT_CHANNEL.load(Ordering::Relaxed),
+        };
+        let frequency_offset = (channel - 10) * 5;
+        Self::radio()
+            .frequency
+            .write(|w| w.frequency().variant(frequency_offset).map().default());
+    }
 }
 
 impl<Task> RadioDriver<NrfRadioDriver, Task> {
@@ -387,11 +455,19 @@ impl RadioState<TaskOff> for RadioDriver<NrfRadioDriver, TaskOff> {
 impl OffState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskOff> {
     /// Changes the default radio channel
     fn set_channel(&mut self, channel: Channel) {
-        let channel: u8 = channel.into();
-        let frequency_offset = (channel - 10) * 5;
-        Self::radio()
-            .frequency
-            .write(|w| w.frequency().variant(frequency_offset).map().default());
+        DEFAULT_CHANNEL.store(channel.into(), Ordering::Relaxed);
+        NrfRadioDriver::set_frequency(None);
+    }
+
+    /// Changes the radio transmission power, rounding down to the next
+    /// supported level
+    fn set_tx_power(&mut self, power: i8) {
+        let level = TX_POWER_LEVELS
+            .into_iter()
+            .find(|level| *level <= power)
+            .unwrap_or(TX_POWER_LEVELS[TX_POWER_LEVELS.len() - 1]);
+        // Resolves to the inherent method which expects a supported level.
+        RadioDriver::set_tx_power(self, level);
     }
 
     fn schedule_rx(
@@ -406,6 +482,7 @@ impl OffState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskOff> {
             todo!("not implemented")
         }
 
+        let channel = rx_task.channel;
         let packetptr = rx_task.radio_frame.as_ptr() as u32;
         RadioTransition::new(
             self,
@@ -413,6 +490,8 @@ impl OffState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskOff> {
             move || {
                 let r = Self::radio();
 
+                NrfRadioDriver::set_frequency(channel);
+
                 // Ramp up the receiver and start packet reception immediately.
                 r.packetptr.write(|w| w.packetptr().variant(packetptr));
 
@@ -420,6 +499,7 @@ impl OffState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskOff> {
 
                 r.shorts.write(|w| {
                     w.rxready_start().enabled();
+                    w.address_rssistart().enabled();
                     w.framestart_bcstart().enabled()
                 });
                 r.tasks_rxen.write(|w| w.tasks_rxen().set_bit());
@@ -450,6 +530,7 @@ impl OffState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskOff> {
         }
 
         let cca = tx_task.cca;
+        let channel = tx_task.channel;
         let packetptr = prepare_tx_frame(&mut tx_task.radio_frame);
         RadioTransition::new(
             self,
@@ -457,6 +538,7 @@ impl OffState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskOff> {
             move || {
                 let r = Self::radio();
 
+                NrfRadioDriver::set_frequency(channel);
                 r.packetptr.write(|w| w.packetptr().variant(packetptr));
                 dma_start_fence();
 
@@ -489,6 +571,54 @@ impl OffState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskOff> {
         )
     }
 
+    fn schedule_ed(
+        self,
+        ed_task: TaskEd,
+    ) -> impl ExternalRadioTransition<NrfRadioDriver, TaskOff, TaskEd> {
+        #[cfg(feature = "rtos-trace")]
+        rtos_trace::trace::task_exec_begin(TASK_ED_SCHEDULE);
+
+        // Timed ED is not supported by the hardware driver. Clients need to
+        // wait until the ED is due themselves, as the driver service does. The
+        // transition into the ED state falls back to Off instead.
+        let timed = matches!(ed_task.start, Timestamp::Scheduled(_));
+
+        // The hardware repeats the measurement EDCNT + 1 times and keeps the
+        // peak value.
+        let iterations =
+            (ed_task.duration.ticks() + PHY_ED_DURATION.ticks() - 1) / PHY_ED_DURATION.ticks();
+        let edcnt = (iterations - 1).clamp(0, ED_MAX_EDCNT as i64) as u32;
+        let channel = ed_task.channel;
+        RadioTransition::new(
+            self,
+            ed_task,
+            move || {
+                if timed {
+                    return Ok(());
+                }
+
+                let r = Self::radio();
+
+                NrfRadioDriver::set_frequency(channel);
+
+                r.edcnt.write(|w| w.edcnt().variant(edcnt));
+                r.events_edend.reset();
+
+                // Ramp up the receiver and start energy detection immediately.
+                r.shorts.write(|w| w.ready_edstart().enabled());
+                r.tasks_rxen.write(|w| w.tasks_rxen().set_bit());
+
+                Ok(())
+            },
+            || Ok(()),
+            || {
+                Self::radio().shorts.reset();
+                Ok(())
+            },
+            false,
+        )
+    }
+
     async fn switch_off() -> Self {
         #[cfg(feature = "rtos-trace")]
         rtos_trace::trace::task_exec_begin(TASK_FALL_BACK);
@@ -578,15 +708,14 @@ impl RadioState<TaskRx> for RadioDriver<NrfRadioDriver, TaskRx> {
 
             // Wait until the remainder of the packet has been received and the
             // receiver becomes idle.
-            core::future::poll_fn(|cx| {
-                if r.events_end.read().events_end().bit_is_set() {
-                    r.events_end.reset();
+            let end = core::future::poll_fn(|cx| {
+                if let Some(end) = RadioInterruptHandler::take_end_event() {
                     // We reset the BCMATCH event here just in case we didn't
                     // retrieve the preliminary frame info for the last RX
                     // packet (e.g. if it was an ACK packet) and therefore also
                     // didn't reset the event.
                     r.events_bcmatch.reset();
-                    Poll::Ready(())
+                    Poll::Ready(end)
                 } else {
                     RadioInterruptHandler::arm(cx, |w| w.end().set_bit());
                     Poll::Pending
@@ -607,8 +736,24 @@ impl RadioState<TaskRx> for RadioDriver<NrfRadioDriver, TaskRx> {
                     }
                     Err(RadioTaskError::Task(RxError::CrcError))
                 } else {
+                    dma_end_fence();
+
+                    // The PHY header may be corrupted, too, so we need to
+                    // validate the length before using it.
                     let rx_task = self.task.take().unwrap();
-                    Ok(RxResult::CrcError(rx_task.radio_frame))
+                    let sdu_length = rx_task.radio_frame.pdu_ref()[0] & 0x7f;
+                    let sdu_length_wo_fcs = if sdu_length as usize > FCS_LEN {
+                        NonZero::new(sdu_length as u16 - FCS_LEN as u16)
+                    } else {
+                        None
+                    };
+                    let rx_info = Self::rx_info(end, &rx_task.radio_frame);
+
+                    Ok(RxResult::CrcError(
+                        rx_task.radio_frame,
+                        sdu_length_wo_fcs,
+                        rx_info,
+                    ))
                 }
             } else {
                 r.events_crcok.reset();
@@ -620,9 +765,11 @@ impl RadioState<TaskRx> for RadioDriver<NrfRadioDriver, TaskRx> {
                 let sdu_length_wo_fcs =
                     NonZero::new(rx_task.radio_frame.pdu_ref()[0] as u16 - FCS_LEN as u16)
                         .expect("invalid length");
+                let rx_info = Self::rx_info(end, &rx_task.radio_frame);
 
                 Ok(RxResult::Frame(
                     rx_task.radio_frame.with_size(sdu_length_wo_fcs),
+                    rx_info,
                 ))
             }
         } else {
@@ -642,13 +789,52 @@ impl RadioState<TaskRx> for RadioDriver<NrfRadioDriver, TaskRx> {
     }
 
     fn exit(&mut self) -> Result<(), SchedulingError> {
-        Self::radio()
-            .shorts
-            .modify(|_, w| w.framestart_bcstart().disabled());
+        Self::radio().shorts.modify(|_, w| {
+            w.address_rssistart().disabled();
+            w.framestart_bcstart().disabled()
+        });
         Ok(())
     }
 }
 
+impl RadioDriver<NrfRadioDriver, TaskRx> {
+    /// Collects the radio metadata of the frame that has just been received
+    /// into the given radio frame.
+    fn rx_info(
+        end: Option<Instant<NrfRadioTimer>>,
+        radio_frame: &RadioFrame<RadioFrameUnsized>,
+    ) -> RxInfo {
+        let r = Self::radio();
+
+        let pdu = radio_frame.pdu_ref();
+        let sdu_length = (pdu[0] & 0x7f) as usize;
+        let timestamp = end.map(|end| rmarker_from_end(end, sdu_length));
+
+        // The radio overwrites the first FCS octet with the LQI. We scale it
+        // to the full 8-bit range like Nordic's IEEE 802.15.4 driver does.
+        let lqi = if sdu_length >= FCS_LEN {
+            pdu[PHY_HDR_LEN + sdu_length - FCS_LEN].saturating_mul(4)
+        } else {
+            0
+        };
+
+        // RSSI sampling is started by the ADDRESS event, i.e. when the SFD has
+        // been received.
+        let rssi = if r.events_rssiend.read().events_rssiend().bit_is_set() {
+            r.events_rssiend.reset();
+            Some(-(r.rssisample.read().rssisample().bits() as i8))
+        } else {
+            None
+        };
+
+        RxInfo {
+            timestamp: timestamp.map(|timestamp| timestamp.tick()),
+            lqi,
+            rssi,
+        }
+    }
+}
+
 impl RxState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskRx> {
     async fn frame_started(&mut self) {
         let r = Self::radio();
@@ -713,11 +899,11 @@ impl RxState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskRx> {
                 {
                     let addressing_fields_lengths = addressing_repr.addressing_fields_lengths();
                     if let Ok(addressing_fields_lengths) = addressing_fields_lengths {
-                        let [dst_pan_id_len, dst_addr_len, ..] = addressing_fields_lengths;
+                        let addressing_fields_len: u8 = addressing_fields_lengths.iter().sum();
                         (
                             Some(fc),
                             Some((addressing_repr, (FC_LEN + seq_nr_len) as usize)),
-                            Some((FC_LEN + seq_nr_len + dst_pan_id_len + dst_addr_len) as usize),
+                            Some((FC_LEN + seq_nr_len + addressing_fields_len) as usize),
                         )
                     } else {
                         (
@@ -771,8 +957,8 @@ impl RxState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskRx> {
 
         let (frame_control, seq_nr_offset, addressing_info, bcc_set) = frame_control.unwrap();
 
-        // Wait until the sequence number and/or destination address fields have
-        // been received.
+        // Wait until the sequence number and/or addressing fields have been
+        // received.
         if bcc_set {
             let bcmatch = core::future::poll_fn(|cx| {
                 if r.events_bcmatch.read().events_bcmatch().bit_is_set() {
@@ -809,8 +995,8 @@ impl RxState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskRx> {
         let pdu_ref = radio_frame.pdu_ref();
         let mpdu_length = pdu_ref[0] as u16;
 
-        // Safety: The bit counter guarantees that all bytes up to the
-        //         addressing fields have been received.
+        // Safety: The bit counter guarantees that all bytes up to the end of
+        //         the addressing fields have been received.
         const HEADROOM: usize = 1;
         let seq_nr = seq_nr_offset.map(|seq_nr_offset| pdu_ref[HEADROOM + seq_nr_offset]);
         let addressing_fields = addressing_info
@@ -861,6 +1047,7 @@ impl RxState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskRx> {
                 //       avoid race conditions, see RX_IDLE case below.
                 r.shorts.write(|w| {
                     w.end_start().enabled();
+                    w.address_rssistart().enabled();
                     w.framestart_bcstart().enabled()
                 });
 
@@ -1085,11 +1272,18 @@ impl RadioState<TaskTx> for RadioDriver<NrfRadioDriver, TaskTx> {
 
         // Wait until the task completed.
         core::future::poll_fn(|cx| {
-            if r.events_end.read().events_end().bit_is_set() {
-                r.events_end.reset();
+            if let Some(end) = RadioInterruptHandler::take_end_event() {
                 r.events_framestart.reset();
                 let tx_task = self.task.take().unwrap();
-                Poll::Ready(Ok(TxResult::Sent(tx_task.radio_frame)))
+                let sdu_length = tx_task.radio_frame.pdu_ref()[0] as usize;
+                let tx_info = TxInfo {
+                    timestamp: end.map(|end| rmarker_from_end(end, sdu_length).tick()),
+                    ack: None,
+                    ack_mpdu: None,
+                    ack_frame_pending: false,
+                    ack_time_correction: None,
+                };
+                Poll::Ready(Ok(TxResult::Sent(tx_task.radio_frame, tx_info)))
             } else {
                 RadioInterruptHandler::arm(cx, |w| w.end().set_bit());
                 Poll::Pending
@@ -1172,9 +1366,10 @@ impl TxState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskTx> {
                 // We need to schedule the BCSTART short _after_ the FRAMESTART
                 // event of the TX packet, otherwise the TX packet will trigger
                 // the BCMATCH event already.
-                Self::radio()
-                    .shorts
-                    .modify(|_, w| w.framestart_bcstart().enabled());
+                Self::radio().shorts.modify(|_, w| {
+                    w.address_rssistart().enabled();
+                    w.framestart_bcstart().enabled()
+                });
 
                 Ok(())
             },
@@ -1336,6 +1531,149 @@ impl TxState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskTx> {
     }
 }
 
+/// Maximum value of the EDCNT register (21 bits).
+const ED_MAX_EDCNT: u32 = (1 << 21) - 1;
+
+/// Scaling factor from the hardware energy level to the IEEE 802.15.4 ED
+/// range, see the nRF52840 product specification, section 6.20.12.5.
+const ED_RSSISCALE: u8 = 4;
+
+/// Radio energy detection state.
+///
+/// Entry: READY event
+/// Exit: EDEND event
+///
+/// State Invariants:
+/// - The radio is in the RXIDLE state.
+/// - The "EDEND" event has been cleared before starting energy detection.
+/// - Only the "EDEND" interrupt is enabled.
+impl RadioState<TaskEd> for RadioDriver<NrfRadioDriver, TaskEd> {
+    async fn transition(&mut self) -> Result<(), RadioTaskError<TaskEd>> {
+        #[cfg(feature = "rtos-trace")]
+        rtos_trace::trace::task_exec_begin(TASK_TRANSITION_TO_ED);
+
+        // Timed ED was not started, see `schedule_ed()`.
+        if let Some(TaskEd {
+            start: Timestamp::Scheduled(_),
+            ..
+        }) = self.task
+        {
+            return Err(RadioTaskError::Scheduling(SchedulingError));
+        }
+
+        // Wait until the state enters.
+        core::future::poll_fn(|cx| {
+            let r = Self::radio();
+            if r.events_rxready.read().events_rxready().bit_is_set() {
+                r.events_rxready.reset();
+                Poll::Ready(())
+            } else {
+                RadioInterruptHandler::arm(cx, |w| w.rxready().set_bit());
+                Poll::Pending
+            }
+        })
+        .await;
+
+        Ok(())
+    }
+
+    async fn run(&mut self, _: bool) -> Result<EdResult, RadioTaskError<TaskEd>> {
+        #[cfg(feature = "rtos-trace")]
+        rtos_trace::trace::task_exec_begin(TASK_ED_RUN);
+
+        let r = Self::radio();
+
+        // Wait until the measurement completed.
+        core::future::poll_fn(|cx| {
+            if r.events_edend.read().events_edend().bit_is_set() {
+                r.events_edend.reset();
+                let _ = self.task.take();
+                let energy_level = r
+                    .edsample
+                    .read()
+                    .edlvl()
+                    .bits()
+                    .saturating_mul(ED_RSSISCALE);
+                Poll::Ready(Ok(EdResult { energy_level }))
+            } else {
+                RadioInterruptHandler::arm(cx, |w| w.edend().set_bit());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    fn exit(&mut self) -> Result<(), SchedulingError> {
+        Ok(())
+    }
+}
+
+impl EdState<NrfRadioDriver> for RadioDriver<NrfRadioDriver, TaskEd> {
+    fn schedule_off(
+        self,
+        off_task: TaskOff,
+    ) -> impl ExternalRadioTransition<NrfRadioDriver, TaskEd, TaskOff> {
+        #[cfg(feature = "rtos-trace")]
+        rtos_trace::trace::task_exec_begin(TASK_OFF_SCHEDULE);
+
+        // Timed Off is not supported by the hardware driver. The radio is
+        // switched off once the measurement completes and the transition is
+        // reported as a fall back.
+        let timed = matches!(off_task.at, Timestamp::Scheduled(_));
+
+        RadioTransition::new(
+            self,
+            off_task,
+            || {
+                // Ramp down the receiver as soon as the measurement completes.
+                //
+                // NOTE: We need to set up the short before checking radio state
+                //       to avoid race conditions, see RX_IDLE case below.
+                Self::radio().shorts.write(|w| w.edend_disable().enabled());
+                Ok(())
+            },
+            || {
+                // NOTE: Read the state _after_ having set the short.
+                let r = Self::radio();
+                match r.state.read().state().variant() {
+                    Some(STATE_A::RX_IDLE) => {
+                        // The measurement completed before we were able to set
+                        // the short, we need to disable the radio manually.
+                        r.tasks_disable.write(|w| w.tasks_disable().set_bit());
+                    }
+                    Some(STATE_A::RX_DISABLE | STATE_A::DISABLED) => {}
+                    _ => unreachable!(),
+                };
+
+                Ok(())
+            },
+            move || {
+                // Cleanup shorts.
+                Self::radio().shorts.reset();
+                if timed {
+                    return Err(RadioTaskError::Scheduling(SchedulingError));
+                }
+                Ok(())
+            },
+            false,
+        )
+    }
+}
+
+/// Derives the time at which the RMARKER of a frame passed the local antenna
+/// from the time at which the radio interrupt handler observed its END event.
+///
+/// Note: The RTC-based radio timer cannot capture radio events. The timestamp
+///       is therefore derived in software by subtracting the air time of the
+///       PHY header and PSDU. It will be late by the interrupt latency and is
+///       subject to the RTC's resolution of ~30µs. Frames whose END event was
+///       not observed by the interrupt handler carry no timestamp.
+fn rmarker_from_end(end: Instant<NrfRadioTimer>, sdu_length: usize) -> Instant<NrfRadioTimer> {
+    // Two symbols per octet.
+    let air_time = Duration::<SymbolsOQpsk250kB>::new(2 * (PHY_HDR_LEN + sdu_length) as i64);
+    end - air_time.convert_into_rounding_up::<NrfRadioTimer>()
+}
+
 fn prepare_tx_frame(radio_frame: &mut RadioFrame<RadioFrameSized>) -> u32 {
     let sdu_length = radio_frame.sdu_wo_fcs_length().get() as u8 + FCS_LEN as u8;
     // Set PHY HDR.
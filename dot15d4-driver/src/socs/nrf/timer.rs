@@ -69,7 +69,6 @@ fn calc_now(period: u32, counter: u32) -> u64 {
 
 struct Alarms {
     pending: Cell<u64>,
-    next: Cell<u64>,
     fired: Cell<u64>,
 }
 
@@ -79,7 +78,6 @@ impl Alarms {
     const fn new() -> Self {
         Self {
             pending: Cell::new(Self::OFF),
-            next: Cell::new(Self::OFF),
             fired: Cell::new(Self::OFF),
         }
     }
@@ -88,29 +86,19 @@ impl Alarms {
         self.pending.get()
     }
 
-    /// Schedules the next timeout.
-    ///
-    /// Returns true if the pending timeout must be programmed into the
-    /// peripheral.
-    fn schedule(&self, timestamp: u64) -> bool {
-        if self.pending.get() == Self::OFF {
-            self.pending.set(timestamp);
-            true
-        } else {
-            let previous = self.next.replace(timestamp);
-            debug_assert_eq!(previous, Self::OFF);
-            false
-        }
+    /// Schedules the next timeout, replacing the pending timeout and any
+    /// timeout that fired but was not yet consumed.
+    fn schedule(&self, timestamp: u64) {
+        self.pending.set(timestamp);
+        self.fired.set(Self::OFF);
     }
 
-    fn fire_pending_and_get_next(&self) -> u64 {
-        let next = self.next.replace(Self::OFF);
-        let fired = self.pending.replace(next);
+    fn fire_pending(&self) {
+        let fired = self.pending.replace(Self::OFF);
         let prev_fired = self.fired.replace(fired);
         if prev_fired != Self::OFF {
             warn!("missed timer event")
         }
-        next
     }
 
     fn get_and_clear_fired(&self) -> u64 {
@@ -210,22 +198,12 @@ impl RtcDriver {
         //       interrupt handler.
         critical_section::with(|cs| {
             let alarms = self.alarms.borrow(cs);
-            let next_alarm = alarms.fire_pending_and_get_next();
-            if next_alarm != Alarms::OFF {
-                let overdue = !self.try_program_alarm(next_alarm);
-                if overdue {
-                    // We lost an alarm. Clients will be able to discover this
-                    // by comparing the fired timeout with the scheduled
-                    // timeouts.
-                    alarms.fire_pending_and_get_next();
-                }
+            alarms.fire_pending();
+            // The fired alarm is kept for the next waiter if there is none
+            // yet, see `RadioTimerApi::schedule_alarm()`.
+            if let Some(waker) = self.waker.borrow_ref(cs).as_ref() {
+                waker.wake_by_ref();
             }
-            self.waker.borrow_ref(cs).as_ref().map_or_else(
-                || {
-                    alarms.get_and_clear_fired();
-                },
-                |waker| waker.wake_by_ref(),
-            )
         });
     }
 
@@ -295,12 +273,10 @@ impl RtcDriver {
     fn schedule_alarm(&self, at: u64) {
         critical_section::with(|cs| {
             let alarms = self.alarms.borrow(cs);
-            let pending_alarm_changed = alarms.schedule(at);
-            if pending_alarm_changed {
-                let overdue = !self.try_program_alarm(at);
-                if overdue {
-                    alarms.fire_pending_and_get_next();
-                }
+            alarms.schedule(at);
+            let overdue = !self.try_program_alarm(at);
+            if overdue {
+                alarms.fire_pending();
             }
         })
     }
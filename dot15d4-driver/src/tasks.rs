@@ -1,11 +1,12 @@
 // TODO: This is a generic, vendor-independent API. Move this file to a place
 //       where it can be accessed by all HALs and the scheduler.
-use core::{convert::Infallible, future::Future, marker::PhantomData};
+use core::{convert::Infallible, future::Future, marker::PhantomData, num::NonZero};
 
 use crate::{
     config::Channel,
     constants::A_MAX_SIFS_FRAME_SIZE,
     frame::{AddressingFields, FrameControl, RadioFrame, RadioFrameSized, RadioFrameUnsized},
+    time::{Duration, Microseconds, SymbolsOQpsk250kB},
 };
 
 /// Tasks can be scheduled as fast as possible ("best effort") or at a
@@ -24,6 +25,7 @@ pub enum Timestamp {
     ///   local antenna.
     /// - Radio Off: Designates the time at which the radio will start to
     ///   ramp-down.
+    /// - ED: Designates the time at which the energy measurement starts.
     Scheduled(u64),
 }
 
@@ -134,7 +136,23 @@ pub struct TaskRx {
 
     /// radio frame allocated to receive incoming frames
     pub radio_frame: RadioFrame<RadioFrameUnsized>,
+
+    /// the channel to receive on, the default channel if `None`, see
+    /// [`OffState::set_channel`]
+    pub channel: Option<Channel>,
+}
+/// Radio metadata of a received frame
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RxInfo {
+    /// The local radio clock tick at which the RMARKER of the frame passed the
+    /// local antenna, if known.
+    pub timestamp: Option<u64>,
+    /// Link quality indicator, see IEEE 802.15.4-2024, section 10.2.6.
+    pub lqi: u8,
+    /// Received signal strength in dBm, if measured by the driver.
+    pub rssi: Option<i8>,
 }
+
 /// RX task result
 #[derive(Debug, PartialEq, Eq)]
 pub enum RxResult {
@@ -142,6 +160,8 @@ pub enum RxResult {
     Frame(
         /// received radio frame
         RadioFrame<RadioFrameSized>,
+        /// radio metadata of the received frame
+        RxInfo,
     ),
     /// A new task was scheduled before a frame was received.
     RxWindowEnded(
@@ -156,6 +176,12 @@ pub enum RxResult {
     CrcError(
         /// recovered radio frame
         RadioFrame<RadioFrameUnsized>,
+        /// SDU length w/o FCS as indicated by the PHY header if plausible,
+        /// i.e. the length of the corrupted frame that may be passed up in
+        /// promiscuous mode
+        Option<NonZero<u16>>,
+        /// radio metadata of the corrupted frame
+        RxInfo,
     ),
     /// A frame with correct CRC was received but didn't match the filtering
     /// requirements, see IEEE 802.15.4-2024, section 6.6.2. This can be useful
@@ -163,6 +189,8 @@ pub enum RxResult {
     FilteredFrame(
         /// received radio frame
         RadioFrame<RadioFrameSized>,
+        /// radio metadata of the received frame
+        RxInfo,
     ),
 }
 #[derive(Debug, PartialEq, Eq)]
@@ -219,20 +247,84 @@ pub struct TaskTx {
 
     /// whether CCA is to be performed as a precondition to send out the frame
     pub cca: bool,
+
+    /// the channel to send on, the default channel if `None`, see
+    /// [`OffState::set_channel`]
+    pub channel: Option<Channel>,
 }
+
+/// The max length (without FCS) of a received ACK frame that is reported
+/// back to the client, see [`AckMpdu`].
+pub const MAX_ACK_MPDU_LENGTH: usize = 32;
+
+/// A copy of the MPDU (without FCS) of a received ACK frame.
+///
+/// ACK frames are received into a buffer owned by the driver service, so
+/// they are copied to be reported back with the sent frame.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AckMpdu {
+    length: u8,
+    bytes: [u8; MAX_ACK_MPDU_LENGTH],
+}
+
+impl AckMpdu {
+    /// Copies the given ACK MPDU, `None` if it is longer than
+    /// [`MAX_ACK_MPDU_LENGTH`].
+    pub fn new(mpdu: &[u8]) -> Option<Self> {
+        if mpdu.len() > MAX_ACK_MPDU_LENGTH {
+            return None;
+        }
+        let mut bytes = [0; MAX_ACK_MPDU_LENGTH];
+        bytes[..mpdu.len()].copy_from_slice(mpdu);
+        Some(Self {
+            length: mpdu.len() as u8,
+            bytes,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.length as usize]
+    }
+}
+
+/// Radio metadata of a sent frame
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TxInfo {
+    /// The local radio clock tick at which the RMARKER of the frame passed the
+    /// local antenna, if known.
+    pub timestamp: Option<u64>,
+    /// Radio metadata of the received ACK frame, if any.
+    pub ack: Option<RxInfo>,
+    /// The received ACK frame, if any and if it fits into an [`AckMpdu`].
+    /// Drivers offloading ACKs may not report it.
+    pub ack_mpdu: Option<AckMpdu>,
+    /// Whether the Frame Pending field of the received ACK frame was set.
+    pub ack_frame_pending: bool,
+    /// The time correction reported in the Time Correction IE of the
+    /// received Enh-Ack frame, if any, see IEEE 802.15.4-2020, section
+    /// 6.5.4.2.
+    pub ack_time_correction: Option<Duration<Microseconds>>,
+}
+
 /// TX task result
 #[derive(Debug, PartialEq, Eq)]
 pub enum TxResult {
     /// The frame was successfully sent and acknowledged if requested.
-    /// Does not yet carry any data but MAY do so in the future.
-    Sent(RadioFrame<RadioFrameSized>), // TODO: Support returning an optional Enh-Ack frame.
+    Sent(
+        /// The radio frame that was sent.
+        RadioFrame<RadioFrameSized>,
+        /// radio metadata of the sent frame and its ACK
+        TxInfo,
+    ),
     /// The frame was sent but the ACK timeout expired or an Enh-ACK frame was
     /// received but its content indicates a NACK (used, e.g. in TSCH to signal
     /// NACK while still transporting time synchronization info).
     Nack(
         /// The radio frame that was not ack'ed.
         RadioFrame<RadioFrameSized>,
-    ), // TODO: Support returning an optional Enh-Ack frame.
+        /// radio metadata of the sent frame and its ACK, if any
+        TxInfo,
+    ),
 }
 #[derive(Debug, PartialEq, Eq)]
 /// TX task error
@@ -248,6 +340,43 @@ impl RadioTask for TaskTx {
     type Error = TxError;
 }
 
+/// Task: measure the energy on the current channel
+///
+/// This task is mandatory and SHALL be implemented by all drivers.
+///
+/// Energy detection (ED) estimates the received signal power within the
+/// bandwidth of the channel without attempting to identify or decode signals,
+/// see IEEE 802.15.4-2024, section 10.2.5.
+///
+/// A single measurement lasts [`crate::constants::PHY_ED_DURATION`]. Drivers
+/// SHALL measure repeatedly for at least the given duration and report the peak
+/// energy level. Drivers MAY offload repeated measurements to hardware.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TaskEd {
+    /// the time at which the measurement SHALL start
+    pub start: Timestamp,
+
+    /// the minimum duration of the measurement
+    pub duration: Duration<SymbolsOQpsk250kB>,
+
+    /// the channel to measure on, the default channel if `None`, see
+    /// [`OffState::set_channel`]
+    pub channel: Option<Channel>,
+}
+/// ED task result
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EdResult {
+    /// The peak energy level measured during the task, see IEEE 802.15.4-2024,
+    /// section 10.2.5: Zero designates a received power less than 10 dB above
+    /// the receiver sensitivity. The measured power maps linearly to the range
+    /// 0..=255 covering at least 40 dB.
+    pub energy_level: u8,
+}
+impl RadioTask for TaskEd {
+    type Result = EdResult;
+    type Error = Infallible;
+}
+
 /// Currently just a placeholder - may report more specific scheduling errors
 /// later on.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -477,6 +606,13 @@ pub trait OffState<RadioDriverImpl>: RadioState<TaskOff> {
     /// set.
     fn set_channel(&mut self, channel: Channel);
 
+    /// Set the transmit power in dBm used for all following TX tasks.
+    ///
+    /// Drivers SHALL round down to the next power level supported by the
+    /// radio. If the given power is below the lowest supported level, the
+    /// lowest level SHALL be used.
+    fn set_tx_power(&mut self, power: i8);
+
     /// Schedules a transition to the RX state.
     fn schedule_rx(
         self,
@@ -492,6 +628,12 @@ pub trait OffState<RadioDriverImpl>: RadioState<TaskOff> {
         tx_task: TaskTx,
     ) -> impl ExternalRadioTransition<RadioDriverImpl, TaskOff, TaskTx>;
 
+    /// Schedules a transition to the ED state.
+    fn schedule_ed(
+        self,
+        ed_task: TaskEd,
+    ) -> impl ExternalRadioTransition<RadioDriverImpl, TaskOff, TaskEd>;
+
     /// Switches the radio off immediately and unconditionally.
     ///
     /// This method will be called whenever a non-recoverable error is
@@ -555,9 +697,10 @@ pub trait RxState<RadioDriverImpl>: RadioState<TaskRx> {
     ///       destination address filter offloading.
     fn frame_started(&mut self) -> impl Future<Output = ()>;
 
-    /// Wait until the destination pan id and address of an incoming frame has
-    /// been received or the frame ends prematurely. This is required for frame
-    /// validation.
+    /// Wait until all addressing fields of an incoming frame have been
+    /// received or the frame ends prematurely. This is required for frame
+    /// validation, including the source PAN ID, before the frame is
+    /// acknowledged.
     ///
     /// Note: It is not guaranteed that a frame will be returned when the RX
     ///       state completes. A CRC, signature or decryption error may occur or
@@ -681,6 +824,22 @@ pub trait TxState<RadioDriverImpl>: RadioState<TaskTx> {
     ) -> impl ExternalRadioTransition<RadioDriverImpl, TaskTx, TaskOff>;
 }
 
+/// Generic characterization of the "Energy Detection" (ED) state.
+///
+/// Drivers will occupy this state while the receiver measures the energy on the
+/// current channel. No frames are received in this state.
+///
+/// Energy detection is infrequent and not timing critical. Therefore the only
+/// way out of this state is switching the radio off.
+pub trait EdState<RadioDriverImpl>: RadioState<TaskEd> {
+    /// Schedules a transition to the Radio Off state once the measurement
+    /// completed.
+    fn schedule_off(
+        self,
+        off_task: TaskOff,
+    ) -> impl ExternalRadioTransition<RadioDriverImpl, TaskEd, TaskOff>;
+}
+
 /// Represents an active radio state transition while it is being traversed.
 pub struct RadioTransition<
     RadioDriverImpl,
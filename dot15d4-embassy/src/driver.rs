@@ -8,13 +8,13 @@ use dot15d4::{
     },
     mac::{
         frame::mpdu::MpduFrame,
-        primitives::{DataRequest, MacIndication, MacRequest},
-        MacBufferAllocator, MacIndicationReceiver, MacRequestSender,
+        primitives::{AssociateIndication, DataRequest, MacConfirm, MacIndication, MacRequest},
+        MacBufferAllocator, MacIndicationReceiver, MacRequestSender, UL_MAX_TX_TOKENS,
     },
     util::{
         allocator::IntoBuffer,
         frame::Frame,
-        sync::{ConsumerToken, RequestToken, ResponseToken},
+        sync::{ConsumerToken, PollingResponseToken, RequestToken, ResponseToken},
     },
 };
 use embassy_net_driver::{Capabilities, HardwareAddress, LinkState};
@@ -25,6 +25,8 @@ pub struct Ieee802154Driver<'driver, RadioDriverImpl: DriverConfig> {
     request_sender: MacRequestSender<'driver>,
     indication_receiver: MacIndicationReceiver<'driver>,
     consumer_token: RefCell<ConsumerToken>,
+    /// Data requests whose confirmation has not been received, yet.
+    pending_confirms: RefCell<heapless::Vec<PollingResponseToken, UL_MAX_TX_TOKENS>>,
     hardware_addr: HardwareAddress,
     driver: PhantomData<RadioDriverImpl>,
 }
@@ -56,25 +58,51 @@ impl<'driver, RadioDriverImpl: DriverConfig> Ieee802154Driver<'driver, RadioDriv
             request_sender,
             indication_receiver,
             consumer_token: RefCell::new(consumer_token),
+            pending_confirms: RefCell::new(heapless::Vec::new()),
             hardware_addr,
             driver: PhantomData,
         }
     }
 
     fn rx_token(&self, cx: &mut Context) -> Option<RxToken<'_>> {
-        let (response_token, indication) = match self.indication_receiver.poll_wait_for_request(
-            cx,
-            &mut self.consumer_token.borrow_mut(),
-            &(),
-        ) {
-            Poll::Ready(request) => request,
-            Poll::Pending => return None,
-        };
+        let (response_token, mpdu) = loop {
+            let (response_token, indication) = match self.indication_receiver.poll_wait_for_request(
+                cx,
+                &mut self.consumer_token.borrow_mut(),
+                &(),
+            ) {
+                Poll::Ready(request) => request,
+                Poll::Pending => return None,
+            };
+
+            match indication {
+                MacIndication::McpsData(data_indication) => {
+                    break (response_token, data_indication.mpdu)
+                }
+                MacIndication::Promiscuous(promiscuous_indication) => {
+                    break (response_token, promiscuous_indication.mpdu)
+                }
+                // Beacons are passed on as is, smoltcp ignores non-data frames.
+                MacIndication::MlmeBeaconNotify(beacon_notify_indication) => {
+                    break (response_token, beacon_notify_indication.mpdu)
+                }
+                MacIndication::MlmeAssociate(associate_indication) => {
+                    self.respond_to_association(&associate_indication);
+                }
+                // The MAC service already dissolved the association, there is
+                // nothing left to do for the network stack.
+                MacIndication::MlmeDisassociate(_) => {}
+                // The MAC service already left TSCH mode and failed the queued
+                // TSCH frames. Rejoining is up to the application.
+                MacIndication::MlmeSyncLoss(_) => {
+                    dot15d4::util::warn!("TSCH synchronization lost");
+                }
+            }
 
-        let mpdu = match indication {
-            MacIndication::McpsData(data_indication) => data_indication.mpdu,
-            _ => unreachable!(),
+            // Indications without frame are consumed immediately.
+            self.indication_receiver.received(response_token, ());
         };
+
         Some(RxToken {
             indication_receiver: &self.indication_receiver,
             radio_frame: mpdu.into_radio_frame::<RadioDriverImpl>(),
@@ -83,7 +111,53 @@ impl<'driver, RadioDriverImpl: DriverConfig> Ieee802154Driver<'driver, RadioDriv
         })
     }
 
+    /// Accepts or rejects an associating device with the short address
+    /// proposed by the MAC service. The device will not be answered if no
+    /// request token is available, just as the MAC service drops indications
+    /// that cannot be delivered. The confirmation is consumed with the pending
+    /// data confirmations.
+    fn respond_to_association(&self, associate_indication: &AssociateIndication) {
+        let Some(request_token) = self.request_sender.try_allocate_request_token() else {
+            return;
+        };
+
+        let request = MacRequest::MlmeAssociateResponse(associate_indication.respond());
+        let response_token = self
+            .request_sender
+            .send_request_polling_response(request_token, request);
+        // Safety: There can be no more pending confirmations than request
+        //         tokens.
+        self.pending_confirms
+            .borrow_mut()
+            .push(response_token)
+            .unwrap();
+    }
+
+    /// Consumes pending data confirmations and releases the ACK frames they
+    /// carry. Confirmations occupy request slots until they are consumed, so
+    /// this must be called before allocating a request token.
+    fn consume_confirms(&self, cx: &mut Context) {
+        let mut pending_confirms = self.pending_confirms.borrow_mut();
+        while let Poll::Ready(matching_response) = self
+            .request_sender
+            .poll_receive_response(cx, &mut pending_confirms)
+        {
+            if let MacConfirm::McpsData(confirm) = matching_response.response {
+                if let Some(ack_frame) = confirm.ack_frame {
+                    // Safety: The MAC service allocated the buffer from the
+                    //         same allocator.
+                    unsafe {
+                        self.buffer_allocator
+                            .deallocate_buffer(ack_frame.into_buffer());
+                    }
+                }
+            }
+        }
+    }
+
     fn tx_token(&self, cx: &mut Context) -> Option<TxToken<'_>> {
+        self.consume_confirms(cx);
+
         // Safety: Always allocate the buffer before trying to allocate a
         //         request token to avoid deadlock (or livelock in this case).
         let buffer = self
@@ -107,6 +181,7 @@ impl<'driver, RadioDriverImpl: DriverConfig> Ieee802154Driver<'driver, RadioDriv
 
         Some(TxToken {
             request_sender: &self.request_sender,
+            pending_confirms: &self.pending_confirms,
             radio_frame: Some(radio_frame),
             request_token: Some(request_token),
             buffer_allocator: &self.buffer_allocator,
@@ -155,6 +230,7 @@ impl<'driver, RadioDriverImpl: DriverConfig> embassy_net_driver::Driver
 
 pub struct TxToken<'token> {
     request_sender: &'token MacRequestSender<'token>,
+    pending_confirms: &'token RefCell<heapless::Vec<PollingResponseToken, UL_MAX_TX_TOKENS>>,
     radio_frame: Option<RadioFrame<RadioFrameUnsized>>,
     request_token: Option<RequestToken>,
     buffer_allocator: &'token MacBufferAllocator,
@@ -162,8 +238,8 @@ pub struct TxToken<'token> {
 
 impl<'token> TxToken<'token> {
     /// Check if the given data request is to be acknowledged, based on its
-    /// addressing. If so, the acknowledgment request flag is set in the frame
-    /// and the sequence number of the frame returned.
+    /// addressing. If so, the acknowledgment request flag is set in the frame.
+    /// The sequence number is stamped by the MAC service.
     fn set_ack_requested(data_request: &mut DataRequest) {
         let dst_address = data_request.dst_addr();
         let ack_tx = matches!(dst_address, Ok(dst_address) if dst_address.is_unicast());
@@ -207,8 +283,15 @@ impl<'token> embassy_net_driver::TxToken for TxToken<'token> {
         Self::set_ack_requested(&mut data_request);
 
         let request = MacRequest::McpsDataRequest(data_request);
-        self.request_sender
-            .send_request_no_response(self.request_token.take().unwrap(), request);
+        let response_token = self
+            .request_sender
+            .send_request_polling_response(self.request_token.take().unwrap(), request);
+        // Safety: There can be no more pending confirmations than request
+        //         tokens.
+        self.pending_confirms
+            .borrow_mut()
+            .push(response_token)
+            .unwrap();
 
         // No need to drop a consumed token.
         mem::forget(self);
@@ -2,7 +2,9 @@ use core::{cell::Cell, marker::PhantomData};
 
 use dot15d4::{
     driver::{
-        tasks::{OffState, RadioDriver, RxState, TaskOff, TaskRx, TaskTx, TxState},
+        tasks::{
+            EdState, OffState, RadioDriver, RxState, TaskEd, TaskOff, TaskRx, TaskTx, TxState,
+        },
         DriverConfig, RadioDriverApi,
     },
     export::*,
@@ -70,6 +72,7 @@ where
     RadioDriver<RadioDriverImpl, TaskOff>: OffState<RadioDriverImpl> + RadioDriverApi,
     RadioDriver<RadioDriverImpl, TaskRx>: RxState<RadioDriverImpl> + RadioDriverApi,
     RadioDriver<RadioDriverImpl, TaskTx>: TxState<RadioDriverImpl> + RadioDriverApi,
+    RadioDriver<RadioDriverImpl, TaskEd>: EdState<RadioDriverImpl>,
 {
     pub async fn run<Rng: RngCore>(&self, rng: Rng) -> ! {
         let radio = self.radio.take().expect("already running");
@@ -409,7 +409,7 @@ impl MpduFieldRanges<MpduWithAllFields> {
     /// frame does not have a payload.
     pub(crate) const fn offset_frame_payload_end(&self) -> u16 {
         #[cfg(feature = "security")]
-        return self.offset_fcs.unwrap().get() - self.length_mic.unwrap().get() as u16;
+        return self.offset_fcs.unwrap().get() - self.length_mic_or_zero() as u16;
         #[cfg(not(feature = "security"))]
         return self.offset_fcs.unwrap().get();
     }
@@ -425,12 +425,21 @@ impl MpduFieldRanges<MpduWithAllFields> {
         }
     }
 
+    /// The length of the MIC, zero if the frame is not secured.
+    #[cfg(feature = "security")]
+    const fn length_mic_or_zero(&self) -> u8 {
+        match self.length_mic {
+            Some(length_mic) => length_mic.get(),
+            None => 0,
+        }
+    }
+
     /// The buffer range containing the MIC.
     pub(crate) const fn range_mic(&self) -> Option<Range<usize>> {
         #[cfg(feature = "security")]
         return {
             let next_offset = self.offset_fcs.unwrap().get() as usize;
-            let offset_mic = next_offset - self.length_mic.unwrap().get() as usize;
+            let offset_mic = next_offset - self.length_mic_or_zero() as usize;
             if offset_mic == next_offset {
                 None
             } else {
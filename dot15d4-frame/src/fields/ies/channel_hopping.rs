@@ -0,0 +1,191 @@
+//! Field accessors for the Channel Hopping IE, see IEEE 802.15.4-2020, section
+//! 7.4.4.31.
+
+use dot15d4_util::{Error, Result};
+
+/// Reader/writer for the content of a Channel Hopping IE, i.e. the nested IE
+/// without its descriptor.
+///
+/// The reduced form only carries the Hopping Sequence ID. The full form also
+/// carries the hopping sequence itself:
+///
+/// ```notrust
+/// +----+--------------+--------------+-------------+-----------------+-----------------+------------------+-------------+
+/// | ID | Channel Page | Num Channels | PHY Config. | Extended Bitmap | Hopping Seq Len | Hopping Sequence | Current Hop |
+/// +----+--------------+--------------+-------------+-----------------+-----------------+------------------+-------------+
+///   1         1              2              4          0/variable            2            2 x Hopping Seq Len       2
+/// ```
+///
+/// The Extended Bitmap is only present for SUN PHYs.
+pub struct ChannelHoppingIe<Bytes> {
+    bytes: Bytes,
+    /// The length of the Extended Bitmap field in bytes.
+    extended_bitmap_len: usize,
+}
+
+impl<Bytes: AsRef<[u8]>> ChannelHoppingIe<Bytes> {
+    const FULL_IE_MIN_LEN: usize = 12;
+
+    /// Create a new [`ChannelHoppingIe`] reader/writer from the given IE
+    /// content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content is neither a reduced IE nor a full IE
+    /// that is large enough to hold the hopping sequence.
+    pub fn new(bytes: Bytes, is_sun_phy: bool) -> Result<Self> {
+        let mut ie = Self {
+            bytes,
+            extended_bitmap_len: 0,
+        };
+
+        if ie.is_reduced() {
+            return Ok(ie);
+        }
+
+        if ie.bytes.as_ref().len() < Self::FULL_IE_MIN_LEN {
+            return Err(Error);
+        }
+        if is_sun_phy {
+            ie.extended_bitmap_len = ie.number_of_channels().div_ceil(u8::BITS as u16) as usize;
+        }
+        let expected_len = Self::FULL_IE_MIN_LEN
+            + ie.extended_bitmap_len
+            + 2 * ie.hopping_sequence_length() as usize;
+        if ie.bytes.as_ref().len() != expected_len {
+            return Err(Error);
+        }
+
+        Ok(ie)
+    }
+
+    /// Whether only the Hopping Sequence ID is present.
+    pub fn is_reduced(&self) -> bool {
+        self.bytes.as_ref().len() == 1
+    }
+
+    fn read_u16(&self, offset: usize) -> u16 {
+        let b = &self.bytes.as_ref()[offset..offset + 2];
+        u16::from_le_bytes([b[0], b[1]])
+    }
+
+    /// Offset of the Hopping Sequence Length field.
+    fn hopping_sequence_length_offset(&self) -> usize {
+        8 + self.extended_bitmap_len
+    }
+
+    /// Return the Hopping Sequence ID field.
+    pub fn hopping_sequence_id(&self) -> u8 {
+        self.bytes.as_ref()[0]
+    }
+
+    /// Return the Channel Page field.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn channel_page(&self) -> u8 {
+        self.bytes.as_ref()[1]
+    }
+
+    /// Return the Number of Channels field.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn number_of_channels(&self) -> u16 {
+        self.read_u16(2)
+    }
+
+    /// Return the PHY Configuration field, a bitmap of the channels
+    /// supported by the PHY.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn phy_configuration(&self) -> u32 {
+        let b = &self.bytes.as_ref()[4..8];
+        u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+    }
+
+    /// Return the Extended Bitmap field, empty unless the IE belongs to a SUN
+    /// PHY.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn extended_bitmap(&self) -> &[u8] {
+        &self.bytes.as_ref()[8..self.hopping_sequence_length_offset()]
+    }
+
+    /// Return the Hopping Sequence Length field.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn hopping_sequence_length(&self) -> u16 {
+        self.read_u16(self.hopping_sequence_length_offset())
+    }
+
+    /// Return the channels of the Hopping Sequence field.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn hopping_sequence(&self) -> impl Iterator<Item = u16> + '_ {
+        let offset = self.hopping_sequence_length_offset() + 2;
+        (0..self.hopping_sequence_length() as usize).map(move |i| self.read_u16(offset + 2 * i))
+    }
+
+    /// Return the Current Hop field, the index of the current channel in the
+    /// hopping sequence.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn current_hop(&self) -> u16 {
+        self.read_u16(self.bytes.as_ref().len() - 2)
+    }
+}
+
+impl<Bytes: AsRef<[u8]> + AsMut<[u8]>> ChannelHoppingIe<Bytes> {
+    fn write_u16(&mut self, offset: usize, value: u16) {
+        self.bytes.as_mut()[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Set the Hopping Sequence ID field.
+    pub fn set_hopping_sequence_id(&mut self, hopping_sequence_id: u8) {
+        self.bytes.as_mut()[0] = hopping_sequence_id;
+    }
+
+    /// Set the Channel Page field.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn set_channel_page(&mut self, channel_page: u8) {
+        self.bytes.as_mut()[1] = channel_page;
+    }
+
+    /// Set the PHY Configuration field.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn set_phy_configuration(&mut self, phy_configuration: u32) {
+        self.bytes.as_mut()[4..8].copy_from_slice(&phy_configuration.to_le_bytes());
+    }
+
+    /// Set the channels of the Hopping Sequence field. The Number of Channels
+    /// and Hopping Sequence Length fields are structural and remain
+    /// unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of channels does not match the Hopping
+    /// Sequence Length field.
+    pub fn set_hopping_sequence(
+        &mut self,
+        channels: impl ExactSizeIterator<Item = u16>,
+    ) -> Result<()> {
+        if channels.len() != self.hopping_sequence_length() as usize {
+            return Err(Error);
+        }
+
+        let offset = self.hopping_sequence_length_offset() + 2;
+        for (i, channel) in channels.enumerate() {
+            self.write_u16(offset + 2 * i, channel);
+        }
+        Ok(())
+    }
+
+    /// Set the Current Hop field.
+    ///
+    /// Must not be called on reduced IEs.
+    pub fn set_current_hop(&mut self, current_hop: u16) {
+        let offset = self.bytes.as_ref().len() - 2;
+        self.write_u16(offset, current_hop);
+    }
+}
@@ -0,0 +1,304 @@
+//! Readers for the header and payload IE lists of an MPDU, see IEEE
+//! 802.15.4-2020, section 7.4.
+
+use core::ops::Range;
+
+use dot15d4_util::{Error, Result};
+
+use crate::repr::{
+    HEADER_TERMINATION_1_ELEMENT_ID, HEADER_TERMINATION_2_ELEMENT_ID, MLME_GROUP_ID,
+    PAYLOAD_TERMINATION_GROUP_ID,
+};
+
+const IE_DESCRIPTOR_LEN: usize = 2;
+
+/// A header IE, see IEEE 802.15.4-2020, section 7.4.2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderIe {
+    pub element_id: u16,
+    /// The position of the IE content within the MPDU.
+    pub content: Range<usize>,
+}
+
+/// A payload IE, see IEEE 802.15.4-2020, section 7.4.3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadIe {
+    pub group_id: u16,
+    /// The position of the IE content within the MPDU.
+    pub content: Range<usize>,
+}
+
+/// A nested IE within an MLME IE, see IEEE 802.15.4-2020, section 7.4.4.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NestedIe {
+    /// Whether this is a long nested IE. Short and long nested IEs have
+    /// separate sub-ID spaces.
+    pub is_long: bool,
+    pub sub_id: u16,
+    /// The position of the IE content within the MPDU.
+    pub content: Range<usize>,
+}
+
+/// Reader for the header and payload IE lists of an unsecured MPDU.
+///
+/// ```notrust
+/// +------------+------------------+-------------+-------------------+---------------+
+/// | Header IEs | Header Term. IE  | Payload IEs | Payload Term. IE  | Frame Payload |
+/// +------------+------------------+-------------+-------------------+---------------+
+/// ```
+///
+/// Termination IEs are omitted if nothing follows them. All IEs are
+/// validated on creation so that the iterators can skip further checks.
+/// Content ranges refer to the MPDU, so that clients may use them to write
+/// IE content in place.
+pub struct IeList<Bytes> {
+    mpdu: Bytes,
+    /// The header IEs without termination IE.
+    header_ies: Range<usize>,
+    /// The payload IEs without termination IE, empty if there are none.
+    payload_ies: Range<usize>,
+    /// The offset of the frame payload following the IEs.
+    frame_payload_offset: usize,
+}
+
+impl<Bytes: AsRef<[u8]>> IeList<Bytes> {
+    /// Create a new [`IeList`] reader for the IEs starting at the given offset
+    /// of the MPDU (without MIC and FCS).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an IE exceeds the MPDU or if a nested IE exceeds
+    /// its MLME IE.
+    pub fn new(mpdu: Bytes, offset: usize) -> Result<Self> {
+        let bytes = mpdu.as_ref();
+        if offset > bytes.len() {
+            return Err(Error);
+        }
+
+        let mut header_ies = offset..bytes.len();
+        let mut payload_ies = bytes.len()..bytes.len();
+        let mut offset = offset;
+        let mut payload_ies_present = false;
+        while offset < bytes.len() {
+            let (element_id, content) = header_ie_descriptor(bytes, offset)?;
+            match element_id {
+                HEADER_TERMINATION_1_ELEMENT_ID | HEADER_TERMINATION_2_ELEMENT_ID => {
+                    header_ies.end = offset;
+                    payload_ies_present = element_id == HEADER_TERMINATION_1_ELEMENT_ID;
+                    offset = content.end;
+                    break;
+                }
+                _ => offset = content.end,
+            }
+        }
+
+        if payload_ies_present {
+            payload_ies = offset..bytes.len();
+            while offset < bytes.len() {
+                let (group_id, content) = payload_ie_descriptor(bytes, offset)?;
+                offset = content.end;
+                match group_id {
+                    MLME_GROUP_ID => {
+                        let mut nested_offset = content.start;
+                        while nested_offset < content.end {
+                            let (_, _, nested_content) =
+                                nested_ie_descriptor(&bytes[..content.end], nested_offset)?;
+                            nested_offset = nested_content.end;
+                        }
+                    }
+                    PAYLOAD_TERMINATION_GROUP_ID => {
+                        payload_ies.end = content.start - IE_DESCRIPTOR_LEN;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Self {
+            mpdu,
+            header_ies,
+            payload_ies,
+            frame_payload_offset: offset,
+        })
+    }
+
+    /// Return the offset of the frame payload following the IEs within the
+    /// MPDU.
+    pub fn frame_payload_offset(&self) -> usize {
+        self.frame_payload_offset
+    }
+
+    /// Iterate over the header IEs, excluding termination IEs.
+    pub fn header_ies(&self) -> impl Iterator<Item = HeaderIe> + '_ {
+        let bytes = self.mpdu.as_ref();
+        let mut offset = self.header_ies.start;
+        core::iter::from_fn(move || {
+            if offset >= self.header_ies.end {
+                return None;
+            }
+            // Safety: The IE was validated on creation.
+            let (element_id, content) = header_ie_descriptor(bytes, offset).ok()?;
+            offset = content.end;
+            Some(HeaderIe {
+                element_id,
+                content,
+            })
+        })
+    }
+
+    /// Iterate over the payload IEs, excluding the termination IE.
+    pub fn payload_ies(&self) -> impl Iterator<Item = PayloadIe> + '_ {
+        let bytes = self.mpdu.as_ref();
+        let mut offset = self.payload_ies.start;
+        core::iter::from_fn(move || {
+            if offset >= self.payload_ies.end {
+                return None;
+            }
+            // Safety: The IE was validated on creation.
+            let (group_id, content) = payload_ie_descriptor(bytes, offset).ok()?;
+            offset = content.end;
+            Some(PayloadIe { group_id, content })
+        })
+    }
+
+    /// Iterate over the nested IEs of all MLME IEs.
+    pub fn nested_ies(&self) -> impl Iterator<Item = NestedIe> + '_ {
+        let bytes = self.mpdu.as_ref();
+        self.payload_ies()
+            .filter(|payload_ie| payload_ie.group_id == MLME_GROUP_ID)
+            .flat_map(move |mlme_ie| {
+                let mut offset = mlme_ie.content.start;
+                core::iter::from_fn(move || {
+                    if offset >= mlme_ie.content.end {
+                        return None;
+                    }
+                    // Safety: The IE was validated on creation.
+                    let (is_long, sub_id, content) =
+                        nested_ie_descriptor(&bytes[..mlme_ie.content.end], offset).ok()?;
+                    offset = content.end;
+                    Some(NestedIe {
+                        is_long,
+                        sub_id,
+                        content,
+                    })
+                })
+            })
+    }
+}
+
+/// Read the IE descriptor at the given offset and locate the IE content.
+///
+/// Returns an error if the descriptor or the content exceed the given bytes.
+fn ie_descriptor(bytes: &[u8], offset: usize, length_mask: u16) -> Result<(u16, Range<usize>)> {
+    let descriptor = bytes.get(offset..offset + IE_DESCRIPTOR_LEN).ok_or(Error)?;
+    let descriptor = u16::from_le_bytes([descriptor[0], descriptor[1]]);
+    let start = offset + IE_DESCRIPTOR_LEN;
+    let content = start..start + (descriptor & length_mask) as usize;
+    if content.end > bytes.len() {
+        return Err(Error);
+    }
+    Ok((descriptor, content))
+}
+
+/// Return the element ID and content of the header IE at the given offset.
+fn header_ie_descriptor(bytes: &[u8], offset: usize) -> Result<(u16, Range<usize>)> {
+    let (descriptor, content) = ie_descriptor(bytes, offset, 0x7f)?;
+    Ok(((descriptor >> 7) & 0xff, content))
+}
+
+/// Return the group ID and content of the payload IE at the given offset.
+fn payload_ie_descriptor(bytes: &[u8], offset: usize) -> Result<(u16, Range<usize>)> {
+    let (descriptor, content) = ie_descriptor(bytes, offset, 0x7ff)?;
+    Ok(((descriptor >> 11) & 0xf, content))
+}
+
+/// Return the type, sub-ID and content of the nested IE at the given offset.
+fn nested_ie_descriptor(bytes: &[u8], offset: usize) -> Result<(bool, u16, Range<usize>)> {
+    let descriptor = bytes.get(offset..offset + IE_DESCRIPTOR_LEN).ok_or(Error)?;
+    let is_long = descriptor[1] & 0x80 != 0;
+    if is_long {
+        let (descriptor, content) = ie_descriptor(bytes, offset, 0x7ff)?;
+        Ok((true, (descriptor >> 11) & 0xf, content))
+    } else {
+        let (descriptor, content) = ie_descriptor(bytes, offset, 0xff)?;
+        Ok((false, (descriptor >> 8) & 0x7f, content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::repr::{
+        CHANNEL_HOPPING_SUB_ID, TIME_CORRECTION_ELEMENT_ID, TSCH_SYNCHRONIZATION_SUB_ID,
+    };
+
+    #[test]
+    fn header_ies_only() {
+        // Frame control, sequence number and a Time Correction IE.
+        let mpdu = [0x02, 0x22, 0x05, 0x02, 0x0f, 0x64, 0x80];
+        let ies = IeList::new(&mpdu, 3).unwrap();
+        let mut header_ies = ies.header_ies();
+        assert_eq!(
+            header_ies.next(),
+            Some(HeaderIe {
+                element_id: TIME_CORRECTION_ELEMENT_ID,
+                content: 5..7,
+            })
+        );
+        assert_eq!(header_ies.next(), None);
+        assert_eq!(ies.payload_ies().count(), 0);
+        assert_eq!(ies.frame_payload_offset(), 7);
+
+        // No IEs at all.
+        let ies = IeList::new(&mpdu, 7).unwrap();
+        assert_eq!(ies.header_ies().count(), 0);
+        assert_eq!(ies.frame_payload_offset(), 7);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn payload_and_nested_ies() {
+        let mpdu = [
+            // Unknown header IE
+            0x01, 0x00, 0xaa,
+            // Header Termination 1 IE
+            0x00, 0x3f,
+            // MLME IE
+            0x0b, 0x88,
+            // TSCH Synchronization IE
+            0x06, 0x1a, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+            // Reduced Channel Hopping IE
+            0x01, 0xc8, 0x00,
+            // Payload Termination IE
+            0x00, 0xf8,
+            // Frame payload
+            0xbb,
+        ];
+        let ies = IeList::new(&mpdu[..], 0).unwrap();
+        assert!(ies
+            .header_ies()
+            .eq([HeaderIe { element_id: 0x00, content: 2..3 }]));
+        assert!(ies
+            .payload_ies()
+            .eq([PayloadIe { group_id: MLME_GROUP_ID, content: 7..18 }]));
+        assert!(ies.nested_ies().eq([
+            NestedIe { is_long: false, sub_id: TSCH_SYNCHRONIZATION_SUB_ID, content: 9..15 },
+            NestedIe { is_long: true, sub_id: CHANNEL_HOPPING_SUB_ID, content: 17..18 },
+        ]));
+        assert_eq!(ies.frame_payload_offset(), 20);
+
+        // Without Payload Termination IE the payload IEs end with the MPDU.
+        let ies = IeList::new(&mpdu[..18], 0).unwrap();
+        assert_eq!(ies.nested_ies().count(), 2);
+        assert_eq!(ies.frame_payload_offset(), 18);
+
+        // Truncated IEs and nested IEs exceeding their MLME IE.
+        assert!(IeList::new(&mpdu[..2], 0).is_err());
+        assert!(IeList::new(&mpdu[..16], 0).is_err());
+        let mut mpdu = mpdu;
+        mpdu[5] = 0x0a;
+        assert!(IeList::new(&mpdu[..], 0).is_err());
+    }
+}
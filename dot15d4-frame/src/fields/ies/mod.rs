@@ -1,3 +1,11 @@
+mod channel_hopping;
+mod list;
+mod time_correction;
 mod tsch;
+mod tsch_synchronization;
 
+pub use channel_hopping::*;
+pub use list::*;
+pub use time_correction::*;
 pub use tsch::*;
+pub use tsch_synchronization::*;
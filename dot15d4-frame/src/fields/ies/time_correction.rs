@@ -0,0 +1,114 @@
+//! Field accessors for the Time Correction IE, see IEEE 802.15.4-2020, section
+//! 7.4.2.7.
+
+use dot15d4_driver::time::{Duration, Microseconds};
+use dot15d4_util::{Error, Result};
+
+/// Reader/writer for the content of a Time Correction IE, i.e. the header IE
+/// without its descriptor.
+///
+/// ```notrust
+/// +-----------------------------+------+
+/// | Time Synchronization Info.  | NACK |
+/// +-----------------------------+------+
+///    bits 0-11 (signed)  12-14     15
+/// ```
+///
+/// The Time Synchronization Information is a signed time correction in
+/// microseconds.
+pub struct TimeCorrectionIe<Bytes> {
+    bytes: Bytes,
+}
+
+impl<Bytes: AsRef<[u8]>> TimeCorrectionIe<Bytes> {
+    const LEN: usize = 2;
+    const NACK: u16 = 0x8000;
+    const TIME_CORRECTION_MASK: u16 = 0x0fff;
+    /// The largest time correction that can be represented in microseconds.
+    pub const MAX_TIME_CORRECTION: i64 = 2047;
+
+    /// Create a new [`TimeCorrectionIe`] reader/writer from the given IE
+    /// content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content does not have the length of a Time
+    /// Correction IE.
+    pub fn new(bytes: Bytes) -> Result<Self> {
+        if bytes.as_ref().len() != Self::LEN {
+            return Err(Error);
+        }
+        Ok(Self { bytes })
+    }
+
+    fn read_u16(&self) -> u16 {
+        let b = self.bytes.as_ref();
+        u16::from_le_bytes([b[0], b[1]])
+    }
+
+    /// Return the time correction, i.e. the difference between the expected
+    /// and the actual reception time of the acknowledged frame as measured by
+    /// the receiver.
+    pub fn time_correction(&self) -> Duration<Microseconds> {
+        // Sign-extend the 12-bit two's complement value.
+        let time_correction = ((self.read_u16() & Self::TIME_CORRECTION_MASK) << 4) as i16 >> 4;
+        Duration::new(time_correction as i64)
+    }
+
+    /// Whether the acknowledged frame was rejected by the receiver.
+    pub fn nack(&self) -> bool {
+        self.read_u16() & Self::NACK != 0
+    }
+}
+
+impl<Bytes: AsRef<[u8]> + AsMut<[u8]>> TimeCorrectionIe<Bytes> {
+    fn write_u16(&mut self, value: u16) {
+        self.bytes.as_mut().copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Set the time correction. Corrections beyond the representable range
+    /// are saturated.
+    pub fn set_time_correction(&mut self, time_correction: Duration<Microseconds>) {
+        let time_correction = time_correction
+            .ticks()
+            .clamp(-Self::MAX_TIME_CORRECTION, Self::MAX_TIME_CORRECTION)
+            as u16
+            & Self::TIME_CORRECTION_MASK;
+        let value = (self.read_u16() & Self::NACK) | time_correction;
+        self.write_u16(value);
+    }
+
+    /// Set whether the acknowledged frame was rejected.
+    pub fn set_nack(&mut self, nack: bool) {
+        let value = self.read_u16() & !Self::NACK;
+        self.write_u16(if nack { value | Self::NACK } else { value });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_correction() {
+        let mut ie = TimeCorrectionIe::new([0u8; 2]).unwrap();
+        ie.set_time_correction(Duration::new(-100));
+        assert_eq!(ie.time_correction(), Duration::new(-100));
+        assert!(!ie.nack());
+
+        ie.set_nack(true);
+        ie.set_time_correction(Duration::new(100));
+        assert_eq!(ie.bytes, [0x64, 0x80]);
+        assert_eq!(ie.time_correction(), Duration::new(100));
+        assert!(ie.nack());
+
+        // Corrections saturate at the range of the 12-bit field.
+        ie.set_time_correction(Duration::new(-5000));
+        assert_eq!(ie.time_correction(), Duration::new(-2047));
+        ie.set_time_correction(Duration::new(5000));
+        assert_eq!(ie.time_correction(), Duration::new(2047));
+        assert!(ie.nack());
+
+        assert!(TimeCorrectionIe::new([0u8; 3]).is_err());
+    }
+}
@@ -17,7 +17,7 @@ use dot15d4_driver::time::{Duration, Microseconds};
 /// | ID | CCA offset | CCA | TX offset | RX offset | RX ACK delay | TX ACK delay | RX wait | ACK wait | RX/TX | Max ACK | Max TX | Timeslot length |
 /// +----+------------+-----+-----------+-----------+--------------+--------------+---------+----------+-------+---------+--------+------------------+
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TschTimeslotTimings {
     id: u8,
     /// Offset from the start of the timeslot to the start of the CCA in
@@ -239,7 +239,7 @@ bitflags! {
     /// | Tx | Rx | Shared | Time keeping | Priority | Reserved |
     /// +----+----+--------+--------------+----------+----------+
     /// ```
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, PartialEq, Eq)]
     pub struct TschLinkOption: u8 {
         /// Transmit.
         const Tx = 0b0000_0001;
@@ -0,0 +1,78 @@
+//! Field accessors for the TSCH Synchronization IE, see IEEE 802.15.4-2020,
+//! section 7.4.4.2.
+
+use dot15d4_util::{Error, Result};
+
+/// Reader/writer for the content of a TSCH Synchronization IE, i.e. the nested
+/// IE without its descriptor.
+///
+/// ```notrust
+/// +-----+-------------+
+/// | ASN | Join Metric |
+/// +-----+-------------+
+///    5         1
+/// ```
+pub struct TschSynchronizationIe<Bytes> {
+    bytes: Bytes,
+}
+
+impl<Bytes: AsRef<[u8]>> TschSynchronizationIe<Bytes> {
+    const ASN_LEN: usize = 5;
+    const LEN: usize = Self::ASN_LEN + 1;
+
+    /// Create a new [`TschSynchronizationIe`] reader/writer from the given IE
+    /// content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content does not have the length of a TSCH
+    /// Synchronization IE.
+    pub fn new(bytes: Bytes) -> Result<Self> {
+        if bytes.as_ref().len() != Self::LEN {
+            return Err(Error);
+        }
+        Ok(Self { bytes })
+    }
+
+    /// Return the 40-bit absolute slot number.
+    pub fn asn(&self) -> u64 {
+        let mut asn = [0u8; 8];
+        asn[..Self::ASN_LEN].copy_from_slice(&self.bytes.as_ref()[..Self::ASN_LEN]);
+        u64::from_le_bytes(asn)
+    }
+
+    /// Return the join metric, i.e. the sender's distance to the PAN
+    /// coordinator.
+    pub fn join_metric(&self) -> u8 {
+        self.bytes.as_ref()[Self::ASN_LEN]
+    }
+}
+
+impl<Bytes: AsRef<[u8]> + AsMut<[u8]>> TschSynchronizationIe<Bytes> {
+    /// Set the absolute slot number. Only the lower 40 bits are written.
+    pub fn set_asn(&mut self, asn: u64) {
+        self.bytes.as_mut()[..Self::ASN_LEN].copy_from_slice(&asn.to_le_bytes()[..Self::ASN_LEN]);
+    }
+
+    /// Set the join metric.
+    pub fn set_join_metric(&mut self, join_metric: u8) {
+        self.bytes.as_mut()[Self::ASN_LEN] = join_metric;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tsch_synchronization() {
+        let mut ie = TschSynchronizationIe::new([0u8; 6]).unwrap();
+        ie.set_asn(0x01_0203_0405);
+        ie.set_join_metric(3);
+        assert_eq!(ie.bytes, [0x05, 0x04, 0x03, 0x02, 0x01, 0x03]);
+        assert_eq!(ie.asn(), 0x01_0203_0405);
+        assert_eq!(ie.join_metric(), 3);
+
+        assert!(TschSynchronizationIe::new([0u8; 5]).is_err());
+    }
+}
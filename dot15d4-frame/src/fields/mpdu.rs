@@ -243,6 +243,13 @@ impl<'repr> MpduRepr<'repr, MpduWithIes> {
         let mpdu_field_ranges =
             mpdu_field_ranges.without_ies_with_payload_length::<Config>(frame_payload_length);
 
+        #[cfg(feature = "ies")]
+        if let (IeListRepr::WithoutTerminationIes(ies), Some(range_ies)) =
+            (self.ies, mpdu_field_ranges.range_ies())
+        {
+            ies.write_ies(&mut mpdu.buffer[range_ies], frame_payload_length > 0);
+        }
+
         Ok(MpduParser {
             mpdu_field_ranges,
             mpdu,
@@ -505,7 +512,10 @@ impl<AnyMpdu, State: MpduParsedUpToSecurity> MpduParser<AnyMpdu, State> {
 
 /// Exposes read-only fields accessible from an MPDU once it is fully parsed.
 impl<ReadOnlyMpdu: AsRef<MpduFrame>> MpduParser<ReadOnlyMpdu, MpduWithAllFields> {
-    // TODO: Add access to IEs.
+    /// The raw header and payload IEs including termination IEs, if any.
+    pub fn ies(&self) -> Option<&[u8]> {
+        Some(&self.mpdu.as_ref().buffer[self.mpdu_field_ranges.range_ies()?])
+    }
 
     pub fn frame_payload(&self) -> Option<&[u8]> {
         Some(&self.mpdu.as_ref().buffer[self.mpdu_field_ranges.range_frame_payload()?])
@@ -518,7 +528,10 @@ impl<ReadOnlyMpdu: AsRef<MpduFrame>> MpduParser<ReadOnlyMpdu, MpduWithAllFields>
 
 /// Exposes write-only fields accessible from an MPDU once it is fully parsed.
 impl<ReadOnlyMpdu: AsMut<MpduFrame>> MpduParser<ReadOnlyMpdu, MpduWithAllFields> {
-    // TODO: Add access to IEs.
+    /// See [`Self::ies()`].
+    pub fn ies_mut(&mut self) -> Option<&mut [u8]> {
+        Some(&mut self.mpdu.as_mut().buffer[self.mpdu_field_ranges.range_ies()?])
+    }
 
     pub fn frame_payload_mut(&mut self) -> Option<&mut [u8]> {
         Some(&mut self.mpdu.as_mut().buffer[self.mpdu_field_ranges.range_frame_payload()?])
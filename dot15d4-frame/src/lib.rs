@@ -39,13 +39,14 @@ impl MpduParsedUpToSecurity for MpduWithAllFields {}
 
 #[cfg(test)]
 mod test {
-    use core::num::NonZeroU16;
+    use core::{num::NonZeroU16, ops::RangeInclusive};
 
     use dot15d4_driver::{
         constants::PHY_MAX_PACKET_SIZE_127,
         frame::{
-            AddressingMode, AddressingRepr, FrameType, FrameVersion, PanIdCompressionRepr,
-            RadioFrameRepr, RadioFrameSized, RadioFrameUnsized,
+            Address, AddressingMode, AddressingRepr, ExtendedAddress, FrameType, FrameVersion,
+            PanId, PanIdCompressionRepr, RadioFrameRepr, RadioFrameSized, RadioFrameUnsized,
+            ShortAddress,
         },
         time::{Frequency, Instant},
         DriverConfig, FcsTwoBytes, RadioTimerApi,
@@ -59,7 +60,11 @@ mod test {
     #[cfg(feature = "security")]
     use crate::repr::{KeyIdRepr, SecurityLevelRepr, SecurityRepr};
     use crate::{
-        mpdu::imm_ack_frame,
+        mpdu::{
+            association_request_frame, association_response_frame, beacon_frame,
+            beacon_request_frame, coordinator_realignment_frame, data_request_frame,
+            disassociation_notification_frame, imm_ack_frame,
+        },
         repr::{MpduRepr, SeqNrRepr},
         MpduWithIes,
     };
@@ -88,6 +93,8 @@ mod test {
         type MaxSduLength = U<PHY_MAX_PACKET_SIZE_127>;
         type Fcs = FcsTwoBytes;
         type Timer = FakeRadioTimer;
+        const TX_POWER_RANGE: RangeInclusive<i8> = -20..=8;
+        const ACCURATE_RX_TIMESTAMPS: bool = false;
     }
 
     #[test]
@@ -214,6 +221,613 @@ mod test {
         }
     }
 
+    #[cfg(feature = "ies")]
+    #[test]
+    fn test_enh_ack_frame() {
+        use crate::mpdu::{enh_ack_frame, ENH_ACK_MPDU_SIZE_WO_FCS};
+
+        const ENH_ACK_LEN: u8 = 7;
+        assert_eq!(ENH_ACK_MPDU_SIZE_WO_FCS, ENH_ACK_LEN as u16);
+
+        const ENH_ACK_FRAME_REPR: RadioFrameRepr<FakeDriverConfig, RadioFrameSized> =
+            RadioFrameRepr::<_, RadioFrameUnsized>::new()
+                .with_sdu(NonZeroU16::new(ENH_ACK_LEN as u16).unwrap());
+        const ENH_ACK_BUF_LEN: usize = ENH_ACK_FRAME_REPR.pdu_length() as usize;
+
+        static mut BUFFER: [u8; ENH_ACK_BUF_LEN] = [0; ENH_ACK_BUF_LEN];
+        #[allow(static_mut_refs)]
+        let buffer = BufferToken::new(unsafe { &mut BUFFER });
+
+        const TEST_SEQ_NUM: u8 = 55;
+        let frame = enh_ack_frame::<FakeDriverConfig>(TEST_SEQ_NUM, buffer);
+
+        let expected_buffer = [
+            0,
+            FrameType::Ack as u8,
+            // IE present
+            0x02 | (FrameVersion::Ieee802154 as u8) << 4,
+            TEST_SEQ_NUM,
+            // Time Correction IE: length 2, element ID 0x1e
+            0x02,
+            0x0f,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            frame_buffer.consume();
+        }
+    }
+
+    #[test]
+    fn test_beacon_request_frame() {
+        const BEACON_REQUEST_LEN: u8 = 8;
+
+        const BEACON_REQUEST_FRAME_REPR: RadioFrameRepr<FakeDriverConfig, RadioFrameSized> =
+            RadioFrameRepr::<_, RadioFrameUnsized>::new()
+                .with_sdu(NonZeroU16::new(BEACON_REQUEST_LEN as u16).unwrap());
+        const BEACON_REQUEST_BUF_LEN: usize = BEACON_REQUEST_FRAME_REPR.pdu_length() as usize;
+
+        static mut BUFFER: [u8; BEACON_REQUEST_BUF_LEN] = [0; BEACON_REQUEST_BUF_LEN];
+        #[allow(static_mut_refs)]
+        let buffer = BufferToken::new(unsafe { &mut BUFFER });
+
+        const TEST_SEQ_NUM: u8 = 42;
+        let frame = beacon_request_frame::<FakeDriverConfig>(false, TEST_SEQ_NUM, buffer);
+
+        let expected_buffer = [
+            0,
+            FrameType::MacCommand as u8,
+            // Short destination address, no source address
+            0x08,
+            TEST_SEQ_NUM,
+            0xff,
+            0xff,
+            0xff,
+            0xff,
+            // Beacon Request command ID
+            0x07,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            frame_buffer.consume();
+        }
+    }
+
+    #[test]
+    fn test_beacon_frame() {
+        let buffer_allocator = dot15d4_util::buffer_allocator!(32, 1);
+
+        // Superframe Specification, GTS and Pending Address fields
+        const BEACON_PAYLOAD_LEN: u16 = 4;
+        let mut frame = beacon_frame::<FakeDriverConfig>(
+            false,
+            AddressingMode::Short,
+            None,
+            BEACON_PAYLOAD_LEN,
+            buffer_allocator,
+        )
+        .unwrap();
+
+        const TEST_SEQ_NUM: u8 = 43;
+        let _ = frame.set_sequence_number(TEST_SEQ_NUM);
+        let mut addressing_fields = frame.addressing_fields_mut().unwrap().unwrap();
+        addressing_fields
+            .src_pan_id_mut()
+            .unwrap()
+            .set_le_bytes([0x34, 0x12]);
+        let _ = addressing_fields
+            .src_address_mut()
+            .unwrap()
+            .set(&Address::Short(ShortAddress::new(&[0x00, 0x00][..])));
+        frame
+            .frame_payload_mut()
+            .unwrap()
+            .copy_from_slice(&[0xff, 0xcf, 0x00, 0x00]);
+
+        let expected_buffer = [
+            0,
+            FrameType::Beacon as u8,
+            // No destination address, frame version 2006, short source
+            // address
+            0x90,
+            TEST_SEQ_NUM,
+            0x34,
+            0x12,
+            0x00,
+            0x00,
+            // Superframe Specification, GTS and Pending Address fields
+            0xff,
+            0xcf,
+            0x00,
+            0x00,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            buffer_allocator.deallocate_buffer(frame_buffer);
+        }
+    }
+
+    #[test]
+    fn test_enhanced_beacon_frame() {
+        let buffer_allocator = dot15d4_util::buffer_allocator!(32, 1);
+
+        let ies = [
+            IeRepr::TschSynchronizationNestedIe,
+            IeRepr::ReducedTschTimeslotNestedIe,
+        ];
+        let mut frame = beacon_frame::<FakeDriverConfig>(
+            true,
+            AddressingMode::Short,
+            Some(IeReprList::new(&ies)),
+            0,
+            buffer_allocator,
+        )
+        .unwrap();
+
+        const TEST_SEQ_NUM: u8 = 44;
+        let _ = frame.set_sequence_number(TEST_SEQ_NUM);
+        let mut addressing_fields = frame.addressing_fields_mut().unwrap().unwrap();
+        addressing_fields
+            .src_pan_id_mut()
+            .unwrap()
+            .set_le_bytes([0x34, 0x12]);
+        let _ = addressing_fields
+            .src_address_mut()
+            .unwrap()
+            .set(&Address::Short(ShortAddress::new(&[0x00, 0x00][..])));
+        assert!(frame.frame_payload().is_none());
+        // ASN of the TSCH Synchronization IE
+        frame.ies_mut().unwrap()[6..11].copy_from_slice(&[1, 2, 3, 4, 5]);
+
+        let expected_buffer = [
+            0,
+            FrameType::Beacon as u8,
+            // IEs present, no destination address, frame version 2015, short
+            // source address
+            0xa2,
+            TEST_SEQ_NUM,
+            0x34,
+            0x12,
+            0x00,
+            0x00,
+            // Header Termination 1 IE
+            0x00,
+            0x3f,
+            // MLME IE
+            0x0b,
+            0x88,
+            // TSCH Synchronization IE
+            0x06,
+            0x1a,
+            1,
+            2,
+            3,
+            4,
+            5,
+            0,
+            // TSCH Timeslot IE
+            0x01,
+            0x1c,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            buffer_allocator.deallocate_buffer(frame_buffer);
+        }
+    }
+
+    #[cfg(feature = "ies")]
+    #[test]
+    fn test_channel_hopping_ie() {
+        use crate::fields::ChannelHoppingIe;
+
+        const NUM_CHANNELS: u8 = 16;
+        const HOPPING_SEQUENCE: [u16; 4] = [15, 25, 26, 20];
+
+        let ies = [IeRepr::FullChannelHoppingNestedIe(
+            NUM_CHANNELS,
+            HOPPING_SEQUENCE.len() as u8,
+            false,
+        )];
+        let ies = IeReprList::new(&ies);
+        const IES_LEN: usize = 26;
+        assert_eq!(ies.ies_length(false) as usize, IES_LEN);
+
+        let mut buffer = [0xff; IES_LEN];
+        ies.write_ies(&mut buffer, false);
+
+        // Header Termination 1 IE, MLME IE and Channel Hopping IE
+        // descriptors.
+        assert_eq!(buffer[..6], [0x00, 0x3f, 0x16, 0x88, 0x14, 0xc8]);
+
+        let mut ie = ChannelHoppingIe::new(&mut buffer[6..], false).unwrap();
+        assert!(!ie.is_reduced());
+        assert_eq!(ie.number_of_channels(), NUM_CHANNELS as u16);
+        assert_eq!(ie.hopping_sequence_length(), HOPPING_SEQUENCE.len() as u16);
+        assert!(ie.extended_bitmap().is_empty());
+
+        ie.set_hopping_sequence_id(1);
+        ie.set_phy_configuration(0x07ff_f800);
+        assert!(ie
+            .set_hopping_sequence(HOPPING_SEQUENCE[..3].iter().copied())
+            .is_err());
+        assert!(ie
+            .set_hopping_sequence(HOPPING_SEQUENCE.iter().copied())
+            .is_ok());
+        ie.set_current_hop(2);
+
+        let ie = ChannelHoppingIe::new(&buffer[6..], false).unwrap();
+        assert_eq!(ie.hopping_sequence_id(), 1);
+        assert_eq!(ie.channel_page(), 0);
+        assert_eq!(ie.phy_configuration(), 0x07ff_f800);
+        assert!(ie.hopping_sequence().eq(HOPPING_SEQUENCE));
+        assert_eq!(ie.current_hop(), 2);
+        assert_eq!(
+            buffer[6..],
+            [1, 0, 16, 0, 0x00, 0xf8, 0xff, 0x07, 4, 0, 15, 0, 25, 0, 26, 0, 20, 0, 2, 0]
+        );
+
+        // Inconsistent lengths are rejected.
+        assert!(ChannelHoppingIe::new(&buffer[6..25], false).is_err());
+        assert!(ChannelHoppingIe::new(&buffer[6..], true).is_err());
+        assert!(ChannelHoppingIe::new(&buffer[6..7], false)
+            .unwrap()
+            .is_reduced());
+    }
+
+    #[test]
+    fn test_association_request_frame() {
+        const ASSOCIATION_REQUEST_LEN: u8 = 19;
+
+        const ASSOCIATION_REQUEST_FRAME_REPR: RadioFrameRepr<FakeDriverConfig, RadioFrameSized> =
+            RadioFrameRepr::<_, RadioFrameUnsized>::new()
+                .with_sdu(NonZeroU16::new(ASSOCIATION_REQUEST_LEN as u16).unwrap());
+        const ASSOCIATION_REQUEST_BUF_LEN: usize =
+            ASSOCIATION_REQUEST_FRAME_REPR.pdu_length() as usize;
+
+        static mut BUFFER: [u8; ASSOCIATION_REQUEST_BUF_LEN] = [0; ASSOCIATION_REQUEST_BUF_LEN];
+        #[allow(static_mut_refs)]
+        let buffer = BufferToken::new(unsafe { &mut BUFFER });
+
+        const TEST_SEQ_NUM: u8 = 42;
+        let coord_address = Address::Short(ShortAddress::new(&[0x01, 0x00][..]));
+        let frame = association_request_frame::<FakeDriverConfig>(
+            &coord_address,
+            PanId::new_owned([0x34, 0x12]),
+            ExtendedAddress::new_owned([1, 2, 3, 4, 5, 6, 7, 8]),
+            0x80,
+            TEST_SEQ_NUM,
+            buffer,
+        );
+
+        let expected_buffer = [
+            0,
+            // Command frame, AR set
+            FrameType::MacCommand as u8 | 0x20,
+            // Short destination address, extended source address
+            0xc8,
+            TEST_SEQ_NUM,
+            0x34,
+            0x12,
+            0x01,
+            0x00,
+            0xff,
+            0xff,
+            1,
+            2,
+            3,
+            4,
+            5,
+            6,
+            7,
+            8,
+            // Association Request command ID, capability information
+            0x01,
+            0x80,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            frame_buffer.consume();
+        }
+    }
+
+    #[test]
+    fn test_association_response_frame() {
+        const ASSOCIATION_RESPONSE_LEN: u8 = 25;
+
+        const ASSOCIATION_RESPONSE_FRAME_REPR: RadioFrameRepr<FakeDriverConfig, RadioFrameSized> =
+            RadioFrameRepr::<_, RadioFrameUnsized>::new()
+                .with_sdu(NonZeroU16::new(ASSOCIATION_RESPONSE_LEN as u16).unwrap());
+        const ASSOCIATION_RESPONSE_BUF_LEN: usize =
+            ASSOCIATION_RESPONSE_FRAME_REPR.pdu_length() as usize;
+
+        static mut BUFFER: [u8; ASSOCIATION_RESPONSE_BUF_LEN] = [0; ASSOCIATION_RESPONSE_BUF_LEN];
+        #[allow(static_mut_refs)]
+        let buffer = BufferToken::new(unsafe { &mut BUFFER });
+
+        const TEST_SEQ_NUM: u8 = 44;
+        let frame = association_response_frame::<FakeDriverConfig>(
+            ExtendedAddress::new_owned([1, 2, 3, 4, 5, 6, 7, 8]),
+            PanId::new_owned([0x34, 0x12]),
+            ExtendedAddress::new_owned([0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18]),
+            ShortAddress::new_owned([0x02, 0x00]),
+            0x00,
+            TEST_SEQ_NUM,
+            buffer,
+        );
+
+        let expected_buffer = [
+            0,
+            // Command frame, AR set, PAN ID compression
+            FrameType::MacCommand as u8 | 0x60,
+            // Extended destination and source addresses
+            0xcc,
+            TEST_SEQ_NUM,
+            0x34,
+            0x12,
+            1,
+            2,
+            3,
+            4,
+            5,
+            6,
+            7,
+            8,
+            0x11,
+            0x12,
+            0x13,
+            0x14,
+            0x15,
+            0x16,
+            0x17,
+            0x18,
+            // Association Response command ID, short address, status
+            0x02,
+            0x02,
+            0x00,
+            0x00,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            frame_buffer.consume();
+        }
+    }
+
+    #[test]
+    fn test_disassociation_notification_frame() {
+        const DISASSOCIATION_NOTIFICATION_LEN: u8 = 17;
+
+        const DISASSOCIATION_NOTIFICATION_FRAME_REPR: RadioFrameRepr<
+            FakeDriverConfig,
+            RadioFrameSized,
+        > = RadioFrameRepr::<_, RadioFrameUnsized>::new()
+            .with_sdu(NonZeroU16::new(DISASSOCIATION_NOTIFICATION_LEN as u16).unwrap());
+        const DISASSOCIATION_NOTIFICATION_BUF_LEN: usize =
+            DISASSOCIATION_NOTIFICATION_FRAME_REPR.pdu_length() as usize;
+
+        static mut BUFFER: [u8; DISASSOCIATION_NOTIFICATION_BUF_LEN] =
+            [0; DISASSOCIATION_NOTIFICATION_BUF_LEN];
+        #[allow(static_mut_refs)]
+        let buffer = BufferToken::new(unsafe { &mut BUFFER });
+
+        const TEST_SEQ_NUM: u8 = 45;
+        let frame = disassociation_notification_frame::<FakeDriverConfig>(
+            &Address::Short(ShortAddress::new(&[0x01, 0x00][..])),
+            PanId::new_owned([0x34, 0x12]),
+            ExtendedAddress::new_owned([0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18]),
+            0x01,
+            TEST_SEQ_NUM,
+            buffer,
+        );
+
+        let expected_buffer = [
+            0,
+            // Command frame, AR set, PAN ID compression
+            FrameType::MacCommand as u8 | 0x60,
+            // Short destination address, extended source address
+            0xc8,
+            TEST_SEQ_NUM,
+            0x34,
+            0x12,
+            0x01,
+            0x00,
+            0x11,
+            0x12,
+            0x13,
+            0x14,
+            0x15,
+            0x16,
+            0x17,
+            0x18,
+            // Disassociation Notification command ID, reason
+            0x03,
+            0x01,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            frame_buffer.consume();
+        }
+    }
+
+    #[test]
+    fn test_coordinator_realignment_frame() {
+        const COORDINATOR_REALIGNMENT_LEN: u8 = 25;
+
+        const COORDINATOR_REALIGNMENT_FRAME_REPR: RadioFrameRepr<
+            FakeDriverConfig,
+            RadioFrameSized,
+        > = RadioFrameRepr::<_, RadioFrameUnsized>::new()
+            .with_sdu(NonZeroU16::new(COORDINATOR_REALIGNMENT_LEN as u16).unwrap());
+        const COORDINATOR_REALIGNMENT_BUF_LEN: usize =
+            COORDINATOR_REALIGNMENT_FRAME_REPR.pdu_length() as usize;
+
+        static mut BUFFER: [u8; COORDINATOR_REALIGNMENT_BUF_LEN] =
+            [0; COORDINATOR_REALIGNMENT_BUF_LEN];
+        #[allow(static_mut_refs)]
+        let buffer = BufferToken::new(unsafe { &mut BUFFER });
+
+        const TEST_SEQ_NUM: u8 = 46;
+        let frame = coordinator_realignment_frame::<FakeDriverConfig>(
+            PanId::new_owned([0x34, 0x12]),
+            ExtendedAddress::new_owned([0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18]),
+            PanId::new_owned([0x78, 0x56]),
+            ShortAddress::new_owned([0x00, 0x00]),
+            15,
+            TEST_SEQ_NUM,
+            buffer,
+        );
+
+        let expected_buffer = [
+            0,
+            FrameType::MacCommand as u8,
+            // Short destination address, extended source address
+            0xc8,
+            TEST_SEQ_NUM,
+            0xff,
+            0xff,
+            0xff,
+            0xff,
+            0x34,
+            0x12,
+            0x11,
+            0x12,
+            0x13,
+            0x14,
+            0x15,
+            0x16,
+            0x17,
+            0x18,
+            // Coordinator Realignment command ID, PAN ID, coordinator short
+            // address, channel, short address
+            0x08,
+            0x78,
+            0x56,
+            0x00,
+            0x00,
+            15,
+            0xff,
+            0xff,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            frame_buffer.consume();
+        }
+    }
+
+    #[test]
+    fn test_data_request_frame() {
+        const DATA_REQUEST_LEN: u8 = 22;
+
+        const DATA_REQUEST_FRAME_REPR: RadioFrameRepr<FakeDriverConfig, RadioFrameSized> =
+            RadioFrameRepr::<_, RadioFrameUnsized>::new()
+                .with_sdu(NonZeroU16::new(DATA_REQUEST_LEN as u16).unwrap());
+        const DATA_REQUEST_BUF_LEN: usize = DATA_REQUEST_FRAME_REPR.pdu_length() as usize;
+
+        static mut BUFFER: [u8; DATA_REQUEST_BUF_LEN] = [0; DATA_REQUEST_BUF_LEN];
+        #[allow(static_mut_refs)]
+        let buffer = BufferToken::new(unsafe { &mut BUFFER });
+
+        const TEST_SEQ_NUM: u8 = 43;
+        let coord_address = Address::Extended(ExtendedAddress::new(
+            &[0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18][..],
+        ));
+        let frame = data_request_frame::<FakeDriverConfig>(
+            &coord_address,
+            PanId::new_owned([0x34, 0x12]),
+            &Address::Extended(ExtendedAddress::new(&[1, 2, 3, 4, 5, 6, 7, 8][..])),
+            TEST_SEQ_NUM,
+            buffer,
+        );
+
+        let expected_buffer = [
+            0,
+            // Command frame, AR set, PAN ID compression
+            FrameType::MacCommand as u8 | 0x60,
+            // Extended destination and source addresses
+            0xcc,
+            TEST_SEQ_NUM,
+            0x34,
+            0x12,
+            0x11,
+            0x12,
+            0x13,
+            0x14,
+            0x15,
+            0x16,
+            0x17,
+            0x18,
+            1,
+            2,
+            3,
+            4,
+            5,
+            6,
+            7,
+            8,
+            // Data Request command ID
+            0x04,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let frame_buffer = frame.into_buffer();
+        assert_eq!(frame_buffer.as_ref(), &expected_buffer);
+
+        unsafe {
+            frame_buffer.consume();
+        }
+    }
+
     fn round_to_alignment(size: usize, alignment: usize) -> usize {
         assert!(alignment > 0 && ((alignment & (alignment - 1)) == 0));
 
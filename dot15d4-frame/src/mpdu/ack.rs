@@ -1,13 +1,17 @@
+use core::ops::Range;
+
 use dot15d4_driver::{
-    frame::{FrameType, FrameVersion},
+    frame::{AddressingRepr, FrameControl, FrameType, FrameVersion},
     DriverConfig,
 };
 use dot15d4_util::allocator::BufferToken;
 
+#[cfg(feature = "ies")]
+use crate::repr::{IeListRepr, IeRepr, IeReprList};
 use crate::{
-    fields::MpduParser,
+    fields::{IeList, MpduParser},
     mpdu::MpduFrame,
-    repr::{mpdu_repr, MpduRepr, SeqNrRepr},
+    repr::{mpdu_repr, MpduRepr, SeqNrRepr, TIME_CORRECTION_ELEMENT_ID},
     MpduWithAllFields, MpduWithIes,
 };
 
@@ -40,3 +44,111 @@ pub fn imm_ack_frame<Config: DriverConfig>(
     let _ = ack_frame.set_sequence_number(seq_num);
     ack_frame
 }
+
+/// IEs of an Enh-Ack MPDU: A Time Correction IE so that TSCH time sources can
+/// correct the clock drift of the sender, see IEEE 802.15.4-2020, section
+/// 6.5.4.2.
+#[cfg(feature = "ies")]
+const ENH_ACK_IES: [IeRepr; 1] = [IeRepr::TimeCorrectionHeaderIe];
+
+/// Structural representation of an Enh-Ack MPDU without addressing fields.
+#[cfg(feature = "ies")]
+pub const ENH_ACK_FRAME_REPR: MpduRepr<MpduWithIes> = mpdu_repr()
+    .with_frame_control(SeqNrRepr::Yes)
+    .without_addressing()
+    .without_security()
+    .with_ies(IeListRepr::WithoutTerminationIes(IeReprList::new(
+        &ENH_ACK_IES,
+    )));
+
+/// Structural representation of an Enh-Ack MPDU without addressing fields.
+///
+/// Note: Without IE support, Enh-Acks carry no Time Correction IE.
+#[cfg(not(feature = "ies"))]
+pub const ENH_ACK_FRAME_REPR: MpduRepr<MpduWithIes> = IMM_ACK_FRAME_REPR;
+
+/// Size of an Enh-Ack MPDU without FCS.
+pub const ENH_ACK_MPDU_SIZE_WO_FCS: u16 = {
+    match ENH_ACK_FRAME_REPR.mpdu_length_wo_fcs(0) {
+        Ok(len) => len.get(),
+        _ => unreachable!(),
+    }
+};
+
+/// Instantiates a reader/writer for an Enh-Ack frame with the given buffer and
+/// initializes it. The content of the Time Correction IE is zeroed.
+pub fn enh_ack_frame<Config: DriverConfig>(
+    seq_num: u8,
+    buffer: BufferToken,
+) -> MpduParser<MpduFrame, MpduWithAllFields> {
+    // Safety: We give a valid configuration and therefore expect the operation
+    //         not to fail.
+    let mut ack_frame = ENH_ACK_FRAME_REPR
+        .into_parsed_mpdu::<Config>(FrameVersion::Ieee802154, FrameType::Ack, 0, buffer)
+        .unwrap();
+    let _ = ack_frame.set_sequence_number(seq_num);
+    ack_frame
+}
+
+/// Locates the content of the Time Correction IE among the header IEs of an
+/// unsecured Enh-Ack MPDU without FCS, see IEEE 802.15.4-2020, section 7.3.3.
+///
+/// Returns `None` if the MPDU is not an Enh-Ack, carries no Time Correction IE
+/// or if its header IEs are malformed.
+pub fn enh_ack_time_correction_ie_range(mpdu: &[u8]) -> Option<Range<usize>> {
+    const TIME_CORRECTION_IE_LEN: usize = 2;
+
+    let frame_control = FrameControl::new(mpdu).ok()?;
+    if !matches!(frame_control.frame_type(), FrameType::Ack)
+        || !matches!(frame_control.frame_version(), FrameVersion::Ieee802154)
+        || frame_control.security_enabled()
+        || !frame_control.information_elements_present()
+    {
+        return None;
+    }
+
+    let mut offset = if frame_control.sequence_number_suppression() {
+        2
+    } else {
+        3
+    };
+    let addressing_repr =
+        AddressingRepr::from_frame_control(FrameControl::new_unchecked(&mpdu[..2])).ok()?;
+    if let Some(addressing_repr) = addressing_repr {
+        offset += addressing_repr.addressing_fields_length().ok()? as usize;
+    }
+
+    IeList::new(mpdu, offset)
+        .ok()?
+        .header_ies()
+        .find(|ie| {
+            ie.element_id == TIME_CORRECTION_ELEMENT_ID
+                && ie.content.len() == TIME_CORRECTION_IE_LEN
+        })
+        .map(|ie| ie.content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_time_correction_ie() {
+        // Enh-Ack with sequence number and a Time Correction IE.
+        let enh_ack = [0x02, 0x22, 0x05, 0x02, 0x0f, 0x64, 0x80];
+        assert_eq!(enh_ack_time_correction_ie_range(&enh_ack), Some(5..7));
+
+        // The IE follows the addressing fields and other header IEs.
+        let enh_ack = [
+            0x02, 0x2a, 0x05, 0xcd, 0xab, 0x34, 0x12, 0x01, 0x00, 0xaa, 0x02, 0x0f, 0x64, 0x80,
+        ];
+        assert_eq!(enh_ack_time_correction_ie_range(&enh_ack), Some(12..14));
+
+        // Immediate ACKs and truncated IEs.
+        assert_eq!(enh_ack_time_correction_ie_range(&[0x02, 0x10, 0x05]), None);
+        assert_eq!(
+            enh_ack_time_correction_ie_range(&[0x02, 0x22, 0x05, 0x02, 0x0f, 0x64]),
+            None
+        );
+    }
+}
@@ -2,10 +2,7 @@ use dot15d4_driver::{
     frame::{AddressingMode, AddressingRepr, FrameType, FrameVersion, PanIdCompressionRepr},
     DriverConfig,
 };
-use dot15d4_util::{
-    allocator::{export::*, AsyncBufferAllocator},
-    Error, Result,
-};
+use dot15d4_util::{allocator::BufferAllocator, Error, Result};
 
 #[cfg(feature = "ies")]
 use crate::repr::IeListRepr;
@@ -13,28 +10,75 @@ use crate::{
     fields::MpduParser,
     mpdu::MpduFrame,
     repr::{mpdu_repr, IeRepr, IeReprList, MpduRepr, SeqNrRepr},
-    MpduWithAllFields, MpduWithSecurity,
+    MpduWithAllFields, MpduWithIes, MpduWithSecurity,
 };
 
-/// Re-usable part of the structural representation of a beacon MPDU.
+/// Re-usable part of the structural representation of a beacon MPDU sent from
+/// the given source addressing mode: No destination addressing fields, the
+/// source PAN ID and the coordinator's address, see IEEE 802.15.4-2020,
+/// section 7.3.1.
 ///
 /// Note: IEs have not yet been configured as they may be individual to each
 ///       beacon frame.
-pub const BEACON_FRAME_REPR: MpduRepr<MpduWithSecurity> = mpdu_repr()
-    .with_frame_control(SeqNrRepr::No)
-    .with_addressing(AddressingRepr::new(
-        AddressingMode::Short,
-        AddressingMode::Absent,
-        true,
-        PanIdCompressionRepr::Legacy,
-    ))
-    .without_security();
+pub const fn beacon_frame_repr(
+    src_addr_mode: AddressingMode,
+) -> MpduRepr<'static, MpduWithSecurity> {
+    mpdu_repr()
+        .with_frame_control(SeqNrRepr::Yes)
+        .with_addressing(AddressingRepr::new(
+            AddressingMode::Absent,
+            src_addr_mode,
+            false,
+            PanIdCompressionRepr::Legacy,
+        ))
+        .without_security()
+}
 
-/// Allocates an instantiates a reader/writer for a beacon frame with the given
-/// IE list and payload representation.
+/// Structural representation of a beacon MPDU sent from a short address.
+pub const BEACON_FRAME_REPR: MpduRepr<MpduWithSecurity> = beacon_frame_repr(AddressingMode::Short);
+
+/// Completes the beacon representation with the given IE list, if any.
+fn beacon_frame_repr_with_ies<'ies>(
+    src_addr_mode: AddressingMode,
+    ies: Option<IeReprList<'ies, IeRepr<'ies>>>,
+) -> MpduRepr<'ies, MpduWithIes> {
+    let beacon_frame_repr = beacon_frame_repr(src_addr_mode);
+    match ies {
+        Some(_ies) => {
+            #[cfg(not(feature = "ies"))]
+            panic!("not supported");
+            #[cfg(feature = "ies")]
+            beacon_frame_repr.with_ies(IeListRepr::WithoutTerminationIes(_ies))
+        }
+        None => beacon_frame_repr.without_ies(),
+    }
+}
+
+/// Calculates the buffer size that [`beacon_frame()`] allocates for the given
+/// source addressing mode, IE list and payload length.
+///
+/// Returns an error if the IE list is inconsistent or the frame would be too
+/// long.
+pub fn beacon_frame_min_buffer_size<'ies, Config: DriverConfig>(
+    src_addr_mode: AddressingMode,
+    ies: Option<IeReprList<'ies, IeRepr<'ies>>>,
+    beacon_payload_length: u16,
+) -> Result<usize> {
+    beacon_frame_repr_with_ies(src_addr_mode, ies).min_buffer_size::<Config>(beacon_payload_length)
+}
+
+/// Allocates an instantiates a reader/writer for a (possibly enhanced) beacon
+/// frame with the given source addressing mode, IE list and payload
+/// representation.
+///
+/// The payload of a beacon comprises the Superframe Specification, GTS and
+/// Pending Address fields followed by the actual beacon payload. An Enhanced
+/// Beacon is a beacon with frame version IEEE 802.15.4 (2015 and later) whose
+/// payload consists of the beacon payload only. IE descriptors are
+/// initialized, IE content is left to the caller.
 ///
 /// Validates the given IE list (if any) and returns an error if inconsistencies
-/// are found.
+/// are found or no buffer is available.
 ///
 /// Note: We assume that this function is called when building beacons from
 ///       scratch. Therefore the given IE list representation must not contain
@@ -44,30 +88,27 @@ pub const BEACON_FRAME_REPR: MpduRepr<MpduWithSecurity> = mpdu_repr()
 ///       efficient than instantiating an IE list and payload slice just to move
 ///       (copy) it into the function and copy it once again into the buffer
 ///       verbatim.
-pub async fn beacon_frame<'ies, Config: DriverConfig, const ALLOCATOR_BACKLOG: usize>(
+pub fn beacon_frame<'ies, Config: DriverConfig>(
+    enhanced: bool,
+    src_addr_mode: AddressingMode,
     ies: Option<IeReprList<'ies, IeRepr<'ies>>>,
     beacon_payload_length: u16,
-    buffer_allocator: AsyncBufferAllocator<ALLOCATOR_BACKLOG>,
-) -> Result<MpduParser<MpduFrame, MpduWithAllFields>>
-where
-    Const<ALLOCATOR_BACKLOG>: ToUInt,
-    U<ALLOCATOR_BACKLOG>: ArrayLength,
-{
-    let beacon_frame_repr = match ies {
-        Some(_ies) => {
-            #[cfg(not(feature = "ies"))]
-            panic!("not supported");
-            #[cfg(feature = "ies")]
-            BEACON_FRAME_REPR.with_ies(IeListRepr::WithoutTerminationIes(_ies))
-        }
-        None => BEACON_FRAME_REPR.without_ies(),
-    };
+    buffer_allocator: BufferAllocator,
+) -> Result<MpduParser<MpduFrame, MpduWithAllFields>> {
+    let beacon_frame_repr = beacon_frame_repr_with_ies(src_addr_mode, ies);
     let min_buffer_size = beacon_frame_repr.min_buffer_size::<Config>(beacon_payload_length)?;
-    let buffer = buffer_allocator.allocate_buffer(min_buffer_size).await;
+    let buffer = buffer_allocator
+        .try_allocate_buffer(min_buffer_size)
+        .map_err(|_| Error)?;
+    let frame_version = if enhanced {
+        FrameVersion::Ieee802154
+    } else {
+        FrameVersion::Ieee802154_2006
+    };
     match beacon_frame_repr.into_parsed_mpdu::<Config>(
-        FrameVersion::Ieee802154_2006,
+        frame_version,
         FrameType::Beacon,
-        0,
+        beacon_payload_length,
         buffer,
     ) {
         Ok(result) => Ok(result),
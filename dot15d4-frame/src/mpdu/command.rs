@@ -0,0 +1,465 @@
+use dot15d4_driver::{
+    frame::{
+        Address, AddressingMode, AddressingRepr, ExtendedAddress, FrameType, FrameVersion, PanId,
+        PanIdCompressionRepr, ShortAddress, BROADCAST_PAN_ID,
+    },
+    DriverConfig,
+};
+use dot15d4_util::allocator::BufferToken;
+
+use crate::{
+    fields::MpduParser,
+    mpdu::MpduFrame,
+    repr::{mpdu_repr, MpduRepr, SeqNrRepr},
+    MpduWithAllFields, MpduWithIes,
+};
+
+/// MAC command identifiers, see IEEE 802.15.4-2020, section 7.5.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CommandId {
+    AssociationRequest = 0x01,
+    AssociationResponse = 0x02,
+    DisassociationNotification = 0x03,
+    DataRequest = 0x04,
+    PanIdConflictNotification = 0x05,
+    OrphanNotification = 0x06,
+    BeaconRequest = 0x07,
+    CoordinatorRealignment = 0x08,
+}
+
+impl TryFrom<u8> for CommandId {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(CommandId::AssociationRequest),
+            0x02 => Ok(CommandId::AssociationResponse),
+            0x03 => Ok(CommandId::DisassociationNotification),
+            0x04 => Ok(CommandId::DataRequest),
+            0x05 => Ok(CommandId::PanIdConflictNotification),
+            0x06 => Ok(CommandId::OrphanNotification),
+            0x07 => Ok(CommandId::BeaconRequest),
+            0x08 => Ok(CommandId::CoordinatorRealignment),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Length of the Command ID field in octets.
+pub const COMMAND_ID_LEN: u16 = 1;
+
+/// Length of the Association Request command payload in octets: Command ID
+/// and Capability Information fields.
+pub const ASSOCIATION_REQUEST_LEN: u16 = COMMAND_ID_LEN + 1;
+
+/// Length of the Association Response command payload in octets: Command ID,
+/// Short Address and Association Status fields.
+pub const ASSOCIATION_RESPONSE_LEN: u16 = COMMAND_ID_LEN + 3;
+
+/// Length of the Disassociation Notification command payload in octets:
+/// Command ID and Disassociation Reason fields.
+pub const DISASSOCIATION_NOTIFICATION_LEN: u16 = COMMAND_ID_LEN + 1;
+
+/// Length of the Coordinator Realignment command payload in octets: Command
+/// ID, PAN ID, Coordinator Short Address, Channel Number and Short Address
+/// fields. The Channel Page field is omitted.
+pub const COORDINATOR_REALIGNMENT_LEN: u16 = COMMAND_ID_LEN + 7;
+
+/// Structural representation of a (possibly enhanced) Beacon Request MPDU: The
+/// broadcast PAN ID and broadcast short destination address, no source
+/// addressing fields, see IEEE 802.15.4-2020, section 7.5.8.
+pub const BEACON_REQUEST_FRAME_REPR: MpduRepr<MpduWithIes> = mpdu_repr()
+    .with_frame_control(SeqNrRepr::Yes)
+    .with_addressing(AddressingRepr::new(
+        AddressingMode::Short,
+        AddressingMode::Absent,
+        false,
+        PanIdCompressionRepr::Legacy,
+    ))
+    .without_security()
+    .without_ies();
+
+/// Instantiates a reader/writer for a Beacon Request command with the given
+/// buffer and initializes it.
+///
+/// An Enhanced Beacon Request is a Beacon Request command with frame version
+/// IEEE 802.15.4 (2015 and later). We do not currently include an EB Filter
+/// IE, i.e. all coordinators receiving the request are asked to respond.
+pub fn beacon_request_frame<Config: DriverConfig>(
+    enhanced: bool,
+    seq_num: u8,
+    buffer: BufferToken,
+) -> MpduParser<MpduFrame, MpduWithAllFields> {
+    let frame_version = if enhanced {
+        FrameVersion::Ieee802154
+    } else {
+        FrameVersion::Ieee802154_2003
+    };
+
+    // Safety: We give a valid configuration and therefore expect the operation
+    //         not to fail.
+    let mut beacon_request_frame = BEACON_REQUEST_FRAME_REPR
+        .into_parsed_mpdu::<Config>(frame_version, FrameType::MacCommand, COMMAND_ID_LEN, buffer)
+        .unwrap();
+    let _ = beacon_request_frame.set_sequence_number(seq_num);
+
+    let mut addressing_fields = beacon_request_frame
+        .addressing_fields_mut()
+        .unwrap()
+        .unwrap();
+    addressing_fields
+        .dst_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(BROADCAST_PAN_ID);
+    let _ = addressing_fields
+        .dst_address_mut()
+        .unwrap()
+        .set(&Address::<&[u8]>::BROADCAST_ADDR);
+
+    beacon_request_frame.frame_payload_mut().unwrap()[0] = CommandId::BeaconRequest as u8;
+    beacon_request_frame
+}
+
+/// Structural representation of an Association Request MPDU addressed to a
+/// coordinator with the given addressing mode: The coordinator's PAN ID and
+/// address, the broadcast source PAN ID and the extended source address of
+/// the device, see IEEE 802.15.4-2020, section 7.5.2.
+pub const fn association_request_frame_repr(
+    coord_addr_mode: AddressingMode,
+) -> MpduRepr<'static, MpduWithIes> {
+    mpdu_repr()
+        .with_frame_control(SeqNrRepr::Yes)
+        .with_addressing(AddressingRepr::new(
+            coord_addr_mode,
+            AddressingMode::Extended,
+            false,
+            PanIdCompressionRepr::Legacy,
+        ))
+        .without_security()
+        .without_ies()
+}
+
+/// Instantiates a reader/writer for an Association Request command with the
+/// given buffer and initializes it.
+///
+/// The command requests an acknowledgment from the coordinator.
+pub fn association_request_frame<Config: DriverConfig>(
+    coord_address: &Address<&[u8]>,
+    coord_pan_id: PanId<[u8; 2]>,
+    extended_address: ExtendedAddress<[u8; 8]>,
+    capability_information: u8,
+    seq_num: u8,
+    buffer: BufferToken,
+) -> MpduParser<MpduFrame, MpduWithAllFields> {
+    // Safety: We give a valid configuration and therefore expect the operation
+    //         not to fail.
+    let mut association_request_frame = association_request_frame_repr((*coord_address).into())
+        .into_parsed_mpdu::<Config>(
+            FrameVersion::Ieee802154_2003,
+            FrameType::MacCommand,
+            ASSOCIATION_REQUEST_LEN,
+            buffer,
+        )
+        .unwrap();
+    association_request_frame.set_ack_request(true);
+    let _ = association_request_frame.set_sequence_number(seq_num);
+
+    let mut addressing_fields = association_request_frame
+        .addressing_fields_mut()
+        .unwrap()
+        .unwrap();
+    addressing_fields
+        .dst_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(coord_pan_id);
+    let _ = addressing_fields
+        .dst_address_mut()
+        .unwrap()
+        .set(coord_address);
+    addressing_fields
+        .src_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(BROADCAST_PAN_ID);
+    let _ = addressing_fields
+        .src_address_mut()
+        .unwrap()
+        .set(&Address::Extended(extended_address));
+
+    let frame_payload = association_request_frame.frame_payload_mut().unwrap();
+    frame_payload[0] = CommandId::AssociationRequest as u8;
+    frame_payload[1] = capability_information;
+    association_request_frame
+}
+
+/// Structural representation of an Association Response MPDU: The PAN ID,
+/// the extended destination address of the associating device and the
+/// extended source address of the coordinator, see IEEE 802.15.4-2020, section
+/// 7.5.3.
+pub const ASSOCIATION_RESPONSE_FRAME_REPR: MpduRepr<MpduWithIes> = mpdu_repr()
+    .with_frame_control(SeqNrRepr::Yes)
+    .with_addressing(AddressingRepr::new(
+        AddressingMode::Extended,
+        AddressingMode::Extended,
+        true,
+        PanIdCompressionRepr::Legacy,
+    ))
+    .without_security()
+    .without_ies();
+
+/// Instantiates a reader/writer for an Association Response command with the
+/// given buffer and initializes it.
+///
+/// The command requests an acknowledgment from the associating device.
+pub fn association_response_frame<Config: DriverConfig>(
+    device_address: ExtendedAddress<[u8; 8]>,
+    pan_id: PanId<[u8; 2]>,
+    coord_extended_address: ExtendedAddress<[u8; 8]>,
+    assoc_short_address: ShortAddress<[u8; 2]>,
+    association_status: u8,
+    seq_num: u8,
+    buffer: BufferToken,
+) -> MpduParser<MpduFrame, MpduWithAllFields> {
+    // Safety: We give a valid configuration and therefore expect the operation
+    //         not to fail.
+    let mut association_response_frame = ASSOCIATION_RESPONSE_FRAME_REPR
+        .into_parsed_mpdu::<Config>(
+            FrameVersion::Ieee802154_2003,
+            FrameType::MacCommand,
+            ASSOCIATION_RESPONSE_LEN,
+            buffer,
+        )
+        .unwrap();
+    association_response_frame.set_ack_request(true);
+    let _ = association_response_frame.set_sequence_number(seq_num);
+
+    let mut addressing_fields = association_response_frame
+        .addressing_fields_mut()
+        .unwrap()
+        .unwrap();
+    addressing_fields
+        .dst_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(pan_id);
+    let _ = addressing_fields
+        .dst_address_mut()
+        .unwrap()
+        .set(&Address::Extended(device_address));
+    let _ = addressing_fields
+        .src_address_mut()
+        .unwrap()
+        .set(&Address::Extended(coord_extended_address));
+
+    let frame_payload = association_response_frame.frame_payload_mut().unwrap();
+    frame_payload[0] = CommandId::AssociationResponse as u8;
+    frame_payload[1..3].copy_from_slice(assoc_short_address.as_ref());
+    frame_payload[3] = association_status;
+    association_response_frame
+}
+
+/// Structural representation of a Disassociation Notification MPDU addressed to
+/// a device with the given addressing mode: The PAN ID and address of the
+/// recipient and the extended source address of the sender, see IEEE
+/// 802.15.4-2020, section 7.5.4.
+pub const fn disassociation_notification_frame_repr(
+    dst_addr_mode: AddressingMode,
+) -> MpduRepr<'static, MpduWithIes> {
+    mpdu_repr()
+        .with_frame_control(SeqNrRepr::Yes)
+        .with_addressing(AddressingRepr::new(
+            dst_addr_mode,
+            AddressingMode::Extended,
+            true,
+            PanIdCompressionRepr::Legacy,
+        ))
+        .without_security()
+        .without_ies()
+}
+
+/// Instantiates a reader/writer for a Disassociation Notification command
+/// with the given buffer and initializes it.
+///
+/// The command is sent by a coordinator to evict a device or by a device
+/// leaving the PAN and requests an acknowledgment from the recipient.
+pub fn disassociation_notification_frame<Config: DriverConfig>(
+    dst_address: &Address<&[u8]>,
+    pan_id: PanId<[u8; 2]>,
+    extended_address: ExtendedAddress<[u8; 8]>,
+    disassociation_reason: u8,
+    seq_num: u8,
+    buffer: BufferToken,
+) -> MpduParser<MpduFrame, MpduWithAllFields> {
+    // Safety: We give a valid configuration and therefore expect the operation
+    //         not to fail.
+    let mut disassociation_notification_frame =
+        disassociation_notification_frame_repr((*dst_address).into())
+            .into_parsed_mpdu::<Config>(
+                FrameVersion::Ieee802154_2003,
+                FrameType::MacCommand,
+                DISASSOCIATION_NOTIFICATION_LEN,
+                buffer,
+            )
+            .unwrap();
+    disassociation_notification_frame.set_ack_request(true);
+    let _ = disassociation_notification_frame.set_sequence_number(seq_num);
+
+    let mut addressing_fields = disassociation_notification_frame
+        .addressing_fields_mut()
+        .unwrap()
+        .unwrap();
+    addressing_fields
+        .dst_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(pan_id);
+    let _ = addressing_fields
+        .dst_address_mut()
+        .unwrap()
+        .set(dst_address);
+    let _ = addressing_fields
+        .src_address_mut()
+        .unwrap()
+        .set(&Address::Extended(extended_address));
+
+    let frame_payload = disassociation_notification_frame
+        .frame_payload_mut()
+        .unwrap();
+    frame_payload[0] = CommandId::DisassociationNotification as u8;
+    frame_payload[1] = disassociation_reason;
+    disassociation_notification_frame
+}
+
+/// Structural representation of a Data Request MPDU polling a coordinator with
+/// the given addressing modes: The coordinator's PAN ID and address and the
+/// source address of the device, see IEEE 802.15.4-2020, section 7.5.5.
+///
+/// Devices poll with their extended address for an Association Response, i.e.
+/// before a short address has been allocated.
+pub const fn data_request_frame_repr(
+    coord_addr_mode: AddressingMode,
+    src_addr_mode: AddressingMode,
+) -> MpduRepr<'static, MpduWithIes> {
+    mpdu_repr()
+        .with_frame_control(SeqNrRepr::Yes)
+        .with_addressing(AddressingRepr::new(
+            coord_addr_mode,
+            src_addr_mode,
+            true,
+            PanIdCompressionRepr::Legacy,
+        ))
+        .without_security()
+        .without_ies()
+}
+
+/// Instantiates a reader/writer for a Data Request command with the given
+/// buffer and initializes it.
+///
+/// The command requests an acknowledgment from the coordinator.
+pub fn data_request_frame<Config: DriverConfig>(
+    coord_address: &Address<&[u8]>,
+    coord_pan_id: PanId<[u8; 2]>,
+    src_address: &Address<&[u8]>,
+    seq_num: u8,
+    buffer: BufferToken,
+) -> MpduParser<MpduFrame, MpduWithAllFields> {
+    // Safety: We give a valid configuration and therefore expect the operation
+    //         not to fail.
+    let mut data_request_frame =
+        data_request_frame_repr((*coord_address).into(), (*src_address).into())
+            .into_parsed_mpdu::<Config>(
+                FrameVersion::Ieee802154_2003,
+                FrameType::MacCommand,
+                COMMAND_ID_LEN,
+                buffer,
+            )
+            .unwrap();
+    data_request_frame.set_ack_request(true);
+    let _ = data_request_frame.set_sequence_number(seq_num);
+
+    let mut addressing_fields = data_request_frame.addressing_fields_mut().unwrap().unwrap();
+    addressing_fields
+        .dst_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(coord_pan_id);
+    let _ = addressing_fields
+        .dst_address_mut()
+        .unwrap()
+        .set(coord_address);
+    let _ = addressing_fields
+        .src_address_mut()
+        .unwrap()
+        .set(src_address);
+
+    data_request_frame.frame_payload_mut().unwrap()[0] = CommandId::DataRequest as u8;
+    data_request_frame
+}
+
+/// Structural representation of a broadcast Coordinator Realignment MPDU: The
+/// broadcast PAN ID and short address, the coordinator's current PAN ID and its
+/// extended source address, see IEEE 802.15.4-2020, section 7.5.10.
+pub const COORDINATOR_REALIGNMENT_FRAME_REPR: MpduRepr<MpduWithIes> = mpdu_repr()
+    .with_frame_control(SeqNrRepr::Yes)
+    .with_addressing(AddressingRepr::new(
+        AddressingMode::Short,
+        AddressingMode::Extended,
+        false,
+        PanIdCompressionRepr::Legacy,
+    ))
+    .without_security()
+    .without_ies();
+
+/// Instantiates a reader/writer for a broadcast Coordinator Realignment
+/// command with the given buffer and initializes it.
+///
+/// The command announces a new PAN ID and/or channel to all devices of the
+/// PAN. Broadcast commands are not acknowledged.
+pub fn coordinator_realignment_frame<Config: DriverConfig>(
+    pan_id: PanId<[u8; 2]>,
+    extended_address: ExtendedAddress<[u8; 8]>,
+    new_pan_id: PanId<[u8; 2]>,
+    coord_short_address: ShortAddress<[u8; 2]>,
+    channel_number: u8,
+    seq_num: u8,
+    buffer: BufferToken,
+) -> MpduParser<MpduFrame, MpduWithAllFields> {
+    // Safety: We give a valid configuration and therefore expect the operation
+    //         not to fail.
+    let mut coordinator_realignment_frame = COORDINATOR_REALIGNMENT_FRAME_REPR
+        .into_parsed_mpdu::<Config>(
+            FrameVersion::Ieee802154_2003,
+            FrameType::MacCommand,
+            COORDINATOR_REALIGNMENT_LEN,
+            buffer,
+        )
+        .unwrap();
+    let _ = coordinator_realignment_frame.set_sequence_number(seq_num);
+
+    let mut addressing_fields = coordinator_realignment_frame
+        .addressing_fields_mut()
+        .unwrap()
+        .unwrap();
+    addressing_fields
+        .dst_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(BROADCAST_PAN_ID);
+    let _ = addressing_fields
+        .dst_address_mut()
+        .unwrap()
+        .set(&Address::<&[u8]>::BROADCAST_ADDR);
+    addressing_fields
+        .src_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(pan_id);
+    let _ = addressing_fields
+        .src_address_mut()
+        .unwrap()
+        .set(&Address::Extended(extended_address));
+
+    let frame_payload = coordinator_realignment_frame.frame_payload_mut().unwrap();
+    frame_payload[0] = CommandId::CoordinatorRealignment as u8;
+    frame_payload[1..3].copy_from_slice(new_pan_id.as_ref());
+    frame_payload[3..5].copy_from_slice(coord_short_address.as_ref());
+    frame_payload[5] = channel_number;
+    // The command is broadcast to all devices of the PAN.
+    frame_payload[6..8].copy_from_slice(&[0xff, 0xff]);
+    coordinator_realignment_frame
+}
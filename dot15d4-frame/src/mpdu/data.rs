@@ -0,0 +1,71 @@
+use dot15d4_driver::{
+    frame::{
+        Address, AddressingMode, AddressingRepr, FrameType, FrameVersion, PanId,
+        PanIdCompressionRepr,
+    },
+    DriverConfig,
+};
+use dot15d4_util::allocator::BufferToken;
+
+use crate::{
+    fields::MpduParser,
+    mpdu::MpduFrame,
+    repr::{mpdu_repr, MpduRepr, SeqNrRepr},
+    MpduWithAllFields, MpduWithIes,
+};
+
+/// Structural representation of a Data MPDU without payload sent within a PAN
+/// with the given addressing modes: The PAN ID and address of the recipient
+/// and the source address of the sender, see IEEE 802.15.4-2020, section 7.3.2.
+pub const fn keep_alive_frame_repr(
+    dst_addr_mode: AddressingMode,
+    src_addr_mode: AddressingMode,
+) -> MpduRepr<'static, MpduWithIes> {
+    mpdu_repr()
+        .with_frame_control(SeqNrRepr::Yes)
+        .with_addressing(AddressingRepr::new(
+            dst_addr_mode,
+            src_addr_mode,
+            true,
+            PanIdCompressionRepr::Legacy,
+        ))
+        .without_security()
+        .without_ies()
+}
+
+/// Instantiates a reader/writer for a keep-alive frame with the given buffer
+/// and initializes it.
+///
+/// Keep-alives are Data frames without payload that request an acknowledgment
+/// from the recipient. TSCH devices send them to their time source to stay
+/// synchronized, see IEEE 802.15.4-2020, section 6.5.4.
+pub fn keep_alive_frame<Config: DriverConfig>(
+    dst_address: &Address<&[u8]>,
+    pan_id: PanId<[u8; 2]>,
+    src_address: &Address<&[u8]>,
+    seq_num: u8,
+    buffer: BufferToken,
+) -> MpduParser<MpduFrame, MpduWithAllFields> {
+    // Safety: We give a valid configuration and therefore expect the operation
+    //         not to fail.
+    let mut keep_alive_frame = keep_alive_frame_repr((*dst_address).into(), (*src_address).into())
+        .into_parsed_mpdu::<Config>(FrameVersion::Ieee802154_2006, FrameType::Data, 0, buffer)
+        .unwrap();
+    keep_alive_frame.set_ack_request(true);
+    let _ = keep_alive_frame.set_sequence_number(seq_num);
+
+    let mut addressing_fields = keep_alive_frame.addressing_fields_mut().unwrap().unwrap();
+    addressing_fields
+        .dst_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(pan_id);
+    let _ = addressing_fields
+        .dst_address_mut()
+        .unwrap()
+        .set(dst_address);
+    let _ = addressing_fields
+        .src_address_mut()
+        .unwrap()
+        .set(src_address);
+    keep_alive_frame
+}
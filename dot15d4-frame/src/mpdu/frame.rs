@@ -49,7 +49,8 @@ impl MpduFrame {
         self.offset as usize..(self.offset as usize + self.pdu_length_wo_fcs() as usize)
     }
 
-    fn pdu_ref_wo_fcs(&self) -> &[u8] {
+    /// Provides read access to the raw MPDU without FCS.
+    pub fn pdu_ref_wo_fcs(&self) -> &[u8] {
         &self.buffer[self.pdu_range_wo_fcs()]
     }
 
@@ -1,7 +1,11 @@
 mod ack;
 mod beacon;
+mod command;
+mod data;
 mod frame;
 
 pub use ack::*;
 pub use beacon::*;
+pub use command::*;
+pub use data::*;
 pub use frame::*;
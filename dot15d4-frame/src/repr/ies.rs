@@ -2,11 +2,34 @@ use const_for::const_for;
 
 use dot15d4_util::{Error, Result};
 
+/// Element IDs of header IEs, see IEEE 802.15.4-2020, section 7.4.2.1.
+pub const TIME_CORRECTION_ELEMENT_ID: u16 = 0x1e;
+pub const HEADER_TERMINATION_1_ELEMENT_ID: u16 = 0x7e;
+pub const HEADER_TERMINATION_2_ELEMENT_ID: u16 = 0x7f;
+
+/// Group IDs of payload IEs, see IEEE 802.15.4-2020, section 7.4.3.1.
+pub const MLME_GROUP_ID: u16 = 0x1;
+pub const PAYLOAD_TERMINATION_GROUP_ID: u16 = 0xf;
+
+/// Sub-IDs of nested IEs, see IEEE 802.15.4-2020, section 7.4.4.1.
+pub const TSCH_SYNCHRONIZATION_SUB_ID: u16 = 0x1a;
+pub const TSCH_SLOTFRAME_AND_LINK_SUB_ID: u16 = 0x1b;
+pub const TSCH_TIMESLOT_SUB_ID: u16 = 0x1c;
+pub const CHANNEL_HOPPING_SUB_ID: u16 = 0x09;
+
+const fn header_ie_descriptor(element_id: u16, content_length: u16) -> [u8; 2] {
+    ((content_length & 0x7f) | (element_id << 7)).to_le_bytes()
+}
+
+const fn payload_ie_descriptor(group_id: u16, content_length: u16) -> [u8; 2] {
+    ((content_length & 0x7ff) | (group_id << 11) | 0x8000).to_le_bytes()
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum IeRepr<'ie> {
     TimeCorrectionHeaderIe,
     ReducedChannelHoppingNestedIe,
-    FullChannelHoppingNestedIe(u8, bool), // num channels, is SUN PHY
+    FullChannelHoppingNestedIe(u8, u8, bool), // num channels, hopping sequence length, is SUN PHY
     TschSynchronizationNestedIe,
     TschSlotframeAndLinkNestedIe(&'ie [u8]), // for each slotframe descriptor: number of links
     ReducedTschTimeslotNestedIe,
@@ -28,13 +51,18 @@ impl IeRepr<'_> {
             let (header_ie_content_len, nested_ie_content_len) = match self {
                 IeRepr::TimeCorrectionHeaderIe => (2, 0),
                 IeRepr::ReducedChannelHoppingNestedIe => (0, 1),
-                IeRepr::FullChannelHoppingNestedIe(num_channels, is_sun_phy) => {
+                IeRepr::FullChannelHoppingNestedIe(
+                    num_channels,
+                    hopping_sequence_len,
+                    is_sun_phy,
+                ) => {
                     let extended_bm_len = if *is_sun_phy {
                         num_channels.div_ceil(u8::BITS as u8) as u16
                     } else {
                         0
                     };
-                    (0, 12 + (*num_channels as u16) + extended_bm_len)
+                    // Each channel of the hopping sequence takes two bytes.
+                    (0, 12 + extended_bm_len + 2 * (*hopping_sequence_len as u16))
                 }
                 IeRepr::TschSynchronizationNestedIe => (0, 6),
                 IeRepr::TschSlotframeAndLinkNestedIe(slotframes) => {
@@ -67,6 +95,85 @@ impl IeRepr<'_> {
     }
 }
 
+impl IeRepr<'_> {
+    const fn is_header_ie(&self) -> bool {
+        matches!(self, IeRepr::TimeCorrectionHeaderIe)
+    }
+
+    /// Writes the IE descriptor and zeroed content into the given buffer and
+    /// returns the number of bytes written.
+    ///
+    /// Structural information required to parse dynamically sized IEs is
+    /// initialized, namely the number of channels of a long Channel Hopping IE
+    /// and the number of slotframes and links of a TSCH Slotframe and Link IE.
+    ///
+    /// Safety: The buffer must be large enough to hold the IE.
+    fn write(&self, buffer: &mut [u8]) -> usize {
+        const IE_HDR_SIZE: usize = 2;
+
+        let (header_ie_len, nested_ie_len) = self.length();
+        let ie_len = (header_ie_len + nested_ie_len) as usize;
+        let content_len = (ie_len - IE_HDR_SIZE) as u16;
+
+        let descriptor = match self {
+            IeRepr::TimeCorrectionHeaderIe => {
+                header_ie_descriptor(TIME_CORRECTION_ELEMENT_ID, content_len)
+            }
+            // Channel Hopping IEs are long nested IEs.
+            IeRepr::ReducedChannelHoppingNestedIe | IeRepr::FullChannelHoppingNestedIe(..) => {
+                ((content_len & 0x7ff) | (CHANNEL_HOPPING_SUB_ID << 11) | 0x8000).to_le_bytes()
+            }
+            IeRepr::TschSynchronizationNestedIe => {
+                ((content_len & 0xff) | (TSCH_SYNCHRONIZATION_SUB_ID << 8)).to_le_bytes()
+            }
+            IeRepr::TschSlotframeAndLinkNestedIe(_) => {
+                ((content_len & 0xff) | (TSCH_SLOTFRAME_AND_LINK_SUB_ID << 8)).to_le_bytes()
+            }
+            IeRepr::ReducedTschTimeslotNestedIe | IeRepr::FullTschTimeslotNestedIe => {
+                ((content_len & 0xff) | (TSCH_TIMESLOT_SUB_ID << 8)).to_le_bytes()
+            }
+        };
+        buffer[..IE_HDR_SIZE].copy_from_slice(&descriptor);
+
+        let content = &mut buffer[IE_HDR_SIZE..ie_len];
+        content.fill(0);
+        match self {
+            IeRepr::FullChannelHoppingNestedIe(num_channels, hopping_sequence_len, is_sun_phy) => {
+                // Hopping Sequence ID and Channel Page precede the Number of
+                // Channels field.
+                content[2..4].copy_from_slice(&(*num_channels as u16).to_le_bytes());
+
+                // PHY Configuration and the Extended Bitmap precede the
+                // Hopping Sequence Length field.
+                let extended_bm_len = if *is_sun_phy {
+                    num_channels.div_ceil(u8::BITS as u8) as usize
+                } else {
+                    0
+                };
+                let offset = 8 + extended_bm_len;
+                content[offset..offset + 2]
+                    .copy_from_slice(&(*hopping_sequence_len as u16).to_le_bytes());
+            }
+            IeRepr::TschSlotframeAndLinkNestedIe(slotframes) => {
+                const LINK_INFO_LEN: usize = 5;
+                const SLOTFRAME_DESCRIPTOR_HDR_LEN: usize = 4;
+
+                content[0] = slotframes.len() as u8;
+                let mut offset = 1;
+                for num_links in slotframes.iter() {
+                    // Slotframe Handle and Slotframe Size precede the number of
+                    // links.
+                    content[offset + 3] = *num_links;
+                    offset += SLOTFRAME_DESCRIPTOR_HDR_LEN + *num_links as usize * LINK_INFO_LEN;
+                }
+            }
+            _ => {}
+        }
+
+        ie_len
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum IeReprWithTermination<'ie> {
     NonTerminationIe(IeRepr<'ie>),
@@ -91,6 +198,10 @@ impl<'ies, IeRepr> IeReprList<'ies, IeRepr> {
     pub const fn new(ies: &'ies [IeRepr]) -> Self {
         Self(ies)
     }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 /// A list of IE representations without termination IEs.
@@ -145,6 +256,68 @@ impl IeReprList<'_, IeRepr<'_>> {
 
         len
     }
+
+    /// Writes the IE list into the given buffer: Header IEs first, then the
+    /// nested IEs wrapped into a single MLME IE. Termination IEs are added as
+    /// required, see [`Self::ies_length()`].
+    ///
+    /// IE content is zeroed except for structural information, see
+    /// [`IeRepr::write()`].
+    ///
+    /// Safety: The buffer must be exactly [`Self::ies_length()`] bytes long.
+    pub fn write_ies(&self, buffer: &mut [u8], has_frame_payload: bool) {
+        debug_assert_eq!(buffer.len(), self.ies_length(has_frame_payload) as usize);
+
+        let mut offset = 0;
+        for ie in self.0.iter().filter(|ie| ie.is_header_ie()) {
+            offset += ie.write(&mut buffer[offset..]);
+        }
+
+        let nested_ies_len = self
+            .0
+            .iter()
+            .filter(|ie| !ie.is_header_ie())
+            .map(|ie| ie.length().1)
+            .sum::<u16>();
+        let has_header_ie = offset > 0;
+        let has_nested_ie = nested_ies_len > 0;
+
+        fn write_descriptor(buffer: &mut [u8], offset: &mut usize, descriptor: [u8; 2]) {
+            buffer[*offset..*offset + 2].copy_from_slice(&descriptor);
+            *offset += 2;
+        }
+
+        if has_nested_ie {
+            write_descriptor(
+                buffer,
+                &mut offset,
+                header_ie_descriptor(HEADER_TERMINATION_1_ELEMENT_ID, 0),
+            );
+            write_descriptor(
+                buffer,
+                &mut offset,
+                payload_ie_descriptor(MLME_GROUP_ID, nested_ies_len),
+            );
+            for ie in self.0.iter().filter(|ie| !ie.is_header_ie()) {
+                offset += ie.write(&mut buffer[offset..]);
+            }
+            if has_frame_payload {
+                write_descriptor(
+                    buffer,
+                    &mut offset,
+                    payload_ie_descriptor(PAYLOAD_TERMINATION_GROUP_ID, 0),
+                );
+            }
+        } else if has_header_ie && has_frame_payload {
+            write_descriptor(
+                buffer,
+                &mut offset,
+                header_ie_descriptor(HEADER_TERMINATION_2_ELEMENT_ID, 0),
+            );
+        }
+
+        debug_assert_eq!(offset, buffer.len());
+    }
 }
 
 /// A list of IE representations including termination IEs.
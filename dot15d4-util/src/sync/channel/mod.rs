@@ -35,7 +35,8 @@
 //!     Sends the given request without consuming the request token. Returns
 //!     synchronously without waiting for delivery. The response produced by the
 //!     receiver must actively awaited or polled, see
-//!     [`Sender::wait_for_response()`] and [`Sender::try_receive_response()`].
+//!     [`Sender::wait_for_response()`], [`Sender::try_receive_response()`] and
+//!     [`Sender::poll_receive_response()`].
 //!
 //!   - [`Sender::wait_for_response()`]
 //!     Asynchronously waits until a response matching any of the given request
@@ -49,6 +50,10 @@
 //!     request tokens is pending. If so, returns the pending response together
 //!     with the corresponding message slot id.
 //!
+//!   - [`Sender::poll_receive_response()`]
+//!     Like [`Sender::try_receive_response()`] but registers the caller's waker
+//!     if no response is pending, for use in synchronous poll-based APIs.
+//!
 //!   - [`Sender::send_request_awaiting_response()`]
 //!     Consumes a request token to send the given request and waits until it
 //!     was delivered to the receiver. Returns the response produced by the
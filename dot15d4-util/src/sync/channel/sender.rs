@@ -249,6 +249,34 @@ where
         }
     }
 
+    /// Polls for a response matching any of the given request tokens, see
+    /// [`Sender::try_receive_response()`]. Registers the context's waker with
+    /// all given request tokens if no response is pending.
+    ///
+    /// The waker remains registered until the response becomes available, so
+    /// the caller may be woken spuriously.
+    pub fn poll_receive_response<const N: usize>(
+        &self,
+        cx: &mut Context,
+        response_tokens: &mut heapless::Vec<PollingResponseToken, N>,
+    ) -> Poll<MatchingResponse<Response>> {
+        if let Some(matching_response) = self.try_receive_response(response_tokens) {
+            return Poll::Ready(matching_response);
+        }
+
+        let mut state = self.channel.state();
+        for response_token in response_tokens.iter() {
+            let slot_state = &mut state.slot_state[response_token.message_slot() as usize];
+            if let SlotState::RequestPollingResponse(maybe_waker) = slot_state {
+                match maybe_waker {
+                    Some(waker) if waker.will_wake(cx.waker()) => {}
+                    _ => *maybe_waker = Some(cx.waker().clone()),
+                }
+            }
+        }
+        Poll::Pending
+    }
+
     /// Sends the given request and then waits until it has been delivered and
     /// the message slot was released.
     ///
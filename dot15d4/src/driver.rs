@@ -3,12 +3,21 @@
 //! This module provides the upper half of the communication pipe towards IEEE
 //! 802.15.4 radio drivers.
 
-use core::cell::Cell;
+use core::{
+    cell::{Cell, RefCell},
+    future::pending,
+};
 
 use crate::{
     mac::{
-        frame::mpdu::{imm_ack_frame, MpduFrame, ACK_MPDU_SIZE_WO_FCS},
-        MacBufferAllocator,
+        frame::{
+            fields::{TimeCorrectionIe, TschTimeslotTimings},
+            mpdu::{
+                enh_ack_frame, enh_ack_time_correction_ie_range, imm_ack_frame, MpduFrame,
+                ACK_MPDU_SIZE_WO_FCS, ENH_ACK_MPDU_SIZE_WO_FCS,
+            },
+        },
+        MacBufferAllocator, PendingTransactions,
     },
     util::{
         frame::Frame,
@@ -19,17 +28,20 @@ use crate::{
 };
 
 use self::{
-    constants::MAC_AIFS,
+    alarm::Alarm,
+    config::Channel as RadioChannel,
+    constants::{MAC_AIFS, PHY_HDR_DURATION, PHY_SHR_DURATION},
     frame::{
-        is_frame_valid_and_for_us, RadioFrame, RadioFrameRepr, RadioFrameSized, RadioFrameUnsized,
+        is_frame_valid_and_for_us, FrameFilter, FrameType, RadioFrame, RadioFrameRepr,
+        RadioFrameSized, RadioFrameUnsized,
     },
     tasks::{
-        CompletedRadioTransition, ExternalRadioTransition, Ifs, OffResult, OffState, RadioDriver,
-        RadioTask, RadioTaskError, RxError, RxResult, RxState, SelfRadioTransition,
-        TaskOff as RadioTaskOff, TaskRx as RadioTaskRx, TaskTx as RadioTaskTx, Timestamp, TxResult,
-        TxState,
+        AckMpdu, CompletedRadioTransition, EdResult, EdState, ExternalRadioTransition, Ifs,
+        OffResult, OffState, PreliminaryFrameInfo, RadioDriver, RadioTask, RadioTaskError, RxError,
+        RxResult, RxState, SelfRadioTransition, TaskEd as RadioTaskEd, TaskOff as RadioTaskOff,
+        TaskRx as RadioTaskRx, TaskTx as RadioTaskTx, Timestamp, TxInfo, TxResult, TxState,
     },
-    time::{timer_frequency, Duration, SymbolsOQpsk250kB},
+    time::{timer_frequency, Duration, Instant, Microseconds, SymbolsOQpsk250kB},
 };
 
 pub use dot15d4_driver::*;
@@ -53,6 +65,7 @@ impl<Task: RadioTask> DriverServiceTask for Task {
 pub type DrvSvcTaskOff = RadioTaskOff;
 pub type DrvSvcTaskRx = RadioTaskRx;
 pub type DrvSvcTaskTx = RadioTaskTx;
+pub type DrvSvcTaskEd = RadioTaskEd;
 
 /// driver service requests encapsulating driver service tasks
 ///
@@ -77,6 +90,38 @@ pub enum DrvSvcRequest {
     /// Frames to be filled by the driver with a PDU received on air must be
     /// empty, i.e. their PDU length cannot yet be known.
     Rx(DrvSvcTaskRx),
+    /// Ends any ongoing RX window and switches the radio off, e.g. to apply a
+    /// new channel. The radio stays off until the next TX or RX request is
+    /// scheduled.
+    Off(DrvSvcTaskOff),
+    /// Switches the radio off if required, then measures the energy on the
+    /// requested or default channel. The radio stays off after the
+    /// measurement.
+    Ed(DrvSvcTaskEd),
+}
+
+impl DrvSvcRequest {
+    /// The time at which the request is to be executed.
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            DrvSvcRequest::Tx(tx_task) => tx_task.at,
+            DrvSvcRequest::Rx(rx_task) => rx_task.start,
+            DrvSvcRequest::Off(off_task) => off_task.at,
+            DrvSvcRequest::Ed(ed_task) => ed_task.start,
+        }
+    }
+}
+
+impl From<DrvSvcTaskOff> for DrvSvcRequest {
+    fn from(value: DrvSvcTaskOff) -> Self {
+        DrvSvcRequest::Off(value)
+    }
+}
+
+impl From<DrvSvcTaskEd> for DrvSvcRequest {
+    fn from(value: DrvSvcTaskEd) -> Self {
+        DrvSvcRequest::Ed(value)
+    }
 }
 
 impl From<DrvSvcTaskTx> for DrvSvcRequest {
@@ -113,6 +158,7 @@ pub enum DrvSvcResponse {
     Off(DrvSvcTaskResult<DrvSvcTaskOff>),
     Tx(DrvSvcTaskResult<DrvSvcTaskTx>),
     Rx(DrvSvcTaskResult<DrvSvcTaskRx>),
+    Ed(DrvSvcTaskResult<DrvSvcTaskEd>),
 }
 
 impl From<OffResult> for DrvSvcResponse {
@@ -132,6 +178,12 @@ impl From<RadioTaskError<RadioTaskOff>> for DrvSvcResponse {
     }
 }
 
+impl From<EdResult> for DrvSvcResponse {
+    fn from(value: EdResult) -> Self {
+        DrvSvcResponse::Ed(Ok(value))
+    }
+}
+
 impl From<TxResult> for DrvSvcResponse {
     fn from(value: TxResult) -> Self {
         DrvSvcResponse::Tx(Ok(value))
@@ -167,7 +219,7 @@ impl From<RadioTaskError<RadioTaskRx>> for DrvSvcResponse {
 }
 
 // TODO: Make channel capacities configurable.
-pub const DRIVER_CHANNEL_CAPACITY: usize = 4;
+pub const DRIVER_CHANNEL_CAPACITY: usize = 7;
 const DRIVER_CHANNEL_BACKLOG: usize = 1;
 
 /// We need distinct receivers for cancellable tasks (currently only Rx) and
@@ -177,11 +229,22 @@ const DRIVER_CHANNEL_CONSUMERS: usize = 2;
 /// To ensure progress, we give precedence of outbound tasks over inbound tasks.
 /// We therefore route these two classes of tasks into separate virtual
 /// channels.
+///
+/// While TSCH is enabled, only timed tasks are served. Untimed tasks stay
+/// queued until TSCH is disabled again.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TaskDirection {
     Outbound,
     Inbound,
     Any,
+    Timed,
+    /// Outbound requests and RX requests on another channel than the ongoing
+    /// RX window, e.g. RX requests following a scan from one channel to the
+    /// next. RX requests without channel receive on the default channel.
+    Preempting {
+        active_channel: RadioChannel,
+        default_channel: RadioChannel,
+    },
 }
 
 /// Currently we do not address different service instances wrapping
@@ -189,12 +252,29 @@ pub enum TaskDirection {
 /// single channel.
 impl HasAddress<TaskDirection> for DrvSvcRequest {
     fn matches(&self, address: &TaskDirection) -> bool {
-        if matches!(*address, TaskDirection::Any) {
-            return true;
+        match *address {
+            TaskDirection::Any => return true,
+            TaskDirection::Timed => {
+                return matches!(self.timestamp(), Timestamp::Scheduled(_));
+            }
+            TaskDirection::Preempting {
+                active_channel,
+                default_channel,
+            } => {
+                return match self {
+                    DrvSvcRequest::Rx(rx_task) => {
+                        rx_task.channel.unwrap_or(default_channel) != active_channel
+                    }
+                    _ => true,
+                };
+            }
+            _ => {}
         }
 
         match self {
-            DrvSvcRequest::Tx(_) => matches!(*address, TaskDirection::Outbound),
+            DrvSvcRequest::Tx(_) | DrvSvcRequest::Off(_) | DrvSvcRequest::Ed(_) => {
+                matches!(*address, TaskDirection::Outbound)
+            }
             DrvSvcRequest::Rx(_) => matches!(*address, TaskDirection::Inbound),
         }
     }
@@ -251,7 +331,7 @@ enum DriverState<RadioDriverImpl> {
 
 /// Structure managing a given driver implementation. Knows about and manages
 /// individual driver capabilities and exposes a unified API to the MAC service.
-pub struct DriverService<'svc, RadioDriverImpl> {
+pub struct DriverService<'svc, RadioDriverImpl: DriverConfig> {
     /// The current radio driver state.
     driver_state: Cell<Option<DriverState<RadioDriverImpl>>>,
 
@@ -261,8 +341,47 @@ pub struct DriverService<'svc, RadioDriverImpl> {
     // Pre-allocated TX ACK frame.
     tx_ack_frame: Cell<Option<RadioFrame<RadioFrameSized>>>,
 
+    // Pre-allocated TX Enh-Ack frame, sent instead of the ACK frame while
+    // TSCH is enabled.
+    tx_enh_ack_frame: Cell<Option<RadioFrame<RadioFrameSized>>>,
+
     // Pre-allocated frame for RX ACK and invalid frame buffering.
     temporary_rx_frame: Cell<Option<RadioFrame<RadioFrameUnsized>>>,
+
+    /// Incoming frame filter, kept in sync with the PIB by the MAC service.
+    frame_filter: &'svc Cell<FrameFilter>,
+
+    /// The default channel set by the MAC service. Applied whenever the radio
+    /// is off before the next request is scheduled.
+    channel: &'svc Cell<RadioChannel>,
+
+    /// The TX power in dBm set by the MAC service. Applied together with the
+    /// default channel.
+    tx_power: &'svc Cell<i8>,
+
+    /// The channel of the ongoing TX or RX task. ACKs are exchanged on the
+    /// same channel.
+    active_channel: Cell<RadioChannel>,
+
+    /// The transaction queue of the MAC service. Determines the Frame Pending
+    /// field of outgoing ACKs.
+    pending_transactions: &'svc RefCell<PendingTransactions>,
+
+    /// The timeslot timings while TSCH is enabled by the MAC service, `None`
+    /// otherwise.
+    timeslot_timings: &'svc Cell<Option<TschTimeslotTimings>>,
+
+    /// The driver service's share of the radio timer alarm.
+    alarm: Alarm<'svc, RadioDriverImpl::Timer>,
+
+    /// The radio timer tick at which the ongoing timed RX window ends unless
+    /// a frame is being received, if any.
+    rx_window_end: Cell<Option<u64>>,
+
+    /// The radio timer tick at which the RMARKER of a frame is expected in
+    /// the ongoing timed RX window, if any. Enh-Acks report the deviation of
+    /// the actual RMARKER from it.
+    expected_rx_rmarker: Cell<Option<u64>>,
 }
 
 impl<'svc, RadioDriverImpl: DriverConfig> DriverService<'svc, RadioDriverImpl>
@@ -270,6 +389,7 @@ where
     RadioDriver<RadioDriverImpl, RadioTaskOff>: OffState<RadioDriverImpl> + RadioDriverApi,
     RadioDriver<RadioDriverImpl, RadioTaskRx>: RxState<RadioDriverImpl> + RadioDriverApi,
     RadioDriver<RadioDriverImpl, RadioTaskTx>: TxState<RadioDriverImpl> + RadioDriverApi,
+    RadioDriver<RadioDriverImpl, RadioTaskEd>: EdState<RadioDriverImpl>,
 {
     /// IFS starts after the reception of the last symbol of the previous PPDU
     /// (END event) and ends with the first symbol of the next PPDU, i.e. the
@@ -294,21 +414,46 @@ where
 
     /// Creates a new [`DriverService`] instance wrapping the given driver
     /// implementation.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         driver: RadioDriver<RadioDriverImpl, RadioTaskOff>,
         driver_service_receiver: DriverRequestReceiver<'svc>,
         buffer_allocator: MacBufferAllocator,
+        frame_filter: &'svc Cell<FrameFilter>,
+        channel: &'svc Cell<RadioChannel>,
+        tx_power: &'svc Cell<i8>,
+        pending_transactions: &'svc RefCell<PendingTransactions>,
+        timeslot_timings: &'svc Cell<Option<TschTimeslotTimings>>,
+        alarm: Alarm<'svc, RadioDriverImpl::Timer>,
     ) -> Self {
         Self {
             driver_state: Cell::new(Some(DriverState::Off(driver))),
             request_receiver: driver_service_receiver,
             tx_ack_frame: Cell::new(Some(Self::allocate_tx_ack_frame(buffer_allocator))),
+            tx_enh_ack_frame: Cell::new(Some(Self::allocate_tx_enh_ack_frame(buffer_allocator))),
             temporary_rx_frame: Cell::new(Some(Self::allocate_temporary_rx_frame(
                 buffer_allocator,
             ))),
+            frame_filter,
+            channel,
+            tx_power,
+            active_channel: Cell::new(channel.get()),
+            pending_transactions,
+            timeslot_timings,
+            alarm,
+            rx_window_end: Cell::new(None),
+            expected_rx_rmarker: Cell::new(None),
         }
     }
 
+    /// Frames with CRC errors are normally dropped by rolling back to the
+    /// ongoing RX task. Only regular frames received in promiscuous mode are
+    /// passed on if the client asked for them.
+    fn rollback_on_crcerror(&self, is_rx_ack: bool) -> bool {
+        let frame_filter = self.frame_filter.get();
+        is_rx_ack || !(frame_filter.promiscuous_mode && frame_filter.accept_crc_errors)
+    }
+
     /// Pre-allocates and pre-populates a re-usable outgoing ACK frame.
     ///
     /// Safety: We have separate incoming and outgoing ACK buffers to ensure
@@ -329,6 +474,25 @@ where
         .into_radio_frame::<RadioDriverImpl>()
     }
 
+    /// Pre-allocates and pre-populates a re-usable outgoing Enh-Ack frame.
+    ///
+    /// Safety: See [`Self::allocate_tx_ack_frame`].
+    fn allocate_tx_enh_ack_frame(
+        buffer_allocator: MacBufferAllocator,
+    ) -> RadioFrame<RadioFrameSized> {
+        let radio_frame_repr = RadioFrameRepr::<RadioDriverImpl, RadioFrameUnsized>::new();
+        let tx_enh_ack_buffer_size = ENH_ACK_MPDU_SIZE_WO_FCS as usize
+            + (radio_frame_repr.fcs_length() + radio_frame_repr.driver_overhead()) as usize;
+
+        enh_ack_frame::<RadioDriverImpl>(
+            0,
+            buffer_allocator
+                .try_allocate_buffer(tx_enh_ack_buffer_size)
+                .expect("no capacity"),
+        )
+        .into_radio_frame::<RadioDriverImpl>()
+    }
+
     /// Pre-allocates a re-usable RX frame for ACK or invalid frame buffering.
     fn allocate_temporary_rx_frame(
         buffer_allocator: MacBufferAllocator,
@@ -385,8 +549,9 @@ where
     }
 
     /// Waits for an incoming frame and receive it or end the Rx window when an
-    /// outbound request is received - whatever happens first. Finally switch to
-    /// the next requested driver state (if any) or turns the radio off.
+    /// outbound request is received or a timed Rx window ends - whatever
+    /// happens first. Finally switch to the next requested driver state (if
+    /// any) or turns the radio off.
     ///
     /// If a request was scheduled: Returns the driver in the requested driver
     /// state together with the corresponding response token.
@@ -399,12 +564,22 @@ where
         rx_task_response_token: ResponseToken,
         consumer_token: &mut ConsumerToken,
     ) -> (DriverState<RadioDriverImpl>, Option<ResponseToken>) {
-        // Wait until a frame is being received or the next outbound request
-        // ends the Rx window.
+        let rx_window_end = async {
+            match self.rx_window_end.get() {
+                Some(rx_window_end) => self.alarm.wait_until(Instant::new(rx_window_end)).await,
+                None => pending().await,
+            }
+        };
+
+        // Wait until a frame is being received, the next outbound request ends
+        // the Rx window or a timed Rx window ends.
         match select(
             rx_driver.frame_started(),
-            self.request_receiver
-                .wait_for_request(consumer_token, &TaskDirection::Outbound),
+            select(
+                self.request_receiver
+                    .wait_for_request(consumer_token, &self.preempting_request_direction()),
+                rx_window_end,
+            ),
         )
         .await
         {
@@ -413,19 +588,43 @@ where
                 let hardware_address = rx_driver.ieee802154_address();
                 let preliminary_frame_info = rx_driver.preliminary_frame_info().await;
                 let ifs = Ifs::from_mpdu_length(preliminary_frame_info.mpdu_length);
-                let frame_is_valid =
-                    is_frame_valid_and_for_us(&hardware_address, &preliminary_frame_info);
+                let frame_filter = self.frame_filter.get();
+                let frame_is_valid = is_frame_valid_and_for_us(
+                    &hardware_address,
+                    &frame_filter,
+                    &preliminary_frame_info,
+                );
 
                 // If the frame is valid and ACK is requested, then
                 // schedule a TX ACK task. Otherwise finalize the Rx
                 // task and receive the next task (if any).
                 if frame_is_valid {
-                    // Safety: Valid frames always have a frame control field.
-                    let ack_request = preliminary_frame_info.frame_control.unwrap().ack_request();
+                    let frame_pending = self.is_frame_pending(&preliminary_frame_info);
+                    // Frames are never acknowledged in promiscuous mode, see
+                    // IEEE 802.15.4-2020, section 6.7.2.
+                    let ack_request = !frame_filter.promiscuous_mode
+                        && preliminary_frame_info
+                            .frame_control
+                            .is_some_and(|frame_control| frame_control.ack_request());
                     let seq_nr = preliminary_frame_info.seq_nr;
-                    if ack_request && seq_nr.is_some() {
-                        self.send_ack(rx_driver, rx_task_response_token, seq_nr.unwrap(), ifs)
-                            .await
+                    if ack_request && seq_nr.is_some() && self.timeslot_timings.get().is_some() {
+                        self.send_enh_ack(
+                            rx_driver,
+                            rx_task_response_token,
+                            seq_nr.unwrap(),
+                            frame_pending,
+                            ifs,
+                        )
+                        .await
+                    } else if ack_request && seq_nr.is_some() {
+                        self.send_ack(
+                            rx_driver,
+                            rx_task_response_token,
+                            seq_nr.unwrap(),
+                            frame_pending,
+                            ifs,
+                        )
+                        .await
                     } else {
                         self.receive_frame(rx_driver, None, rx_task_response_token, ifs)
                             .await
@@ -436,10 +635,42 @@ where
                 }
             }
             // We received an outbound request.
-            Either::Second(tx_request) => {
+            Either::Second(Either::First(tx_request)) => {
                 self.end_rx_window(rx_driver, rx_task_response_token, None, Some(tx_request))
                     .await
             }
+            // The timed Rx window ended without a frame being received.
+            Either::Second(Either::Second(())) => {
+                self.rx_window_end.set(None);
+                self.end_rx_window(rx_driver, rx_task_response_token, None, None)
+                    .await
+            }
+        }
+    }
+
+    /// Whether the Frame Pending field of the ACK to the given incoming frame
+    /// is to be set, see IEEE 802.15.4-2020, section 6.7.3.
+    ///
+    /// The command ID is not known when the ACK is prepared. We therefore
+    /// announce pending frames in ACKs to all MAC commands, not only to Data
+    /// Request commands.
+    fn is_frame_pending(&self, preliminary_frame_info: &PreliminaryFrameInfo) -> bool {
+        let is_command =
+            preliminary_frame_info
+                .frame_control
+                .as_ref()
+                .is_some_and(|frame_control| {
+                    matches!(frame_control.frame_type(), FrameType::MacCommand)
+                });
+        let src_address = preliminary_frame_info
+            .addressing_fields
+            .as_ref()
+            .and_then(|addressing_fields| addressing_fields.src_address());
+        match src_address {
+            Some(src_address) if is_command => {
+                self.pending_transactions.borrow().is_pending(&src_address)
+            }
+            _ => false,
         }
     }
 
@@ -456,6 +687,7 @@ where
         rx_driver: RadioDriver<RadioDriverImpl, RadioTaskRx>,
         rx_task_response_token: ResponseToken,
         ack_seq_nr: u8,
+        frame_pending: bool,
         next_task_ifs: Ifs,
     ) -> (DriverState<RadioDriverImpl>, Option<ResponseToken>) {
         // Safety: We use the TX ACK frame sequentially and exclusively from
@@ -464,12 +696,16 @@ where
 
         let mut tx_ack_mpdu = MpduFrame::from_radio_frame(tx_ack_frame);
         let _ = tx_ack_mpdu.set_sequence_number(ack_seq_nr);
+        tx_ack_mpdu
+            .frame_control_mut()
+            .set_frame_pending(frame_pending);
         let tx_ack_frame = tx_ack_mpdu.into_radio_frame::<RadioDriverImpl>();
 
         let tx_ack_task = RadioTaskTx {
             at: Timestamp::BestEffort,
             radio_frame: tx_ack_frame,
             cca: false,
+            channel: Some(self.active_channel.get()),
         };
 
         match rx_driver
@@ -511,6 +747,144 @@ where
         }
     }
 
+    /// Finalizes ongoing frame reception, then sends an Enh-Ack macTsTxAckDelay
+    /// after the end of the received frame as required while TSCH is enabled,
+    /// see IEEE 802.15.4-2020, section 6.5.4.3. Then switches to the next
+    /// requested driver state (if any) or turns the radio off.
+    ///
+    /// Frames with CRC errors are not acknowledged and the driver is left in
+    /// the RX state.
+    ///
+    /// If a request was scheduled: Returns the driver in the requested driver
+    /// state together with the corresponding response token.
+    ///
+    /// If the radio was turned off: Returns the driver in the off state and no
+    /// response token.
+    async fn send_enh_ack(
+        &self,
+        rx_driver: RadioDriver<RadioDriverImpl, RadioTaskRx>,
+        rx_task_response_token: ResponseToken,
+        ack_seq_nr: u8,
+        frame_pending: bool,
+        next_task_ifs: Ifs,
+    ) -> (DriverState<RadioDriverImpl>, Option<ResponseToken>) {
+        // The Enh-Ack is sent much later than an immediate ACK. We therefore
+        // switch the radio off in between.
+        let (off_driver, time_correction) = match rx_driver
+            .schedule_off(
+                RadioTaskOff {
+                    at: Timestamp::BestEffort,
+                },
+                true,
+            )
+            .execute_transition()
+            .await
+        {
+            CompletedRadioTransition::Entered(transition_result) => {
+                let rx_task_result = transition_result.prev_task_result;
+                // Frames received in a timed RX window are expected at the
+                // center of the window.
+                let time_correction = match (&rx_task_result, self.expected_rx_rmarker.get()) {
+                    (RxResult::Frame(_, rx_info), Some(expected_rmarker))
+                        if RadioDriverImpl::ACCURATE_RX_TIMESTAMPS =>
+                    {
+                        rx_info.timestamp.map(|rmarker| {
+                            let time_correction: Duration<RadioDriverImpl::Timer> =
+                                Instant::new(expected_rmarker) - Instant::new(rmarker);
+                            time_correction.convert_into_rounding_down::<Microseconds>()
+                        })
+                    }
+                    _ => None,
+                };
+                let frame_end = match &rx_task_result {
+                    // The PHY header and PSDU follow the RMARKER at two
+                    // symbols per octet.
+                    RxResult::Frame(rx_frame, rx_info) => Some(rx_info.timestamp.map_or_else(
+                        RadioDriverImpl::Timer::now,
+                        |rmarker| {
+                            let psdu_duration = Duration::<SymbolsOQpsk250kB>::new(
+                                2 * rx_frame.sdu_length().get() as i64,
+                            );
+                            Instant::new(rmarker)
+                                + (PHY_HDR_DURATION + psdu_duration).convert_into_rounding_up()
+                        },
+                    )),
+                    _ => None,
+                };
+                self.request_receiver
+                    .received(rx_task_response_token, rx_task_result.into());
+
+                let off_driver = transition_result.this_state;
+                let Some(frame_end) = frame_end else {
+                    return (DriverState::Off(off_driver), None);
+                };
+
+                // Safety: The Enh-Ack is only sent while TSCH is enabled.
+                let tx_ack_delay = self
+                    .timeslot_timings
+                    .get()
+                    .unwrap_or_default()
+                    .tx_ack_delay();
+                self.alarm
+                    .wait_until(frame_end + tx_ack_delay.convert_into_rounding_up())
+                    .await;
+                (off_driver, time_correction)
+            }
+            // CRC mismatch: Leave the driver in the RX state.
+            CompletedRadioTransition::Rollback(rx_driver, rx_task_error, rx_task_result, ..) => {
+                debug_assert!(matches!(
+                    rx_task_error,
+                    RadioTaskError::Task(RxError::CrcError)
+                ));
+                debug_assert!(rx_task_result.is_none());
+
+                return (DriverState::Rx(rx_driver), Some(rx_task_response_token));
+            }
+            // Safety: Switching the radio off is infallible.
+            CompletedRadioTransition::Fallback(..) => unreachable!(),
+        };
+
+        // Safety: We use the TX Enh-Ack frame sequentially and exclusively
+        //         from this method.
+        let tx_ack_frame = self.tx_enh_ack_frame.take().unwrap();
+
+        let mut tx_ack_mpdu = MpduFrame::from_radio_frame(tx_ack_frame);
+        let _ = tx_ack_mpdu.set_sequence_number(ack_seq_nr);
+        tx_ack_mpdu
+            .frame_control_mut()
+            .set_frame_pending(frame_pending);
+        // Frames received outside of timed RX windows or without accurate
+        // timestamp cannot be corrected.
+        let pdu = tx_ack_mpdu.pdu_mut_wo_fcs();
+        if let Some(range) = enh_ack_time_correction_ie_range(pdu) {
+            if let Ok(mut time_correction_ie) = TimeCorrectionIe::new(&mut pdu[range]) {
+                time_correction_ie.set_time_correction(time_correction.unwrap_or(Duration::ZERO));
+            }
+        }
+        let tx_ack_frame = tx_ack_mpdu.into_radio_frame::<RadioDriverImpl>();
+
+        let tx_ack_task = RadioTaskTx {
+            at: Timestamp::BestEffort,
+            radio_frame: tx_ack_frame,
+            cca: false,
+            channel: Some(self.active_channel.get()),
+        };
+        match off_driver
+            .schedule_tx(tx_ack_task)
+            .execute_transition()
+            .await
+        {
+            CompletedRadioTransition::Entered(transition_result) => {
+                let tx_driver = transition_result.this_state;
+                self.send_frame(tx_driver, None, None, next_task_ifs).await
+            }
+            // Safety: Scheduling ACK cannot fall back as it does no CCA.
+            CompletedRadioTransition::Fallback(..) => unreachable!(),
+            // Safety: The Off task doesn't roll back.
+            CompletedRadioTransition::Rollback(..) => unreachable!(),
+        }
+    }
+
     /// Finalizes ongoing frame reception. Then switches to the next requested
     /// driver state (if any) or turns the radio off.
     ///
@@ -522,7 +896,7 @@ where
     async fn receive_frame(
         &self,
         rx_driver: RadioDriver<RadioDriverImpl, RadioTaskRx>,
-        rx_ack_info: Option<(RadioFrame<RadioFrameSized>, u8)>,
+        rx_ack_info: Option<(RadioFrame<RadioFrameSized>, TxInfo, u8)>,
         prev_task_response_token: ResponseToken,
         next_task_ifs: Ifs,
     ) -> (DriverState<RadioDriverImpl>, Option<ResponseToken>) {
@@ -530,36 +904,71 @@ where
             this: &DriverService<'_, RadioDriverImpl>,
             prev_task_response_token: ResponseToken,
             rx_task_result: RxResult,
-            rx_ack_info: Option<(RadioFrame<RadioFrameSized>, u8)>,
+            rx_ack_info: Option<(RadioFrame<RadioFrameSized>, TxInfo, u8)>,
         ) {
-            if let Some((tx_radio_frame, rx_task_ack_seq_nr)) = rx_ack_info {
+            if let Some((tx_radio_frame, tx_info, rx_task_ack_seq_nr)) = rx_ack_info {
                 // Expect RX ACK frame
                 let (tx_result, recovered_rx_frame) = match rx_task_result {
-                    RxResult::Frame(rx_ack_frame) => {
-                        // TODO: Support enhanced ACK.
-                        const ACK_FC_MASK: u16 = !0x1000; // Frame version 2003 or 2006
+                    RxResult::Frame(rx_ack_frame, rx_info) => {
+                        const ACK_FC_FRAME_PENDING: u16 = 0x0010;
+                        // Frame version 2003 or 2006, frame pending
+                        const ACK_FC_MASK: u16 = !(0x1000 | ACK_FC_FRAME_PENDING);
                         const ACK_FC: u16 = 0x0002; // Frame type ACK, other flags all zero
-                        let ack = if rx_ack_frame.sdu_wo_fcs_length().get() == 3 {
-                            let sdu = rx_ack_frame.sdu_ref();
-                            let fc = u16::from_le_bytes([sdu[0], sdu[1]]) & ACK_FC_MASK;
-                            fc == ACK_FC && sdu[2] == rx_task_ack_seq_nr
+                                                    // Frame type, sequence number suppression and frame
+                                                    // version
+                        const ENH_ACK_FC_MASK: u16 = 0x0007 | 0x0100 | 0x3000;
+                        // Frame type ACK, sequence number present, frame
+                        // version 2015. Enh-Acks may carry addressing fields
+                        // and IEs.
+                        const ENH_ACK_FC: u16 = 0x0002 | 0x2000;
+                        let sdu_length = rx_ack_frame.sdu_wo_fcs_length().get();
+                        let sdu = &rx_ack_frame.sdu_ref()[..sdu_length as usize];
+                        let (ack, ack_frame_pending) = if sdu_length >= 3 {
+                            let fc = u16::from_le_bytes([sdu[0], sdu[1]]);
+                            let is_ack = (sdu_length == 3 && fc & ACK_FC_MASK == ACK_FC)
+                                || fc & ENH_ACK_FC_MASK == ENH_ACK_FC;
+                            (
+                                is_ack && sdu[2] == rx_task_ack_seq_nr,
+                                fc & ACK_FC_FRAME_PENDING != 0,
+                            )
                         } else {
-                            false
+                            (false, false)
+                        };
+                        // Enh-Acks carry a time correction and may reject the
+                        // frame while still synchronizing the sender.
+                        let time_correction_ie = if ack {
+                            enh_ack_time_correction_ie_range(sdu)
+                                .and_then(|range| TimeCorrectionIe::new(&sdu[range]).ok())
+                        } else {
+                            None
                         };
                         let tx_result = if ack {
-                            TxResult::Sent(tx_radio_frame)
+                            let tx_info = TxInfo {
+                                ack: Some(rx_info),
+                                ack_mpdu: AckMpdu::new(sdu),
+                                ack_frame_pending,
+                                ack_time_correction: time_correction_ie
+                                    .as_ref()
+                                    .map(TimeCorrectionIe::time_correction),
+                                ..tx_info
+                            };
+                            if time_correction_ie.is_some_and(|ie| ie.nack()) {
+                                TxResult::Nack(tx_radio_frame, tx_info)
+                            } else {
+                                TxResult::Sent(tx_radio_frame, tx_info)
+                            }
                         } else {
-                            TxResult::Nack(tx_radio_frame)
+                            TxResult::Nack(tx_radio_frame, tx_info)
                         };
                         (tx_result, rx_ack_frame.forget_size::<RadioDriverImpl>())
                     }
-                    RxResult::FilteredFrame(recovered_rx_frame) => {
+                    RxResult::FilteredFrame(recovered_rx_frame, _) => {
                         let recovered_rx_frame =
                             recovered_rx_frame.forget_size::<RadioDriverImpl>();
-                        (TxResult::Nack(tx_radio_frame), recovered_rx_frame)
+                        (TxResult::Nack(tx_radio_frame, tx_info), recovered_rx_frame)
                     }
-                    RxResult::CrcError(recovered_rx_frame) => {
-                        (TxResult::Nack(tx_radio_frame), recovered_rx_frame)
+                    RxResult::CrcError(recovered_rx_frame, ..) => {
+                        (TxResult::Nack(tx_radio_frame, tx_info), recovered_rx_frame)
                     }
                     RxResult::RxWindowEnded(_) => unreachable!(),
                 };
@@ -575,10 +984,12 @@ where
 
         let next_request = self
             .request_receiver
-            .try_receive_request(&TaskDirection::Any);
+            .try_receive_request(&self.next_request_direction());
         match next_request {
             Some((next_response_token, next_request)) => match next_request {
-                DrvSvcRequest::Tx(tx_task) => {
+                DrvSvcRequest::Tx(tx_task)
+                    if self.can_follow_back_to_back(tx_task.at, tx_task.channel) =>
+                {
                     let tx_task_ack_seq_nr = tx_task.radio_frame.ack_seq_num();
                     let tx_task_ifs = Ifs::from_mpdu_length(tx_task.radio_frame.sdu_length().get());
                     match rx_driver
@@ -621,10 +1032,14 @@ where
                         CompletedRadioTransition::Rollback(..) => unreachable!(),
                     }
                 }
-                DrvSvcRequest::Rx(rx_task) => {
+                DrvSvcRequest::Rx(rx_task)
+                    if self.can_follow_back_to_back(rx_task.start, rx_task.channel) =>
+                {
                     // We're already receiving another request and are
                     // therefore guaranteed to make progress. Therefore
                     // scheduling RX back-to-back is ok.
+                    self.rx_window_end.set(None);
+                    self.expected_rx_rmarker.set(None);
                     match rx_driver
                         .schedule_rx(rx_task, false)
                         .execute_transition()
@@ -649,13 +1064,45 @@ where
                         CompletedRadioTransition::Fallback(..) => unreachable!(),
                     }
                 }
+                // Off and ED requests as well as timed tasks or tasks on
+                // another channel.
+                next_request => {
+                    let off_task = Self::off_task();
+                    match rx_driver
+                        .schedule_off(off_task, false)
+                        .execute_transition()
+                        .await
+                    {
+                        CompletedRadioTransition::Entered(transition_result) => {
+                            let rx_task_result = transition_result.prev_task_result;
+                            handle_rx_task_result(
+                                self,
+                                prev_task_response_token,
+                                rx_task_result,
+                                rx_ack_info,
+                            );
+
+                            self.schedule_request(
+                                transition_result.this_state,
+                                next_response_token,
+                                next_request,
+                            )
+                            .await
+                        }
+                        // Safety: The transition task was programmed to not
+                        //         roll back on CRC error.
+                        CompletedRadioTransition::Rollback(..) => unreachable!(),
+                        // Safety: Switching the radio off is infallible.
+                        CompletedRadioTransition::Fallback(..) => unreachable!(),
+                    }
+                }
             },
             None => match rx_driver
                 .schedule_off(
                     RadioTaskOff {
                         at: Timestamp::BestEffort,
                     },
-                    true,
+                    self.rollback_on_crcerror(rx_ack_info.is_some()),
                 )
                 .execute_transition()
                 .await
@@ -716,6 +1163,7 @@ where
         let rx_task = RadioTaskRx {
             start: Timestamp::BestEffort,
             radio_frame: temporary_rx_frame,
+            channel: Some(self.active_channel.get()),
         };
         match rx_driver
             .schedule_rx(rx_task, false)
@@ -725,11 +1173,12 @@ where
             CompletedRadioTransition::Entered(transition_result) => {
                 let rx_task_result = transition_result.prev_task_result;
                 let recovered_rx_frame = match rx_task_result {
-                    RxResult::Frame(invalid_frame) | RxResult::FilteredFrame(invalid_frame) => {
+                    RxResult::Frame(invalid_frame, _)
+                    | RxResult::FilteredFrame(invalid_frame, _) => {
                         invalid_frame.forget_size::<RadioDriverImpl>()
                     }
                     RxResult::RxWindowEnded(recovered_rx_frame)
-                    | RxResult::CrcError(recovered_rx_frame) => recovered_rx_frame,
+                    | RxResult::CrcError(recovered_rx_frame, ..) => recovered_rx_frame,
                 };
 
                 // Safety: Unsized frames (aka RX frames) for the same driver
@@ -763,13 +1212,13 @@ where
         &self,
         rx_driver: RadioDriver<RadioDriverImpl, RadioTaskRx>,
         prev_task_response_token: ResponseToken,
-        rx_ack_info: Option<RadioFrame<RadioFrameSized>>,
+        rx_ack_info: Option<(RadioFrame<RadioFrameSized>, TxInfo)>,
         next_request: Option<(ResponseToken, DrvSvcRequest)>,
     ) -> (DriverState<RadioDriverImpl>, Option<ResponseToken>) {
         fn handle_rx_task_result<RadioDriverImpl: DriverConfig>(
             this: &DriverService<'_, RadioDriverImpl>,
             rx_task_result: RxResult,
-            rx_ack_info: Option<RadioFrame<RadioFrameSized>>,
+            rx_ack_info: Option<(RadioFrame<RadioFrameSized>, TxInfo)>,
             prev_task_response_token: ResponseToken,
         ) {
             // It is improbable but possible that an inbound frame arrives just
@@ -778,18 +1227,18 @@ where
             //
             // Note: Well timed protocols should not experience this situation.
             let rx_radio_frame = match rx_task_result {
-                RxResult::Frame(radio_frame) | RxResult::FilteredFrame(radio_frame) => {
+                RxResult::Frame(radio_frame, _) | RxResult::FilteredFrame(radio_frame, _) => {
                     radio_frame.forget_size::<RadioDriverImpl>()
                 }
-                RxResult::RxWindowEnded(radio_frame) | RxResult::CrcError(radio_frame) => {
+                RxResult::RxWindowEnded(radio_frame) | RxResult::CrcError(radio_frame, ..) => {
                     radio_frame
                 }
             };
 
-            if let Some(tx_radio_frame) = rx_ack_info {
+            if let Some((tx_radio_frame, tx_info)) = rx_ack_info {
                 // End RX ACK window
                 this.temporary_rx_frame.set(Some(rx_radio_frame));
-                let tx_task_result = TxResult::Nack(tx_radio_frame);
+                let tx_task_result = TxResult::Nack(tx_radio_frame, tx_info);
                 this.request_receiver
                     .received(prev_task_response_token, tx_task_result.into());
             } else {
@@ -801,7 +1250,9 @@ where
         }
 
         match next_request {
-            Some((tx_task_response_token, DrvSvcRequest::Tx(tx_task))) => {
+            Some((tx_task_response_token, DrvSvcRequest::Tx(tx_task)))
+                if self.can_follow_back_to_back(tx_task.at, tx_task.channel) =>
+            {
                 let tx_task_ack_seq_nr = tx_task.radio_frame.ack_seq_num();
                 let tx_task_ifs = Ifs::from_mpdu_length(tx_task.radio_frame.sdu_length().get());
                 match rx_driver
@@ -846,9 +1297,11 @@ where
                     CompletedRadioTransition::Rollback(..) => unreachable!(),
                 }
             }
-            Some((rx_task_response_token, DrvSvcRequest::Rx(rx_task))) => {
-                let tx_task_result = if let Some(tx_radio_frame) = rx_ack_info {
-                    TxResult::Nack(tx_radio_frame)
+            Some((rx_task_response_token, DrvSvcRequest::Rx(rx_task)))
+                if self.can_follow_back_to_back(rx_task.start, rx_task.channel) =>
+            {
+                let tx_task_result = if let Some((tx_radio_frame, tx_info)) = rx_ack_info {
+                    TxResult::Nack(tx_radio_frame, tx_info)
                 } else {
                     // Safety: We only ever end an RX window with another RX task
                     //         after an RX ACK window timed out.
@@ -858,15 +1311,17 @@ where
                 // Continue the ongoing reception and recover the temporary
                 // frame from the incoming RX task instead.
                 self.temporary_rx_frame.set(Some(rx_task.radio_frame));
+                self.rx_window_end.set(None);
+                self.expected_rx_rmarker.set(None);
 
                 self.request_receiver
                     .received(prev_task_response_token, tx_task_result.into());
                 (DriverState::Rx(rx_driver), Some(rx_task_response_token))
             }
-            None => {
-                let off_task = RadioTaskOff {
-                    at: Timestamp::BestEffort,
-                };
+            // Off and ED requests as well as timed tasks or tasks on another
+            // channel.
+            next_request => {
+                let off_task = Self::off_task();
                 match rx_driver
                     .schedule_off(off_task, false)
                     .execute_transition()
@@ -882,7 +1337,13 @@ where
                         );
 
                         let off_driver = transition_result.this_state;
-                        (DriverState::Off(off_driver), None)
+                        match next_request {
+                            Some((next_response_token, next_request)) => {
+                                self.schedule_request(off_driver, next_response_token, next_request)
+                                    .await
+                            }
+                            None => (DriverState::Off(off_driver), None),
+                        }
                     }
                     // Safety: Switching the driver off from an RX state
                     //         w/o rollback should be infallible.
@@ -921,11 +1382,9 @@ where
                 // Tx ACK: recover the pre-allocated ACK frame.
                 debug_assert!(ack_seq_nr.is_none());
                 match tx_task_result {
-                    TxResult::Sent(radio_frame) => {
-                        this.tx_ack_frame.set(Some(radio_frame));
-                    }
+                    TxResult::Sent(radio_frame, _) => this.recover_tx_ack_frame(radio_frame),
                     // Safety: Ack frames don't ask for ACK.
-                    TxResult::Nack(_) => unreachable!(),
+                    TxResult::Nack(..) => unreachable!(),
                 }
             }
         }
@@ -940,10 +1399,12 @@ where
 
         let next_request = self
             .request_receiver
-            .try_receive_request(&TaskDirection::Any);
+            .try_receive_request(&self.next_request_direction());
         match next_request {
             Some((next_response_token, next_request)) => match next_request {
-                DrvSvcRequest::Tx(tx_task) => {
+                DrvSvcRequest::Tx(tx_task)
+                    if self.can_follow_back_to_back(tx_task.at, tx_task.channel) =>
+                {
                     let tx_task_ack_seq_nr = tx_task.radio_frame.ack_seq_num();
                     let tx_task_ifs = Ifs::from_mpdu_length(tx_task.radio_frame.sdu_length().get());
                     match tx_driver
@@ -977,11 +1438,11 @@ where
                             } else {
                                 // Tx ACK: recover the pre-allocated ACK frame.
                                 match tx_task_result {
-                                    TxResult::Sent(radio_frame) => {
-                                        self.tx_ack_frame.set(Some(radio_frame));
+                                    TxResult::Sent(radio_frame, _) => {
+                                        self.recover_tx_ack_frame(radio_frame)
                                     }
                                     // Safety: Ack frames don't ask for ACK.
-                                    TxResult::Nack(_) => unreachable!(),
+                                    TxResult::Nack(..) => unreachable!(),
                                 }
                             }
 
@@ -996,7 +1457,11 @@ where
                         CompletedRadioTransition::Rollback(..) => unreachable!(),
                     }
                 }
-                DrvSvcRequest::Rx(rx_task) => {
+                DrvSvcRequest::Rx(rx_task)
+                    if self.can_follow_back_to_back(rx_task.start, rx_task.channel) =>
+                {
+                    self.rx_window_end.set(None);
+                    self.expected_rx_rmarker.set(None);
                     match tx_driver
                         .schedule_rx(rx_task, next_task_ifs)
                         .execute_transition()
@@ -1021,6 +1486,33 @@ where
                         CompletedRadioTransition::Fallback(..) => unreachable!(),
                     }
                 }
+                // Off and ED requests as well as timed tasks or tasks on
+                // another channel.
+                next_request => {
+                    let off_task = Self::off_task();
+                    match tx_driver.schedule_off(off_task).execute_transition().await {
+                        CompletedRadioTransition::Entered(transition_result) => {
+                            let tx_task_result = transition_result.prev_task_result;
+                            handle_tx_task_result(
+                                self,
+                                tx_task_response_token,
+                                tx_task_result,
+                                ack_seq_nr,
+                            )
+                            .await;
+
+                            self.schedule_request(
+                                transition_result.this_state,
+                                next_response_token,
+                                next_request,
+                            )
+                            .await
+                        }
+                        // Safety: Switching the driver off from a TX state
+                        //         should be infallible.
+                        _ => unreachable!(),
+                    }
+                }
             },
             None => {
                 match tx_driver
@@ -1064,20 +1556,21 @@ where
         let rx_ack_task = RadioTaskRx {
             start: Timestamp::BestEffort,
             radio_frame: rx_ack_frame,
+            channel: Some(self.active_channel.get()),
         };
-        let (mut rx_driver, tx_radio_frame) = match tx_driver
+        let (mut rx_driver, tx_radio_frame, tx_info) = match tx_driver
             .schedule_rx(rx_ack_task, Ifs::Aifs)
             .execute_transition()
             .await
         {
             CompletedRadioTransition::Entered(transition_result) => {
                 let tx_task_result = transition_result.prev_task_result;
-                let tx_radio_frame = match tx_task_result {
-                    TxResult::Sent(tx_radio_frame) => tx_radio_frame,
-                    TxResult::Nack(_) => unreachable!(),
+                let (tx_radio_frame, tx_info) = match tx_task_result {
+                    TxResult::Sent(tx_radio_frame, tx_info) => (tx_radio_frame, tx_info),
+                    TxResult::Nack(..) => unreachable!(),
                 };
                 let rx_driver = transition_result.this_state;
-                (rx_driver, tx_radio_frame)
+                (rx_driver, tx_radio_frame, tx_info)
             }
             // Safety: The TX task doesn't roll back.
             CompletedRadioTransition::Rollback(..) => unreachable!(),
@@ -1088,9 +1581,19 @@ where
         // Note: This is just a rough estimate with some safety margin for now.
         //       Precise timing requires timestamp and RX window support in the
         //       driver.
-        let timeout = RadioDriverImpl::Timer::wait_for_alarm_at(
-            RadioDriverImpl::Timer::now() + Self::DRIVER_RX_ACK_TIMEOUT,
-        );
+        let ack_timeout = match self.timeslot_timings.get() {
+            // Enh-Acks are expected within macTsAckWait after macTsRxAckDelay,
+            // see IEEE 802.15.4-2020, section 6.5.4.3.
+            Some(timeslot_timings) => {
+                (timeslot_timings.rx_ack_delay() + timeslot_timings.ack_wait())
+                    .convert_into_rounding_up()
+                    + (PHY_SHR_DURATION + PHY_HDR_DURATION).convert_into_rounding_up()
+            }
+            None => Self::DRIVER_RX_ACK_TIMEOUT,
+        };
+        let timeout = self
+            .alarm
+            .wait_until(RadioDriverImpl::Timer::now() + ack_timeout);
 
         let next_task_ifs = Ifs::from_mpdu_length(tx_radio_frame.sdu_length().get());
         match select(rx_driver.frame_started(), timeout).await {
@@ -1098,7 +1601,7 @@ where
                 // Receive and validate the incoming frame.
                 self.receive_frame(
                     rx_driver,
-                    Some((tx_radio_frame, ack_seq_nr)),
+                    Some((tx_radio_frame, tx_info, ack_seq_nr)),
                     tx_task_response_token,
                     next_task_ifs,
                 )
@@ -1108,11 +1611,11 @@ where
                 // Timeout
                 let next_request = self
                     .request_receiver
-                    .try_receive_request(&TaskDirection::Any);
+                    .try_receive_request(&self.next_request_direction());
                 self.end_rx_window(
                     rx_driver,
                     tx_task_response_token,
-                    Some(tx_radio_frame),
+                    Some((tx_radio_frame, tx_info)),
                     next_request,
                 )
                 .await
@@ -1120,7 +1623,8 @@ where
         }
     }
 
-    /// Waits for the next request to arrive and then schedules it.
+    /// Waits for the next request to arrive and schedules it from the off
+    /// state.
     ///
     /// Returns the driver in the requested driver state together with the
     /// corresponding response token.
@@ -1132,45 +1636,245 @@ where
         loop {
             let (next_response_token, next_request) = self
                 .request_receiver
-                .wait_for_request(consumer_token, &TaskDirection::Any)
+                .wait_for_request(consumer_token, &self.next_request_direction())
                 .await;
-            match next_request {
-                DrvSvcRequest::Tx(tx_task) => {
-                    let tx_task_ack_seq_nr = tx_task.radio_frame.ack_seq_num();
-                    let tx_task_ifs = Ifs::from_mpdu_length(tx_task.radio_frame.sdu_length().get());
-                    match off_driver.schedule_tx(tx_task).execute_transition().await {
-                        CompletedRadioTransition::Entered(transition_result) => {
-                            let tx_driver = transition_result.this_state;
-                            break (
-                                DriverState::Tx(tx_driver, tx_task_ack_seq_nr, tx_task_ifs),
-                                next_response_token,
-                            );
-                        }
-                        CompletedRadioTransition::Fallback(transition_result, tx_task_error) => {
-                            // Send back the result of the failed transition.
-                            self.request_receiver
-                                .received(next_response_token, tx_task_error.into());
+            match self
+                .schedule_request(off_driver, next_response_token, next_request)
+                .await
+            {
+                (driver_state, Some(response_token)) => break (driver_state, response_token),
+                // Wait for the next request.
+                (DriverState::Off(driver), None) => off_driver = driver,
+                // Safety: Only the off state comes without response token.
+                _ => unreachable!(),
+            }
+        }
+    }
 
-                            // Wait for the next request.
-                            off_driver = transition_result.this_state;
-                            continue;
-                        }
-                        // Safety: The Off task doesn't roll back.
-                        CompletedRadioTransition::Rollback(..) => unreachable!(),
+    /// Delays timed requests until they are due, then applies the current
+    /// default channel and TX power and schedules the given request. Off requests are confirmed immediately as the radio is
+    /// already off. ED requests are executed right away as the radio returns to
+    /// the off state afterwards.
+    ///
+    /// If a TX or RX task was scheduled: Returns the driver in the requested
+    /// driver state together with the corresponding response token.
+    ///
+    /// Otherwise: Returns the driver in the off state and no response token.
+    async fn schedule_request(
+        &self,
+        mut off_driver: RadioDriver<RadioDriverImpl, RadioTaskOff>,
+        response_token: ResponseToken,
+        request: DrvSvcRequest,
+    ) -> (DriverState<RadioDriverImpl>, Option<ResponseToken>) {
+        let request = self.delay_timed_request(request).await;
+        off_driver.set_channel(self.channel.get());
+        off_driver.set_tx_power(self.tx_power.get());
+        match request {
+            DrvSvcRequest::Tx(tx_task) => {
+                self.active_channel
+                    .set(tx_task.channel.unwrap_or(self.channel.get()));
+                let tx_task_ack_seq_nr = tx_task.radio_frame.ack_seq_num();
+                let tx_task_ifs = Ifs::from_mpdu_length(tx_task.radio_frame.sdu_length().get());
+                match off_driver.schedule_tx(tx_task).execute_transition().await {
+                    CompletedRadioTransition::Entered(transition_result) => {
+                        let tx_driver = transition_result.this_state;
+                        (
+                            DriverState::Tx(tx_driver, tx_task_ack_seq_nr, tx_task_ifs),
+                            Some(response_token),
+                        )
+                    }
+                    CompletedRadioTransition::Fallback(transition_result, tx_task_error) => {
+                        // Send back the result of the failed transition.
+                        self.request_receiver
+                            .received(response_token, tx_task_error.into());
+                        (DriverState::Off(transition_result.this_state), None)
                     }
+                    // Safety: The Off task doesn't roll back.
+                    CompletedRadioTransition::Rollback(..) => unreachable!(),
                 }
-                DrvSvcRequest::Rx(rx_task) => {
-                    match off_driver.schedule_rx(rx_task).execute_transition().await {
-                        CompletedRadioTransition::Entered(transition_result) => {
-                            let rx_driver = transition_result.this_state;
-                            break (DriverState::Rx(rx_driver), next_response_token);
-                        }
-                        // Safety: The Off task doesn't roll back.
-                        CompletedRadioTransition::Rollback(..) => unreachable!(),
-                        // Safety: Scheduling an RX task doesn't fall back.
-                        CompletedRadioTransition::Fallback(..) => unreachable!(),
+            }
+            DrvSvcRequest::Rx(rx_task) => {
+                self.active_channel
+                    .set(rx_task.channel.unwrap_or(self.channel.get()));
+                match off_driver.schedule_rx(rx_task).execute_transition().await {
+                    CompletedRadioTransition::Entered(transition_result) => {
+                        let rx_driver = transition_result.this_state;
+                        (DriverState::Rx(rx_driver), Some(response_token))
                     }
+                    // Safety: The Off task doesn't roll back.
+                    CompletedRadioTransition::Rollback(..) => unreachable!(),
+                    // Safety: Scheduling an RX task doesn't fall back.
+                    CompletedRadioTransition::Fallback(..) => unreachable!(),
+                }
+            }
+            DrvSvcRequest::Off(_) => {
+                self.request_receiver
+                    .received(response_token, OffResult::Off.into());
+                (DriverState::Off(off_driver), None)
+            }
+            DrvSvcRequest::Ed(ed_task) => {
+                let off_driver = self
+                    .detect_energy(off_driver, ed_task, response_token)
+                    .await;
+                (DriverState::Off(off_driver), None)
+            }
+        }
+    }
+
+    /// Whether a TX or RX task with the given timestamp and channel can be
+    /// scheduled back-to-back to the ongoing task, i.e. is untimed and uses the
+    /// same channel. Other tasks are scheduled once the radio was switched off.
+    fn can_follow_back_to_back(&self, timestamp: Timestamp, channel: Option<RadioChannel>) -> bool {
+        timestamp == Timestamp::BestEffort
+            && channel.unwrap_or(self.channel.get()) == self.active_channel.get()
+    }
+
+    /// The task switching the radio off before the next request is scheduled
+    /// from the off state.
+    ///
+    /// Drivers don't support timed off tasks, yet. Timed requests are
+    /// therefore delayed by [`Self::delay_timed_request`] once the radio is
+    /// off.
+    fn off_task() -> RadioTaskOff {
+        RadioTaskOff {
+            at: Timestamp::BestEffort,
+        }
+    }
+
+    /// Polyfills timed requests in software: Waits until the given request is
+    /// due, then returns it as a best effort request.
+    ///
+    /// TX requests are timestamped with their RMARKER and therefore started
+    /// the SHR duration earlier. Timed RX windows are centered on the expected
+    /// RMARKER, last macTsRxWait and are ended by [`Self::try_receive_frame`]
+    /// unless a frame is being received.
+    async fn delay_timed_request(&self, request: DrvSvcRequest) -> DrvSvcRequest {
+        let shr_duration: Duration<RadioDriverImpl::Timer> =
+            PHY_SHR_DURATION.convert_into_rounding_up();
+        match request {
+            DrvSvcRequest::Tx(mut tx_task) => {
+                if let Timestamp::Scheduled(at) = tx_task.at {
+                    self.alarm.wait_until(Instant::new(at) - shr_duration).await;
+                    tx_task.at = Timestamp::BestEffort;
                 }
+                DrvSvcRequest::Tx(tx_task)
+            }
+            DrvSvcRequest::Rx(mut rx_task) => {
+                if let Timestamp::Scheduled(start) = rx_task.start {
+                    let rx_wait: Duration<RadioDriverImpl::Timer> = self
+                        .timeslot_timings
+                        .get()
+                        .unwrap_or_default()
+                        .rx_wait()
+                        .convert_into_rounding_up();
+                    let half_rx_wait = rx_wait.ticks() / 2;
+                    let rx_window_end = Instant::<RadioDriverImpl::Timer>::new(start)
+                        + Duration::new(half_rx_wait)
+                        + PHY_HDR_DURATION.convert_into_rounding_up();
+                    self.rx_window_end.set(Some(rx_window_end.tick()));
+                    self.expected_rx_rmarker.set(Some(start));
+                    self.alarm
+                        .wait_until(
+                            Instant::new(start) - shr_duration - Duration::new(half_rx_wait),
+                        )
+                        .await;
+                    rx_task.start = Timestamp::BestEffort;
+                } else {
+                    self.rx_window_end.set(None);
+                    self.expected_rx_rmarker.set(None);
+                }
+                DrvSvcRequest::Rx(rx_task)
+            }
+            DrvSvcRequest::Off(mut off_task) => {
+                if let Timestamp::Scheduled(at) = off_task.at {
+                    self.alarm.wait_until(Instant::new(at)).await;
+                    off_task.at = Timestamp::BestEffort;
+                }
+                DrvSvcRequest::Off(off_task)
+            }
+            DrvSvcRequest::Ed(mut ed_task) => {
+                if let Timestamp::Scheduled(start) = ed_task.start {
+                    self.alarm.wait_until(Instant::new(start)).await;
+                    ed_task.start = Timestamp::BestEffort;
+                }
+                DrvSvcRequest::Ed(ed_task)
+            }
+        }
+    }
+
+    /// The requests served next: Only timed requests are served while TSCH is
+    /// enabled.
+    fn next_request_direction(&self) -> TaskDirection {
+        if self.timeslot_timings.get().is_some() {
+            TaskDirection::Timed
+        } else {
+            TaskDirection::Any
+        }
+    }
+
+    /// The requests ending an ongoing RX window: Outbound requests and RX
+    /// requests on another channel make progress before inbound requests on
+    /// the same channel unless TSCH is enabled. Then the next timed request
+    /// ends the RX window.
+    fn preempting_request_direction(&self) -> TaskDirection {
+        if self.timeslot_timings.get().is_some() {
+            TaskDirection::Timed
+        } else {
+            TaskDirection::Preempting {
+                active_channel: self.active_channel.get(),
+                default_channel: self.channel.get(),
+            }
+        }
+    }
+
+    /// Measures the energy on the requested or default channel, switches the
+    /// radio off again and responds with the measured energy level.
+    async fn detect_energy(
+        &self,
+        mut off_driver: RadioDriver<RadioDriverImpl, RadioTaskOff>,
+        ed_task: DrvSvcTaskEd,
+        response_token: ResponseToken,
+    ) -> RadioDriver<RadioDriverImpl, RadioTaskOff> {
+        off_driver.set_channel(self.channel.get());
+
+        let ed_driver = match off_driver.schedule_ed(ed_task).execute_transition().await {
+            CompletedRadioTransition::Entered(transition_result) => transition_result.this_state,
+            CompletedRadioTransition::Fallback(transition_result, _) => {
+                self.request_receiver.received(
+                    response_token,
+                    DrvSvcResponse::Ed(Err(DrvSvcTaskError::RadioError)),
+                );
+                return transition_result.this_state;
+            }
+            // Safety: The Off task doesn't roll back.
+            CompletedRadioTransition::Rollback(..) => unreachable!(),
+        };
+
+        let off_task = RadioTaskOff {
+            at: Timestamp::BestEffort,
+        };
+        match ed_driver.schedule_off(off_task).execute_transition().await {
+            CompletedRadioTransition::Entered(transition_result) => {
+                self.request_receiver
+                    .received(response_token, transition_result.prev_task_result.into());
+                transition_result.this_state
+            }
+            // Safety: The ED task doesn't roll back and switching the radio off
+            //         is infallible.
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> DriverService<'_, RadioDriverImpl> {
+    /// Recovers a sent pre-allocated ACK or Enh-Ack frame. Only one of both
+    /// frames is in flight at a time.
+    fn recover_tx_ack_frame(&self, radio_frame: RadioFrame<RadioFrameSized>) {
+        match self.tx_ack_frame.take() {
+            None => self.tx_ack_frame.set(Some(radio_frame)),
+            tx_ack_frame => {
+                self.tx_ack_frame.set(tx_ack_frame);
+                self.tx_enh_ack_frame.set(Some(radio_frame));
             }
         }
     }
@@ -4,8 +4,8 @@ pub mod mac;
 
 use dot15d4_driver::{
     tasks::{
-        OffState, RxState, TaskOff as RadioTaskOff, TaskRx as RadioTaskRx, TaskTx as RadioTaskTx,
-        TxState,
+        EdState, OffState, RxState, TaskEd as RadioTaskEd, TaskOff as RadioTaskOff,
+        TaskRx as RadioTaskRx, TaskTx as RadioTaskTx, TxState,
     },
     RadioDriverApi,
 };
@@ -15,36 +15,69 @@ pub mod export {
     pub use rand_core::RngCore;
 }
 
+use core::cell::{Cell, RefCell};
+
 use rand_core::RngCore;
 
 use self::{
     driver::{
+        alarm::AlarmMultiplexer,
+        config::Channel,
+        frame::FrameFilter,
         tasks::{RadioDriver, TaskOff},
         DriverConfig, DriverRequestChannel, DriverService,
     },
-    mac::{MacBufferAllocator, MacIndicationSender, MacRequestReceiver, MacService},
+    mac::{
+        primitives::{SequentialShortAddressAllocator, ShortAddressAllocator},
+        MacBufferAllocator, MacIndicationSender, MacRequestReceiver, MacService,
+        PendingTransactions,
+    },
     util::sync::{mutex::Mutex, select, Either},
 };
 
-pub struct Device<RadioDriverImpl: DriverConfig, Rng> {
+pub struct Device<
+    RadioDriverImpl: DriverConfig,
+    Rng,
+    Allocator: ShortAddressAllocator = SequentialShortAddressAllocator,
+> {
     radio: RadioDriver<RadioDriverImpl, TaskOff>,
     rng: Mutex<Rng>,
+    /// Allocates short addresses to devices associating with this device
+    /// when acting as a coordinator.
+    short_address_allocator: Allocator,
 }
 
 impl<RadioDriverImpl: DriverConfig, Rng: RngCore> Device<RadioDriverImpl, Rng> {
     pub fn new(radio: RadioDriver<RadioDriverImpl, TaskOff>, rng: Rng) -> Self {
+        Self::with_short_address_allocator(radio, rng, SequentialShortAddressAllocator::default())
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig, Rng: RngCore, Allocator: ShortAddressAllocator>
+    Device<RadioDriverImpl, Rng, Allocator>
+{
+    /// Creates a device that allocates short addresses to associating devices
+    /// with the given allocator.
+    pub fn with_short_address_allocator(
+        radio: RadioDriver<RadioDriverImpl, TaskOff>,
+        rng: Rng,
+        short_address_allocator: Allocator,
+    ) -> Self {
         Self {
             radio,
             rng: Mutex::new(rng),
+            short_address_allocator,
         }
     }
 }
 
-impl<RadioDriverImpl: DriverConfig, Rng: RngCore> Device<RadioDriverImpl, Rng>
+impl<RadioDriverImpl: DriverConfig, Rng: RngCore, Allocator: ShortAddressAllocator>
+    Device<RadioDriverImpl, Rng, Allocator>
 where
     RadioDriver<RadioDriverImpl, RadioTaskOff>: OffState<RadioDriverImpl> + RadioDriverApi,
     RadioDriver<RadioDriverImpl, RadioTaskRx>: RxState<RadioDriverImpl> + RadioDriverApi,
     RadioDriver<RadioDriverImpl, RadioTaskTx>: TxState<RadioDriverImpl> + RadioDriverApi,
+    RadioDriver<RadioDriverImpl, RadioTaskEd>: EdState<RadioDriverImpl>,
 {
     pub async fn run<'upper_layer>(
         mut self,
@@ -56,10 +89,24 @@ where
         self::trace::instrument();
 
         let driver_service_channel = DriverRequestChannel::new();
+        let frame_filter = Cell::new(FrameFilter::default());
+        let channel = Cell::new(Channel::default());
+        let tx_power = Cell::new(0);
+        let pending_transactions = RefCell::new(PendingTransactions::new());
+        let timeslot_timings = Cell::new(None);
+        // The MAC and the driver service share the alarm of the radio timer.
+        let alarms = AlarmMultiplexer::<RadioDriverImpl::Timer, 2>::new();
+        let [driver_service_alarm, mac_service_alarm] = alarms.alarms();
         let driver_service = DriverService::new(
             self.radio,
             driver_service_channel.receiver(),
             buffer_allocator,
+            &frame_filter,
+            &channel,
+            &tx_power,
+            &pending_transactions,
+            &timeslot_timings,
+            driver_service_alarm,
         );
         let mut mac_service = MacService::<'_, Rng, RadioDriverImpl>::new(
             &mut self.rng,
@@ -67,11 +114,24 @@ where
             request_receiver,
             indication_sender,
             driver_service_channel.sender(),
+            &frame_filter,
+            &channel,
+            &tx_power,
+            &pending_transactions,
+            &timeslot_timings,
+            mac_service_alarm,
+            &mut self.short_address_allocator,
         );
 
-        match select::select(mac_service.run(), driver_service.run()).await {
+        match select::select(
+            mac_service.run(),
+            select::select(driver_service.run(), alarms.run()),
+        )
+        .await
+        {
             Either::First(_) => panic!("MAC service terminated"),
-            Either::Second(_) => panic!("Driver service terminated"),
+            Either::Second(Either::First(_)) => panic!("Driver service terminated"),
+            Either::Second(Either::Second(_)) => panic!("Alarm multiplexer terminated"),
         }
     }
 
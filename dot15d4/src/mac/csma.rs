@@ -0,0 +1,126 @@
+//! Unslotted CSMA-CA, see IEEE 802.15.4-2020, section 6.2.5.1.
+
+use rand_core::RngCore;
+
+use crate::{
+    driver::{
+        constants::MAC_UNIT_BACKOFF_PERIOD,
+        time::{Duration, Frequency},
+    },
+    mac::pib::Pib,
+    util::sync::mutex::Mutex,
+};
+
+/// Object-safe access to the MAC service's random number generator.
+///
+/// This lets MAC tasks draw random backoffs without having to be generic over
+/// the RNG type.
+pub(crate) trait BackoffRng {
+    fn next_u32(&self) -> u32;
+}
+
+impl<Rng: RngCore> BackoffRng for Mutex<Rng> {
+    fn next_u32(&self) -> u32 {
+        // Safety: The RNG is exclusively used from within the MAC service which
+        //         never holds the lock across await points.
+        self.try_lock().expect("RNG locked").next_u32()
+    }
+}
+
+/// State of a single unslotted CSMA-CA run.
+///
+/// The algorithm is initialized once per frame transmission attempt with NB = 0
+/// and BE = macMinBe. Each time the channel is assessed busy, NB and BE are
+/// incremented until macMaxCsmaBackoffs is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CsmaCa {
+    /// The number of times the CSMA-CA algorithm was required to back off
+    /// while attempting the current transmission.
+    nb: u8,
+    /// The backoff exponent.
+    be: u8,
+    /// macMaxBe
+    max_be: u8,
+    /// macMaxCsmaBackoffs
+    max_csma_backoffs: u8,
+}
+
+impl CsmaCa {
+    pub fn new(min_be: u8, max_be: u8, max_csma_backoffs: u8) -> Self {
+        debug_assert!(min_be <= max_be);
+        Self {
+            nb: 0,
+            be: min_be,
+            max_be,
+            max_csma_backoffs,
+        }
+    }
+
+    /// Initializes the algorithm from the current PIB attributes.
+    pub fn from_pib(pib: &Pib) -> Self {
+        Self::new(pib.min_be, pib.max_be, pib.max_csma_backoffs)
+    }
+
+    /// Calculates a random delay of (0..2^BE - 1) unit backoff periods from the
+    /// given random number.
+    pub fn backoff<F: Frequency>(&self, random: u32) -> Duration<F> {
+        let backoff_periods = random & ((1u32 << self.be) - 1);
+        (MAC_UNIT_BACKOFF_PERIOD * backoff_periods as usize).convert_into_rounding_up()
+    }
+
+    /// Updates NB and BE after the channel was assessed busy.
+    ///
+    /// Returns `false` if the maximum number of backoffs was exceeded, i.e. the
+    /// algorithm terminates with a channel access failure.
+    pub fn channel_busy(&mut self) -> bool {
+        self.nb += 1;
+        self.be = (self.be + 1).min(self.max_be);
+        self.nb <= self.max_csma_backoffs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::driver::time::SymbolsOQpsk250kB;
+
+    #[test]
+    fn backoff_exponent() {
+        let mut csma = CsmaCa::new(3, 5, 4);
+        assert_eq!(csma.be, 3);
+
+        assert!(csma.channel_busy());
+        assert_eq!((csma.nb, csma.be), (1, 4));
+        assert!(csma.channel_busy());
+        assert_eq!((csma.nb, csma.be), (2, 5));
+        assert!(csma.channel_busy());
+        assert_eq!((csma.nb, csma.be), (3, 5));
+        assert!(csma.channel_busy());
+        assert_eq!((csma.nb, csma.be), (4, 5));
+
+        // macMaxCsmaBackoffs exceeded
+        assert!(!csma.channel_busy());
+    }
+
+    #[test]
+    fn no_backoffs() {
+        let mut csma = CsmaCa::new(0, 0, 0);
+        assert_eq!(csma.backoff::<SymbolsOQpsk250kB>(u32::MAX), Duration::ZERO);
+        assert!(!csma.channel_busy());
+    }
+
+    #[test]
+    fn backoff_range() {
+        let csma = CsmaCa::new(3, 5, 4);
+        assert_eq!(csma.backoff::<SymbolsOQpsk250kB>(0), Duration::ZERO);
+        assert_eq!(
+            csma.backoff::<SymbolsOQpsk250kB>(u32::MAX),
+            MAC_UNIT_BACKOFF_PERIOD * 7
+        );
+        assert_eq!(
+            csma.backoff::<SymbolsOQpsk250kB>(0b1010),
+            MAC_UNIT_BACKOFF_PERIOD * 2
+        );
+    }
+}
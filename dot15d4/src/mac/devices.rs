@@ -0,0 +1,361 @@
+//! Devices associated with this coordinator.
+
+use crate::driver::frame::{ExtendedAddress, ShortAddress};
+
+use super::mlme::associate::CapabilityInformation;
+
+/// The max number of devices that may be associated with this coordinator.
+pub const MAC_MAX_ASSOCIATED_DEVICES: usize = 16;
+
+/// The short address allocated to devices that communicate with their
+/// extended address only.
+const SHORT_ADDRESS_NONE: u16 = 0xfffe;
+
+/// A device associated with this coordinator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssociatedDevice {
+    pub extended_address: ExtendedAddress<[u8; 8]>,
+    /// The short address allocated to the device, 0xfffe if the device uses
+    /// its extended address only.
+    pub short_address: ShortAddress<[u8; 2]>,
+    /// The capability information announced by the device when associating.
+    pub capability_information: CapabilityInformation,
+}
+
+/// A short address reserved for a device while its association is in
+/// progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reservation {
+    extended_address: ExtendedAddress<[u8; 8]>,
+    short_address: ShortAddress<[u8; 2]>,
+    /// The tick at which the reservation lapses, `None` while an Association
+    /// Response is in flight.
+    expiry: Option<u64>,
+}
+
+/// The table of devices associated with this coordinator.
+///
+/// Short addresses proposed to associating devices are reserved until the
+/// device is recorded or its association fails, so that they are not
+/// proposed twice.
+#[derive(Debug, Default)]
+pub struct AssociatedDevices {
+    devices: heapless::Vec<AssociatedDevice, MAC_MAX_ASSOCIATED_DEVICES>,
+    reservations: heapless::Vec<Reservation, MAC_MAX_ASSOCIATED_DEVICES>,
+}
+
+impl AssociatedDevices {
+    pub const fn new() -> Self {
+        Self {
+            devices: heapless::Vec::new(),
+            reservations: heapless::Vec::new(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AssociatedDevice> {
+        self.devices.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    /// Whether there is no room for another device, counting devices whose
+    /// association is in progress.
+    pub fn is_full(&self) -> bool {
+        self.devices.len() + self.reservations.len() >= MAC_MAX_ASSOCIATED_DEVICES
+    }
+
+    /// Whether the given short address is allocated to an associated device
+    /// or reserved for an associating device.
+    pub fn is_short_address_in_use(&self, short_address: &ShortAddress<[u8; 2]>) -> bool {
+        self.get_by_short_address(short_address).is_some()
+            || self
+                .reservations
+                .iter()
+                .any(|reservation| reservation.short_address == *short_address)
+    }
+
+    /// Looks up a device by its extended address.
+    pub fn get(&self, extended_address: &ExtendedAddress<[u8; 8]>) -> Option<&AssociatedDevice> {
+        self.iter()
+            .find(|device| device.extended_address == *extended_address)
+    }
+
+    /// Looks up a device by its short address.
+    pub fn get_by_short_address(
+        &self,
+        short_address: &ShortAddress<[u8; 2]>,
+    ) -> Option<&AssociatedDevice> {
+        if short_address.into_u16() == SHORT_ADDRESS_NONE {
+            return None;
+        }
+
+        self.iter()
+            .find(|device| device.short_address == *short_address)
+    }
+
+    /// Adds the given device to the table or updates its entry if it re-
+    /// associated.
+    ///
+    /// Returns the device if the table is full.
+    pub fn insert(&mut self, device: AssociatedDevice) -> Result<(), AssociatedDevice> {
+        match self
+            .devices
+            .iter_mut()
+            .find(|listed| listed.extended_address == device.extended_address)
+        {
+            Some(listed) => {
+                *listed = device;
+                Ok(())
+            }
+            None => self.devices.push(device),
+        }
+    }
+
+    /// Removes a device from the table, e.g. once it disassociated.
+    pub fn remove(
+        &mut self,
+        extended_address: &ExtendedAddress<[u8; 8]>,
+    ) -> Option<AssociatedDevice> {
+        let index = self
+            .devices
+            .iter()
+            .position(|device| device.extended_address == *extended_address)?;
+        Some(self.devices.swap_remove(index))
+    }
+
+    /// Returns the short address reserved for the given device, if any.
+    pub(crate) fn reserved_short_address(
+        &self,
+        extended_address: &ExtendedAddress<[u8; 8]>,
+    ) -> Option<ShortAddress<[u8; 2]>> {
+        self.reservations
+            .iter()
+            .find(|reservation| reservation.extended_address == *extended_address)
+            .map(|reservation| reservation.short_address)
+    }
+
+    /// Reserves the given short address for the given device until the given
+    /// tick or until released if `None`. Replaces an earlier reservation of
+    /// the device.
+    ///
+    /// Short addresses not allocated by the coordinator are not reserved.
+    pub(crate) fn reserve(
+        &mut self,
+        extended_address: ExtendedAddress<[u8; 8]>,
+        short_address: ShortAddress<[u8; 2]>,
+        expiry: Option<u64>,
+    ) {
+        if short_address.into_u16() >= SHORT_ADDRESS_NONE {
+            return;
+        }
+
+        self.release(&extended_address);
+        // The capacity is checked before proposing an address. A full table
+        // only rejects reservations of devices that will not fit anyway.
+        let _ = self.reservations.push(Reservation {
+            extended_address,
+            short_address,
+            expiry,
+        });
+    }
+
+    /// Releases the reservation of the given device, if any.
+    pub(crate) fn release(&mut self, extended_address: &ExtendedAddress<[u8; 8]>) {
+        self.reservations
+            .retain(|reservation| reservation.extended_address != *extended_address);
+    }
+
+    /// Releases reservations that lapsed at the given tick.
+    pub(crate) fn release_expired(&mut self, now: u64) {
+        self.reservations
+            .retain(|reservation| reservation.expiry.is_none_or(|expiry| expiry > now));
+    }
+}
+
+/// Allocates short addresses to devices associating with this coordinator.
+///
+/// The MAC service proposes the allocated address to the next higher layer
+/// with the MLME-ASSOCIATE.indication. Implement this trait to plug in a
+/// custom addressing scheme.
+pub trait ShortAddressAllocator {
+    /// Allocates a short address for the given device. Devices already in the
+    /// table or with a reserved short address keep their short address and
+    /// will not be passed in.
+    ///
+    /// The allocated address SHALL NOT be in use, see
+    /// [`AssociatedDevices::is_short_address_in_use()`], and SHALL differ from
+    /// the coordinator's own short address.
+    ///
+    /// Returns `None` if no short address is available.
+    fn allocate(
+        &mut self,
+        extended_address: &ExtendedAddress<[u8; 8]>,
+        capability_information: CapabilityInformation,
+        coord_short_address: ShortAddress<[u8; 2]>,
+        associated_devices: &AssociatedDevices,
+    ) -> Option<ShortAddress<[u8; 2]>>;
+}
+
+/// Allocates the lowest short address that is not in use, yet.
+#[derive(Debug, Clone, Copy)]
+pub struct SequentialShortAddressAllocator {
+    /// The first short address to be allocated.
+    first: u16,
+}
+
+impl SequentialShortAddressAllocator {
+    pub const fn new(first: u16) -> Self {
+        Self { first }
+    }
+}
+
+impl Default for SequentialShortAddressAllocator {
+    /// Leaves short address 0x0000 to the PAN coordinator.
+    fn default() -> Self {
+        Self::new(0x0001)
+    }
+}
+
+impl ShortAddressAllocator for SequentialShortAddressAllocator {
+    fn allocate(
+        &mut self,
+        _extended_address: &ExtendedAddress<[u8; 8]>,
+        _capability_information: CapabilityInformation,
+        coord_short_address: ShortAddress<[u8; 2]>,
+        associated_devices: &AssociatedDevices,
+    ) -> Option<ShortAddress<[u8; 2]>> {
+        if associated_devices.is_full() {
+            return None;
+        }
+
+        (self.first..SHORT_ADDRESS_NONE)
+            .map(|short_address| ShortAddress::new_owned(short_address.to_le_bytes()))
+            .find(|short_address| {
+                *short_address != coord_short_address
+                    && !associated_devices.is_short_address_in_use(short_address)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: u8, short_address: u16) -> AssociatedDevice {
+        AssociatedDevice {
+            extended_address: ExtendedAddress::new_owned([id; 8]),
+            short_address: ShortAddress::new_owned(short_address.to_le_bytes()),
+            capability_information: CapabilityInformation::default(),
+        }
+    }
+
+    #[test]
+    fn associated_devices() {
+        let mut devices = AssociatedDevices::new();
+        devices.insert(device(1, 0x0001)).unwrap();
+        devices.insert(device(2, 0x0002)).unwrap();
+
+        // Re-association updates the existing entry.
+        devices.insert(device(1, 0x0003)).unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(
+            devices
+                .get(&ExtendedAddress::new_owned([1; 8]))
+                .unwrap()
+                .short_address
+                .into_u16(),
+            0x0003
+        );
+        assert!(devices
+            .get_by_short_address(&ShortAddress::new_owned([0x01, 0x00]))
+            .is_none());
+
+        assert_eq!(
+            devices.remove(&ExtendedAddress::new_owned([2; 8])),
+            Some(device(2, 0x0002))
+        );
+        assert_eq!(devices.len(), 1);
+    }
+
+    #[test]
+    fn sequential_short_address_allocator() {
+        let mut allocator = SequentialShortAddressAllocator::default();
+        let mut devices = AssociatedDevices::new();
+        devices.insert(device(1, 0x0001)).unwrap();
+        devices.insert(device(2, 0x0003)).unwrap();
+
+        let allocate = |allocator: &mut SequentialShortAddressAllocator,
+                        devices: &AssociatedDevices| {
+            allocator
+                .allocate(
+                    &ExtendedAddress::new_owned([3; 8]),
+                    CapabilityInformation::default(),
+                    ShortAddress::new_owned([0x04, 0x00]),
+                    devices,
+                )
+                .map(|short_address| short_address.into_u16())
+        };
+
+        assert_eq!(allocate(&mut allocator, &devices), Some(0x0002));
+
+        // Reserved addresses and the coordinator's own address are skipped.
+        devices.reserve(
+            ExtendedAddress::new_owned([4; 8]),
+            ShortAddress::new_owned([0x02, 0x00]),
+            Some(100),
+        );
+        assert_eq!(allocate(&mut allocator, &devices), Some(0x0005));
+
+        // Lapsed reservations are released.
+        devices.release_expired(99);
+        assert_eq!(allocate(&mut allocator, &devices), Some(0x0005));
+        devices.release_expired(100);
+        assert_eq!(allocate(&mut allocator, &devices), Some(0x0002));
+
+        for id in 3..MAC_MAX_ASSOCIATED_DEVICES as u8 + 1 {
+            devices.insert(device(id, 0x0100 + id as u16)).unwrap();
+        }
+        assert_eq!(allocate(&mut allocator, &devices), None);
+    }
+
+    #[test]
+    fn reservations() {
+        let mut devices = AssociatedDevices::new();
+        let device_address = ExtendedAddress::new_owned([1; 8]);
+        let short_address = ShortAddress::new_owned([0x01, 0x00]);
+
+        devices.reserve(device_address, short_address, Some(100));
+        assert_eq!(
+            devices.reserved_short_address(&device_address),
+            Some(short_address)
+        );
+        assert!(devices.is_short_address_in_use(&short_address));
+
+        // A pinned reservation does not lapse.
+        devices.reserve(device_address, short_address, None);
+        devices.release_expired(1000);
+        assert!(devices.is_short_address_in_use(&short_address));
+
+        devices.release(&device_address);
+        assert_eq!(devices.reserved_short_address(&device_address), None);
+        assert!(!devices.is_short_address_in_use(&short_address));
+
+        // Devices communicating with their extended address are not reserved.
+        devices.reserve(device_address, ShortAddress::new_owned([0xfe, 0xff]), None);
+        assert_eq!(devices.reserved_short_address(&device_address), None);
+
+        // Reservations count towards the capacity.
+        for id in 0..MAC_MAX_ASSOCIATED_DEVICES as u8 - 1 {
+            devices.insert(device(id + 2, 0x0100 + id as u16)).unwrap();
+        }
+        assert!(!devices.is_full());
+        devices.reserve(device_address, short_address, None);
+        assert!(devices.is_full());
+    }
+}
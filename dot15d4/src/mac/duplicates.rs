@@ -0,0 +1,139 @@
+//! Duplicate detection of incoming frames.
+//!
+//! A frame is retransmitted if its ACK got lost although the frame itself was
+//! received. The MAC service remembers the source and sequence number of
+//! recently received frames so that retransmissions are acknowledged by the
+//! driver service but not indicated to the next higher layer a second time.
+//! In TSCH mode, the ASN of the slot in which a frame was received takes the
+//! place of its sequence number.
+
+use crate::driver::frame::Address;
+
+/// The max number of recently received frames that are remembered. The
+/// oldest entry is evicted once the cache is full.
+pub const MAC_MAX_DUPLICATE_ENTRIES: usize = 8;
+
+/// The source address of a received frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceAddress {
+    Short([u8; 2]),
+    Extended([u8; 8]),
+}
+
+impl SourceAddress {
+    fn from_address(address: &Address<&[u8]>) -> Option<Self> {
+        match address {
+            Address::Short(address) => Some(Self::Short(address.as_ref().try_into().ok()?)),
+            Address::Extended(address) => Some(Self::Extended(address.as_ref().try_into().ok()?)),
+            Address::Absent => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ReceivedFrame {
+    src_address: SourceAddress,
+    /// The DSN of the frame or, in TSCH mode, the ASN of the slot in which it
+    /// was received.
+    sequence_number: u64,
+    /// The radio timer tick after which the entry is forgotten.
+    expiry: u64,
+}
+
+/// A bounded cache of recently received frames.
+#[derive(Debug, Default)]
+pub(crate) struct DuplicateCache(heapless::Vec<ReceivedFrame, MAC_MAX_DUPLICATE_ENTRIES>);
+
+impl DuplicateCache {
+    pub const fn new() -> Self {
+        Self(heapless::Vec::new())
+    }
+
+    /// Checks whether a frame with the given source address and sequence
+    /// number was received within the last `timeout` radio timer ticks.
+    /// Otherwise remembers the frame until `now + timeout`.
+    ///
+    /// Frames without source address are never considered duplicates.
+    pub fn is_duplicate(
+        &mut self,
+        src_address: &Address<&[u8]>,
+        sequence_number: u64,
+        now: u64,
+        timeout: u64,
+    ) -> bool {
+        let Some(src_address) = SourceAddress::from_address(src_address) else {
+            return false;
+        };
+
+        self.0.retain(|received_frame| received_frame.expiry > now);
+
+        if self.0.iter().any(|received_frame| {
+            received_frame.src_address == src_address
+                && received_frame.sequence_number == sequence_number
+        }) {
+            return true;
+        }
+
+        // Remember a single frame per source: Its next frame will have a
+        // different sequence number.
+        self.0
+            .retain(|received_frame| received_frame.src_address != src_address);
+        if self.0.is_full() {
+            // Safety: The cache is full and therefore not empty.
+            let oldest = (0..self.0.len())
+                .min_by_key(|index| self.0[*index].expiry)
+                .unwrap();
+            self.0.swap_remove(oldest);
+        }
+        let _ = self.0.push(ReceivedFrame {
+            src_address,
+            sequence_number,
+            expiry: now + timeout,
+        });
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::driver::frame::{ExtendedAddress, ShortAddress};
+
+    use super::*;
+
+    #[test]
+    fn detect_duplicates() {
+        let mut cache = DuplicateCache::new();
+        let short_address = Address::Short(ShortAddress::new(&[0x01, 0x00][..]));
+        let extended_address = Address::Extended(ExtendedAddress::new(&[0x01; 8][..]));
+
+        assert!(!cache.is_duplicate(&short_address, 7, 0, 100));
+        assert!(cache.is_duplicate(&short_address, 7, 50, 100));
+        // Same sequence number from a different source.
+        assert!(!cache.is_duplicate(&extended_address, 7, 50, 100));
+        // The next frame of the same source.
+        assert!(!cache.is_duplicate(&short_address, 8, 60, 100));
+        assert!(!cache.is_duplicate(&short_address, 7, 60, 100));
+
+        // Entries expire after the timeout.
+        assert!(!cache.is_duplicate(&extended_address, 7, 150, 100));
+        assert!(!cache.is_duplicate(&Address::Absent, 7, 150, 100));
+        assert!(!cache.is_duplicate(&Address::Absent, 7, 160, 100));
+    }
+
+    #[test]
+    fn evict_oldest_entry() {
+        let mut cache = DuplicateCache::new();
+        let addresses: [[u8; 2]; MAC_MAX_DUPLICATE_ENTRIES + 1] =
+            core::array::from_fn(|index| [index as u8, 0x00]);
+        for (now, address) in addresses.iter().enumerate() {
+            let address = Address::Short(ShortAddress::new(&address[..]));
+            assert!(!cache.is_duplicate(&address, 1, now as u64, 100));
+        }
+
+        let first_address = Address::Short(ShortAddress::new(&addresses[0][..]));
+        let second_address = Address::Short(ShortAddress::new(&addresses[1][..]));
+        assert!(cache.is_duplicate(&second_address, 1, 10, 100));
+        assert!(!cache.is_duplicate(&first_address, 1, 10, 100));
+    }
+}
@@ -0,0 +1,413 @@
+//! Indirect transmission, see IEEE 802.15.4-2020, section 6.7.3.
+//!
+//! A coordinator keeps frames for devices that don't listen continuously until
+//! the device polls for them with a Data Request command. The driver service
+//! sets the Frame Pending field of ACKs to Data Request commands from devices
+//! with pending frames.
+
+use core::{cell::RefCell, mem};
+
+use rand_core::RngCore;
+
+use crate::{
+    driver::{
+        const_config,
+        constants::A_BASE_SUPERFRAME_DURATION,
+        frame::{Address, AddressingFields, ExtendedAddress, ShortAddress},
+        DriverConfig, RadioTimerApi,
+    },
+    mac::{
+        csma::BackoffRng,
+        mcps::data::{DataConfirm, DataError, DataRequest, DataRequestResult, DataRequestTask},
+        pib::Pib,
+        task::*,
+    },
+};
+
+use super::MacService;
+
+/// The max number of transactions that may be pending at the same time, see
+/// `DOT15D4_MAC_MAX_PENDING_TRANSACTIONS`.
+///
+/// Note: A pending transaction is kept by the request task that queued it
+///       until the device polls for it or the transaction expires. Pending
+///       transactions therefore compete for request task slots with all other
+///       requests and a capacity beyond [`super::UL_MAX_TX_TOKENS`] cannot be
+///       used.
+pub const MAC_MAX_PENDING_TRANSACTIONS: usize = const_config::MAC_MAX_PENDING_TRANSACTIONS;
+
+/// The extended and short address with which a device may poll for a frame.
+pub(crate) type PollAddresses = (
+    Option<ExtendedAddress<[u8; 8]>>,
+    Option<ShortAddress<[u8; 2]>>,
+);
+
+/// A frame waiting to be polled by a device.
+///
+/// The frame itself is kept by the MAC task that queued the transaction. The
+/// MAC service marks the transaction as polled and wakes the task once the
+/// device sends a Data Request command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PendingTransaction {
+    /// The extended address of the device to which the frame is addressed, if
+    /// known.
+    pub device_address: Option<ExtendedAddress<[u8; 8]>>,
+    /// The short address of the device, if it may poll with its short address.
+    pub short_address: Option<ShortAddress<[u8; 2]>>,
+    /// The handle of the MSDU, if the frame was queued by the next higher
+    /// layer and may therefore be purged.
+    pub msdu_handle: Option<u8>,
+    /// The index of the MAC task that queued the transaction.
+    pub task_index: usize,
+    /// Whether the device polled for the frame.
+    pub polled: bool,
+}
+
+impl PendingTransaction {
+    /// Whether the transaction is addressed to the given poll source.
+    fn matches(&self, src_address: &Address<&[u8]>) -> bool {
+        match src_address {
+            Address::Extended(src_address) => self
+                .device_address
+                .is_some_and(|device_address| device_address.as_ref() == src_address.as_ref()),
+            Address::Short(src_address) => self
+                .short_address
+                .is_some_and(|short_address| short_address.as_ref() == src_address.as_ref()),
+            Address::Absent => false,
+        }
+    }
+}
+
+/// The transaction queue of a coordinator, see IEEE 802.15.4-2020, section
+/// 6.7.3.
+///
+/// Shared between the MAC service, which queues and dequeues transactions,
+/// and the driver service, which announces pending frames in ACKs.
+#[derive(Debug, Default)]
+pub struct PendingTransactions(heapless::Vec<PendingTransaction, MAC_MAX_PENDING_TRANSACTIONS>);
+
+impl PendingTransactions {
+    pub const fn new() -> Self {
+        Self(heapless::Vec::new())
+    }
+
+    /// Queues the given transaction.
+    ///
+    /// Returns the transaction if the queue is full.
+    pub(crate) fn enqueue(
+        &mut self,
+        transaction: PendingTransaction,
+    ) -> Result<(), PendingTransaction> {
+        self.0.push(transaction)
+    }
+
+    /// Removes the transaction queued by the given task, if it is still
+    /// queued.
+    pub(crate) fn dequeue(&mut self, task_index: usize) -> Option<PendingTransaction> {
+        let index = self
+            .0
+            .iter()
+            .position(|transaction| transaction.task_index == task_index)?;
+        Some(self.0.swap_remove(index))
+    }
+
+    /// Marks the first transaction pending for the given poll source as
+    /// polled.
+    ///
+    /// Returns the index of the task that queued the transaction.
+    pub(crate) fn poll(&mut self, src_address: &Address<&[u8]>) -> Option<usize> {
+        let transaction = self
+            .0
+            .iter_mut()
+            .find(|transaction| !transaction.polled && transaction.matches(src_address))?;
+        transaction.polled = true;
+        Some(transaction.task_index)
+    }
+
+    /// Removes the transaction with the given MSDU handle unless the device
+    /// already polled for it.
+    ///
+    /// Returns the index of the task that queued the transaction.
+    pub(crate) fn purge(&mut self, msdu_handle: u8) -> Option<usize> {
+        let index = self.0.iter().position(|transaction| {
+            !transaction.polled && transaction.msdu_handle == Some(msdu_handle)
+        })?;
+        Some(self.0.swap_remove(index).task_index)
+    }
+
+    /// Whether a frame is waiting to be polled by the given device.
+    pub(crate) fn is_pending(&self, src_address: &Address<&[u8]>) -> bool {
+        self.0
+            .iter()
+            .any(|transaction| !transaction.polled && transaction.matches(src_address))
+    }
+}
+
+/// Sends a frame to a device once it polled for it.
+///
+/// The task queues a pending transaction and waits for the MAC service to wake
+/// it when the device sends a Data Request command. The frame is then sent by
+/// a data request sub-task, i.e. with CSMA-CA and retransmissions. The
+/// transaction expires after macTransactionPersistenceTime.
+pub(crate) struct IndirectTxTask<'task, RadioDriverImpl: DriverConfig> {
+    transaction: PendingTransaction,
+    pib: &'task RefCell<Pib>,
+    pending_transactions: &'task RefCell<PendingTransactions>,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    state: IndirectTxState<'task, RadioDriverImpl>,
+}
+
+enum IndirectTxState<'task, RadioDriverImpl: DriverConfig> {
+    Initial(DataRequest),
+    /// Waiting for the device to poll for the frame.
+    WaitingForPoll(DataRequest),
+    Sending(DataRequestTask<'task, RadioDriverImpl>),
+    Terminated,
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> IndirectTxTask<'task, RadioDriverImpl> {
+    /// Creates a task sending the given frame to the given device. The task
+    /// index is used by the MAC service to wake the task.
+    pub fn new(
+        data_request: DataRequest,
+        device_address: Option<ExtendedAddress<[u8; 8]>>,
+        short_address: Option<ShortAddress<[u8; 2]>>,
+        task_index: usize,
+        pib: &'task RefCell<Pib>,
+        pending_transactions: &'task RefCell<PendingTransactions>,
+        rng: &'task dyn BackoffRng,
+    ) -> Self {
+        Self {
+            transaction: PendingTransaction {
+                device_address,
+                short_address,
+                msdu_handle: None,
+                task_index,
+                polled: false,
+            },
+            pib,
+            pending_transactions,
+            rng,
+            state: IndirectTxState::Initial(data_request),
+        }
+    }
+
+    /// Allows the next higher layer to purge the frame by its MSDU handle, see
+    /// IEEE 802.15.4-2020, section 8.3.5.
+    pub fn purgeable(mut self) -> Self {
+        if let IndirectTxState::Initial(data_request) = &self.state {
+            self.transaction.msdu_handle = Some(data_request.msdu_handle());
+        }
+        self
+    }
+
+    /// Removes our transaction from the queue.
+    ///
+    /// Returns whether the device polled for it or `None` if the transaction
+    /// was purged.
+    fn dequeue_transaction(&self) -> Option<bool> {
+        self.pending_transactions
+            .borrow_mut()
+            .dequeue(self.transaction.task_index)
+            .map(|transaction| transaction.polled)
+    }
+
+    /// Gives up on a frame that was never sent.
+    fn terminate(
+        self,
+        data_request: DataRequest,
+        error: DataError,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let confirm = DataConfirm {
+            msdu_handle: data_request.msdu_handle(),
+            status: Err(error),
+            timestamp: None,
+            acked: false,
+            retries: 0,
+            ack_link_quality: None,
+            ack_rssi: None,
+            ack_frame: None,
+            frame_pending: false,
+        };
+        let radio_frame = data_request
+            .into_mpdu()
+            .into_radio_frame::<RadioDriverImpl>()
+            .forget_size::<RadioDriverImpl>();
+        MacTaskTransition::Terminated(DataRequestResult {
+            confirm,
+            radio_frame,
+            ack_mpdu: None,
+        })
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for IndirectTxTask<'_, RadioDriverImpl>
+{
+    type Result = DataRequestResult;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, IndirectTxState::Terminated) {
+            IndirectTxState::Initial(data_request) => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                if self.transaction.device_address.is_none()
+                    && self.transaction.short_address.is_none()
+                {
+                    return self.terminate(data_request, DataError::InvalidAddress);
+                }
+
+                if self
+                    .pending_transactions
+                    .borrow_mut()
+                    .enqueue(self.transaction)
+                    .is_err()
+                {
+                    return self.terminate(data_request, DataError::TransactionOverflow);
+                }
+
+                let persistence_time = A_BASE_SUPERFRAME_DURATION
+                    * self.pib.borrow().transaction_persistence_time as usize;
+                self.state = IndirectTxState::WaitingForPoll(data_request);
+                let expiry =
+                    RadioDriverImpl::Timer::now() + persistence_time.convert_into_rounding_up();
+                MacTaskTransition::TimerRequest(self, expiry)
+            }
+            IndirectTxState::WaitingForPoll(data_request) => {
+                debug_assert!(matches!(event, MacTaskEvent::TimerExpired));
+
+                match self.dequeue_transaction() {
+                    Some(true) => {}
+                    Some(false) => {
+                        return self.terminate(data_request, DataError::TransactionExpired)
+                    }
+                    None => return self.terminate(data_request, DataError::Purged),
+                }
+
+                let data_request_task =
+                    DataRequestTask::new(data_request, &mut self.pib.borrow_mut(), self.rng);
+                self.state = IndirectTxState::Sending(data_request_task);
+                self.step(MacTaskEvent::Entry)
+            }
+            IndirectTxState::Sending(data_request_task) => match data_request_task.step(event) {
+                MacTaskTransition::DrvSvcRequest(data_request_task, driver_request, _) => {
+                    self.state = IndirectTxState::Sending(data_request_task);
+                    MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                }
+                MacTaskTransition::TimerRequest(data_request_task, expiry) => {
+                    self.state = IndirectTxState::Sending(data_request_task);
+                    MacTaskTransition::TimerRequest(self, expiry)
+                }
+                MacTaskTransition::Terminated(result) => MacTaskTransition::Terminated(result),
+            },
+            // Safety: Terminated tasks are not stepped.
+            IndirectTxState::Terminated => unreachable!(),
+        }
+    }
+}
+
+impl<Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'_, Rng, RadioDriverImpl> {
+    /// Marks the first transaction pending for the device sending the given
+    /// Data Request command as polled.
+    ///
+    /// Returns the index of the MAC task to be woken, if any.
+    pub(crate) fn receive_data_request(
+        &self,
+        addressing_fields: &AddressingFields<&[u8]>,
+    ) -> Option<usize> {
+        let src_address = addressing_fields.src_address()?;
+        self.pending_transactions.borrow_mut().poll(&src_address)
+    }
+
+    /// Looks up the addresses with which the recipient of the given frame may
+    /// poll for it. Both are `None` if the frame has no unicast destination.
+    pub(crate) fn indirect_tx_addresses(&self, data_request: &DataRequest) -> PollAddresses {
+        let associated_devices = self.associated_devices.borrow();
+        match data_request.dst_addr() {
+            Ok(Address::Extended(address)) => {
+                let Ok(address) = address.as_ref().try_into() else {
+                    return (None, None);
+                };
+                let device_address = ExtendedAddress::new_owned(address);
+                let short_address = associated_devices
+                    .get(&device_address)
+                    .map(|device| device.short_address)
+                    .filter(|short_address| short_address.into_u16() != 0xfffe);
+                (Some(device_address), short_address)
+            }
+            Ok(Address::Short(address)) if address.into_u16() < 0xfffe => {
+                let short_address = ShortAddress::<[u8; 2]>::from_u16(address.into_u16());
+                let device_address = associated_devices
+                    .get_by_short_address(&short_address)
+                    .map(|device| device.extended_address);
+                (device_address, Some(short_address))
+            }
+            _ => (None, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE_ADDRESS: [u8; 8] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+    fn transaction(task_index: usize, msdu_handle: Option<u8>) -> PendingTransaction {
+        PendingTransaction {
+            device_address: Some(ExtendedAddress::new_owned(DEVICE_ADDRESS)),
+            short_address: Some(ShortAddress::new_owned([0x01, 0x00])),
+            msdu_handle,
+            task_index,
+            polled: false,
+        }
+    }
+
+    #[test]
+    fn poll_pending_transactions() {
+        let mut pending_transactions = PendingTransactions::new();
+        for task_index in 0..MAC_MAX_PENDING_TRANSACTIONS {
+            pending_transactions
+                .enqueue(transaction(task_index, None))
+                .unwrap();
+        }
+        assert!(pending_transactions
+            .enqueue(transaction(MAC_MAX_PENDING_TRANSACTIONS, None))
+            .is_err());
+
+        let extended_address = Address::Extended(ExtendedAddress::new(&DEVICE_ADDRESS[..]));
+        let short_address = Address::Short(ShortAddress::new(&[0x01, 0x00][..]));
+        let other_address = Address::Short(ShortAddress::new(&[0x02, 0x00][..]));
+        assert!(pending_transactions.is_pending(&short_address));
+        assert!(!pending_transactions.is_pending(&other_address));
+
+        assert_eq!(pending_transactions.poll(&extended_address), Some(0));
+        for task_index in 1..MAC_MAX_PENDING_TRANSACTIONS {
+            assert_eq!(pending_transactions.poll(&short_address), Some(task_index));
+        }
+        assert_eq!(pending_transactions.poll(&short_address), None);
+        assert!(!pending_transactions.is_pending(&short_address));
+
+        assert_eq!(
+            pending_transactions.dequeue(0).map(|t| t.polled),
+            Some(true)
+        );
+        assert_eq!(pending_transactions.dequeue(0), None);
+    }
+
+    #[test]
+    fn purge_pending_transactions() {
+        let mut pending_transactions = PendingTransactions::new();
+        pending_transactions.enqueue(transaction(0, None)).unwrap();
+        pending_transactions
+            .enqueue(transaction(1, Some(7)))
+            .unwrap();
+
+        // Frames queued by the MAC sublayer cannot be purged.
+        assert_eq!(pending_transactions.purge(0), None);
+        assert_eq!(pending_transactions.purge(7), Some(1));
+        assert_eq!(pending_transactions.purge(7), None);
+        assert_eq!(pending_transactions.dequeue(1), None);
+    }
+}
@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use core::{marker::PhantomData, num::NonZero};
+use core::{cell::Cell, marker::PhantomData, mem};
 
 #[cfg(feature = "rtos-trace")]
 use crate::trace::{
@@ -8,39 +8,50 @@ use crate::trace::{
 };
 use crate::{
     driver::{
-        frame::{
-            Address, AddressingMode, PanId, RadioFrame, RadioFrameRepr, RadioFrameSized,
-            RadioFrameUnsized,
-        },
-        tasks::{RxError, RxResult, Timestamp, TxError, TxResult},
+        config::Channel,
+        constants::{FCS_LEN, PHY_MAX_PACKET_SIZE_127},
+        frame::{Address, AddressingMode, PanId, RadioFrame, RadioFrameRepr, RadioFrameUnsized},
+        tasks::{AckMpdu, RxError, RxInfo, RxResult, Timestamp, TxError, TxInfo, TxResult},
         DriverConfig, DrvSvcRequest, DrvSvcResponse, DrvSvcTaskError, DrvSvcTaskRx, DrvSvcTaskTx,
+        RadioTimerApi,
+    },
+    mac::{
+        csma::{BackoffRng, CsmaCa},
+        frame::mpdu::MpduFrame,
+        pib::Pib,
+        task::*,
+        tsch::AbsoluteSlotNumber,
+        MacBufferAllocator,
     },
-    mac::{frame::mpdu::MpduFrame, task::*, MacBufferAllocator},
     util::{Error, Result as SimplifiedResult},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataError {
-    // TODO: not supported
     TransactionOverflow,
-    // TODO: not supported
     TransactionExpired,
-    // TODO: not supported
     ChannelAccessFailure,
-    // TODO: not supported
     InvalidAddress,
-    // TODO: not supported
     NoAck,
     // TODO: not supported
     CounterError,
-    // TODO: not supported
     FrameTooLong,
     // TODO: not supported
     InvalidParameter,
+    /// Not part of the standard: The frame was purged from the transaction
+    /// queue by an MCPS-PURGE.request.
+    Purged,
 }
 
 pub struct DataRequest {
     /// The frame to be sent.
     mpdu: MpduFrame,
+    /// The handle associated with the MSDU, returned in the corresponding
+    /// [`DataConfirm`].
+    msdu_handle: u8,
+    /// Whether the frame is kept in the transaction queue until the recipient
+    /// polls for it.
+    indirect_tx: bool,
 }
 
 /// Represents an MLME-DATA.request.
@@ -50,7 +61,24 @@ pub struct DataRequest {
 ///       move existing data around.
 impl DataRequest {
     pub fn new(mpdu: MpduFrame) -> Self {
-        Self { mpdu }
+        Self {
+            mpdu,
+            msdu_handle: 0,
+            indirect_tx: false,
+        }
+    }
+
+    /// Recovers the frame, e.g. if it could not be sent.
+    pub(crate) fn into_mpdu(self) -> MpduFrame {
+        self.mpdu
+    }
+
+    pub fn msdu_handle(&self) -> u8 {
+        self.msdu_handle
+    }
+
+    pub fn set_msdu_handle(&mut self, msdu_handle: u8) {
+        self.msdu_handle = msdu_handle;
     }
 
     pub fn src_addr_mode(&self) -> AddressingMode {
@@ -108,14 +136,31 @@ impl DataRequest {
     pub fn tx_options(&mut self) -> TxOptions<'_> {
         TxOptions {
             mpdu: &mut self.mpdu,
+            indirect_tx: &mut self.indirect_tx,
         }
     }
+
+    pub(crate) fn is_indirect_tx(&self) -> bool {
+        self.indirect_tx
+    }
 }
 pub struct TxOptions<'mpdu> {
     mpdu: &'mpdu mut MpduFrame,
+    indirect_tx: &'mpdu mut bool,
 }
 
 impl<'mpdu> TxOptions<'mpdu> {
+    /// Whether the frame is sent indirectly, i.e. queued by a coordinator
+    /// until the recipient polls for it, see IEEE 802.15.4-2020, section
+    /// 6.7.3.
+    pub fn indirect_tx(&self) -> bool {
+        *self.indirect_tx
+    }
+
+    pub fn set_indirect_tx(&mut self, indirect_tx: bool) {
+        *self.indirect_tx = indirect_tx;
+    }
+
     pub fn ack_tx(&self) -> bool {
         self.mpdu.frame_control().ack_request()
     }
@@ -145,64 +190,213 @@ impl<'mpdu> TxOptions<'mpdu> {
     }
 }
 
+/// Represents an MCPS-DATA.confirm, see IEEE 802.15.4-2020, section 8.3.3.
 pub struct DataConfirm {
-    /// Timestamp of frame transmission
-    pub timestamp: Option<NonZero<u32>>,
+    /// The handle of the MSDU being confirmed
+    pub msdu_handle: u8,
+    /// The status of the data transmission
+    pub status: Result<(), DataError>,
+    /// Radio clock tick at which the RMARKER of the last transmission attempt
+    /// passed the local antenna, if the frame was sent
+    pub timestamp: Option<u64>,
     /// Whether the frame has been acknowledged or not
     pub acked: bool,
+    /// The number of retransmissions required to send the frame
+    pub retries: u8,
+    /// Link quality indicator of the received ACK frame, if any
+    pub ack_link_quality: Option<u8>,
+    /// Received signal strength of the ACK frame in dBm, if measured by the
+    /// driver
+    pub ack_rssi: Option<i8>,
+    /// The received ACK frame, if any
+    ///
+    /// Note: The frame re-uses the buffer of the sent frame. Clients must
+    ///       release it to the MAC's buffer allocator.
+    pub ack_frame: Option<MpduFrame>,
+    /// Not part of the standard: Whether the Frame Pending field of the
+    /// received ACK was set, i.e. the recipient has more data for us.
+    pub frame_pending: bool,
 }
 
+/// Represents an MCPS-DATA.indication, see IEEE 802.15.4-2020, section 8.3.4.
 pub struct DataIndication {
     /// The received frame.
     pub mpdu: MpduFrame,
-    /// Timestamp of frame reception
-    pub timestamp: Option<NonZero<u32>>,
+    /// Link quality indicator of the received frame (mpduLinkQuality)
+    pub link_quality: u8,
+    /// Received signal strength in dBm, if measured by the driver
+    pub rssi: Option<i8>,
+    /// Radio clock tick at which the RMARKER of the frame passed the local
+    /// antenna, if known
+    pub timestamp: Option<u64>,
+}
+
+/// Represents a frame received in promiscuous mode.
+///
+/// In promiscuous mode the MAC passes all received frames to the next higher
+/// layer unchanged, whatever their type or addressing, see IEEE 802.15.4-2020,
+/// section 6.7.2. Frames with an invalid FCS are only indicated if requested.
+pub struct PromiscuousIndication {
+    /// The received frame.
+    pub mpdu: MpduFrame,
+    /// Whether the FCS of the frame was valid
+    pub crc_ok: bool,
+    /// The channel on which the frame was received
+    pub channel: Channel,
+    /// Link quality indicator of the received frame
+    pub lqi: u8,
+    /// Received signal strength in dBm, if measured by the driver
+    pub rssi: Option<i8>,
+    /// Radio clock tick at which the RMARKER of the frame passed the local
+    /// antenna, if known
+    pub timestamp: Option<u64>,
 }
 
 pub(crate) struct DataRequestTask<'task, RadioDriverImpl: DriverConfig> {
-    state: DataRequestState<'task, RadioDriverImpl>,
+    /// The handle of the MSDU being sent.
+    msdu_handle: u8,
+    /// Whether the MPDU requests an ACK.
+    ack_requested: bool,
+    /// Unslotted CSMA-CA state of the current transmission attempt.
+    csma_ca: CsmaCa,
+    /// CSMA-CA state to start each retransmission from.
+    initial_csma_ca: CsmaCa,
+    /// macMaxFrameRetries at the time the request was issued.
+    max_frame_retries: u8,
+    /// The number of retransmissions so far.
+    retries: u8,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    state: DataRequestState<RadioDriverImpl>,
 }
 
-enum DataRequestState<'task, RadioDriverImpl: DriverConfig> {
+enum DataRequestState<RadioDriverImpl: DriverConfig> {
     Initial(
         /// MPDU to be sent.
         MpduFrame,
         /// Placeholder for future references.
-        PhantomData<&'task RadioDriverImpl>,
+        PhantomData<RadioDriverImpl>,
+    ),
+    BackingOff(
+        /// MPDU to be sent once the backoff period expired.
+        MpduFrame,
     ),
     SendingFrame,
 }
 
-impl<RadioDriverImpl: DriverConfig> DataRequestTask<'_, RadioDriverImpl> {
-    pub fn new(data_request: DataRequest) -> Self {
+impl<'task, RadioDriverImpl: DriverConfig> DataRequestTask<'task, RadioDriverImpl> {
+    /// Creates a task sending the given frame. Stamps the frame with the next
+    /// sequence number from the PIB unless the sequence number is suppressed.
+    /// Retransmissions keep that number.
+    pub fn new(mut data_request: DataRequest, pib: &mut Pib, rng: &'task dyn BackoffRng) -> Self {
+        pib.stamp_sequence_number(&mut data_request.mpdu);
+
+        let csma_ca = CsmaCa::from_pib(pib);
         Self {
+            msdu_handle: data_request.msdu_handle,
+            ack_requested: data_request.mpdu.frame_control().ack_request(),
+            csma_ca,
+            initial_csma_ca: csma_ca,
+            max_frame_retries: pib.max_frame_retries,
+            retries: 0,
+            rng,
             state: DataRequestState::Initial(data_request.mpdu, PhantomData),
         }
     }
 
-    fn handle_tx_driver_response(response: DrvSvcResponse) -> DataRequestResult {
+    /// Delays the transmission by a random number of backoff periods as
+    /// required by the CSMA-CA algorithm. Sends the frame immediately if no
+    /// backoff is required.
+    fn back_off(mut self, tx_mpdu: MpduFrame) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let backoff = self.csma_ca.backoff(self.rng.next_u32());
+        if backoff.ticks() == 0 {
+            self.state = DataRequestState::SendingFrame;
+            return MacTaskTransition::DrvSvcRequest(self, Self::tx_task(tx_mpdu), None);
+        }
+
+        self.state = DataRequestState::BackingOff(tx_mpdu);
+        let backoff_end = RadioDriverImpl::Timer::now() + backoff;
+        MacTaskTransition::TimerRequest(self, backoff_end)
+    }
+
+    /// Terminates the task with the given status and the radio metadata of
+    /// the last transmission attempt, if the frame was sent.
+    fn terminate(
+        self,
+        status: Result<(), DataError>,
+        radio_frame: RadioFrame<RadioFrameUnsized>,
+        tx_info: Option<TxInfo>,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let ack_rx_info = tx_info.and_then(|tx_info| tx_info.ack);
+        let confirm = DataConfirm {
+            msdu_handle: self.msdu_handle,
+            acked: status.is_ok() && self.ack_requested,
+            status,
+            timestamp: tx_info.and_then(|tx_info| tx_info.timestamp),
+            retries: self.retries,
+            ack_link_quality: ack_rx_info.map(|rx_info| rx_info.lqi),
+            ack_rssi: ack_rx_info.and_then(|rx_info| rx_info.rssi),
+            ack_frame: None,
+            frame_pending: tx_info.is_some_and(|tx_info| tx_info.ack_frame_pending),
+        };
+        MacTaskTransition::Terminated(DataRequestResult {
+            confirm,
+            radio_frame,
+            ack_mpdu: tx_info.and_then(|tx_info| tx_info.ack_mpdu),
+        })
+    }
+
+    fn handle_tx_driver_response(
+        mut self,
+        response: DrvSvcResponse,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
         match response {
             DrvSvcResponse::Tx(tx_result) => match tx_result {
-                Ok(TxResult::Sent(sent_tx_frame)) => {
+                Ok(TxResult::Sent(sent_tx_frame, tx_info)) => {
                     #[cfg(feature = "rtos-trace")]
                     rtos_trace::trace::marker(TX_FRAME);
 
-                    DataRequestResult::Sent(sent_tx_frame.forget_size::<RadioDriverImpl>())
+                    self.terminate(
+                        Ok(()),
+                        sent_tx_frame.forget_size::<RadioDriverImpl>(),
+                        Some(tx_info),
+                    )
                 }
-                // TODO: resend
-                Ok(TxResult::Nack(unacknowledged_tx_frame)) => {
+                Ok(TxResult::Nack(unacknowledged_tx_frame, tx_info)) => {
                     #[cfg(feature = "rtos-trace")]
                     rtos_trace::trace::marker(TX_NACK);
 
-                    DataRequestResult::Nack(unacknowledged_tx_frame)
+                    if self.retries < self.max_frame_retries {
+                        // Retransmit the unchanged MPDU, starting over with a
+                        // fresh CSMA-CA run, see IEEE 802.15.4-2020, section
+                        // 6.7.4.4.
+                        self.retries += 1;
+                        self.csma_ca = self.initial_csma_ca;
+                        let tx_mpdu = MpduFrame::from_radio_frame(unacknowledged_tx_frame);
+                        return self.back_off(tx_mpdu);
+                    }
+
+                    self.terminate(
+                        Err(DataError::NoAck),
+                        unacknowledged_tx_frame.forget_size::<RadioDriverImpl>(),
+                        Some(tx_info),
+                    )
                 }
                 Err(tx_error) => match tx_error {
-                    // TODO: CSMA/CA
                     DrvSvcTaskError::Task(TxError::CcaBusy(unsent_tx_frame)) => {
                         #[cfg(feature = "rtos-trace")]
                         rtos_trace::trace::marker(TX_CCABUSY);
 
-                        DataRequestResult::CcaBusy(unsent_tx_frame)
+                        if self.csma_ca.channel_busy() {
+                            let tx_mpdu = MpduFrame::from_radio_frame(unsent_tx_frame);
+                            return self.back_off(tx_mpdu);
+                        }
+
+                        self.terminate(
+                            Err(DataError::ChannelAccessFailure),
+                            unsent_tx_frame.forget_size::<RadioDriverImpl>(),
+                            None,
+                        )
                     }
                     // TODO: Implement if required by a driver implementation.
                     _ => unreachable!(),
@@ -217,33 +411,22 @@ impl<RadioDriverImpl: DriverConfig> DataRequestTask<'_, RadioDriverImpl> {
         DrvSvcTaskTx {
             at: Timestamp::BestEffort,
             radio_frame: tx_mpdu.into_radio_frame::<RadioDriverImpl>(),
-            // TODO: CSMA/CA
-            cca: false,
+            cca: true,
+            channel: None,
         }
         .into()
     }
 }
 
 /// Final result of a data request task.
-pub(crate) enum DataRequestResult {
-    /// The Tx frame was sent.
-    ///
-    /// If ACK was requested, this result will only be returned if the frame was
-    /// successfully acknowledged. Otherwise this result merely indicates that
-    /// the frame was accepted by the driver and transmitted over the air.
-    Sent(
-        /// recovered Tx radio frame
-        RadioFrame<RadioFrameUnsized>,
-    ),
-    CcaBusy(
-        /// unsent radio frame
-        RadioFrame<RadioFrameSized>,
-    ),
-    /// Not acknowledged: timeout or explicit NACK
-    Nack(
-        /// recovered Tx radio frame
-        RadioFrame<RadioFrameSized>,
-    ),
+pub(crate) struct DataRequestResult {
+    /// The confirmation to be returned to the upper layer.
+    pub confirm: DataConfirm,
+    /// The recovered Tx radio frame.
+    pub radio_frame: RadioFrame<RadioFrameUnsized>,
+    /// The received ACK frame, if any. Copied into the recovered Tx radio
+    /// frame when handing the confirmation to the upper layer.
+    pub ack_mpdu: Option<AckMpdu>,
 }
 
 impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
@@ -255,17 +438,27 @@ impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
         #[cfg(feature = "rtos-trace")]
         rtos_trace::trace::task_exec_begin(MAC_REQUEST);
 
-        match self.state {
+        match mem::replace(&mut self.state, DataRequestState::SendingFrame) {
             DataRequestState::Initial(tx_mpdu, _) => {
                 debug_assert!(matches!(event, MacTaskEvent::Entry));
-                self.state = DataRequestState::SendingFrame;
+
+                if tx_mpdu.pdu_length_wo_fcs() as usize + FCS_LEN > PHY_MAX_PACKET_SIZE_127 {
+                    let radio_frame = tx_mpdu
+                        .into_radio_frame::<RadioDriverImpl>()
+                        .forget_size::<RadioDriverImpl>();
+                    return self.terminate(Err(DataError::FrameTooLong), radio_frame, None);
+                }
+
+                self.back_off(tx_mpdu)
+            }
+            DataRequestState::BackingOff(tx_mpdu) => {
+                debug_assert!(matches!(event, MacTaskEvent::TimerExpired));
                 MacTaskTransition::DrvSvcRequest(self, Self::tx_task(tx_mpdu), None)
             }
             DataRequestState::SendingFrame => {
                 match event {
                     MacTaskEvent::DrvSvcResponse(driver_response) => {
-                        let request_result = Self::handle_tx_driver_response(driver_response);
-                        MacTaskTransition::Terminated(request_result)
+                        self.handle_tx_driver_response(driver_response)
                     }
                     // Safety: We issued a Tx task and therefore expect a Tx result.
                     _ => unreachable!(),
@@ -275,8 +468,31 @@ impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
     }
 }
 
+/// Intermediate result of a data indication task, i.e. a received frame.
+pub(crate) struct DataIndicationResult {
+    /// The received MPDU.
+    pub mpdu: MpduFrame,
+    /// Radio metadata of the received MPDU.
+    pub rx_info: RxInfo,
+    /// Whether the FCS of the MPDU was valid. Frames with an invalid FCS may
+    /// only be indicated in promiscuous mode.
+    pub crc_ok: bool,
+    /// The channel of the indication task's RX task, `None` for the default
+    /// channel. Frames received in TSCH slots carry `None` as they are never
+    /// collected by scans.
+    pub channel: Option<Channel>,
+    /// The ASN of the TSCH slot in which the frame was received, `None`
+    /// outside of TSCH slots.
+    pub asn: Option<AbsoluteSlotNumber>,
+}
+
 pub(crate) struct DataIndicationTask<'task, RadioDriverImpl: DriverConfig> {
     buffer_allocator: MacBufferAllocator,
+    /// The channel scanned by an ongoing scan, if any. Frames are received on
+    /// it instead of the default channel.
+    scan_channel: &'task Cell<Option<Channel>>,
+    /// The channel of the outstanding RX task.
+    rx_channel: Option<Channel>,
     state: DataIndicationState<'task, RadioDriverImpl>,
 }
 
@@ -287,14 +503,19 @@ enum DataIndicationState<'task, RadioDriverImpl: DriverConfig> {
 }
 
 impl<'task, RadioDriverImpl: DriverConfig> DataIndicationTask<'task, RadioDriverImpl> {
-    pub fn new(buffer_allocator: MacBufferAllocator) -> Self {
+    pub fn new(
+        scan_channel: &'task Cell<Option<Channel>>,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
         Self {
             buffer_allocator,
+            scan_channel,
+            rx_channel: None,
             state: DataIndicationState::Initial(PhantomData),
         }
     }
 
-    fn allocate_rx_radio_frame(
+    pub(crate) fn allocate_rx_radio_frame(
         buffer_allocator: &MacBufferAllocator,
     ) -> Option<RadioFrame<RadioFrameUnsized>> {
         let rx_buffer = buffer_allocator.try_allocate_buffer(
@@ -311,21 +532,25 @@ impl<'task, RadioDriverImpl: DriverConfig> DataIndicationTask<'task, RadioDriver
         }
     }
 
-    fn handle_rx_driver_response(
-        &self,
+    pub(crate) fn handle_rx_driver_response(
         response: DrvSvcResponse,
-    ) -> Result<MpduFrame, RadioFrame<RadioFrameUnsized>> {
+    ) -> Result<DataIndicationResult, RadioFrame<RadioFrameUnsized>> {
         match response {
             DrvSvcResponse::Rx(rx_result) => match rx_result {
                 Ok(rx_result) => match rx_result {
-                    RxResult::Frame(rx_frame) => {
+                    RxResult::Frame(rx_frame, rx_info) => {
                         #[cfg(feature = "rtos-trace")]
                         rtos_trace::trace::marker(RX_FRAME);
 
-                        let mpdu = MpduFrame::from_radio_frame(rx_frame);
-                        Ok(mpdu)
+                        Ok(DataIndicationResult {
+                            mpdu: MpduFrame::from_radio_frame(rx_frame),
+                            rx_info,
+                            crc_ok: true,
+                            channel: None,
+                            asn: None,
+                        })
                     }
-                    RxResult::FilteredFrame(recovered_radio_frame) => {
+                    RxResult::FilteredFrame(recovered_radio_frame, _) => {
                         #[cfg(feature = "rtos-trace")]
                         rtos_trace::trace::marker(RX_INVALID);
 
@@ -337,11 +562,24 @@ impl<'task, RadioDriverImpl: DriverConfig> DataIndicationTask<'task, RadioDriver
 
                         Err(recovered_radio_frame)
                     }
-                    RxResult::CrcError(recovered_radio_frame) => {
+                    RxResult::CrcError(radio_frame, sdu_length_wo_fcs, rx_info) => {
                         #[cfg(feature = "rtos-trace")]
                         rtos_trace::trace::marker(RX_CRC_ERROR);
 
-                        Err(recovered_radio_frame)
+                        // Corrupted frames are passed on as the MAC service
+                        // may want to indicate them in promiscuous mode.
+                        match sdu_length_wo_fcs {
+                            Some(sdu_length_wo_fcs) => Ok(DataIndicationResult {
+                                mpdu: MpduFrame::from_radio_frame(
+                                    radio_frame.with_size(sdu_length_wo_fcs),
+                                ),
+                                rx_info,
+                                crc_ok: false,
+                                channel: None,
+                                asn: None,
+                            }),
+                            None => Err(radio_frame),
+                        }
                     }
                 },
                 Err(rx_task_error) => match rx_task_error {
@@ -359,29 +597,24 @@ impl<'task, RadioDriverImpl: DriverConfig> DataIndicationTask<'task, RadioDriver
     }
 
     fn produce_indication_and_restart_rx(
-        rx_mpdu: MpduFrame,
-        buffer_allocator: MacBufferAllocator,
+        mut self,
+        mut data_indication: DataIndicationResult,
     ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
-        let data_indication = DataIndication {
-            mpdu: rx_mpdu,
-            timestamp: None,
-        };
+        data_indication.channel = self.rx_channel;
         let next_rx_radio_frame =
-            Self::allocate_rx_radio_frame(&buffer_allocator).expect("no capacity");
-        MacTaskTransition::DrvSvcRequest(
-            Self {
-                buffer_allocator,
-                state: DataIndicationState::WaitingForFrame,
-            },
-            Self::rx_task(next_rx_radio_frame),
-            Some(data_indication),
-        )
+            Self::allocate_rx_radio_frame(&self.buffer_allocator).expect("no capacity");
+        let rx_task = self.rx_task(next_rx_radio_frame);
+        MacTaskTransition::DrvSvcRequest(self, rx_task, Some(data_indication))
     }
 
-    fn rx_task(radio_frame: RadioFrame<RadioFrameUnsized>) -> DrvSvcRequest {
+    /// Receives on the scanned channel while scanning, otherwise on the
+    /// default channel.
+    fn rx_task(&mut self, radio_frame: RadioFrame<RadioFrameUnsized>) -> DrvSvcRequest {
+        self.rx_channel = self.scan_channel.get();
         DrvSvcTaskRx {
             start: Timestamp::BestEffort,
             radio_frame,
+            channel: self.rx_channel,
         }
         .into()
     }
@@ -390,7 +623,7 @@ impl<'task, RadioDriverImpl: DriverConfig> DataIndicationTask<'task, RadioDriver
 impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
     for DataIndicationTask<'_, RadioDriverImpl>
 {
-    type Result = DataIndication;
+    type Result = DataIndicationResult;
 
     fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
         #[cfg(feature = "rtos-trace")]
@@ -403,26 +636,24 @@ impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
                 let rx_radio_frame =
                     Self::allocate_rx_radio_frame(&self.buffer_allocator).expect("no capacity");
                 self.state = DataIndicationState::WaitingForFrame;
-                MacTaskTransition::DrvSvcRequest(self, Self::rx_task(rx_radio_frame), None)
+                let rx_task = self.rx_task(rx_radio_frame);
+                MacTaskTransition::DrvSvcRequest(self, rx_task, None)
             }
             DataIndicationState::WaitingForFrame => match event {
                 MacTaskEvent::DrvSvcResponse(driver_response) => {
-                    match self.handle_rx_driver_response(driver_response) {
-                        // We successfully received an MPDU.
-                        Ok(rx_mpdu) => {
+                    match Self::handle_rx_driver_response(driver_response) {
+                        // We received an MPDU.
+                        Ok(data_indication) => {
                             self.state = DataIndicationState::WaitingForFrame;
-                            Self::produce_indication_and_restart_rx(rx_mpdu, self.buffer_allocator)
+                            self.produce_indication_and_restart_rx(data_indication)
                         }
                         // The previous Rx task ended without receiving a valid
                         // frame. Start waiting for the next frame.
                         Err(recovered_rx_radio_frame) => {
                             // Wait for the next frame
                             self.state = DataIndicationState::WaitingForFrame;
-                            MacTaskTransition::DrvSvcRequest(
-                                self,
-                                Self::rx_task(recovered_rx_radio_frame),
-                                None,
-                            )
+                            let rx_task = self.rx_task(recovered_rx_radio_frame);
+                            MacTaskTransition::DrvSvcRequest(self, rx_task, None)
                         }
                     }
                 }
@@ -2,21 +2,40 @@ use rand_core::RngCore;
 
 use crate::{driver::DriverConfig, mac::MacService};
 
-#[allow(dead_code)]
+/// Represents an MCPS-PURGE.request, see IEEE 802.15.4-2020, section 8.3.5.
+pub struct PurgeRequest {
+    /// The handle of the MSDU to be purged from the transaction queue.
+    pub msdu_handle: u8,
+}
+
+/// Represents an MCPS-PURGE.confirm, see IEEE 802.15.4-2020, section 8.3.6.
 pub struct PurgeConfirm {
-    msdu_handle: u8,
+    pub msdu_handle: u8,
+    pub status: Result<(), PurgeError>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PurgeError {
+    /// No MSDU with the given handle is waiting in the transaction queue.
     InvalidHandle,
 }
 
-#[allow(dead_code)]
 impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, RadioDriverImpl> {
     /// Allows a higher layer to purge an MSDU from the transaction
     /// queue.
-    async fn purge_request(&mut self) -> Result<PurgeConfirm, PurgeError> {
-        // TODO: not supported
-        Err(PurgeError::InvalidHandle)
+    ///
+    /// Returns the index of the MAC task that queued the MSDU, if any. The
+    /// task must be woken so that it confirms the MSDU as purged and releases
+    /// it.
+    pub(crate) fn purge_request(&self, request: &PurgeRequest) -> (PurgeConfirm, Option<usize>) {
+        let task_index = self
+            .pending_transactions
+            .borrow_mut()
+            .purge(request.msdu_handle);
+        let confirm = PurgeConfirm {
+            msdu_handle: request.msdu_handle,
+            status: task_index.map(|_| ()).ok_or(PurgeError::InvalidHandle),
+        };
+        (confirm, task_index)
     }
 }
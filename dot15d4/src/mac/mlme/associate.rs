@@ -1,14 +1,1085 @@
+use core::{
+    cell::{Cell, RefCell},
+    mem,
+};
+
 use rand_core::RngCore;
 
-use crate::{driver::DriverConfig, mac::MacService};
+use crate::{
+    driver::{
+        config::Channel,
+        constants::A_BASE_SUPERFRAME_DURATION,
+        frame::{Address, AddressingFields, ExtendedAddress, FrameFilter, PanId, ShortAddress},
+        tasks::{RxInfo, Timestamp},
+        DriverConfig, DrvSvcResponse, DrvSvcTaskOff, RadioTimerApi,
+    },
+    mac::{
+        csma::BackoffRng,
+        devices::{AssociatedDevice, AssociatedDevices},
+        frame::mpdu::{
+            association_request_frame, association_request_frame_repr, association_response_frame,
+            data_request_frame, CommandId, MpduFrame, ASSOCIATION_REQUEST_LEN,
+            ASSOCIATION_RESPONSE_FRAME_REPR, ASSOCIATION_RESPONSE_LEN,
+        },
+        indirect::{IndirectTxTask, PendingTransactions},
+        mcps::data::{DataError, DataRequest, DataRequestResult, DataRequestTask},
+        pib::Pib,
+        primitives::MacIndication,
+        task::*,
+        MacBufferAllocator, MacService,
+    },
+    util::allocator::{BufferToken, IntoBuffer},
+};
+
+use super::{
+    comm_status::{CommStatusError, CommStatusIndication},
+    scan::CoordAddress,
+};
+
+/// The max time to wait for the coordinator to prepare an Association
+/// Response (macResponseWaitTime), in multiples of aBaseSuperframeDuration.
+const MAC_RESPONSE_WAIT_TIME: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssociateError {
+    /// The coordinator cannot accept another device.
+    PanAtCapacity,
+    /// The coordinator denied access to the PAN.
+    PanAccessDenied,
+    /// The coordinator rejected the hopping sequence offset of the device.
+    HoppingSequenceOffsetDuplication,
+    /// A command could not be sent as the channel was busy.
+    ChannelAccessFailure,
+    /// A command was not acknowledged by the coordinator.
+    NoAck,
+    /// The coordinator did not send an Association Response in time.
+    NoData,
+    // TODO: not supported
+    CounterError,
+    // TODO: not supported
+    FrameTooLong,
+    /// The request parameters are invalid, the device has no extended address
+    /// or another association is in progress.
+    InvalidParameter,
+}
+
+impl From<DataError> for AssociateError {
+    fn from(value: DataError) -> Self {
+        match value {
+            DataError::ChannelAccessFailure => AssociateError::ChannelAccessFailure,
+            DataError::NoAck => AssociateError::NoAck,
+            DataError::CounterError => AssociateError::CounterError,
+            DataError::FrameTooLong => AssociateError::FrameTooLong,
+            _ => AssociateError::InvalidParameter,
+        }
+    }
+}
+
+impl AssociateError {
+    /// Interprets the Association Status field of an Association Response,
+    /// see IEEE 802.15.4-2020, section 7.5.3.3.
+    fn from_association_status(association_status: u8) -> Result<(), Self> {
+        match AssociationStatus::try_from(association_status) {
+            Ok(AssociationStatus::Successful | AssociationStatus::FastAssociationSuccessful) => {
+                Ok(())
+            }
+            Ok(AssociationStatus::PanAtCapacity) => Err(AssociateError::PanAtCapacity),
+            Ok(AssociationStatus::HoppingSequenceOffsetDuplication) => {
+                Err(AssociateError::HoppingSequenceOffsetDuplication)
+            }
+            // Reserved values are treated as a refusal.
+            Ok(AssociationStatus::PanAccessDenied) | Err(()) => {
+                Err(AssociateError::PanAccessDenied)
+            }
+        }
+    }
+}
+
+/// The Association Status field of an Association Response command, see IEEE
+/// 802.15.4-2020, section 7.5.3.3, table 7-50.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AssociationStatus {
+    Successful = 0x00,
+    PanAtCapacity = 0x01,
+    PanAccessDenied = 0x02,
+    HoppingSequenceOffsetDuplication = 0x03,
+    FastAssociationSuccessful = 0x80,
+}
+
+impl AssociationStatus {
+    pub const fn is_successful(&self) -> bool {
+        matches!(
+            self,
+            AssociationStatus::Successful | AssociationStatus::FastAssociationSuccessful
+        )
+    }
+}
+
+impl TryFrom<u8> for AssociationStatus {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(AssociationStatus::Successful),
+            0x01 => Ok(AssociationStatus::PanAtCapacity),
+            0x02 => Ok(AssociationStatus::PanAccessDenied),
+            0x03 => Ok(AssociationStatus::HoppingSequenceOffsetDuplication),
+            0x80 => Ok(AssociationStatus::FastAssociationSuccessful),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The Capability Information field of an Association Request command, see
+/// IEEE 802.15.4-2020, section 7.5.2.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CapabilityInformation(u8);
+
+impl CapabilityInformation {
+    /// Describes a device that does not use security.
+    pub const fn new(
+        full_function_device: bool,
+        mains_powered: bool,
+        rx_on_when_idle: bool,
+        allocate_address: bool,
+    ) -> Self {
+        Self(
+            (full_function_device as u8) << 1
+                | (mains_powered as u8) << 2
+                | (rx_on_when_idle as u8) << 3
+                | (allocate_address as u8) << 7,
+        )
+    }
+
+    pub const fn from_u8(value: u8) -> Self {
+        Self(value)
+    }
+
+    pub const fn into_u8(self) -> u8 {
+        self.0
+    }
+
+    /// Whether the device is a full-function device (FFD).
+    pub const fn full_function_device(&self) -> bool {
+        self.0 & (1 << 1) != 0
+    }
+
+    /// Whether the device is receiving power from the alternating current
+    /// mains.
+    pub const fn mains_powered(&self) -> bool {
+        self.0 & (1 << 2) != 0
+    }
+
+    /// Whether the device does not disable its receiver to conserve power
+    /// during idle periods.
+    pub const fn rx_on_when_idle(&self) -> bool {
+        self.0 & (1 << 3) != 0
+    }
+
+    /// Whether the device is capable of sending and receiving secured frames.
+    pub const fn security_capability(&self) -> bool {
+        self.0 & (1 << 6) != 0
+    }
+
+    /// Whether the device wishes the coordinator to allocate a short address.
+    pub const fn allocate_address(&self) -> bool {
+        self.0 & (1 << 7) != 0
+    }
+}
+
+/// Represents an MLME-ASSOCIATE.request, see IEEE 802.15.4-2020, section
+/// 8.2.3.1.
+pub struct AssociateRequest {
+    /// The channel on which to attempt association.
+    pub channel: Channel,
+    pub channel_page: u8,
+    pub coord_address: CoordAddress,
+    pub coord_pan_id: PanId<[u8; 2]>,
+    pub capability_information: CapabilityInformation,
+}
+
+impl AssociateRequest {
+    pub fn new(
+        channel: Channel,
+        coord_address: CoordAddress,
+        coord_pan_id: PanId<[u8; 2]>,
+        capability_information: CapabilityInformation,
+    ) -> Self {
+        Self {
+            channel,
+            channel_page: 0,
+            coord_address,
+            coord_pan_id,
+            capability_information,
+        }
+    }
+
+    fn validate(&self) -> Result<(), AssociateError> {
+        let coord_address = self.coord_address.as_address();
+        if self.channel_page != 0
+            || self.coord_pan_id.into_u16() == 0xffff
+            || coord_address.is_broadcast()
+        {
+            return Err(AssociateError::InvalidParameter);
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents an MLME-ASSOCIATE.confirm, see IEEE 802.15.4-2020, section
+/// 8.2.3.4.
+pub struct AssociateConfirm {
+    /// The short address allocated by the coordinator. 0xfffe if the device
+    /// shall use its extended address, 0xffff if the association failed.
+    pub assoc_short_address: ShortAddress<[u8; 2]>,
+    pub status: Result<(), AssociateError>,
+}
+
+/// An Association Response command received from the coordinator, see IEEE
+/// 802.15.4-2020, section 7.5.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AssociationResponse {
+    pub coord_extended_address: [u8; 8],
+    pub assoc_short_address: u16,
+    pub association_status: u8,
+}
+
+impl AssociationResponse {
+    /// Extracts an Association Response from the given command, see
+    /// [`super::parse_command`].
+    ///
+    /// Returns `None` if the command was not sent from an extended address or
+    /// is too short.
+    pub(crate) fn from_command(
+        addressing_fields: &AddressingFields<&[u8]>,
+        payload: &[u8],
+    ) -> Option<Self> {
+        let coord_extended_address = match addressing_fields.src_address()? {
+            Address::Extended(address) => address.as_ref().try_into().ok()?,
+            _ => return None,
+        };
+
+        // The payload excludes the Command ID.
+        let payload = payload.get(..ASSOCIATION_RESPONSE_LEN as usize - 1)?;
+
+        Some(Self {
+            coord_extended_address,
+            assoc_short_address: u16::from_le_bytes([payload[0], payload[1]]),
+            association_status: payload[2],
+        })
+    }
+}
+
+/// Hands an Association Response over from the MAC service to the ongoing
+/// association.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AssociationResponseSlot {
+    /// No association is in progress.
+    Idle,
+    /// An association is waiting for the coordinator's response.
+    Pending,
+    Received(AssociationResponse),
+}
+
+/// Associates the device with a coordinator in a non-beacon-enabled PAN, see
+/// IEEE 802.15.4-2020, section 6.4.1.
+///
+/// The task switches to the requested channel and PAN and sends an Association
+/// Request command. After macResponseWaitTime it polls the coordinator with a
+/// Data Request command. The Association Response is then received by the
+/// indication tasks and handed over by the MAC service.
+///
+/// Commands are sent by a data request sub-task, i.e. with CSMA-CA and
+/// retransmissions. Both commands re-use the same buffer.
+pub(crate) struct AssociateTask<'task, RadioDriverImpl: DriverConfig> {
+    request: AssociateRequest,
+    pib: &'task RefCell<Pib>,
+    /// The incoming frame filter shared with the driver service.
+    frame_filter: &'task Cell<FrameFilter>,
+    /// The channel shared with the driver service.
+    channel: &'task Cell<Channel>,
+    /// The Association Response handed over by the MAC service.
+    association_response: &'task Cell<AssociationResponseSlot>,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    buffer_allocator: MacBufferAllocator,
+    /// The extended address of the device, once validated.
+    extended_address: Option<ExtendedAddress<[u8; 8]>>,
+    /// The command buffer while no command is being sent.
+    buffer: Option<BufferToken>,
+    state: AssociateState<'task, RadioDriverImpl>,
+}
+
+enum AssociateState<'task, RadioDriverImpl: DriverConfig> {
+    Initial,
+    /// Waiting for the radio to switch off so that the coordinator's channel
+    /// is applied.
+    SwitchingChannel,
+    SendingCommand(CommandId, DataRequestTask<'task, RadioDriverImpl>),
+    /// Waiting for macResponseWaitTime so that the coordinator can prepare its
+    /// response.
+    WaitingForResponse,
+    /// Waiting for the Association Response once the Data Request has been
+    /// acknowledged.
+    ReceivingResponse,
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> AssociateTask<'task, RadioDriverImpl> {
+    pub fn new(
+        request: AssociateRequest,
+        pib: &'task RefCell<Pib>,
+        frame_filter: &'task Cell<FrameFilter>,
+        channel: &'task Cell<Channel>,
+        association_response: &'task Cell<AssociationResponseSlot>,
+        rng: &'task dyn BackoffRng,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        Self {
+            request,
+            pib,
+            frame_filter,
+            channel,
+            association_response,
+            rng,
+            buffer_allocator,
+            extended_address: None,
+            buffer: None,
+            state: AssociateState::Initial,
+        }
+    }
+
+    /// Switches to the coordinator's channel and PAN, see IEEE 802.15.4-2020,
+    /// section 6.4.1.
+    fn prepare_pib(&self) {
+        let mut pib = self.pib.borrow_mut();
+        pib.current_channel = self.request.channel;
+        pib.pan_id = self.request.coord_pan_id;
+        match self.request.coord_address {
+            CoordAddress::Short(coord_address) => {
+                pib.coord_short_address = coord_address.into_u16();
+            }
+            CoordAddress::Extended(coord_address) => {
+                // Safety: Extended addresses are eight bytes long.
+                pib.coord_extended_address = Some(coord_address.as_ref().try_into().unwrap());
+            }
+        }
+        self.frame_filter.set(pib.frame_filter());
+        self.channel.set(pib.current_channel);
+    }
+
+    fn allocate_buffer(&self) -> BufferToken {
+        let coord_address = self.request.coord_address.as_address();
+        // The Data Request command is never longer than the Association
+        // Request command.
+        let buffer_size = association_request_frame_repr(coord_address.into())
+            .min_buffer_size::<RadioDriverImpl>(ASSOCIATION_REQUEST_LEN)
+            .unwrap();
+        self.buffer_allocator
+            .try_allocate_buffer(buffer_size)
+            .expect("no capacity")
+    }
+
+    /// Builds the given command and starts sending it.
+    fn send_command(
+        mut self,
+        command_id: CommandId,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let buffer = self.buffer.take().unwrap();
+        let coord_address = self.request.coord_address.as_address();
+        let coord_pan_id = self.request.coord_pan_id;
+        // Safety: The extended address was validated on entry.
+        let extended_address = self.extended_address.unwrap();
+
+        let mpdu = match command_id {
+            CommandId::AssociationRequest => association_request_frame::<RadioDriverImpl>(
+                &coord_address,
+                coord_pan_id,
+                extended_address,
+                self.request.capability_information.into_u8(),
+                0,
+                buffer,
+            ),
+            _ => data_request_frame::<RadioDriverImpl>(
+                &coord_address,
+                coord_pan_id,
+                &Address::Extended(ExtendedAddress::new(extended_address.as_ref())),
+                0,
+                buffer,
+            ),
+        }
+        .into_mpdu_frame();
+
+        let command_task =
+            DataRequestTask::new(DataRequest::new(mpdu), &mut self.pib.borrow_mut(), self.rng);
+        self.state = AssociateState::SendingCommand(command_id, command_task);
+        self.step(MacTaskEvent::Entry)
+    }
+
+    /// Waits for the coordinator once the given command was acknowledged.
+    fn wait_for_response(
+        mut self,
+        command_id: CommandId,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let wait_time = if matches!(command_id, CommandId::AssociationRequest) {
+            self.state = AssociateState::WaitingForResponse;
+            A_BASE_SUPERFRAME_DURATION * MAC_RESPONSE_WAIT_TIME
+        } else {
+            self.state = AssociateState::ReceivingResponse;
+            self.pib.borrow().max_frame_total_wait_time()
+        };
+        let expiry = RadioDriverImpl::Timer::now() + wait_time.convert_into_rounding_up();
+        MacTaskTransition::TimerRequest(self, expiry)
+    }
+
+    /// Updates the PIB with the outcome of the association and confirms it.
+    ///
+    /// The device leaves the PAN if the association failed.
+    fn terminate(
+        mut self,
+        response: Result<AssociationResponse, AssociateError>,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        if let Some(buffer) = self.buffer.take() {
+            // Safety: We allocated the buffer ourselves.
+            unsafe {
+                self.buffer_allocator.deallocate_buffer(buffer);
+            }
+        }
+        self.association_response.set(AssociationResponseSlot::Idle);
 
-pub struct AssociateConfirm;
+        let response = response.and_then(|response| {
+            AssociateError::from_association_status(response.association_status).map(|_| response)
+        });
+
+        let mut pib = self.pib.borrow_mut();
+        let assoc_short_address = match response {
+            Ok(response) => {
+                pib.short_address = response.assoc_short_address;
+                pib.coord_extended_address = Some(response.coord_extended_address);
+                response.assoc_short_address
+            }
+            Err(_) => {
+                pib.reset_association();
+                0xffff
+            }
+        };
+        self.frame_filter.set(pib.frame_filter());
+        drop(pib);
+
+        MacTaskTransition::Terminated(AssociateConfirm {
+            assoc_short_address: ShortAddress::new_owned(assoc_short_address.to_le_bytes()),
+            status: response.map(|_| ()),
+        })
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for AssociateTask<'_, RadioDriverImpl>
+{
+    type Result = AssociateConfirm;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, AssociateState::Initial) {
+            AssociateState::Initial => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                let extended_address = self.pib.borrow().extended_address;
+                let status = match extended_address {
+                    Some(_) if self.association_response.get() == AssociationResponseSlot::Idle => {
+                        self.request.validate()
+                    }
+                    _ => Err(AssociateError::InvalidParameter),
+                };
+                if let Err(error) = status {
+                    // Leave the PIB alone.
+                    return MacTaskTransition::Terminated(AssociateConfirm {
+                        assoc_short_address: ShortAddress::new_owned([0xff, 0xff]),
+                        status: Err(error),
+                    });
+                }
+
+                self.extended_address = extended_address.map(ExtendedAddress::new_owned);
+                self.association_response
+                    .set(AssociationResponseSlot::Pending);
+                self.prepare_pib();
+                self.buffer = Some(self.allocate_buffer());
+
+                self.state = AssociateState::SwitchingChannel;
+                MacTaskTransition::DrvSvcRequest(
+                    self,
+                    DrvSvcTaskOff {
+                        at: Timestamp::BestEffort,
+                    }
+                    .into(),
+                    None,
+                )
+            }
+            AssociateState::SwitchingChannel => {
+                debug_assert!(matches!(
+                    event,
+                    MacTaskEvent::DrvSvcResponse(DrvSvcResponse::Off(Ok(_)))
+                ));
+                self.send_command(CommandId::AssociationRequest)
+            }
+            AssociateState::SendingCommand(command_id, command_task) => {
+                match command_task.step(event) {
+                    MacTaskTransition::DrvSvcRequest(command_task, driver_request, _) => {
+                        self.state = AssociateState::SendingCommand(command_id, command_task);
+                        MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                    }
+                    MacTaskTransition::TimerRequest(command_task, expiry) => {
+                        self.state = AssociateState::SendingCommand(command_id, command_task);
+                        MacTaskTransition::TimerRequest(self, expiry)
+                    }
+                    MacTaskTransition::Terminated(DataRequestResult {
+                        confirm,
+                        radio_frame,
+                        ..
+                    }) => {
+                        self.buffer = Some(radio_frame.into_buffer());
+                        match confirm.status {
+                            Ok(()) => self.wait_for_response(command_id),
+                            Err(error) => self.terminate(Err(error.into())),
+                        }
+                    }
+                }
+            }
+            AssociateState::WaitingForResponse => {
+                debug_assert!(matches!(event, MacTaskEvent::TimerExpired));
+                self.send_command(CommandId::DataRequest)
+            }
+            AssociateState::ReceivingResponse => {
+                debug_assert!(matches!(event, MacTaskEvent::TimerExpired));
+                match self.association_response.get() {
+                    AssociationResponseSlot::Received(response) => self.terminate(Ok(response)),
+                    _ => self.terminate(Err(AssociateError::NoData)),
+                }
+            }
+        }
+    }
+}
+
+/// An Association Request command received from a device, see IEEE
+/// 802.15.4-2020, section 7.5.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AssociationRequest {
+    pub device_address: ExtendedAddress<[u8; 8]>,
+    pub capability_information: CapabilityInformation,
+}
+
+impl AssociationRequest {
+    /// Extracts an Association Request from the given command, see
+    /// [`super::parse_command`].
+    ///
+    /// Returns `None` if the command was not sent from an extended address or
+    /// is too short.
+    pub(crate) fn from_command(
+        addressing_fields: &AddressingFields<&[u8]>,
+        payload: &[u8],
+    ) -> Option<Self> {
+        let device_address = match addressing_fields.src_address()? {
+            Address::Extended(address) => {
+                ExtendedAddress::new_owned(address.as_ref().try_into().ok()?)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            device_address,
+            capability_information: CapabilityInformation::from_u8(*payload.first()?),
+        })
+    }
+}
+
+/// Represents an MLME-ASSOCIATE.indication, see IEEE 802.15.4-2020, section
+/// 8.2.3.2.
+pub struct AssociateIndication {
+    /// The extended address of the device requesting association.
+    pub device_address: ExtendedAddress<[u8; 8]>,
+    pub capability_information: CapabilityInformation,
+    /// Not part of the standard: The short address proposed by the
+    /// coordinator's short address allocator. 0xfffe if the device did not ask
+    /// for a short address, `None` if no short address is available.
+    pub proposed_short_address: Option<ShortAddress<[u8; 2]>>,
+    /// Link quality indicator of the received Association Request
+    pub link_quality: u8,
+    /// Radio clock tick at which the RMARKER of the Association Request passed
+    /// the local antenna, if known
+    pub timestamp: Option<u64>,
+}
+
+impl AssociateIndication {
+    /// Accepts the device with the proposed short address or rejects it if no
+    /// short address is available.
+    pub fn respond(&self) -> AssociateResponse {
+        let (assoc_short_address, status) = match self.proposed_short_address {
+            Some(short_address) => (short_address, AssociationStatus::Successful),
+            None => (
+                ShortAddress::new_owned([0xff, 0xff]),
+                AssociationStatus::PanAtCapacity,
+            ),
+        };
+
+        AssociateResponse {
+            device_address: self.device_address,
+            assoc_short_address,
+            status,
+            capability_information: self.capability_information,
+        }
+    }
+}
+
+/// Represents an MLME-ASSOCIATE.response, see IEEE 802.15.4-2020, section
+/// 8.2.3.3.
+///
+/// The Association Response command is queued for indirect transmission. The
+/// outcome is reported with an MLME-COMM-STATUS.indication.
+pub struct AssociateResponse {
+    pub device_address: ExtendedAddress<[u8; 8]>,
+    /// The short address allocated to the device. 0xfffe if the device shall
+    /// use its extended address, 0xffff if the association was rejected.
+    pub assoc_short_address: ShortAddress<[u8; 2]>,
+    pub status: AssociationStatus,
+    /// Not part of the standard: The capability information of the device to
+    /// be recorded in the associated device table.
+    pub capability_information: CapabilityInformation,
+}
+
+/// Responds to an association attempt in a non-beacon-enabled PAN, see IEEE
+/// 802.15.4-2020, section 6.4.1.
+///
+/// The task queues a pending transaction and waits for the device to poll for
+/// the Association Response with a Data Request command. The MAC service wakes
+/// the task once the Data Request has been received. The transaction expires
+/// after macTransactionPersistenceTime.
+///
+/// The device is recorded in the associated device table once a successful
+/// response has been acknowledged.
+pub(crate) struct AssociateResponseTask<'task, RadioDriverImpl: DriverConfig> {
+    response: AssociateResponse,
+    /// The index of this task, used to match pending transactions.
+    task_index: usize,
+    pib: &'task RefCell<Pib>,
+    associated_devices: &'task RefCell<AssociatedDevices>,
+    pending_transactions: &'task RefCell<PendingTransactions>,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    buffer_allocator: MacBufferAllocator,
+    state: AssociateResponseState<'task, RadioDriverImpl>,
+}
+
+enum AssociateResponseState<'task, RadioDriverImpl: DriverConfig> {
+    Initial,
+    SendingResponse(IndirectTxTask<'task, RadioDriverImpl>),
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> AssociateResponseTask<'task, RadioDriverImpl> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        response: AssociateResponse,
+        task_index: usize,
+        pib: &'task RefCell<Pib>,
+        associated_devices: &'task RefCell<AssociatedDevices>,
+        pending_transactions: &'task RefCell<PendingTransactions>,
+        rng: &'task dyn BackoffRng,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        Self {
+            response,
+            task_index,
+            pib,
+            associated_devices,
+            pending_transactions,
+            rng,
+            buffer_allocator,
+            state: AssociateResponseState::Initial,
+        }
+    }
+
+    /// Checks whether the response can be sent and builds the command.
+    fn build_response(&self) -> Result<MpduFrame, CommStatusError> {
+        let pib = self.pib.borrow();
+        let coord_extended_address = pib
+            .extended_address
+            .ok_or(CommStatusError::InvalidParameter)?;
+
+        let mut associated_devices = self.associated_devices.borrow_mut();
+        if self.response.status.is_successful() {
+            let device_address = &self.response.device_address;
+            let short_address = &self.response.assoc_short_address;
+            let listed_short_address = associated_devices
+                .get(device_address)
+                .map(|device| device.short_address);
+            let reserved_short_address = associated_devices.reserved_short_address(device_address);
+            let is_new = listed_short_address.is_none() && reserved_short_address.is_none();
+            let is_proposed = listed_short_address == Some(*short_address)
+                || reserved_short_address == Some(*short_address);
+            // The next higher layer may override the proposed address but
+            // must not hand out addresses in use.
+            if (is_new && associated_devices.is_full())
+                || (!is_proposed && associated_devices.is_short_address_in_use(short_address))
+            {
+                return Err(CommStatusError::InvalidParameter);
+            }
+
+            // Keep the address reserved until the response is acknowledged
+            // or fails.
+            associated_devices.reserve(*device_address, *short_address, None);
+        }
+
+        let buffer_size = ASSOCIATION_RESPONSE_FRAME_REPR
+            .min_buffer_size::<RadioDriverImpl>(ASSOCIATION_RESPONSE_LEN)
+            .unwrap();
+        let buffer = self
+            .buffer_allocator
+            .try_allocate_buffer(buffer_size)
+            .expect("no capacity");
+
+        Ok(association_response_frame::<RadioDriverImpl>(
+            self.response.device_address,
+            pib.pan_id,
+            ExtendedAddress::new_owned(coord_extended_address),
+            self.response.assoc_short_address,
+            self.response.status as u8,
+            0,
+            buffer,
+        )
+        .into_mpdu_frame())
+    }
+
+    /// Records the device once the response was acknowledged and reports the
+    /// outcome.
+    fn terminate(
+        self,
+        status: Result<(), CommStatusError>,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let mut associated_devices = self.associated_devices.borrow_mut();
+        associated_devices.release(&self.response.device_address);
+        if status.is_ok() && self.response.status.is_successful() {
+            // The address was reserved on entry, so the device fits.
+            let _ = associated_devices.insert(AssociatedDevice {
+                extended_address: self.response.device_address,
+                short_address: self.response.assoc_short_address,
+                capability_information: self.response.capability_information,
+            });
+        }
+        drop(associated_devices);
+
+        MacTaskTransition::Terminated(CommStatusIndication {
+            pan_id: self.pib.borrow().pan_id,
+            dst_address: self.response.device_address,
+            status,
+        })
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for AssociateResponseTask<'_, RadioDriverImpl>
+{
+    type Result = CommStatusIndication;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, AssociateResponseState::Initial) {
+            AssociateResponseState::Initial => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                let mpdu = match self.build_response() {
+                    Ok(mpdu) => mpdu,
+                    Err(error) => return self.terminate(Err(error)),
+                };
+
+                // The device polls with its extended address as it has not
+                // been allocated a short address, yet.
+                let response_task = IndirectTxTask::new(
+                    DataRequest::new(mpdu),
+                    Some(self.response.device_address),
+                    None,
+                    self.task_index,
+                    self.pib,
+                    self.pending_transactions,
+                    self.rng,
+                );
+                self.state = AssociateResponseState::SendingResponse(response_task);
+                self.step(MacTaskEvent::Entry)
+            }
+            AssociateResponseState::SendingResponse(response_task) => {
+                match response_task.step(event) {
+                    MacTaskTransition::DrvSvcRequest(response_task, driver_request, _) => {
+                        self.state = AssociateResponseState::SendingResponse(response_task);
+                        MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                    }
+                    MacTaskTransition::TimerRequest(response_task, expiry) => {
+                        self.state = AssociateResponseState::SendingResponse(response_task);
+                        MacTaskTransition::TimerRequest(self, expiry)
+                    }
+                    MacTaskTransition::Terminated(DataRequestResult {
+                        confirm,
+                        radio_frame,
+                        ..
+                    }) => {
+                        // Safety: We allocated the buffer ourselves.
+                        unsafe {
+                            self.buffer_allocator
+                                .deallocate_buffer(radio_frame.into_buffer());
+                        }
+                        self.terminate(confirm.status.map_err(CommStatusError::from))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'_, Rng, RadioDriverImpl> {
+    /// Hands the given Association Response command over to an ongoing
+    /// association.
+    pub(crate) fn receive_association_response(
+        &self,
+        addressing_fields: &AddressingFields<&[u8]>,
+        payload: &[u8],
+    ) {
+        if self.association_response.get() != AssociationResponseSlot::Pending {
+            return;
+        }
+
+        if let Some(response) = AssociationResponse::from_command(addressing_fields, payload) {
+            self.association_response
+                .set(AssociationResponseSlot::Received(response));
+        }
+    }
+
+    /// Indicates the given Association Request command to the next higher
+    /// layer if association is permitted.
+    ///
+    /// Devices that are already associated are proposed their current short
+    /// address. Otherwise the short address allocator is asked for a new one.
+    ///
+    /// Proposed addresses are reserved until the device polls for the
+    /// Association Response. Devices repeating their request are proposed the
+    /// reserved address again.
+    pub(crate) fn receive_association_request(
+        &self,
+        addressing_fields: &AddressingFields<&[u8]>,
+        payload: &[u8],
+        rx_info: &RxInfo,
+    ) {
+        if !self.pib.borrow().association_permit {
+            return;
+        }
+
+        let Some(AssociationRequest {
+            device_address,
+            capability_information,
+        }) = AssociationRequest::from_command(addressing_fields, payload)
+        else {
+            return;
+        };
+
+        let now = RadioDriverImpl::Timer::now();
+        let (coord_short_address, frame_total_wait_time) = {
+            let pib = self.pib.borrow();
+            (
+                ShortAddress::new_owned(pib.short_address.to_le_bytes()),
+                pib.max_frame_total_wait_time(),
+            )
+        };
+
+        let mut associated_devices = self.associated_devices.borrow_mut();
+        associated_devices.release_expired(now.tick());
+        let proposed_short_address = match associated_devices.get(&device_address) {
+            Some(device) => Some(device.short_address),
+            None if !capability_information.allocate_address() => {
+                Some(ShortAddress::new_owned([0xfe, 0xff]))
+            }
+            None => associated_devices
+                .reserved_short_address(&device_address)
+                .or_else(|| {
+                    self.short_address_allocator.borrow_mut().allocate(
+                        &device_address,
+                        capability_information,
+                        coord_short_address,
+                        &associated_devices,
+                    )
+                }),
+        };
+        if let Some(short_address) = proposed_short_address {
+            if associated_devices.get(&device_address).is_none() {
+                // The device polls for the response once macResponseWaitTime
+                // elapsed.
+                let wait_time =
+                    A_BASE_SUPERFRAME_DURATION * MAC_RESPONSE_WAIT_TIME + frame_total_wait_time;
+                let expiry = now + wait_time.convert_into_rounding_up();
+                associated_devices.reserve(device_address, short_address, Some(expiry.tick()));
+            }
+        }
+        drop(associated_devices);
+
+        self.try_indicate(MacIndication::MlmeAssociate(AssociateIndication {
+            device_address,
+            capability_information,
+            proposed_short_address,
+            link_quality: rx_info.lqi,
+            timestamp: rx_info.timestamp,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::parse_command, *};
+
+    #[test]
+    fn capability_information() {
+        let capability_information = CapabilityInformation::new(true, false, true, true);
+        assert_eq!(capability_information.into_u8(), 0x8a);
+        assert!(capability_information.full_function_device());
+        assert!(!capability_information.mains_powered());
+        assert!(capability_information.rx_on_when_idle());
+        assert!(!capability_information.security_capability());
+        assert!(capability_information.allocate_address());
+    }
+
+    #[test]
+    fn association_response_from_command() {
+        #[rustfmt::skip]
+        let association_response = [
+            // Frame control: command, AR, PAN ID compression, extended
+            // destination and source addresses
+            0x63, 0xcc,
+            // Sequence number
+            0x05,
+            // Destination PAN ID
+            0x34, 0x12,
+            // Destination address
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            // Source address
+            0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18,
+            // Association Response: short address, status
+            0x02, 0x01, 0x00, 0x00,
+        ];
+
+        let (addressing_fields, command_id, payload) =
+            parse_command(&association_response).unwrap();
+        assert_eq!(command_id, CommandId::AssociationResponse);
+        let response = AssociationResponse::from_command(&addressing_fields, payload).unwrap();
+        assert_eq!(
+            response.coord_extended_address,
+            [0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18]
+        );
+        assert_eq!(response.assoc_short_address, 0x0001);
+        assert_eq!(
+            AssociateError::from_association_status(response.association_status),
+            Ok(())
+        );
+
+        // Truncated command
+        assert!(AssociationResponse::from_command(&addressing_fields, &payload[..2]).is_none());
+
+        // Unknown command
+        let mut other_command = association_response;
+        other_command[21] = 0x7f;
+        assert!(parse_command(&other_command).is_none());
+    }
+
+    #[test]
+    fn association_request_from_command() {
+        #[rustfmt::skip]
+        let association_request = [
+            // Frame control: command, AR, short destination and extended
+            // source addresses
+            0x23, 0xc8,
+            // Sequence number
+            0x07,
+            // Destination PAN ID
+            0x34, 0x12,
+            // Destination address
+            0x00, 0x00,
+            // Source PAN ID
+            0xff, 0xff,
+            // Source address
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            // Association Request: capability information
+            0x01, 0x8a,
+        ];
+
+        let (addressing_fields, command_id, payload) = parse_command(&association_request).unwrap();
+        assert_eq!(command_id, CommandId::AssociationRequest);
+        let request = AssociationRequest::from_command(&addressing_fields, payload).unwrap();
+        assert_eq!(
+            request.device_address,
+            ExtendedAddress::new_owned([0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08])
+        );
+        assert_eq!(
+            request.capability_information,
+            CapabilityInformation::new(true, false, true, true)
+        );
+    }
+
+    #[test]
+    fn respond_to_associate_indication() {
+        let mut indication = AssociateIndication {
+            device_address: ExtendedAddress::new_owned([1; 8]),
+            capability_information: CapabilityInformation::new(false, false, false, true),
+            proposed_short_address: Some(ShortAddress::new_owned([0x01, 0x00])),
+            link_quality: 0xff,
+            timestamp: None,
+        };
+
+        let response = indication.respond();
+        assert_eq!(response.status, AssociationStatus::Successful);
+        assert_eq!(response.assoc_short_address.into_u16(), 0x0001);
+
+        indication.proposed_short_address = None;
+        let response = indication.respond();
+        assert_eq!(response.status, AssociationStatus::PanAtCapacity);
+        assert_eq!(response.assoc_short_address.into_u16(), 0xffff);
+    }
+
+    #[test]
+    fn association_status() {
+        assert_eq!(
+            AssociateError::from_association_status(0x01),
+            Err(AssociateError::PanAtCapacity)
+        );
+        assert_eq!(
+            AssociateError::from_association_status(0x02),
+            Err(AssociateError::PanAccessDenied)
+        );
+        assert_eq!(AssociateError::from_association_status(0x80), Ok(()));
+    }
 
-#[allow(dead_code)]
-impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, RadioDriverImpl> {
-    /// Requests the association with a coordinator.
-    async fn mlme_associate_request(&self) -> Result<AssociateConfirm, ()> {
-        // TODO: support association
-        Err(())
+    #[test]
+    fn validate_associate_request() {
+        let coord_address = CoordAddress::Short(ShortAddress::new_owned([0x00, 0x00]));
+        let coord_pan_id = PanId::new_owned([0x34, 0x12]);
+        assert!(AssociateRequest::new(
+            Channel::_11,
+            coord_address,
+            coord_pan_id,
+            CapabilityInformation::default()
+        )
+        .validate()
+        .is_ok());
+        assert_eq!(
+            AssociateRequest::new(
+                Channel::_11,
+                CoordAddress::Short(ShortAddress::new_owned([0xff, 0xff])),
+                coord_pan_id,
+                CapabilityInformation::default()
+            )
+            .validate(),
+            Err(AssociateError::InvalidParameter)
+        );
+        assert_eq!(
+            AssociateRequest::new(
+                Channel::_11,
+                coord_address,
+                PanId::new_owned([0xff, 0xff]),
+                CapabilityInformation::default()
+            )
+            .validate(),
+            Err(AssociateError::InvalidParameter)
+        );
     }
 }
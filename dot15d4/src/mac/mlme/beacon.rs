@@ -1,13 +1,504 @@
 #![allow(dead_code)]
-use crate::mac::frame::mpdu::MpduFrame;
+use core::{cell::RefCell, mem};
 
-pub struct BeaconRequest {}
+use rand_core::RngCore;
 
-pub struct BeaconConfirm {}
+use crate::{
+    driver::{
+        config::Channel,
+        frame::{Address, AddressingMode, ExtendedAddress, ShortAddress},
+        tasks::RxInfo,
+        time::Instant,
+        DriverConfig, RadioTimerApi,
+    },
+    mac::{
+        csma::BackoffRng,
+        frame::{
+            mpdu::{beacon_frame, beacon_frame_min_buffer_size, MpduFrame},
+            repr::{IeRepr, IeReprList},
+        },
+        mcps::data::{DataError, DataRequest, DataRequestResult, DataRequestTask},
+        pib::Pib,
+        primitives::MacIndication,
+        task::*,
+        MacBufferAllocator, MacService,
+    },
+    util::allocator::IntoBuffer,
+};
 
+use super::scan::PanDescriptor;
+
+/// Length of the Superframe Specification, GTS and Pending Address fields of a
+/// beacon without GTS descriptors and pending addresses.
+const BEACON_FIELDS_LEN: u16 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeaconType {
+    Beacon,
+    EnhancedBeacon,
+}
+
+/// Represents an MLME-BEACON.request, see IEEE 802.15.4-2020, section
+/// 8.2.18.1.
+///
+/// The beacon is broadcast on the current channel. Secured beacons are not
+/// supported.
+pub struct BeaconRequest {
+    pub beacon_type: BeaconType,
+    /// The IEs carried by an Enhanced Beacon. Ignored for other beacons.
+    pub ies: IeReprList<'static, IeRepr<'static>>,
+}
+
+impl BeaconRequest {
+    pub fn new(beacon_type: BeaconType) -> Self {
+        Self {
+            beacon_type,
+            ies: IeReprList::new(&[]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeaconError {
+    /// The beacon could not be sent as the channel was busy.
+    ChannelAccessFailure,
+    // TODO: not supported
+    FrameTooLong,
+    /// The beacon requires an extended source address but none was set or the
+    /// IEs are inconsistent.
+    InvalidParameter,
+    /// Not part of the standard: No buffer was available for the beacon.
+    NoBuffer,
+}
+
+impl From<DataError> for BeaconError {
+    fn from(value: DataError) -> Self {
+        match value {
+            DataError::ChannelAccessFailure => BeaconError::ChannelAccessFailure,
+            DataError::FrameTooLong => BeaconError::FrameTooLong,
+            // Beacons are neither acknowledged nor sent indirectly.
+            _ => BeaconError::InvalidParameter,
+        }
+    }
+}
+
+/// Represents an MLME-BEACON.confirm, see IEEE 802.15.4-2020, section
+/// 8.2.18.2.
+pub struct BeaconConfirm {
+    pub status: Result<(), BeaconError>,
+}
+
+/// Represents an MLME-BEACON-NOTIFY.indication, see IEEE 802.15.4-2020,
+/// section 8.2.5.1.
 pub struct BeaconNotifyIndication {
     /// The received beacon frame
     pub mpdu: MpduFrame,
-    /// Timestamp of frame reception
-    pub timestamp: u32,
+    /// The PAN descriptor extracted from the beacon
+    pub pan_descriptor: PanDescriptor,
+    /// Radio clock tick at which the RMARKER of the beacon passed the local
+    /// antenna, if known
+    pub timestamp: Option<u64>,
+    /// The offset of the beacon payload within the MPDU
+    sdu_offset: usize,
+}
+
+impl BeaconNotifyIndication {
+    /// The beacon payload (SDU) following the beacon fields and IEs, if any.
+    pub fn sdu(&self) -> &[u8] {
+        &self.mpdu.pdu_ref_wo_fcs()[self.sdu_offset..]
+    }
+}
+
+/// The Superframe Specification field of a beacon frame, see IEEE
+/// 802.15.4-2020, section 7.3.1.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuperframeSpecification(u16);
+
+impl SuperframeSpecification {
+    pub const fn new(
+        beacon_order: u8,
+        superframe_order: u8,
+        final_cap_slot: u8,
+        battery_life_extension: bool,
+        pan_coordinator: bool,
+        association_permit: bool,
+    ) -> Self {
+        Self(
+            (beacon_order & 0xf) as u16
+                | ((superframe_order & 0xf) as u16) << 4
+                | ((final_cap_slot & 0xf) as u16) << 8
+                | (battery_life_extension as u16) << 12
+                | (pan_coordinator as u16) << 14
+                | (association_permit as u16) << 15,
+        )
+    }
+
+    pub const fn from_le_bytes(le_bytes: [u8; 2]) -> Self {
+        Self(u16::from_le_bytes(le_bytes))
+    }
+
+    pub const fn into_le_bytes(self) -> [u8; 2] {
+        self.0.to_le_bytes()
+    }
+
+    /// The transmission interval of the beacon (macBeaconOrder). A value of 15
+    /// designates a non-beacon-enabled PAN.
+    pub const fn beacon_order(&self) -> u8 {
+        (self.0 & 0xf) as u8
+    }
+
+    /// The length of the active portion of the superframe (macSuperframeOrder).
+    pub const fn superframe_order(&self) -> u8 {
+        ((self.0 >> 4) & 0xf) as u8
+    }
+
+    /// The final superframe slot utilized by the CAP.
+    pub const fn final_cap_slot(&self) -> u8 {
+        ((self.0 >> 8) & 0xf) as u8
+    }
+
+    /// Whether frames transmitted to the beaconing device during its CAP are
+    /// required to start in or before macBattLifeExtPeriods.
+    pub const fn battery_life_extension(&self) -> bool {
+        self.0 & (1 << 12) != 0
+    }
+
+    /// Whether the beacon was sent by the PAN coordinator.
+    pub const fn pan_coordinator(&self) -> bool {
+        self.0 & (1 << 14) != 0
+    }
+
+    /// Whether the beaconing device accepts association (macAssociationPermit).
+    pub const fn association_permit(&self) -> bool {
+        self.0 & (1 << 15) != 0
+    }
+}
+
+/// Builds a beacon from the given PIB. The beacon is sent from the extended
+/// address if the device has no short address (0xfffe or 0xffff).
+///
+/// An Enhanced Beacon carries the given IEs but no payload.
+pub(crate) fn build_beacon_frame<RadioDriverImpl: DriverConfig>(
+    pib: &Pib,
+    beacon_type: BeaconType,
+    ies: IeReprList<'static, IeRepr<'static>>,
+    buffer_allocator: MacBufferAllocator,
+) -> Result<MpduFrame, BeaconError> {
+    let src_addr_mode = if pib.short_address >= 0xfffe {
+        if pib.extended_address.is_none() {
+            return Err(BeaconError::InvalidParameter);
+        }
+        AddressingMode::Extended
+    } else {
+        AddressingMode::Short
+    };
+
+    let (enhanced, ies, beacon_payload_length) = match beacon_type {
+        BeaconType::Beacon => (false, None, BEACON_FIELDS_LEN),
+        BeaconType::EnhancedBeacon => (true, Some(ies).filter(|ies| !ies.is_empty()), 0),
+    };
+    // Inconsistent IEs are rejected up front so that failing to build the
+    // beacon means that no buffer was available.
+    beacon_frame_min_buffer_size::<RadioDriverImpl>(src_addr_mode, ies, beacon_payload_length)
+        .map_err(|_| BeaconError::InvalidParameter)?;
+    let mut beacon = beacon_frame::<RadioDriverImpl>(
+        enhanced,
+        src_addr_mode,
+        ies,
+        beacon_payload_length,
+        buffer_allocator,
+    )
+    .map_err(|_| BeaconError::NoBuffer)?;
+    let mut addressing_fields = beacon.addressing_fields_mut().unwrap().unwrap();
+    addressing_fields
+        .src_pan_id_mut()
+        .unwrap()
+        .set_le_bytes(pib.pan_id);
+    let mut src_address = addressing_fields.src_address_mut().unwrap();
+    let _ = match src_addr_mode {
+        // Safety: The extended address was checked above.
+        AddressingMode::Extended => src_address.set(&Address::Extended(
+            ExtendedAddress::new_owned(pib.extended_address.unwrap()),
+        )),
+        _ => src_address.set(&Address::Short(ShortAddress::new_owned(
+            pib.short_address.to_le_bytes(),
+        ))),
+    };
+
+    match beacon_type {
+        BeaconType::Beacon => {
+            // A non-beacon-enabled PAN has neither an active nor an
+            // inactive portion, the full superframe belongs to the CAP.
+            let superframe_specification = SuperframeSpecification::new(
+                15,
+                15,
+                15,
+                false,
+                pib.pan_coordinator,
+                pib.association_permit,
+            );
+            let beacon_fields = beacon.frame_payload_mut().unwrap();
+            beacon_fields[..2].copy_from_slice(&superframe_specification.into_le_bytes());
+            // Neither GTS descriptors nor pending addresses.
+            beacon_fields[2..].fill(0);
+        }
+        // IE content is zeroed. The TSCH slot task fills in the TSCH IEs of
+        // the Enhanced Beacons sent on advertising links.
+        BeaconType::EnhancedBeacon => {}
+    }
+
+    Ok(beacon.into_mpdu_frame())
+}
+
+/// Sends a beacon or Enhanced Beacon in a non-beacon-enabled PAN, e.g. in
+/// response to a Beacon Request command, see IEEE 802.15.4-2020, section
+/// 6.3.1.2.
+///
+/// Beacons are sent using unslotted CSMA-CA and are not acknowledged.
+pub(crate) struct BeaconTask<'task, RadioDriverImpl: DriverConfig> {
+    beacon_type: BeaconType,
+    ies: IeReprList<'static, IeRepr<'static>>,
+    pib: &'task RefCell<Pib>,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    buffer_allocator: MacBufferAllocator,
+    state: BeaconState<'task, RadioDriverImpl>,
+}
+
+enum BeaconState<'task, RadioDriverImpl: DriverConfig> {
+    Initial,
+    Sending(DataRequestTask<'task, RadioDriverImpl>),
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> BeaconTask<'task, RadioDriverImpl> {
+    pub fn new(
+        beacon_request: BeaconRequest,
+        pib: &'task RefCell<Pib>,
+        rng: &'task dyn BackoffRng,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        Self {
+            beacon_type: beacon_request.beacon_type,
+            ies: beacon_request.ies,
+            pib,
+            rng,
+            buffer_allocator,
+            state: BeaconState::Initial,
+        }
+    }
+
+    fn beacon_sent(
+        self,
+        result: DataRequestResult,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        // Safety: We allocated the buffer ourselves.
+        unsafe {
+            self.buffer_allocator
+                .deallocate_buffer(result.radio_frame.into_buffer());
+        }
+        MacTaskTransition::Terminated(BeaconConfirm {
+            status: result.confirm.status.map_err(BeaconError::from),
+        })
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for BeaconTask<'_, RadioDriverImpl>
+{
+    type Result = BeaconConfirm;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, BeaconState::Initial) {
+            BeaconState::Initial => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                let beacon = build_beacon_frame::<RadioDriverImpl>(
+                    &self.pib.borrow(),
+                    self.beacon_type,
+                    self.ies,
+                    self.buffer_allocator,
+                );
+                let beacon = match beacon {
+                    Ok(beacon) => beacon,
+                    Err(error) => {
+                        return MacTaskTransition::Terminated(BeaconConfirm { status: Err(error) })
+                    }
+                };
+                self.state = BeaconState::Sending(DataRequestTask::new(
+                    DataRequest::new(beacon),
+                    &mut self.pib.borrow_mut(),
+                    self.rng,
+                ));
+                self.step(MacTaskEvent::Entry)
+            }
+            BeaconState::Sending(beacon_task) => match beacon_task.step(event) {
+                MacTaskTransition::DrvSvcRequest(beacon_task, driver_request, _) => {
+                    self.state = BeaconState::Sending(beacon_task);
+                    MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                }
+                MacTaskTransition::TimerRequest(beacon_task, expiry) => {
+                    self.state = BeaconState::Sending(beacon_task);
+                    MacTaskTransition::TimerRequest(self, expiry)
+                }
+                MacTaskTransition::Terminated(result) => self.beacon_sent(result),
+            },
+        }
+    }
+}
+
+/// Sends Enhanced Beacons every aBaseSuperframeDuration *
+/// 2^macEnhancedBeaconOrder symbols, see IEEE 802.15.4-2020, section 6.3.
+///
+/// The task terminates once macEnhancedBeaconOrder is set to 15 or the device
+/// is no longer a coordinator. Beacons that cannot be sent are not retried.
+pub(crate) struct EnhancedBeaconTask<'task, RadioDriverImpl: DriverConfig> {
+    pib: &'task RefCell<Pib>,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    buffer_allocator: MacBufferAllocator,
+    state: EnhancedBeaconState<'task, RadioDriverImpl>,
+}
+
+enum EnhancedBeaconState<'task, RadioDriverImpl: DriverConfig> {
+    Initial,
+    /// Sending the beacon of the current period. Contains the start of the
+    /// next period.
+    Sending(
+        BeaconTask<'task, RadioDriverImpl>,
+        Instant<RadioDriverImpl::Timer>,
+    ),
+    /// Waiting for the next period.
+    Waiting,
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> EnhancedBeaconTask<'task, RadioDriverImpl> {
+    pub fn new(
+        pib: &'task RefCell<Pib>,
+        rng: &'task dyn BackoffRng,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        Self {
+            pib,
+            rng,
+            buffer_allocator,
+            state: EnhancedBeaconState::Initial,
+        }
+    }
+
+    /// Starts a new period with a beacon or terminates if no more beacons are
+    /// to be sent.
+    fn start_period(mut self) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let pib = self.pib.borrow();
+        let Some(interval) = pib.enhanced_beacon_interval().filter(|_| pib.coordinator) else {
+            return MacTaskTransition::Terminated(());
+        };
+        let next_period = RadioDriverImpl::Timer::now() + interval.convert_into_rounding_up();
+        let beacon_request = BeaconRequest {
+            beacon_type: BeaconType::EnhancedBeacon,
+            ies: pib.enhanced_beacon_ies,
+        };
+        drop(pib);
+
+        let beacon_task =
+            BeaconTask::new(beacon_request, self.pib, self.rng, self.buffer_allocator);
+        self.state = EnhancedBeaconState::Sending(beacon_task, next_period);
+        self.step(MacTaskEvent::Entry)
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for EnhancedBeaconTask<'_, RadioDriverImpl>
+{
+    type Result = ();
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, EnhancedBeaconState::Initial) {
+            EnhancedBeaconState::Initial => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+                self.start_period()
+            }
+            EnhancedBeaconState::Sending(beacon_task, next_period) => {
+                match beacon_task.step(event) {
+                    MacTaskTransition::DrvSvcRequest(beacon_task, driver_request, _) => {
+                        self.state = EnhancedBeaconState::Sending(beacon_task, next_period);
+                        MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                    }
+                    MacTaskTransition::TimerRequest(beacon_task, expiry) => {
+                        self.state = EnhancedBeaconState::Sending(beacon_task, next_period);
+                        MacTaskTransition::TimerRequest(self, expiry)
+                    }
+                    MacTaskTransition::Terminated(_) => {
+                        self.state = EnhancedBeaconState::Waiting;
+                        MacTaskTransition::TimerRequest(self, next_period)
+                    }
+                }
+            }
+            EnhancedBeaconState::Waiting => {
+                debug_assert!(matches!(event, MacTaskEvent::TimerExpired));
+                self.start_period()
+            }
+        }
+    }
+}
+
+impl<Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'_, Rng, RadioDriverImpl> {
+    /// Schedules a beacon in response to a Beacon Request command if the
+    /// device has been started as a coordinator, see IEEE 802.15.4-2020,
+    /// section 6.3.1.2.
+    pub(crate) fn receive_beacon_request(&self) {
+        if self.pib.borrow().coordinator {
+            self.beacon_requested.set(true);
+        }
+    }
+
+    /// Passes a received beacon to the upper layer if it carries a beacon
+    /// payload or macAutoRequest is disabled, see IEEE 802.15.4-2020, section
+    /// 8.2.5.1. Consumes the frame otherwise.
+    ///
+    /// The PAN descriptor reports the channel the beacon was received on.
+    pub(crate) fn receive_beacon(&self, mpdu: MpduFrame, rx_info: &RxInfo, channel: Channel) {
+        let Some((pan_descriptor, sdu_offset)) =
+            PanDescriptor::parse_beacon(mpdu.pdu_ref_wo_fcs(), channel, rx_info)
+        else {
+            self.drop_incoming_mpdu(mpdu);
+            return;
+        };
+
+        let has_sdu = sdu_offset < mpdu.pdu_ref_wo_fcs().len();
+        if self.pib.borrow().auto_request && !has_sdu {
+            self.drop_incoming_mpdu(mpdu);
+            return;
+        }
+
+        self.try_indicate(MacIndication::MlmeBeaconNotify(BeaconNotifyIndication {
+            mpdu,
+            pan_descriptor,
+            timestamp: rx_info.timestamp,
+            sdu_offset,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn superframe_specification() {
+        let superframe_specification = SuperframeSpecification::new(15, 15, 15, false, true, true);
+        assert_eq!(superframe_specification.into_le_bytes(), [0xff, 0xcf]);
+        assert_eq!(superframe_specification.beacon_order(), 15);
+        assert_eq!(superframe_specification.superframe_order(), 15);
+        assert_eq!(superframe_specification.final_cap_slot(), 15);
+        assert!(!superframe_specification.battery_life_extension());
+        assert!(superframe_specification.pan_coordinator());
+        assert!(superframe_specification.association_permit());
+
+        let superframe_specification = SuperframeSpecification::new(3, 2, 9, true, false, false);
+        assert_eq!(
+            SuperframeSpecification::from_le_bytes(superframe_specification.into_le_bytes()),
+            superframe_specification
+        );
+        assert_eq!(superframe_specification.into_le_bytes(), [0x23, 0x19]);
+    }
 }
@@ -0,0 +1,54 @@
+use crate::{
+    driver::frame::{ExtendedAddress, PanId},
+    mac::mcps::data::DataError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommStatusError {
+    /// No more transactions can be queued for indirect transmission.
+    TransactionOverflow,
+    /// The device did not poll for the frame within
+    /// macTransactionPersistenceTime.
+    TransactionExpired,
+    /// The frame could not be sent as the channel was busy.
+    ChannelAccessFailure,
+    /// The frame was not acknowledged by the device.
+    NoAck,
+    // TODO: not supported
+    CounterError,
+    // TODO: not supported
+    FrameTooLong,
+    /// The response parameters are invalid or the MAC sublayer is not able to
+    /// send it, e.g. due to a missing extended address.
+    InvalidParameter,
+}
+
+impl From<DataError> for CommStatusError {
+    fn from(value: DataError) -> Self {
+        match value {
+            DataError::TransactionOverflow => CommStatusError::TransactionOverflow,
+            DataError::TransactionExpired => CommStatusError::TransactionExpired,
+            DataError::ChannelAccessFailure => CommStatusError::ChannelAccessFailure,
+            DataError::NoAck => CommStatusError::NoAck,
+            DataError::CounterError => CommStatusError::CounterError,
+            DataError::FrameTooLong => CommStatusError::FrameTooLong,
+            // Frames queued by the MAC sublayer itself cannot be purged.
+            DataError::InvalidAddress | DataError::InvalidParameter | DataError::Purged => {
+                CommStatusError::InvalidParameter
+            }
+        }
+    }
+}
+
+/// Represents an MLME-COMM-STATUS.indication, see IEEE 802.15.4-2020, section
+/// 8.2.5.2.
+///
+/// Reports the outcome of a frame sent on behalf of an MLME response primitive,
+/// e.g. the Association Response command sent in response to an
+/// MLME-ASSOCIATE.response.
+pub struct CommStatusIndication {
+    pub pan_id: PanId<[u8; 2]>,
+    /// The device to which the frame was addressed.
+    pub dst_address: ExtendedAddress<[u8; 8]>,
+    pub status: Result<(), CommStatusError>,
+}
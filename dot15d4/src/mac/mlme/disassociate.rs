@@ -0,0 +1,423 @@
+use core::{
+    cell::{Cell, RefCell},
+    mem,
+};
+
+use rand_core::RngCore;
+
+use crate::{
+    driver::{
+        frame::{Address, AddressingFields, ExtendedAddress, FrameFilter, PanId},
+        DriverConfig,
+    },
+    mac::{
+        csma::BackoffRng,
+        devices::{AssociatedDevice, AssociatedDevices},
+        frame::mpdu::{
+            disassociation_notification_frame, disassociation_notification_frame_repr, MpduFrame,
+            DISASSOCIATION_NOTIFICATION_LEN,
+        },
+        indirect::{IndirectTxTask, PendingTransactions},
+        mcps::data::{DataError, DataRequest, DataRequestResult, DataRequestTask},
+        pib::Pib,
+        primitives::MacIndication,
+        task::*,
+        MacBufferAllocator, MacService,
+    },
+    util::allocator::IntoBuffer,
+};
+
+use super::scan::CoordAddress;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisassociateError {
+    /// No more transactions can be queued for indirect transmission.
+    TransactionOverflow,
+    /// The device did not poll for the notification within
+    /// macTransactionPersistenceTime.
+    TransactionExpired,
+    /// The notification could not be sent as the channel was busy.
+    ChannelAccessFailure,
+    /// The notification was not acknowledged by its recipient.
+    NoAck,
+    // TODO: not supported
+    CounterError,
+    // TODO: not supported
+    FrameTooLong,
+    /// The recipient is neither our coordinator nor an associated device, the
+    /// PAN ID does not match or the device has no extended address.
+    InvalidParameter,
+}
+
+impl From<DataError> for DisassociateError {
+    fn from(value: DataError) -> Self {
+        match value {
+            DataError::TransactionOverflow => DisassociateError::TransactionOverflow,
+            DataError::TransactionExpired => DisassociateError::TransactionExpired,
+            DataError::ChannelAccessFailure => DisassociateError::ChannelAccessFailure,
+            DataError::NoAck => DisassociateError::NoAck,
+            DataError::CounterError => DisassociateError::CounterError,
+            DataError::FrameTooLong => DisassociateError::FrameTooLong,
+            // Frames queued by the MAC sublayer itself cannot be purged.
+            DataError::InvalidAddress | DataError::InvalidParameter | DataError::Purged => {
+                DisassociateError::InvalidParameter
+            }
+        }
+    }
+}
+
+/// The Disassociation Reason field of a Disassociation Notification command,
+/// see IEEE 802.15.4-2020, section 7.5.4.2, table 7-51.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DisassociationReason {
+    /// The coordinator evicts the device.
+    CoordinatorWishesDeviceToLeave = 0x01,
+    /// The device leaves the PAN.
+    DeviceWishesToLeave = 0x02,
+}
+
+impl TryFrom<u8> for DisassociationReason {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(DisassociationReason::CoordinatorWishesDeviceToLeave),
+            0x02 => Ok(DisassociationReason::DeviceWishesToLeave),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Represents an MLME-DISASSOCIATE.request, see IEEE 802.15.4-2020, section
+/// 8.2.4.1.
+pub struct DisassociateRequest {
+    /// The recipient of the Disassociation Notification: Our coordinator if
+    /// the device leaves the PAN, an associated device if a coordinator evicts
+    /// it.
+    pub device_address: CoordAddress,
+    pub device_pan_id: PanId<[u8; 2]>,
+    pub disassociate_reason: DisassociationReason,
+    /// Whether a coordinator keeps the notification until the device polls
+    /// for it. Ignored when leaving the PAN.
+    pub tx_indirect: bool,
+}
+
+/// Represents an MLME-DISASSOCIATE.indication, see IEEE 802.15.4-2020, section
+/// 8.2.4.2.
+pub struct DisassociateIndication {
+    /// The extended address of the sender of the Disassociation Notification.
+    pub device_address: ExtendedAddress<[u8; 8]>,
+    pub disassociate_reason: DisassociationReason,
+}
+
+/// Represents an MLME-DISASSOCIATE.confirm, see IEEE 802.15.4-2020, section
+/// 8.2.4.3.
+pub struct DisassociateConfirm {
+    pub status: Result<(), DisassociateError>,
+    pub device_address: CoordAddress,
+    pub device_pan_id: PanId<[u8; 2]>,
+}
+
+/// The recipient of a Disassociation Notification.
+enum Recipient {
+    /// The device leaves the PAN.
+    Coordinator,
+    /// The coordinator evicts the given device.
+    Device(AssociatedDevice),
+}
+
+/// Sends a Disassociation Notification, see IEEE 802.15.4-2020, section
+/// 6.4.2.
+///
+/// The association is dissolved even if the notification could not be
+/// delivered: A device leaving the PAN resets its association state in the
+/// PIB, a coordinator removes the evicted device from its table.
+pub(crate) struct DisassociateTask<'task, RadioDriverImpl: DriverConfig> {
+    request: DisassociateRequest,
+    /// The index of this task, used to match pending transactions.
+    task_index: usize,
+    pib: &'task RefCell<Pib>,
+    /// The incoming frame filter shared with the driver service.
+    frame_filter: &'task Cell<FrameFilter>,
+    associated_devices: &'task RefCell<AssociatedDevices>,
+    pending_transactions: &'task RefCell<PendingTransactions>,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    buffer_allocator: MacBufferAllocator,
+    state: DisassociateState<'task, RadioDriverImpl>,
+}
+
+enum DisassociateState<'task, RadioDriverImpl: DriverConfig> {
+    Initial,
+    SendingDirectly(Recipient, DataRequestTask<'task, RadioDriverImpl>),
+    SendingIndirectly(Recipient, IndirectTxTask<'task, RadioDriverImpl>),
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> DisassociateTask<'task, RadioDriverImpl> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        request: DisassociateRequest,
+        task_index: usize,
+        pib: &'task RefCell<Pib>,
+        frame_filter: &'task Cell<FrameFilter>,
+        associated_devices: &'task RefCell<AssociatedDevices>,
+        pending_transactions: &'task RefCell<PendingTransactions>,
+        rng: &'task dyn BackoffRng,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        Self {
+            request,
+            task_index,
+            pib,
+            frame_filter,
+            associated_devices,
+            pending_transactions,
+            rng,
+            buffer_allocator,
+            state: DisassociateState::Initial,
+        }
+    }
+
+    /// Determines whether we leave the PAN or evict a device.
+    fn recipient(&self) -> Result<Recipient, DisassociateError> {
+        let pib = self.pib.borrow();
+        if pib.extended_address.is_none()
+            || self.request.device_pan_id.into_u16() != pib.pan_id.into_u16()
+        {
+            return Err(DisassociateError::InvalidParameter);
+        }
+
+        let is_coordinator = match self.request.device_address {
+            // 0xfffe and 0xffff designate a coordinator without short address.
+            CoordAddress::Short(device_address) => {
+                pib.coord_short_address < 0xfffe
+                    && device_address.into_u16() == pib.coord_short_address
+            }
+            CoordAddress::Extended(device_address) => pib
+                .coord_extended_address
+                .is_some_and(|coord_address| coord_address.as_slice() == device_address.as_ref()),
+        };
+        if is_coordinator {
+            return Ok(Recipient::Coordinator);
+        }
+
+        let associated_devices = self.associated_devices.borrow();
+        let device = match self.request.device_address {
+            CoordAddress::Short(device_address) => {
+                associated_devices.get_by_short_address(&device_address)
+            }
+            CoordAddress::Extended(device_address) => associated_devices.get(&device_address),
+        };
+        device
+            .map(|device| Recipient::Device(*device))
+            .ok_or(DisassociateError::InvalidParameter)
+    }
+
+    fn build_notification(&self) -> MpduFrame {
+        let pib = self.pib.borrow();
+        let dst_address = self.request.device_address.as_address();
+        let buffer_size = disassociation_notification_frame_repr(dst_address.into())
+            .min_buffer_size::<RadioDriverImpl>(DISASSOCIATION_NOTIFICATION_LEN)
+            .unwrap();
+        let buffer = self
+            .buffer_allocator
+            .try_allocate_buffer(buffer_size)
+            .expect("no capacity");
+
+        // Safety: The extended address was checked on entry.
+        let extended_address = ExtendedAddress::new_owned(pib.extended_address.unwrap());
+        disassociation_notification_frame::<RadioDriverImpl>(
+            &dst_address,
+            pib.pan_id,
+            extended_address,
+            self.request.disassociate_reason as u8,
+            0,
+            buffer,
+        )
+        .into_mpdu_frame()
+    }
+
+    /// Dissolves the association and confirms the request.
+    fn terminate(
+        self,
+        recipient: Option<Recipient>,
+        status: Result<(), DisassociateError>,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match recipient {
+            Some(Recipient::Coordinator) => {
+                let mut pib = self.pib.borrow_mut();
+                pib.reset_association();
+                self.frame_filter.set(pib.frame_filter());
+            }
+            Some(Recipient::Device(device)) => {
+                self.associated_devices
+                    .borrow_mut()
+                    .remove(&device.extended_address);
+            }
+            None => {}
+        }
+
+        MacTaskTransition::Terminated(DisassociateConfirm {
+            status,
+            device_address: self.request.device_address,
+            device_pan_id: self.request.device_pan_id,
+        })
+    }
+
+    /// Releases the notification once it was sent (or not) and terminates.
+    fn notification_sent(
+        self,
+        recipient: Recipient,
+        result: DataRequestResult,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        // Safety: We allocated the buffer ourselves.
+        unsafe {
+            self.buffer_allocator
+                .deallocate_buffer(result.radio_frame.into_buffer());
+        }
+        let status = result.confirm.status.map_err(DisassociateError::from);
+        self.terminate(Some(recipient), status)
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for DisassociateTask<'_, RadioDriverImpl>
+{
+    type Result = DisassociateConfirm;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, DisassociateState::Initial) {
+            DisassociateState::Initial => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                let recipient = match self.recipient() {
+                    Ok(recipient) => recipient,
+                    // Leave the association alone.
+                    Err(error) => return self.terminate(None, Err(error)),
+                };
+
+                let data_request = DataRequest::new(self.build_notification());
+                self.state = match recipient {
+                    Recipient::Device(device) if self.request.tx_indirect => {
+                        let short_address = Some(device.short_address)
+                            .filter(|short_address| short_address.into_u16() != 0xfffe);
+                        DisassociateState::SendingIndirectly(
+                            recipient,
+                            IndirectTxTask::new(
+                                data_request,
+                                Some(device.extended_address),
+                                short_address,
+                                self.task_index,
+                                self.pib,
+                                self.pending_transactions,
+                                self.rng,
+                            ),
+                        )
+                    }
+                    _ => DisassociateState::SendingDirectly(
+                        recipient,
+                        DataRequestTask::new(data_request, &mut self.pib.borrow_mut(), self.rng),
+                    ),
+                };
+                self.step(MacTaskEvent::Entry)
+            }
+            DisassociateState::SendingDirectly(recipient, notification_task) => {
+                match notification_task.step(event) {
+                    MacTaskTransition::DrvSvcRequest(notification_task, driver_request, _) => {
+                        self.state =
+                            DisassociateState::SendingDirectly(recipient, notification_task);
+                        MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                    }
+                    MacTaskTransition::TimerRequest(notification_task, expiry) => {
+                        self.state =
+                            DisassociateState::SendingDirectly(recipient, notification_task);
+                        MacTaskTransition::TimerRequest(self, expiry)
+                    }
+                    MacTaskTransition::Terminated(result) => {
+                        self.notification_sent(recipient, result)
+                    }
+                }
+            }
+            DisassociateState::SendingIndirectly(recipient, notification_task) => {
+                match notification_task.step(event) {
+                    MacTaskTransition::DrvSvcRequest(notification_task, driver_request, _) => {
+                        self.state =
+                            DisassociateState::SendingIndirectly(recipient, notification_task);
+                        MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                    }
+                    MacTaskTransition::TimerRequest(notification_task, expiry) => {
+                        self.state =
+                            DisassociateState::SendingIndirectly(recipient, notification_task);
+                        MacTaskTransition::TimerRequest(self, expiry)
+                    }
+                    MacTaskTransition::Terminated(result) => {
+                        self.notification_sent(recipient, result)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'_, Rng, RadioDriverImpl> {
+    /// Dissolves the association with the sender of the given Disassociation
+    /// Notification command and indicates it to the next higher layer.
+    ///
+    /// Notifications from devices that are neither our coordinator nor
+    /// associated with us are ignored.
+    pub(crate) fn receive_disassociation_notification(
+        &self,
+        addressing_fields: &AddressingFields<&[u8]>,
+        payload: &[u8],
+    ) {
+        let Some(Address::Extended(src_address)) = addressing_fields.src_address() else {
+            return;
+        };
+        let Ok(src_address) = <[u8; 8]>::try_from(src_address.as_ref()) else {
+            return;
+        };
+        let Some(Ok(disassociate_reason)) = payload.first().map(|reason| (*reason).try_into())
+        else {
+            return;
+        };
+        let device_address = ExtendedAddress::new_owned(src_address);
+
+        let mut pib = self.pib.borrow_mut();
+        if pib.coord_extended_address == Some(src_address) {
+            pib.reset_association();
+            self.frame_filter.set(pib.frame_filter());
+        } else if self
+            .associated_devices
+            .borrow_mut()
+            .remove(&device_address)
+            .is_none()
+        {
+            return;
+        }
+        drop(pib);
+
+        self.try_indicate(MacIndication::MlmeDisassociate(DisassociateIndication {
+            device_address,
+            disassociate_reason,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassociation_reason() {
+        assert_eq!(
+            DisassociationReason::try_from(0x01),
+            Ok(DisassociationReason::CoordinatorWishesDeviceToLeave)
+        );
+        assert_eq!(
+            DisassociationReason::try_from(0x02),
+            Ok(DisassociationReason::DeviceWishesToLeave)
+        );
+        assert_eq!(DisassociationReason::try_from(0x00), Err(()));
+    }
+}
@@ -0,0 +1,246 @@
+#![allow(dead_code)]
+use rand_core::RngCore;
+
+use crate::{
+    driver::DriverConfig,
+    mac::{pib::Pib, MacService},
+};
+
+use super::set::SetRequestAttribute;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetError {
+    /// The attribute is not supported.
+    UnsupportedAttribute,
+    /// Not part of the standard: The attribute is supported but currently
+    /// has no value, e.g. an address that has not been assigned yet.
+    NoValue,
+}
+
+/// Attributes that may be read by an upper layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetRequestAttribute {
+    // IEEE 802.15.4-2020, section 8.4.3.1, table 8-94
+    MacExtendedAddress,
+    MacAssociatedPanCoord,
+    MacAssociationPermit,
+    MacAutoRequest,
+    MacBeaconOrder,
+    MacBsn,
+    MacCoordExtendedAddress,
+    MacCoordShortAddress,
+    MacDsn,
+    MacEbsn,
+    MacEnhancedBeaconOrder,
+    MacImplicitBroadcast,
+    MacMaxBe,
+    MacMaxCsmaBackoffs,
+    MacMaxFrameRetries,
+    MacMinBe,
+    MacPanId,
+    MacPromiscuousMode,
+    MacRxOnWhenIdle,
+    MacSecurityEnabled,
+    MacShortAddress,
+    MacTransactionPersistenceTime,
+    // IEEE 802.15.4-2020, section 8.4.3.4, table 8-98
+    MacHoppingSequenceId,
+    MacChannelPage,
+    MacNumberOfChannels,
+    MacPhyConfiguration,
+    MacHoppingSequenceList,
+    MacCurrentHop,
+    // IEEE 802.15.4-2020, section 11.3, table 11-2
+    PhyCurrentChannel,
+    PhyTxPower,
+    // Not part of the standard
+    MacPromiscuousCrcErrors,
+    MacEnhancedBeaconIes,
+    MacDuplicateDetectionTimeout,
+    MacNeighborEvictionPolicy,
+    MacTschKeepAlivePeriod,
+    MacTschDesyncTimeout,
+}
+
+impl GetRequestAttribute {
+    /// Reads the attribute value from the PIB.
+    pub(crate) fn read(&self, pib: &Pib) -> Result<SetRequestAttribute, GetError> {
+        let value = match self {
+            GetRequestAttribute::MacExtendedAddress => SetRequestAttribute::MacExtendedAddress(
+                pib.extended_address.ok_or(GetError::NoValue)?,
+            ),
+            GetRequestAttribute::MacAssociatedPanCoord => {
+                SetRequestAttribute::MacAssociatedPanCoord(pib.associated_pan_coord)
+            }
+            GetRequestAttribute::MacAssociationPermit => {
+                SetRequestAttribute::MacAssociationPermit(pib.association_permit)
+            }
+            GetRequestAttribute::MacAutoRequest => {
+                SetRequestAttribute::MacAutoRequest(pib.auto_request)
+            }
+            GetRequestAttribute::MacBeaconOrder => {
+                SetRequestAttribute::MacBeaconOrder(pib.beacon_order)
+            }
+            GetRequestAttribute::MacBsn => SetRequestAttribute::MacBsn(pib.bsn),
+            GetRequestAttribute::MacCoordExtendedAddress => {
+                SetRequestAttribute::MacCoordExtendedAddress(
+                    pib.coord_extended_address.ok_or(GetError::NoValue)?,
+                )
+            }
+            GetRequestAttribute::MacCoordShortAddress => {
+                SetRequestAttribute::MacCoordShortAddress(pib.coord_short_address)
+            }
+            GetRequestAttribute::MacDsn => SetRequestAttribute::MacDsn(pib.dsn),
+            GetRequestAttribute::MacEbsn => SetRequestAttribute::MacEbsn(pib.ebsn),
+            GetRequestAttribute::MacEnhancedBeaconOrder => {
+                SetRequestAttribute::MacEnhancedBeaconOrder(pib.enhanced_beacon_order)
+            }
+            GetRequestAttribute::MacImplicitBroadcast => {
+                SetRequestAttribute::MacImplicitBroadcast(pib.implicit_broadcast)
+            }
+            GetRequestAttribute::MacMaxBe => SetRequestAttribute::MacMaxBe(pib.max_be),
+            GetRequestAttribute::MacMaxCsmaBackoffs => {
+                SetRequestAttribute::MacMaxCsmaBackoffs(pib.max_csma_backoffs)
+            }
+            GetRequestAttribute::MacMaxFrameRetries => {
+                SetRequestAttribute::MacMaxFrameRetries(pib.max_frame_retries)
+            }
+            GetRequestAttribute::MacMinBe => SetRequestAttribute::MacMinBe(pib.min_be),
+            GetRequestAttribute::MacPanId => SetRequestAttribute::MacPanId(pib.pan_id.into_u16()),
+            GetRequestAttribute::MacPromiscuousMode => {
+                SetRequestAttribute::MacPromiscuousMode(pib.promiscuous_mode)
+            }
+            GetRequestAttribute::MacRxOnWhenIdle => {
+                SetRequestAttribute::MacRxOnWhenIdle(pib.rx_on_when_idle)
+            }
+            GetRequestAttribute::MacSecurityEnabled => {
+                SetRequestAttribute::MacSecurityEnabled(pib.security_enabled)
+            }
+            GetRequestAttribute::MacShortAddress => {
+                SetRequestAttribute::MacShortAddress(pib.short_address)
+            }
+            GetRequestAttribute::MacTransactionPersistenceTime => {
+                SetRequestAttribute::MacTransactionPersistenceTime(pib.transaction_persistence_time)
+            }
+            GetRequestAttribute::MacHoppingSequenceId => {
+                SetRequestAttribute::MacHoppingSequenceId(pib.hopping_sequence_id)
+            }
+            GetRequestAttribute::MacChannelPage => {
+                SetRequestAttribute::MacChannelPage(pib.channel_page)
+            }
+            GetRequestAttribute::MacNumberOfChannels => {
+                SetRequestAttribute::MacNumberOfChannels(pib.number_of_channels)
+            }
+            GetRequestAttribute::MacPhyConfiguration => {
+                SetRequestAttribute::MacPhyConfiguration(pib.phy_configuration)
+            }
+            GetRequestAttribute::MacHoppingSequenceList => {
+                SetRequestAttribute::MacHoppingSequenceList(pib.hopping_sequence)
+            }
+            GetRequestAttribute::MacCurrentHop => {
+                SetRequestAttribute::MacCurrentHop(pib.current_hop)
+            }
+            GetRequestAttribute::PhyCurrentChannel => {
+                SetRequestAttribute::PhyCurrentChannel(pib.current_channel)
+            }
+            GetRequestAttribute::PhyTxPower => SetRequestAttribute::PhyTxPower(pib.tx_power),
+            GetRequestAttribute::MacPromiscuousCrcErrors => {
+                SetRequestAttribute::MacPromiscuousCrcErrors(pib.promiscuous_crc_errors)
+            }
+            GetRequestAttribute::MacEnhancedBeaconIes => {
+                SetRequestAttribute::MacEnhancedBeaconIes(pib.enhanced_beacon_ies)
+            }
+            GetRequestAttribute::MacDuplicateDetectionTimeout => {
+                SetRequestAttribute::MacDuplicateDetectionTimeout(
+                    pib.duplicate_detection_timeout.ticks() as u16,
+                )
+            }
+            GetRequestAttribute::MacNeighborEvictionPolicy => {
+                SetRequestAttribute::MacNeighborEvictionPolicy(pib.neighbor_eviction_policy)
+            }
+            GetRequestAttribute::MacTschKeepAlivePeriod => {
+                SetRequestAttribute::MacTschKeepAlivePeriod(
+                    (pib.tsch_keep_alive_period.ticks() / 1000) as u16,
+                )
+            }
+            GetRequestAttribute::MacTschDesyncTimeout => SetRequestAttribute::MacTschDesyncTimeout(
+                (pib.tsch_desync_timeout.ticks() / 1000) as u16,
+            ),
+        };
+        Ok(value)
+    }
+}
+
+#[allow(dead_code)]
+impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, RadioDriverImpl> {
+    /// Used by the next higher layer to read the value of the indicated MAC
+    /// PIB attribute.
+    ///
+    /// * `attribute` - Attribute to read
+    pub(crate) async fn mlme_get_request(
+        &self,
+        attribute: &GetRequestAttribute,
+    ) -> Result<SetRequestAttribute, GetError> {
+        attribute.read(&self.pib.borrow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mac::tsch::schedule::TSCH_DEFAULT_HOPPING_SEQUENCE;
+
+    use super::*;
+
+    #[test]
+    fn read_written_attributes() {
+        let mut pib = Pib::default();
+        assert_eq!(
+            GetRequestAttribute::MacExtendedAddress.read(&pib),
+            Err(GetError::NoValue)
+        );
+
+        for attribute in [
+            SetRequestAttribute::MacExtendedAddress([1, 2, 3, 4, 5, 6, 7, 8]),
+            SetRequestAttribute::MacShortAddress(0x1234),
+            SetRequestAttribute::MacRxOnWhenIdle(true),
+            SetRequestAttribute::PhyTxPower(-8),
+            SetRequestAttribute::MacPromiscuousCrcErrors(true),
+            SetRequestAttribute::MacCurrentHop(5),
+        ] {
+            attribute.write(&mut pib).unwrap();
+        }
+
+        assert_eq!(
+            GetRequestAttribute::MacExtendedAddress.read(&pib),
+            Ok(SetRequestAttribute::MacExtendedAddress([
+                1, 2, 3, 4, 5, 6, 7, 8
+            ]))
+        );
+        assert_eq!(
+            GetRequestAttribute::MacShortAddress.read(&pib),
+            Ok(SetRequestAttribute::MacShortAddress(0x1234))
+        );
+        assert_eq!(
+            GetRequestAttribute::MacRxOnWhenIdle.read(&pib),
+            Ok(SetRequestAttribute::MacRxOnWhenIdle(true))
+        );
+        assert_eq!(
+            GetRequestAttribute::PhyTxPower.read(&pib),
+            Ok(SetRequestAttribute::PhyTxPower(-8))
+        );
+        assert_eq!(
+            GetRequestAttribute::MacPromiscuousCrcErrors.read(&pib),
+            Ok(SetRequestAttribute::MacPromiscuousCrcErrors(true))
+        );
+        assert_eq!(
+            GetRequestAttribute::MacCurrentHop.read(&pib),
+            Ok(SetRequestAttribute::MacCurrentHop(5))
+        );
+        assert_eq!(
+            GetRequestAttribute::MacHoppingSequenceList.read(&pib),
+            Ok(SetRequestAttribute::MacHoppingSequenceList(
+                TSCH_DEFAULT_HOPPING_SEQUENCE
+            ))
+        );
+    }
+}
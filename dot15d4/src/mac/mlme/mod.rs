@@ -1,6 +1,55 @@
 pub mod associate;
 pub mod beacon;
+pub mod comm_status;
+pub mod disassociate;
+pub mod get;
+pub mod poll;
 pub mod reset;
 pub mod scan;
 pub mod set;
 pub mod start;
+pub mod sync_loss;
+pub mod tsch;
+
+use crate::{
+    driver::frame::{AddressingFields, AddressingRepr, FrameControl, FrameType},
+    mac::frame::mpdu::CommandId,
+};
+
+/// The addressing fields, Command ID and remaining payload of a MAC command.
+pub(crate) type ParsedCommand<'mpdu> = (AddressingFields<&'mpdu [u8]>, CommandId, &'mpdu [u8]);
+
+/// Splits a MAC command MPDU (without FCS) into its addressing fields, its
+/// Command ID and the remaining command payload.
+///
+/// Returns `None` if the frame is not a well-formed MAC command. Secured
+/// commands and commands with IEs are not supported, yet.
+pub(crate) fn parse_command(mpdu: &[u8]) -> Option<ParsedCommand<'_>> {
+    let frame_control = FrameControl::new(mpdu).ok()?;
+    if !matches!(frame_control.frame_type(), FrameType::MacCommand)
+        || frame_control.security_enabled()
+        || frame_control.information_elements_present()
+    {
+        return None;
+    }
+
+    let offset = if frame_control.sequence_number_suppression() {
+        2
+    } else {
+        3
+    };
+
+    let addressing_repr =
+        AddressingRepr::from_frame_control(FrameControl::new_unchecked(&mpdu[..2])).ok()??;
+    let addressing_length = addressing_repr.addressing_fields_length().ok()? as usize;
+    let addressing_fields = AddressingFields::new(
+        mpdu.get(offset..offset + addressing_length)?,
+        addressing_repr,
+    )
+    .ok()?;
+
+    let (command_id, payload) = mpdu.get(offset + addressing_length..)?.split_first()?;
+    let command_id = CommandId::try_from(*command_id).ok()?;
+
+    Some((addressing_fields, command_id, payload))
+}
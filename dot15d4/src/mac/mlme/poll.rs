@@ -0,0 +1,299 @@
+use core::{
+    cell::{Cell, RefCell},
+    mem,
+};
+
+use rand_core::RngCore;
+
+use crate::{
+    driver::{
+        frame::{Address, ExtendedAddress, PanId, ShortAddress},
+        DriverConfig, RadioTimerApi,
+    },
+    mac::{
+        csma::BackoffRng,
+        frame::mpdu::{data_request_frame, data_request_frame_repr, MpduFrame, COMMAND_ID_LEN},
+        mcps::data::{DataError, DataRequest, DataRequestResult, DataRequestTask},
+        pib::Pib,
+        task::*,
+        MacBufferAllocator, MacService,
+    },
+    util::allocator::IntoBuffer,
+};
+
+use super::scan::CoordAddress;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollError {
+    /// The Data Request command could not be sent as the channel was busy.
+    ChannelAccessFailure,
+    /// The Data Request command was not acknowledged by the coordinator.
+    NoAck,
+    /// The coordinator has no data pending for the device or its data did not
+    /// arrive in time.
+    NoData,
+    // TODO: not supported
+    CounterError,
+    // TODO: not supported
+    FrameTooLong,
+    /// The device has no address to poll with or another poll is in progress.
+    InvalidParameter,
+}
+
+impl From<DataError> for PollError {
+    fn from(value: DataError) -> Self {
+        match value {
+            DataError::ChannelAccessFailure => PollError::ChannelAccessFailure,
+            DataError::NoAck => PollError::NoAck,
+            DataError::CounterError => PollError::CounterError,
+            DataError::FrameTooLong => PollError::FrameTooLong,
+            _ => PollError::InvalidParameter,
+        }
+    }
+}
+
+/// Represents an MLME-POLL.request, see IEEE 802.15.4-2020, section 8.2.14.1.
+pub struct PollRequest {
+    pub coord_address: CoordAddress,
+    pub coord_pan_id: PanId<[u8; 2]>,
+}
+
+/// Represents an MLME-POLL.confirm, see IEEE 802.15.4-2020, section 8.2.14.2.
+pub struct PollConfirm {
+    pub status: Result<(), PollError>,
+}
+
+/// Hands the reception of polled data over from the MAC service to the
+/// ongoing poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PollSlot {
+    /// No poll is in progress.
+    Idle,
+    /// A poll is sending its Data Request command to the given coordinator.
+    Pending(CoordAddress),
+    /// A poll is waiting for data from the given coordinator.
+    Waiting {
+        task_index: usize,
+        coord_address: CoordAddress,
+    },
+    /// The coordinator sent its data.
+    Received,
+}
+
+/// Requests pending data from a coordinator, see IEEE 802.15.4-2020, section
+/// 6.7.3.
+///
+/// The task sends a Data Request command by a data request sub-task, i.e. with
+/// CSMA-CA and retransmissions. If the coordinator announces pending data in
+/// its ACK, the task waits up to macMaxFrameTotalWaitTime for the data. The
+/// data itself is indicated to the next higher layer via MCPS-DATA.
+pub(crate) struct PollTask<'task, RadioDriverImpl: DriverConfig> {
+    request: PollRequest,
+    task_index: usize,
+    pib: &'task RefCell<Pib>,
+    /// The reception of polled data handed over by the MAC service.
+    poll_slot: &'task Cell<PollSlot>,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    buffer_allocator: MacBufferAllocator,
+    state: PollState<'task, RadioDriverImpl>,
+}
+
+enum PollState<'task, RadioDriverImpl: DriverConfig> {
+    Initial,
+    SendingCommand(DataRequestTask<'task, RadioDriverImpl>),
+    /// Waiting for the data announced by the coordinator.
+    ReceivingData,
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> PollTask<'task, RadioDriverImpl> {
+    /// Creates a task polling the given coordinator. The task index is used
+    /// by the MAC service to wake the task once the data arrived.
+    pub fn new(
+        request: PollRequest,
+        task_index: usize,
+        pib: &'task RefCell<Pib>,
+        poll_slot: &'task Cell<PollSlot>,
+        rng: &'task dyn BackoffRng,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        Self {
+            request,
+            task_index,
+            pib,
+            poll_slot,
+            rng,
+            buffer_allocator,
+            state: PollState::Initial,
+        }
+    }
+
+    /// Builds the Data Request command. The device polls with its short
+    /// address if it has one, see IEEE 802.15.4-2020, section 6.7.3.
+    ///
+    /// Returns `None` if the device has no address to poll with.
+    fn build_data_request(&self) -> Option<MpduFrame> {
+        let pib = self.pib.borrow();
+        let short_address;
+        let extended_address;
+        let src_address = if pib.short_address < 0xfffe {
+            short_address = pib.short_address.to_le_bytes();
+            Address::Short(ShortAddress::new(&short_address[..]))
+        } else {
+            extended_address = pib.extended_address?;
+            Address::Extended(ExtendedAddress::new(&extended_address[..]))
+        };
+        let coord_address = self.request.coord_address.as_address();
+
+        let buffer_size = data_request_frame_repr(coord_address.into(), src_address.into())
+            .min_buffer_size::<RadioDriverImpl>(COMMAND_ID_LEN)
+            .unwrap();
+        let buffer = self
+            .buffer_allocator
+            .try_allocate_buffer(buffer_size)
+            .expect("no capacity");
+
+        let mpdu = data_request_frame::<RadioDriverImpl>(
+            &coord_address,
+            self.request.coord_pan_id,
+            &src_address,
+            0,
+            buffer,
+        )
+        .into_mpdu_frame();
+        Some(mpdu)
+    }
+
+    /// Releases the Data Request command and waits for the announced data, if
+    /// any.
+    fn command_sent(
+        mut self,
+        result: DataRequestResult,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        // Safety: We allocated the buffer ourselves.
+        unsafe {
+            self.buffer_allocator
+                .deallocate_buffer(result.radio_frame.into_buffer());
+        }
+
+        if let Err(error) = result.confirm.status {
+            return self.terminate(Err(error.into()));
+        }
+
+        // The data may already have been received while the ACK was being
+        // processed.
+        match self.poll_slot.get() {
+            PollSlot::Received => return self.terminate(Ok(())),
+            _ if !result.confirm.frame_pending => return self.terminate(Err(PollError::NoData)),
+            _ => {}
+        }
+
+        self.poll_slot.set(PollSlot::Waiting {
+            task_index: self.task_index,
+            coord_address: self.request.coord_address,
+        });
+        self.state = PollState::ReceivingData;
+        let expiry = RadioDriverImpl::Timer::now()
+            + self
+                .pib
+                .borrow()
+                .max_frame_total_wait_time()
+                .convert_into_rounding_up();
+        MacTaskTransition::TimerRequest(self, expiry)
+    }
+
+    fn terminate(
+        self,
+        status: Result<(), PollError>,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        self.poll_slot.set(PollSlot::Idle);
+        MacTaskTransition::Terminated(PollConfirm { status })
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for PollTask<'_, RadioDriverImpl>
+{
+    type Result = PollConfirm;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, PollState::Initial) {
+            PollState::Initial => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                // Leave the slot of another poll alone.
+                if self.poll_slot.get() != PollSlot::Idle {
+                    return MacTaskTransition::Terminated(PollConfirm {
+                        status: Err(PollError::InvalidParameter),
+                    });
+                }
+
+                let Some(mpdu) = self.build_data_request() else {
+                    return MacTaskTransition::Terminated(PollConfirm {
+                        status: Err(PollError::InvalidParameter),
+                    });
+                };
+
+                self.poll_slot
+                    .set(PollSlot::Pending(self.request.coord_address));
+                let command_task = DataRequestTask::new(
+                    DataRequest::new(mpdu),
+                    &mut self.pib.borrow_mut(),
+                    self.rng,
+                );
+                self.state = PollState::SendingCommand(command_task);
+                self.step(MacTaskEvent::Entry)
+            }
+            PollState::SendingCommand(command_task) => match command_task.step(event) {
+                MacTaskTransition::DrvSvcRequest(command_task, driver_request, _) => {
+                    self.state = PollState::SendingCommand(command_task);
+                    MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                }
+                MacTaskTransition::TimerRequest(command_task, expiry) => {
+                    self.state = PollState::SendingCommand(command_task);
+                    MacTaskTransition::TimerRequest(self, expiry)
+                }
+                MacTaskTransition::Terminated(result) => self.command_sent(result),
+            },
+            PollState::ReceivingData => {
+                debug_assert!(matches!(event, MacTaskEvent::TimerExpired));
+                match self.poll_slot.get() {
+                    PollSlot::Received => self.terminate(Ok(())),
+                    _ => self.terminate(Err(PollError::NoData)),
+                }
+            }
+        }
+    }
+}
+
+impl<Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'_, Rng, RadioDriverImpl> {
+    /// Hands the given data frame over to an ongoing poll if it was sent by
+    /// the polled coordinator.
+    ///
+    /// Returns the index of the MAC task to be woken, if any.
+    pub(crate) fn receive_polled_data(&self, mpdu: &MpduFrame) -> Option<usize> {
+        // The task is only woken once it waits for the data.
+        let (task_index, coord_address) = match self.poll_slot.get() {
+            PollSlot::Pending(coord_address) => (None, coord_address),
+            PollSlot::Waiting {
+                task_index,
+                coord_address,
+            } => (Some(task_index), coord_address),
+            _ => return None,
+        };
+
+        let addressing_fields = mpdu
+            .reader()
+            .parse_addressing()
+            .ok()?
+            .into_addressing_fields()
+            .ok()??;
+        let src_address = addressing_fields.src_address()?;
+        if src_address.as_le_bytes() != coord_address.as_address().as_le_bytes() {
+            return None;
+        }
+
+        self.poll_slot.set(PollSlot::Received);
+        task_index
+    }
+}
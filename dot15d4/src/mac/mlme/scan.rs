@@ -1,10 +1,54 @@
-#![allow(dead_code)]
-use core::ops::RangeInclusive;
+use core::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    mem,
+    ops::RangeInclusive,
+};
 
-use rand_core::RngCore;
+use crate::{
+    driver::{
+        config::Channel,
+        constants::A_BASE_SUPERFRAME_DURATION,
+        frame::{
+            Address, AddressingFields, AddressingRepr, ExtendedAddress, FrameControl, FrameType,
+            FrameVersion, PanId, RadioFrame, RadioFrameSized, ShortAddress,
+        },
+        tasks::{EdResult, RxInfo, Timestamp, TxError, TxResult},
+        time::{Duration, Frequency, SymbolsOQpsk250kB},
+        DriverConfig, DrvSvcRequest, DrvSvcResponse, DrvSvcTaskEd, DrvSvcTaskError, DrvSvcTaskOff,
+        DrvSvcTaskTx, RadioTimerApi,
+    },
+    mac::{
+        csma::BackoffRng,
+        frame::{
+            fields::{IeList, TschSynchronizationIe},
+            mpdu::{beacon_request_frame, MpduFrame, BEACON_REQUEST_FRAME_REPR, COMMAND_ID_LEN},
+            repr::TSCH_SYNCHRONIZATION_SUB_ID,
+        },
+        pib::Pib,
+        task::*,
+        MacBufferAllocator,
+    },
+    util::allocator::IntoBuffer,
+};
 
-use crate::{driver::DriverConfig, mac::MacService};
+use super::beacon::SuperframeSpecification;
 
+/// The max number of PAN descriptors that will be collected during a single
+/// scan. The scan ends early once this number has been reached.
+pub const MAC_MAX_PAN_DESCRIPTORS: usize = 8;
+
+/// The max scan duration exponent, see IEEE 802.15.4-2020, section 8.2.10.1.
+const MAX_SCAN_DURATION: u8 = 14;
+
+/// The max number of channels scanned during a single ED scan, i.e. all
+/// channels of the 2.4 GHz band.
+pub const MAC_MAX_ED_CHANNELS: usize = 16;
+
+pub type PanDescriptorList = heapless::Vec<PanDescriptor, MAC_MAX_PAN_DESCRIPTORS>;
+pub type EnergyDetectList = heapless::Vec<u8, MAC_MAX_ED_CHANNELS>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScanType {
     Ed,
     Active,
@@ -13,54 +57,898 @@ pub enum ScanType {
     EnhancedActiveScan,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScanChannels {
+    /// All channels of the 2.4 GHz band in ascending order.
     All,
     Single(u8),
+    /// All channels of the 2.4 GHz band in random order, e.g. to find the
+    /// Enhanced Beacons of a TSCH network which hops over all channels.
+    RandomHopping,
 }
 
-pub struct ScanConfirm {
-    scan_type: ScanType,
-    channel_page: u8,
+impl ScanChannels {
+    /// The first and last channel of the 2.4 GHz band.
+    const CHANNELS: RangeInclusive<u8> = 11..=26;
+
+    /// Returns the channels to be scanned. The given random value determines
+    /// the order of a [`ScanChannels::RandomHopping`] scan.
+    pub fn channels(self, random: u32) -> ScanChannelIter {
+        let (first, len, start, stride) = match self {
+            ScanChannels::All => (*Self::CHANNELS.start(), Self::CHANNELS.len() as u8, 0, 1),
+            ScanChannels::Single(channel) => (channel, 1, 0, 1),
+            ScanChannels::RandomHopping => {
+                let len = Self::CHANNELS.len() as u8;
+                // Any odd stride visits all 16 channels exactly once.
+                let start = (random % len as u32) as u8;
+                let stride = 2 * ((random >> 8) % (len as u32 / 2)) as u8 + 1;
+                (*Self::CHANNELS.start(), len, start, stride)
+            }
+        };
+        ScanChannelIter {
+            first,
+            len,
+            start,
+            stride,
+            index: 0,
+        }
+    }
+}
+
+/// Iterates over the channels of a scan, see [`ScanChannels::channels()`].
+#[derive(Debug, Clone)]
+pub struct ScanChannelIter {
+    /// The lowest channel.
+    first: u8,
+    /// The number of channels.
+    len: u8,
+    /// The index of the channel scanned first.
+    start: u8,
+    /// The distance between subsequent channel indices.
+    stride: u8,
+    /// The number of channels returned so far.
+    index: u8,
+}
+
+impl Iterator for ScanChannelIter {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.index >= self.len {
+            return None;
+        }
+        let offset =
+            (self.start as usize + self.index as usize * self.stride as usize) % self.len as usize;
+        self.index += 1;
+        Some(self.first + offset as u8)
+    }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScanError {
-    // TODO: not supported
+    /// The scan ended early as [`MAC_MAX_PAN_DESCRIPTORS`] were found.
     LimitReached,
-    // TODO: not supported
+    /// No beacon was received during an active or passive scan.
     NoBeacon,
-    // TODO: not supported
+    /// Another scan was requested while a scan was ongoing.
     ScanInProgress,
     // TODO: not supported
     CounterError,
     // TODO: not supported
     FrameTooLong,
-    // TODO: not supported
+    /// A requested channel is not supported by the PHY.
     BadChannel,
-    // TODO: not supported
+    /// The scan type is not supported or the scan duration is out of range.
     InvalidParameter,
 }
 
-impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, RadioDriverImpl> {
-    /// Initiates a channel scan over a given set of channels.
-    pub(crate) async fn mlme_scan_request(
-        &self,
-        _scan_type: ScanType,
-        _scan_channels: ScanChannels,
-        _scan_duration: u8,
-        _channel_page: u8,
-    ) -> Result<ScanConfirm, ScanError> {
-        Err(ScanError::InvalidParameter)
+/// Represents an MLME-SCAN.request, see IEEE 802.15.4-2020, section 8.2.10.1.
+pub struct ScanRequest {
+    pub scan_type: ScanType,
+    pub scan_channels: ScanChannels,
+    /// Determines the time spent listening or measuring on each channel, i.e.
+    /// aBaseSuperframeDuration * (2^n + 1) symbols with n in 0..=14.
+    pub scan_duration: u8,
+    pub channel_page: u8,
+}
+
+impl ScanRequest {
+    pub fn new(scan_type: ScanType, scan_channels: ScanChannels, scan_duration: u8) -> Self {
+        Self {
+            scan_type,
+            scan_channels,
+            scan_duration,
+            channel_page: 0,
+        }
+    }
+
+    fn validate(&self) -> Result<(), ScanError> {
+        if !matches!(
+            self.scan_type,
+            ScanType::Ed | ScanType::Active | ScanType::Passive | ScanType::EnhancedActiveScan
+        ) || self.scan_duration > MAX_SCAN_DURATION
+            || self.channel_page != 0
+        {
+            return Err(ScanError::InvalidParameter);
+        }
+
+        if let ScanChannels::Single(channel) = self.scan_channels {
+            Channel::try_from(channel).map_err(|_| ScanError::BadChannel)?;
+        }
+
+        Ok(())
     }
 }
 
-// Implement IntoIterator for Channels so you can write: for x in scan_channels { ... }
-impl IntoIterator for ScanChannels {
-    type Item = u8;
-    type IntoIter = RangeInclusive<u8>;
+/// Represents an MLME-SCAN.confirm, see IEEE 802.15.4-2020, section 8.2.10.2.
+pub struct ScanConfirm {
+    /// The status of the scan. PAN descriptors are also reported if the limit
+    /// was reached.
+    pub status: Result<(), ScanError>,
+    pub scan_type: ScanType,
+    pub channel_page: u8,
+    /// The PANs found during an active or passive scan.
+    pub pan_descriptors: PanDescriptorList,
+    /// The peak energy level measured on each scanned channel during an ED
+    /// scan, in the order the channels were scanned.
+    pub energy_detect_list: EnergyDetectList,
+}
+
+/// The address of a coordinator found during a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordAddress {
+    Short(ShortAddress<[u8; 2]>),
+    Extended(ExtendedAddress<[u8; 8]>),
+}
 
-    fn into_iter(self) -> Self::IntoIter {
+impl CoordAddress {
+    /// Borrows the coordinator address as a frame address.
+    pub fn as_address(&self) -> Address<&[u8]> {
         match self {
-            ScanChannels::All => 11_u8..=26,
-            ScanChannels::Single(ch) => ch..=ch,
+            CoordAddress::Short(address) => Address::Short(ShortAddress::new(address.as_ref())),
+            CoordAddress::Extended(address) => {
+                Address::Extended(ExtendedAddress::new(address.as_ref()))
+            }
+        }
+    }
+}
+
+/// Synchronization information advertised by a TSCH coordinator in the TSCH
+/// Synchronization IE of its enhanced beacons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TschSync {
+    /// The 40-bit absolute slot number of the beacon.
+    pub asn: u64,
+    /// The join metric, i.e. the coordinator's distance to the PAN
+    /// coordinator.
+    pub join_metric: u8,
+}
+
+/// Describes a PAN found during a scan (PANDescriptor).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PanDescriptor {
+    /// The source address of the beacon.
+    pub coord_address: CoordAddress,
+    /// The PAN ID of the coordinator.
+    pub coord_pan_id: PanId<[u8; 2]>,
+    /// The channel on which the beacon was received.
+    pub channel: Channel,
+    /// The superframe specification of the beacon. Enhanced beacons don't
+    /// have one.
+    pub superframe_spec: Option<SuperframeSpecification>,
+    /// Whether the coordinator accepts GTS requests.
+    pub gts_permit: bool,
+    /// The link quality indicator of the received beacon.
+    pub link_quality: u8,
+    /// Radio clock tick at which the RMARKER of the beacon passed the local
+    /// antenna, if known
+    pub timestamp: Option<u64>,
+    /// The content of the TSCH Synchronization IE, if present.
+    pub tsch_sync: Option<TschSync>,
+}
+
+impl PanDescriptor {
+    /// Extracts a PAN descriptor from the given beacon or enhanced beacon MPDU
+    /// (without FCS).
+    ///
+    /// Returns `None` if the frame is not a well-formed beacon with a source
+    /// address. Secured beacons are not supported, yet.
+    pub(crate) fn from_beacon(mpdu: &[u8], channel: Channel, rx_info: &RxInfo) -> Option<Self> {
+        Self::parse_beacon(mpdu, channel, rx_info).map(|(pan_descriptor, _)| pan_descriptor)
+    }
+
+    /// Like [`Self::from_beacon`] but additionally returns the offset of the
+    /// beacon payload within the MPDU.
+    pub(crate) fn parse_beacon(
+        mpdu: &[u8],
+        channel: Channel,
+        rx_info: &RxInfo,
+    ) -> Option<(Self, usize)> {
+        let BeaconHeader {
+            frame_control,
+            coord_address,
+            coord_pan_id,
+            offset,
+        } = parse_beacon_header(mpdu)?;
+
+        let mut tsch_sync = None;
+        let beacon_payload = if frame_control.information_elements_present() {
+            let ies = IeList::new(mpdu, offset).ok()?;
+            tsch_sync = ies
+                .nested_ies()
+                .filter(|nested_ie| {
+                    !nested_ie.is_long && nested_ie.sub_id == TSCH_SYNCHRONIZATION_SUB_ID
+                })
+                .find_map(|nested_ie| TschSynchronizationIe::new(&mpdu[nested_ie.content]).ok())
+                .map(|ie| TschSync {
+                    asn: ie.asn(),
+                    join_metric: ie.join_metric(),
+                });
+            &mpdu[ies.frame_payload_offset()..]
+        } else {
+            &mpdu[offset..]
+        };
+
+        // Only beacons prior to IEEE 802.15.4-2015 have fixed superframe, GTS
+        // and pending address fields.
+        let (superframe_spec, gts_permit, beacon_fields_length) =
+            match frame_control.frame_version() {
+                FrameVersion::Ieee802154_2003 | FrameVersion::Ieee802154_2006 => {
+                    let superframe_spec = SuperframeSpecification::from_le_bytes(
+                        beacon_payload.get(..2)?.try_into().ok()?,
+                    );
+                    let gts_permit = beacon_payload.get(2)? & 0x80 != 0;
+                    let beacon_fields_length = beacon_fields_length(beacon_payload)?;
+                    (Some(superframe_spec), gts_permit, beacon_fields_length)
+                }
+                _ => (None, false, 0),
+            };
+        let beacon_payload_offset = mpdu.len() - beacon_payload.len() + beacon_fields_length;
+
+        let pan_descriptor = Self {
+            coord_address,
+            coord_pan_id,
+            channel,
+            superframe_spec,
+            gts_permit,
+            link_quality: rx_info.lqi,
+            timestamp: rx_info.timestamp,
+            tsch_sync,
+        };
+        Some((pan_descriptor, beacon_payload_offset))
+    }
+
+    /// Whether the sender of this beacon is a better TSCH time source than the
+    /// sender of the other beacon, i.e. it is closer to the PAN coordinator
+    /// according to its join metric or, at the same distance, its beacon was
+    /// received with a better link quality, see IEEE 802.15.4-2020, section
+    /// 6.3.6.
+    ///
+    /// Beacons without TSCH Synchronization IE are never preferred.
+    pub fn is_preferred_time_source_over(&self, other: &PanDescriptor) -> bool {
+        let Some(tsch_sync) = self.tsch_sync else {
+            return false;
+        };
+        match other.tsch_sync {
+            Some(other_tsch_sync) => {
+                (tsch_sync.join_metric, u8::MAX - self.link_quality)
+                    < (other_tsch_sync.join_metric, u8::MAX - other.link_quality)
+            }
+            None => true,
         }
     }
+
+    /// Whether both descriptors describe the same coordinator, i.e. the scan
+    /// received several beacons from it.
+    pub(crate) fn is_same_coordinator(&self, other: &PanDescriptor) -> bool {
+        self.coord_address == other.coord_address
+            && self.coord_pan_id == other.coord_pan_id
+            && self.channel == other.channel
+    }
+}
+
+/// The length of the Superframe Specification, GTS and Pending Address fields
+/// at the start of the given beacon payload, see IEEE 802.15.4-2020, section
+/// 7.3.1.
+///
+/// Returns `None` if the fields are truncated.
+fn beacon_fields_length(beacon_payload: &[u8]) -> Option<usize> {
+    // Superframe Specification and GTS Specification fields
+    let mut length = 3;
+    let gts_descriptor_count = (beacon_payload.get(2)? & 0x07) as usize;
+    if gts_descriptor_count > 0 {
+        // GTS Directions field and GTS descriptors
+        length += 1 + 3 * gts_descriptor_count;
+    }
+
+    let pending_address_spec = beacon_payload.get(length)?;
+    let num_short_addresses = (pending_address_spec & 0x07) as usize;
+    let num_extended_addresses = ((pending_address_spec >> 4) & 0x07) as usize;
+    length += 1 + 2 * num_short_addresses + 8 * num_extended_addresses;
+
+    (length <= beacon_payload.len()).then_some(length)
+}
+
+/// The MAC header fields of a beacon up to its IEs.
+struct BeaconHeader<'mpdu> {
+    frame_control: FrameControl<&'mpdu [u8]>,
+    coord_address: CoordAddress,
+    coord_pan_id: PanId<[u8; 2]>,
+    /// The offset of the IEs or beacon fields within the MPDU.
+    offset: usize,
+}
+
+/// Parses the frame control and addressing fields of an unsecured beacon.
+fn parse_beacon_header(mpdu: &[u8]) -> Option<BeaconHeader<'_>> {
+    let frame_control = FrameControl::new(mpdu).ok()?;
+    if !matches!(frame_control.frame_type(), FrameType::Beacon) || frame_control.security_enabled()
+    {
+        return None;
+    }
+
+    let mut offset = if frame_control.sequence_number_suppression() {
+        2
+    } else {
+        3
+    };
+
+    let addressing_repr =
+        AddressingRepr::from_frame_control(FrameControl::new_unchecked(&mpdu[..2])).ok()??;
+    let addressing_length = addressing_repr.addressing_fields_length().ok()? as usize;
+    let addressing_fields = AddressingFields::new(
+        mpdu.get(offset..offset + addressing_length)?,
+        addressing_repr,
+    )
+    .ok()?;
+    offset += addressing_length;
+
+    let coord_address = match addressing_fields.src_address()? {
+        Address::Short(address) => {
+            CoordAddress::Short(ShortAddress::new_owned(address.as_ref().try_into().ok()?))
+        }
+        Address::Extended(address) => CoordAddress::Extended(ExtendedAddress::new_owned(
+            address.as_ref().try_into().ok()?,
+        )),
+        Address::Absent => return None,
+    };
+    let coord_pan_id = addressing_fields
+        .src_pan_id()
+        .or(addressing_fields.dst_pan_id())?;
+    let coord_pan_id = PanId::new_owned(coord_pan_id.as_ref().try_into().ok()?);
+
+    Some(BeaconHeader {
+        frame_control,
+        coord_address,
+        coord_pan_id,
+        offset,
+    })
+}
+
+/// Reads the IEs of the given beacon MPDU (without FCS).
+///
+/// Returns `None` if the frame is not a well-formed beacon. The IE list is
+/// empty if the beacon carries no IEs.
+pub(crate) fn parse_beacon_ies(mpdu: &[u8]) -> Option<IeList<&[u8]>> {
+    let header = parse_beacon_header(mpdu)?;
+    let offset = if header.frame_control.information_elements_present() {
+        header.offset
+    } else {
+        mpdu.len()
+    };
+    IeList::new(mpdu, offset).ok()
+}
+
+/// The time spent listening on each channel: aBaseSuperframeDuration * (2^n +
+/// 1) symbols, see IEEE 802.15.4-2020, section 8.2.10.1.
+fn scan_duration_per_channel<Timer: Frequency>(scan_duration: u8) -> Duration<Timer> {
+    debug_assert!(scan_duration <= MAX_SCAN_DURATION);
+    (A_BASE_SUPERFRAME_DURATION * ((1_usize << scan_duration) + 1)).convert_into_rounding_up()
+}
+
+/// Scans the requested channels one by one for beacons or energy.
+///
+/// The task makes the indication tasks receive on each channel in turn
+/// without changing the default channel. For an active scan it then
+/// broadcasts a (possibly enhanced) Beacon Request command on that channel.
+/// Beacons are received by the indication tasks and collected into the shared
+/// PAN descriptor list by the MAC service while the list is present.
+///
+/// An ED scan instead asks the driver service to measure the peak energy on
+/// each channel for the scan duration.
+pub(crate) struct ScanTask<'task, RadioDriverImpl: DriverConfig> {
+    radio: PhantomData<RadioDriverImpl>,
+    scan_type: ScanType,
+    scan_duration: u8,
+    channel_page: u8,
+    pib: &'task RefCell<Pib>,
+    /// Channels still to be scanned.
+    channels: ScanChannelIter,
+    /// The channel being scanned, shared with the indication tasks.
+    scan_channel: &'task Cell<Option<Channel>>,
+    /// PAN descriptors collected by the MAC service. Present while a scan is
+    /// in progress.
+    pan_descriptors: &'task RefCell<Option<PanDescriptorList>>,
+    buffer_allocator: MacBufferAllocator,
+    /// Re-usable Beacon Request command, if this is an active scan.
+    beacon_request: Option<RadioFrame<RadioFrameSized>>,
+    /// Energy levels measured so far, if this is an ED scan.
+    energy_detect_list: EnergyDetectList,
+    /// The validation result of the scan request.
+    status: Result<(), ScanError>,
+    state: ScanState,
+}
+
+enum ScanState {
+    Initial,
+    /// Waiting for the radio to switch off so that the indication tasks
+    /// receive on the next channel.
+    SwitchingChannel,
+    SendingBeaconRequest,
+    /// Waiting for beacons until the scan duration expires.
+    Listening,
+    /// Waiting for the energy measurement on the current channel.
+    DetectingEnergy,
+    /// Waiting for the radio to switch off so that the indication tasks
+    /// receive on the default channel again.
+    RestoringChannel,
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> ScanTask<'task, RadioDriverImpl> {
+    pub fn new(
+        scan_request: ScanRequest,
+        pib: &'task RefCell<Pib>,
+        scan_channel: &'task Cell<Option<Channel>>,
+        pan_descriptors: &'task RefCell<Option<PanDescriptorList>>,
+        rng: &dyn BackoffRng,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        let status = scan_request.validate();
+        Self {
+            radio: PhantomData,
+            scan_type: scan_request.scan_type,
+            scan_duration: scan_request.scan_duration,
+            channel_page: scan_request.channel_page,
+            channels: scan_request.scan_channels.channels(rng.next_u32()),
+            pib,
+            scan_channel,
+            pan_descriptors,
+            buffer_allocator,
+            beacon_request: None,
+            energy_detect_list: EnergyDetectList::new(),
+            status,
+            state: ScanState::Initial,
+        }
+    }
+
+    fn allocate_beacon_request(&self) -> RadioFrame<RadioFrameSized> {
+        let buffer_size = BEACON_REQUEST_FRAME_REPR
+            .min_buffer_size::<RadioDriverImpl>(COMMAND_ID_LEN)
+            .unwrap();
+        let buffer = self
+            .buffer_allocator
+            .try_allocate_buffer(buffer_size)
+            .expect("no capacity");
+        let enhanced = matches!(self.scan_type, ScanType::EnhancedActiveScan);
+        beacon_request_frame::<RadioDriverImpl>(enhanced, 0, buffer)
+            .into_radio_frame::<RadioDriverImpl>()
+    }
+
+    /// Switches to the next channel or ends the scan if all channels have
+    /// been scanned or the PAN descriptor list is full.
+    fn scan_next_channel(mut self) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let is_ed_scan = matches!(self.scan_type, ScanType::Ed);
+        let limit_reached = !is_ed_scan
+            && self
+                .pan_descriptors
+                .borrow()
+                .as_ref()
+                .is_some_and(|pan_descriptors| pan_descriptors.is_full());
+
+        let next_channel = if limit_reached {
+            None
+        } else {
+            // Safety: Channels were validated.
+            self.channels
+                .next()
+                .map(|channel| Channel::try_from(channel).unwrap())
+        };
+
+        match next_channel {
+            Some(channel) if is_ed_scan => {
+                self.state = ScanState::DetectingEnergy;
+                let ed_task = DrvSvcTaskEd {
+                    start: Timestamp::BestEffort,
+                    duration: scan_duration_per_channel::<SymbolsOQpsk250kB>(self.scan_duration),
+                    channel: Some(channel),
+                };
+                return MacTaskTransition::DrvSvcRequest(self, ed_task.into(), None);
+            }
+            Some(channel) => {
+                // Ending the ongoing RX window makes the indication tasks
+                // restart receiving on the given channel.
+                self.scan_channel.set(Some(channel));
+                self.state = ScanState::SwitchingChannel;
+            }
+            None => {
+                self.scan_channel.set(None);
+                self.state = ScanState::RestoringChannel;
+            }
+        }
+
+        MacTaskTransition::DrvSvcRequest(
+            self,
+            DrvSvcTaskOff {
+                at: Timestamp::BestEffort,
+            }
+            .into(),
+            None,
+        )
+    }
+
+    fn listen(mut self) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        self.state = ScanState::Listening;
+        let scan_end = RadioDriverImpl::Timer::now()
+            + scan_duration_per_channel::<RadioDriverImpl::Timer>(self.scan_duration);
+        MacTaskTransition::TimerRequest(self, scan_end)
+    }
+
+    fn terminate(mut self) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        if let Some(beacon_request) = self.beacon_request.take() {
+            // Safety: We allocated the buffer ourselves.
+            unsafe {
+                self.buffer_allocator
+                    .deallocate_buffer(beacon_request.into_buffer());
+            }
+        }
+
+        let mut status = self.status;
+        let pan_descriptors = if matches!(status, Err(ScanError::ScanInProgress)) {
+            // Leave the ongoing scan alone.
+            PanDescriptorList::new()
+        } else {
+            let pan_descriptors = self.pan_descriptors.take().unwrap_or_default();
+            if matches!(self.scan_type, ScanType::Ed) {
+                // Beacons received in between measurements are not reported.
+                PanDescriptorList::new()
+            } else {
+                if status.is_ok() {
+                    if pan_descriptors.is_full() {
+                        status = Err(ScanError::LimitReached);
+                    } else if pan_descriptors.is_empty() {
+                        status = Err(ScanError::NoBeacon);
+                    }
+                }
+                pan_descriptors
+            }
+        };
+
+        MacTaskTransition::Terminated(ScanConfirm {
+            status,
+            scan_type: self.scan_type,
+            channel_page: self.channel_page,
+            pan_descriptors,
+            energy_detect_list: self.energy_detect_list,
+        })
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for ScanTask<'_, RadioDriverImpl>
+{
+    type Result = ScanConfirm;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, ScanState::Initial) {
+            ScanState::Initial => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                if self.pan_descriptors.borrow().is_some() {
+                    self.status = Err(ScanError::ScanInProgress);
+                }
+                if self.status.is_err() {
+                    return self.terminate();
+                }
+
+                self.pan_descriptors.replace(Some(PanDescriptorList::new()));
+                if matches!(
+                    self.scan_type,
+                    ScanType::Active | ScanType::EnhancedActiveScan
+                ) {
+                    self.beacon_request = Some(self.allocate_beacon_request());
+                }
+                self.scan_next_channel()
+            }
+            ScanState::SwitchingChannel => {
+                debug_assert!(matches!(
+                    event,
+                    MacTaskEvent::DrvSvcResponse(DrvSvcResponse::Off(Ok(_)))
+                ));
+
+                match self.beacon_request.take() {
+                    Some(beacon_request) => {
+                        // Each Beacon Request is a new command and therefore
+                        // gets a new sequence number.
+                        let mut beacon_request = MpduFrame::from_radio_frame(beacon_request);
+                        self.pib
+                            .borrow_mut()
+                            .stamp_sequence_number(&mut beacon_request);
+
+                        self.state = ScanState::SendingBeaconRequest;
+                        let tx_task = DrvSvcTaskTx {
+                            at: Timestamp::BestEffort,
+                            radio_frame: beacon_request.into_radio_frame::<RadioDriverImpl>(),
+                            cca: true,
+                            channel: self.scan_channel.get(),
+                        };
+                        MacTaskTransition::DrvSvcRequest(self, DrvSvcRequest::Tx(tx_task), None)
+                    }
+                    None => self.listen(),
+                }
+            }
+            ScanState::SendingBeaconRequest => {
+                // We listen for beacons even if the Beacon Request could not be
+                // sent as beacons may still be received from beacon-enabled
+                // coordinators.
+                let beacon_request = match event {
+                    MacTaskEvent::DrvSvcResponse(DrvSvcResponse::Tx(tx_result)) => {
+                        match tx_result {
+                            Ok(TxResult::Sent(radio_frame, _) | TxResult::Nack(radio_frame, _))
+                            | Err(DrvSvcTaskError::Task(TxError::CcaBusy(radio_frame))) => {
+                                radio_frame
+                            }
+                            // TODO: Implement if required by a driver implementation.
+                            Err(DrvSvcTaskError::RadioError) => unreachable!(),
+                        }
+                    }
+                    // Safety: We issued a Tx task and therefore expect a Tx
+                    //         result.
+                    _ => unreachable!(),
+                };
+                self.beacon_request = Some(beacon_request);
+                self.listen()
+            }
+            ScanState::Listening => {
+                debug_assert!(matches!(event, MacTaskEvent::TimerExpired));
+                self.scan_next_channel()
+            }
+            ScanState::DetectingEnergy => {
+                let energy_level = match event {
+                    MacTaskEvent::DrvSvcResponse(DrvSvcResponse::Ed(ed_result)) => {
+                        match ed_result {
+                            Ok(EdResult { energy_level }) => energy_level,
+                            // TODO: Implement if required by a driver implementation.
+                            Err(_) => unreachable!(),
+                        }
+                    }
+                    // Safety: We issued an ED task and therefore expect an ED
+                    //         result.
+                    _ => unreachable!(),
+                };
+                // Safety: We scan at most MAC_MAX_ED_CHANNELS channels.
+                self.energy_detect_list.push(energy_level).unwrap();
+                self.scan_next_channel()
+            }
+            ScanState::RestoringChannel => {
+                debug_assert!(matches!(
+                    event,
+                    MacTaskEvent::DrvSvcResponse(DrvSvcResponse::Off(Ok(_)))
+                ));
+                self.terminate()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::driver::time::SymbolsOQpsk250kB;
+
+    const RX_INFO: RxInfo = RxInfo {
+        timestamp: Some(1234),
+        lqi: 200,
+        rssi: Some(-60),
+    };
+
+    #[test]
+    fn scan_duration() {
+        assert_eq!(
+            scan_duration_per_channel::<SymbolsOQpsk250kB>(0),
+            Duration::new(2 * 960)
+        );
+        assert_eq!(
+            scan_duration_per_channel::<SymbolsOQpsk250kB>(14),
+            Duration::new(16385 * 960)
+        );
+    }
+
+    #[test]
+    fn validate_scan_request() {
+        assert!(ScanRequest::new(ScanType::Active, ScanChannels::All, 14)
+            .validate()
+            .is_ok());
+        assert!(ScanRequest::new(ScanType::Ed, ScanChannels::Single(11), 5)
+            .validate()
+            .is_ok());
+        assert_eq!(
+            ScanRequest::new(ScanType::Passive, ScanChannels::All, 15).validate(),
+            Err(ScanError::InvalidParameter)
+        );
+        assert_eq!(
+            ScanRequest::new(ScanType::Orphan, ScanChannels::All, 3).validate(),
+            Err(ScanError::InvalidParameter)
+        );
+        assert_eq!(
+            ScanRequest::new(ScanType::Passive, ScanChannels::Single(27), 3).validate(),
+            Err(ScanError::BadChannel)
+        );
+    }
+
+    #[test]
+    fn scan_channels() {
+        let channels: heapless::Vec<u8, 16> = ScanChannels::All.channels(1234).collect();
+        assert_eq!(
+            channels,
+            [11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26]
+        );
+        assert!(ScanChannels::Single(15).channels(1234).eq([15]));
+
+        // Random hopping visits each channel once.
+        for random in [0, 1, 0x1234_5678, 0xdead_beef, u32::MAX] {
+            let mut channels: heapless::Vec<u8, 16> =
+                ScanChannels::RandomHopping.channels(random).collect();
+            channels.sort_unstable();
+            assert!(channels.iter().copied().eq(11..=26));
+        }
+        assert!(!ScanChannels::RandomHopping
+            .channels(0x0305)
+            .eq(ScanChannels::All.channels(0)));
+    }
+
+    #[test]
+    fn preferred_time_source() {
+        let beacon = |join_metric: Option<u8>, link_quality: u8| PanDescriptor {
+            coord_address: CoordAddress::Short(ShortAddress::new_owned([0x01, 0x00])),
+            coord_pan_id: PanId::new_owned([0xcd, 0xab]),
+            channel: Channel::_11,
+            superframe_spec: None,
+            gts_permit: false,
+            link_quality,
+            timestamp: Some(1234),
+            tsch_sync: join_metric.map(|join_metric| TschSync {
+                asn: 0,
+                join_metric,
+            }),
+        };
+
+        // Closer to the PAN coordinator
+        assert!(beacon(Some(1), 50).is_preferred_time_source_over(&beacon(Some(2), 200)));
+        // Better link at the same distance
+        assert!(beacon(Some(1), 200).is_preferred_time_source_over(&beacon(Some(1), 50)));
+        assert!(!beacon(Some(1), 200).is_preferred_time_source_over(&beacon(Some(1), 200)));
+        // Not a TSCH network
+        assert!(beacon(Some(3), 0).is_preferred_time_source_over(&beacon(None, 255)));
+        assert!(!beacon(None, 255).is_preferred_time_source_over(&beacon(Some(3), 0)));
+    }
+
+    #[test]
+    fn pan_descriptor_from_beacon() {
+        #[rustfmt::skip]
+        let beacon = [
+            // Frame control: beacon, 2003, short source address
+            0x00, 0x80,
+            // Sequence number
+            0x2a,
+            // Source PAN ID and address
+            0x34, 0x12, 0x01, 0x00,
+            // Superframe specification: non-beacon-enabled PAN coordinator,
+            // association permitted
+            0xff, 0xcf,
+            // GTS specification: GTS permitted
+            0x80,
+            // Pending address specification
+            0x00,
+        ];
+
+        let pan_descriptor = PanDescriptor::from_beacon(&beacon, Channel::_15, &RX_INFO).unwrap();
+        assert_eq!(
+            pan_descriptor.coord_address,
+            CoordAddress::Short(ShortAddress::new_owned([0x01, 0x00]))
+        );
+        assert_eq!(pan_descriptor.coord_pan_id.into_u16(), 0x1234);
+        assert_eq!(pan_descriptor.channel, Channel::_15);
+        assert_eq!(pan_descriptor.link_quality, 200);
+        assert_eq!(pan_descriptor.timestamp, Some(1234));
+        assert!(pan_descriptor.gts_permit);
+        assert!(pan_descriptor.tsch_sync.is_none());
+
+        let superframe_spec = pan_descriptor.superframe_spec.unwrap();
+        assert_eq!(superframe_spec.beacon_order(), 15);
+        assert_eq!(superframe_spec.superframe_order(), 15);
+        assert!(superframe_spec.pan_coordinator());
+        assert!(superframe_spec.association_permit());
+    }
+
+    #[test]
+    fn pan_descriptor_from_enhanced_beacon() {
+        #[rustfmt::skip]
+        let enhanced_beacon = [
+            // Frame control: beacon, 2015, PAN ID compression, IEs present,
+            // short destination address, extended source address
+            0x40, 0xea,
+            // Sequence number
+            0x01,
+            // Destination PAN ID and address
+            0xcd, 0xab, 0xff, 0xff,
+            // Source address
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            // Header Termination 1 IE
+            0x00, 0x3f,
+            // MLME IE
+            0x08, 0x88,
+            // TSCH Synchronization IE: ASN, join metric
+            0x06, 0x1a, 0x05, 0x04, 0x03, 0x02, 0x01, 0x02,
+        ];
+
+        let pan_descriptor =
+            PanDescriptor::from_beacon(&enhanced_beacon, Channel::_20, &RX_INFO).unwrap();
+        assert_eq!(
+            pan_descriptor.coord_address,
+            CoordAddress::Extended(ExtendedAddress::new_owned([
+                0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08
+            ]))
+        );
+        assert_eq!(pan_descriptor.coord_pan_id.into_u16(), 0xabcd);
+        assert!(pan_descriptor.superframe_spec.is_none());
+        assert_eq!(
+            pan_descriptor.tsch_sync,
+            Some(TschSync {
+                asn: 0x0102030405,
+                join_metric: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn beacon_payload_offset() {
+        #[rustfmt::skip]
+        let beacon = [
+            // Frame control: beacon, 2006, short source address
+            0x00, 0x90,
+            // Sequence number
+            0x2a,
+            // Source PAN ID and address
+            0x34, 0x12, 0x01, 0x00,
+            // Superframe specification
+            0xff, 0xcf,
+            // GTS specification: one descriptor
+            0x81,
+            // GTS directions and descriptor
+            0x00, 0x02, 0x00, 0x12,
+            // Pending address specification: one short, one extended address
+            0x11,
+            // Pending addresses
+            0x03, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            // Beacon payload
+            0xaa, 0xbb,
+        ];
+
+        let (_, offset) = PanDescriptor::parse_beacon(&beacon, Channel::_15, &RX_INFO).unwrap();
+        assert_eq!(&beacon[offset..], &[0xaa, 0xbb]);
+
+        // Pending addresses truncated
+        assert!(PanDescriptor::parse_beacon(&beacon[..20], Channel::_15, &RX_INFO).is_none());
+    }
+
+    #[test]
+    fn reject_non_beacons() {
+        // Data frame
+        let data_frame = [0x41, 0x88, 0x01, 0xcd, 0xab, 0xff, 0xff, 0x01, 0x00];
+        assert!(PanDescriptor::from_beacon(&data_frame, Channel::_11, &RX_INFO).is_none());
+
+        // Truncated beacon
+        let beacon = [0x00, 0x80, 0x2a, 0x34, 0x12, 0x01, 0x00, 0xff];
+        assert!(PanDescriptor::from_beacon(&beacon, Channel::_11, &RX_INFO).is_none());
+    }
 }
@@ -1,19 +1,225 @@
 #![allow(dead_code)]
 use rand_core::RngCore;
 
-use crate::{driver::DriverConfig, mac::MacService};
+use crate::{
+    driver::{config::Channel, time::Duration, DriverConfig},
+    mac::{
+        frame::repr::{IeRepr, IeReprList},
+        neighbors::EvictionPolicy,
+        pib::Pib,
+        tsch::schedule::TschHoppingSequence,
+        MacService,
+    },
+};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SetError {
+    /// The value is out of the valid range of the given attribute.
     InvalidParameter,
 }
 
 /// Attributes that may be written by an upper layer
+///
+/// Also used to report the value of an attribute read via MLME-GET.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SetRequestAttribute {
     // IEEE 802.15.4-2020, section 8.4.3.1, table 8-94
     MacExtendedAddress([u8; 8]),
+    MacAssociatedPanCoord(bool),
     MacAssociationPermit(bool),
+    MacAutoRequest(bool),
+    MacBeaconOrder(u8),
+    MacBsn(u8),
+    MacCoordExtendedAddress([u8; 8]),
+    MacCoordShortAddress(u16),
+    MacDsn(u8),
+    MacEbsn(u8),
+    MacEnhancedBeaconOrder(u8),
+    MacImplicitBroadcast(bool),
+    MacMaxBe(u8),
+    MacMaxCsmaBackoffs(u8),
+    MacMaxFrameRetries(u8),
+    MacMinBe(u8),
     MacPanId(u16),
+    MacPromiscuousMode(bool),
+    MacRxOnWhenIdle(bool),
+    MacSecurityEnabled(bool),
     MacShortAddress(u16),
+    MacTransactionPersistenceTime(u16),
+    // IEEE 802.15.4-2020, section 8.4.3.4, table 8-98
+    MacHoppingSequenceId(u8),
+    MacChannelPage(u8),
+    MacNumberOfChannels(u16),
+    MacPhyConfiguration(u32),
+    /// Also sets macHoppingSequenceLength.
+    MacHoppingSequenceList(TschHoppingSequence),
+    MacCurrentHop(u16),
+    // IEEE 802.15.4-2020, section 11.3, table 11-2
+    PhyCurrentChannel(Channel),
+    PhyTxPower(i8),
+    // Not part of the standard: Whether frames with an invalid FCS are
+    // indicated in promiscuous mode.
+    MacPromiscuousCrcErrors(bool),
+    // Not part of the standard: The IEs carried by periodic Enhanced Beacons.
+    MacEnhancedBeaconIes(IeReprList<'static, IeRepr<'static>>),
+    // Not part of the standard: The time in milliseconds during which
+    // retransmitted frames are recognized as duplicates, 0 to disable.
+    MacDuplicateDetectionTimeout(u16),
+    // Not part of the standard: Which neighbor is replaced once the neighbor
+    // table is full.
+    MacNeighborEvictionPolicy(EvictionPolicy),
+    // Not part of the standard: The time in seconds without frames exchanged
+    // with the TSCH time source after which a keep-alive is sent, 0 to
+    // disable keep-alives.
+    MacTschKeepAlivePeriod(u16),
+    // Not part of the standard: The time in seconds without frames exchanged
+    // with the TSCH time source after which synchronization is lost.
+    MacTschDesyncTimeout(u16),
+}
+
+impl SetRequestAttribute {
+    /// Validates the attribute value against the ranges given in IEEE
+    /// 802.15.4-2020, table 8-94, and writes it to the PIB.
+    ///
+    /// The PIB remains unchanged if the value is invalid.
+    pub(crate) fn write(&self, pib: &mut Pib) -> Result<(), SetError> {
+        match *self {
+            SetRequestAttribute::MacExtendedAddress(extended_address) => {
+                pib.extended_address = Some(extended_address)
+            }
+            SetRequestAttribute::MacAssociatedPanCoord(associated_pan_coord) => {
+                pib.associated_pan_coord = associated_pan_coord
+            }
+            SetRequestAttribute::MacAssociationPermit(association_permit) => {
+                pib.association_permit = association_permit
+            }
+            SetRequestAttribute::MacAutoRequest(auto_request) => pib.auto_request = auto_request,
+            SetRequestAttribute::MacBeaconOrder(beacon_order) => {
+                if beacon_order > 15 {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.beacon_order = beacon_order
+            }
+            SetRequestAttribute::MacBsn(bsn) => pib.bsn = bsn,
+            SetRequestAttribute::MacCoordExtendedAddress(coord_extended_address) => {
+                pib.coord_extended_address = Some(coord_extended_address)
+            }
+            SetRequestAttribute::MacCoordShortAddress(coord_short_address) => {
+                pib.coord_short_address = coord_short_address
+            }
+            SetRequestAttribute::MacDsn(dsn) => pib.dsn = dsn,
+            SetRequestAttribute::MacEbsn(ebsn) => pib.ebsn = ebsn,
+            SetRequestAttribute::MacEnhancedBeaconOrder(enhanced_beacon_order) => {
+                if enhanced_beacon_order > 15 {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.enhanced_beacon_order = enhanced_beacon_order
+            }
+            SetRequestAttribute::MacImplicitBroadcast(implicit_broadcast) => {
+                pib.implicit_broadcast = implicit_broadcast
+            }
+            SetRequestAttribute::MacMaxBe(max_be) => {
+                // macMinBe must not exceed macMaxBe.
+                if !(3..=8).contains(&max_be) || max_be < pib.min_be {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.max_be = max_be
+            }
+            SetRequestAttribute::MacMaxCsmaBackoffs(max_csma_backoffs) => {
+                if max_csma_backoffs > 5 {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.max_csma_backoffs = max_csma_backoffs
+            }
+            SetRequestAttribute::MacMaxFrameRetries(max_frame_retries) => {
+                if max_frame_retries > 7 {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.max_frame_retries = max_frame_retries
+            }
+            SetRequestAttribute::MacMinBe(min_be) => {
+                if min_be > pib.max_be {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.min_be = min_be
+            }
+            SetRequestAttribute::MacPanId(pan_id) => pib.pan_id.set_u16(pan_id),
+            SetRequestAttribute::MacPromiscuousMode(promiscuous_mode) => {
+                pib.promiscuous_mode = promiscuous_mode
+            }
+            SetRequestAttribute::MacRxOnWhenIdle(rx_on_when_idle) => {
+                pib.rx_on_when_idle = rx_on_when_idle
+            }
+            SetRequestAttribute::MacSecurityEnabled(security_enabled) => {
+                // Security is not supported.
+                if security_enabled {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.security_enabled = security_enabled
+            }
+            SetRequestAttribute::MacShortAddress(short_address) => {
+                pib.short_address = short_address
+            }
+            SetRequestAttribute::MacTransactionPersistenceTime(transaction_persistence_time) => {
+                pib.transaction_persistence_time = transaction_persistence_time
+            }
+            SetRequestAttribute::MacHoppingSequenceId(hopping_sequence_id) => {
+                pib.hopping_sequence_id = hopping_sequence_id
+            }
+            SetRequestAttribute::MacChannelPage(channel_page) => pib.channel_page = channel_page,
+            SetRequestAttribute::MacNumberOfChannels(number_of_channels) => {
+                pib.number_of_channels = number_of_channels
+            }
+            SetRequestAttribute::MacPhyConfiguration(phy_configuration) => {
+                pib.phy_configuration = phy_configuration
+            }
+            SetRequestAttribute::MacHoppingSequenceList(hopping_sequence) => {
+                // Restart at the beginning of a shorter sequence.
+                if pib.current_hop as usize >= hopping_sequence.len() {
+                    pib.current_hop = 0;
+                }
+                pib.hopping_sequence = hopping_sequence
+            }
+            SetRequestAttribute::MacCurrentHop(current_hop) => {
+                if current_hop as usize >= pib.hopping_sequence.len() {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.current_hop = current_hop
+            }
+            SetRequestAttribute::PhyCurrentChannel(current_channel) => {
+                pib.current_channel = current_channel
+            }
+            SetRequestAttribute::PhyTxPower(tx_power) => pib.tx_power = tx_power,
+            SetRequestAttribute::MacPromiscuousCrcErrors(promiscuous_crc_errors) => {
+                pib.promiscuous_crc_errors = promiscuous_crc_errors
+            }
+            SetRequestAttribute::MacEnhancedBeaconIes(enhanced_beacon_ies) => {
+                pib.enhanced_beacon_ies = enhanced_beacon_ies
+            }
+            SetRequestAttribute::MacDuplicateDetectionTimeout(timeout) => {
+                pib.duplicate_detection_timeout = Duration::new(timeout as i64)
+            }
+            SetRequestAttribute::MacNeighborEvictionPolicy(eviction_policy) => {
+                pib.neighbor_eviction_policy = eviction_policy
+            }
+            SetRequestAttribute::MacTschKeepAlivePeriod(keep_alive_period) => {
+                // Keep-alives must be sent before synchronization is lost.
+                let keep_alive_period = Duration::new(keep_alive_period as i64 * 1000);
+                if keep_alive_period >= pib.tsch_desync_timeout {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.tsch_keep_alive_period = keep_alive_period
+            }
+            SetRequestAttribute::MacTschDesyncTimeout(desync_timeout) => {
+                let desync_timeout = Duration::new(desync_timeout as i64 * 1000);
+                if desync_timeout <= pib.tsch_keep_alive_period {
+                    return Err(SetError::InvalidParameter);
+                }
+                pib.tsch_desync_timeout = desync_timeout
+            }
+        }
+        Ok(())
+    }
 }
 
 #[allow(dead_code)]
@@ -21,24 +227,109 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
     /// Used by the next higher layer to attempt to write the given value to
     /// the indicated MAC PIB attribute.
     ///
+    /// A new hopping sequence also applies to all slotframes of the TSCH
+    /// schedule. It is rejected if a link's channel offset exceeds it. The TX
+    /// power must be within the range supported by the radio.
+    ///
     /// * `attribute` - Attribute to write
     pub(crate) async fn mlme_set_request(
         &self,
         attribute: &SetRequestAttribute,
     ) -> Result<(), SetError> {
-        let mut pib = self.pib.borrow_mut();
-        match attribute {
-            SetRequestAttribute::MacPanId(pan_id) => pib.pan_id.set_u16(*pan_id),
-            SetRequestAttribute::MacShortAddress(short_address) => {
-                pib.short_address = *short_address
-            }
-            SetRequestAttribute::MacExtendedAddress(extended_address) => {
-                pib.extended_address = Some(*extended_address)
-            }
-            SetRequestAttribute::MacAssociationPermit(association_permit) => {
-                pib.association_permit = *association_permit
+        if let SetRequestAttribute::MacHoppingSequenceList(hopping_sequence) = attribute {
+            self.tsch_schedule
+                .borrow_mut()
+                .set_hopping_sequence(*hopping_sequence)
+                .map_err(|_| SetError::InvalidParameter)?;
+        }
+        if let SetRequestAttribute::PhyTxPower(tx_power) = attribute {
+            if !RadioDriverImpl::TX_POWER_RANGE.contains(tx_power) {
+                return Err(SetError::InvalidParameter);
             }
         }
+
+        let mut pib = self.pib.borrow_mut();
+        attribute.write(&mut pib)?;
+        self.frame_filter.set(pib.frame_filter());
+        self.channel.set(pib.current_channel);
+        self.tx_power.set(pib.tx_power);
+        self.neighbors
+            .borrow_mut()
+            .set_eviction_policy(pib.neighbor_eviction_policy);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_valid_attributes() {
+        let mut pib = Pib::default();
+
+        SetRequestAttribute::MacMaxBe(8).write(&mut pib).unwrap();
+        SetRequestAttribute::MacMinBe(5).write(&mut pib).unwrap();
+        SetRequestAttribute::MacMaxFrameRetries(7)
+            .write(&mut pib)
+            .unwrap();
+        SetRequestAttribute::MacPanId(0xabcd)
+            .write(&mut pib)
+            .unwrap();
+        SetRequestAttribute::PhyCurrentChannel(Channel::_15)
+            .write(&mut pib)
+            .unwrap();
+        SetRequestAttribute::MacCurrentHop(15)
+            .write(&mut pib)
+            .unwrap();
+        let hopping_sequence = TschHoppingSequence::new(&[15, 25, 26, 20]).unwrap();
+        SetRequestAttribute::MacHoppingSequenceList(hopping_sequence)
+            .write(&mut pib)
+            .unwrap();
+        SetRequestAttribute::MacTschDesyncTimeout(60)
+            .write(&mut pib)
+            .unwrap();
+        SetRequestAttribute::MacTschKeepAlivePeriod(20)
+            .write(&mut pib)
+            .unwrap();
+
+        assert_eq!((pib.min_be, pib.max_be), (5, 8));
+        assert_eq!(pib.max_frame_retries, 7);
+        assert_eq!(pib.pan_id.into_u16(), 0xabcd);
+        assert_eq!(pib.current_channel, Channel::_15);
+        // The current hop restarts within the shorter hopping sequence.
+        assert_eq!(pib.hopping_sequence, hopping_sequence);
+        assert_eq!(pib.current_hop, 0);
+        assert_eq!(pib.tsch_desync_timeout, Duration::new(60_000));
+        assert_eq!(pib.tsch_keep_alive_period, Duration::new(20_000));
+    }
+
+    #[test]
+    fn reject_out_of_range_attributes() {
+        let mut pib = Pib::default();
+        SetRequestAttribute::MacMaxBe(4).write(&mut pib).unwrap();
+        SetRequestAttribute::MacMinBe(3).write(&mut pib).unwrap();
+
+        for attribute in [
+            SetRequestAttribute::MacMaxBe(2),
+            SetRequestAttribute::MacMaxBe(9),
+            // macMinBe must not exceed macMaxBe.
+            SetRequestAttribute::MacMinBe(5),
+            SetRequestAttribute::MacMaxCsmaBackoffs(6),
+            SetRequestAttribute::MacMaxFrameRetries(8),
+            SetRequestAttribute::MacBeaconOrder(16),
+            SetRequestAttribute::MacEnhancedBeaconOrder(16),
+            // The default hopping sequence has 16 channels.
+            SetRequestAttribute::MacCurrentHop(16),
+            // Keep-alives must be sent before synchronization is lost.
+            SetRequestAttribute::MacTschKeepAlivePeriod(30),
+            SetRequestAttribute::MacTschDesyncTimeout(10),
+            SetRequestAttribute::MacSecurityEnabled(true),
+        ] {
+            assert_eq!(attribute.write(&mut pib), Err(SetError::InvalidParameter));
+        }
+
+        // The PIB remains unchanged.
+        assert_eq!((pib.min_be, pib.max_be), (3, 4));
+    }
+}
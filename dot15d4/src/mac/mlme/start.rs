@@ -1,15 +1,326 @@
-use rand_core::RngCore;
+use core::{
+    cell::{Cell, RefCell},
+    mem,
+};
 
-use crate::{driver::DriverConfig, mac::MacService};
+use crate::{
+    driver::{
+        config::Channel,
+        frame::{ExtendedAddress, FrameFilter, PanId, ShortAddress},
+        tasks::Timestamp,
+        DriverConfig, DrvSvcResponse, DrvSvcTaskOff,
+    },
+    mac::{
+        csma::BackoffRng,
+        frame::mpdu::{
+            coordinator_realignment_frame, MpduFrame, COORDINATOR_REALIGNMENT_FRAME_REPR,
+            COORDINATOR_REALIGNMENT_LEN,
+        },
+        mcps::data::{DataError, DataRequest, DataRequestResult, DataRequestTask},
+        pib::Pib,
+        task::*,
+        MacBufferAllocator,
+    },
+    util::allocator::IntoBuffer,
+};
 
-struct StartConfirm {}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartError {
+    /// macShortAddress is 0xffff, i.e. no short address has been set.
+    NoShortAddress,
+    /// The Coordinator Realignment command could not be sent as the channel
+    /// was busy.
+    ChannelAccessFailure,
+    // TODO: not supported
+    CounterError,
+    // TODO: not supported
+    FrameTooLong,
+    /// The request parameters are invalid or not supported, e.g. a
+    /// beacon-enabled PAN was requested.
+    InvalidParameter,
+}
+
+impl From<DataError> for StartError {
+    fn from(value: DataError) -> Self {
+        match value {
+            DataError::ChannelAccessFailure => StartError::ChannelAccessFailure,
+            DataError::CounterError => StartError::CounterError,
+            DataError::FrameTooLong => StartError::FrameTooLong,
+            // Broadcast commands are neither acknowledged nor sent indirectly.
+            _ => StartError::InvalidParameter,
+        }
+    }
+}
+
+/// Represents an MLME-START.request, see IEEE 802.15.4-2020, section
+/// 8.2.12.1.
+///
+/// Only non-beacon-enabled PANs are supported, i.e. the beacon order must be
+/// 15.
+pub struct StartRequest {
+    pub pan_id: PanId<[u8; 2]>,
+    pub channel: Channel,
+    pub channel_page: u8,
+    /// How often the coordinator transmits its beacon (macBeaconOrder).
+    pub beacon_order: u8,
+    /// The length of the active portion of the superframe
+    /// (macSuperframeOrder). Ignored in non-beacon-enabled PANs.
+    pub superframe_order: u8,
+    /// Whether the device becomes the PAN coordinator of a new PAN.
+    pub pan_coordinator: bool,
+    pub battery_life_extension: bool,
+    /// Whether a Coordinator Realignment command is broadcast before a
+    /// running PAN is reconfigured.
+    pub coord_realignment: bool,
+}
+
+impl StartRequest {
+    /// Creates a request to start a non-beacon-enabled PAN on the given
+    /// channel.
+    pub fn new(pan_id: PanId<[u8; 2]>, channel: Channel, pan_coordinator: bool) -> Self {
+        Self {
+            pan_id,
+            channel,
+            channel_page: 0,
+            beacon_order: 15,
+            superframe_order: 15,
+            pan_coordinator,
+            battery_life_extension: false,
+            coord_realignment: false,
+        }
+    }
+
+    fn validate(&self, pib: &Pib) -> Result<(), StartError> {
+        if pib.short_address == 0xffff {
+            return Err(StartError::NoShortAddress);
+        }
+
+        // Only channel page 0 (O-QPSK in the 2.4 GHz band) and
+        // non-beacon-enabled PANs are supported.
+        let is_valid = self.channel_page == 0
+            && self.beacon_order == 15
+            && self.pan_id.into_u16() != 0xffff
+            // Beacons from a coordinator without short address carry its
+            // extended address.
+            && (pib.short_address != 0xfffe || pib.extended_address.is_some());
+        if !is_valid {
+            return Err(StartError::InvalidParameter);
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents an MLME-START.confirm, see IEEE 802.15.4-2020, section
+/// 8.2.12.2.
+pub struct StartConfirm {
+    pub status: Result<(), StartError>,
+}
+
+/// Starts a non-beacon-enabled PAN or reconfigures a running one, see IEEE
+/// 802.15.4-2020, section 6.3.3.
+///
+/// If requested, devices of a running PAN are informed about the new
+/// configuration by a broadcast Coordinator Realignment command. The new
+/// configuration is only applied once the command has been sent.
+pub(crate) struct StartTask<'task, RadioDriverImpl: DriverConfig> {
+    request: StartRequest,
+    pib: &'task RefCell<Pib>,
+    /// The incoming frame filter shared with the driver service.
+    frame_filter: &'task Cell<FrameFilter>,
+    /// The radio channel shared with the driver service.
+    channel: &'task Cell<Channel>,
+    /// Random number generator for CSMA-CA backoffs.
+    rng: &'task dyn BackoffRng,
+    buffer_allocator: MacBufferAllocator,
+    state: StartState<'task, RadioDriverImpl>,
+}
+
+enum StartState<'task, RadioDriverImpl: DriverConfig> {
+    Initial,
+    /// Announcing the new configuration to the devices of the running PAN.
+    SendingRealignment(DataRequestTask<'task, RadioDriverImpl>),
+    /// Waiting for the radio to switch off so that the new channel is applied.
+    SwitchingChannel,
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> StartTask<'task, RadioDriverImpl> {
+    pub fn new(
+        request: StartRequest,
+        pib: &'task RefCell<Pib>,
+        frame_filter: &'task Cell<FrameFilter>,
+        channel: &'task Cell<Channel>,
+        rng: &'task dyn BackoffRng,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        Self {
+            request,
+            pib,
+            frame_filter,
+            channel,
+            rng,
+            buffer_allocator,
+            state: StartState::Initial,
+        }
+    }
+
+    /// Builds the Coordinator Realignment command announcing the new
+    /// configuration on the current channel and PAN ID.
+    fn build_realignment(&self) -> MpduFrame {
+        let pib = self.pib.borrow();
+        let buffer_size = COORDINATOR_REALIGNMENT_FRAME_REPR
+            .min_buffer_size::<RadioDriverImpl>(COORDINATOR_REALIGNMENT_LEN)
+            .unwrap();
+        let buffer = self
+            .buffer_allocator
+            .try_allocate_buffer(buffer_size)
+            .expect("no capacity");
+
+        // Safety: The extended address was checked on entry.
+        let extended_address = ExtendedAddress::new_owned(pib.extended_address.unwrap());
+        coordinator_realignment_frame::<RadioDriverImpl>(
+            pib.pan_id,
+            extended_address,
+            self.request.pan_id,
+            ShortAddress::new_owned(pib.short_address.to_le_bytes()),
+            self.request.channel.into(),
+            0,
+            buffer,
+        )
+        .into_mpdu_frame()
+    }
+
+    /// Applies the new configuration and switches the radio off so that the
+    /// driver service picks up the new channel.
+    fn apply_configuration(mut self) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let mut pib = self.pib.borrow_mut();
+        pib.pan_id = self.request.pan_id;
+        pib.current_channel = self.request.channel;
+        pib.beacon_order = self.request.beacon_order;
+        pib.pan_coordinator = self.request.pan_coordinator;
+        pib.coordinator = true;
+        self.frame_filter.set(pib.frame_filter());
+        self.channel.set(pib.current_channel);
+        drop(pib);
+
+        self.state = StartState::SwitchingChannel;
+        MacTaskTransition::DrvSvcRequest(
+            self,
+            DrvSvcTaskOff {
+                at: Timestamp::BestEffort,
+            }
+            .into(),
+            None,
+        )
+    }
+
+    /// Releases the realignment command and applies the new configuration if
+    /// it was sent.
+    fn realignment_sent(
+        self,
+        result: DataRequestResult,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        // Safety: We allocated the buffer ourselves.
+        unsafe {
+            self.buffer_allocator
+                .deallocate_buffer(result.radio_frame.into_buffer());
+        }
+        match result.confirm.status {
+            Ok(()) => self.apply_configuration(),
+            Err(error) => MacTaskTransition::Terminated(StartConfirm {
+                status: Err(error.into()),
+            }),
+        }
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for StartTask<'_, RadioDriverImpl>
+{
+    type Result = StartConfirm;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, StartState::Initial) {
+            StartState::Initial => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                let pib = self.pib.borrow();
+                if let Err(error) = self.request.validate(&pib) {
+                    return MacTaskTransition::Terminated(StartConfirm { status: Err(error) });
+                }
+
+                // Realignment only makes sense if we are already running a
+                // PAN.
+                let realign = self.request.coord_realignment && pib.coordinator;
+                if realign && pib.extended_address.is_none() {
+                    return MacTaskTransition::Terminated(StartConfirm {
+                        status: Err(StartError::InvalidParameter),
+                    });
+                }
+                drop(pib);
+
+                if !realign {
+                    return self.apply_configuration();
+                }
+
+                let data_request = DataRequest::new(self.build_realignment());
+                self.state = StartState::SendingRealignment(DataRequestTask::new(
+                    data_request,
+                    &mut self.pib.borrow_mut(),
+                    self.rng,
+                ));
+                self.step(MacTaskEvent::Entry)
+            }
+            StartState::SendingRealignment(realignment_task) => {
+                match realignment_task.step(event) {
+                    MacTaskTransition::DrvSvcRequest(realignment_task, driver_request, _) => {
+                        self.state = StartState::SendingRealignment(realignment_task);
+                        MacTaskTransition::DrvSvcRequest(self, driver_request, None)
+                    }
+                    MacTaskTransition::TimerRequest(realignment_task, expiry) => {
+                        self.state = StartState::SendingRealignment(realignment_task);
+                        MacTaskTransition::TimerRequest(self, expiry)
+                    }
+                    MacTaskTransition::Terminated(result) => self.realignment_sent(result),
+                }
+            }
+            StartState::SwitchingChannel => {
+                debug_assert!(matches!(
+                    event,
+                    MacTaskEvent::DrvSvcResponse(DrvSvcResponse::Off(Ok(_)))
+                ));
+                MacTaskTransition::Terminated(StartConfirm { status: Ok(()) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let mut pib = Pib::default();
+        let request = StartRequest::new(PanId::new_owned([0x34, 0x12]), Channel::_15, true);
+        assert_eq!(request.validate(&pib), Err(StartError::NoShortAddress));
+
+        pib.short_address = 0x0000;
+        assert_eq!(request.validate(&pib), Ok(()));
+
+        // A coordinator without short address needs an extended address.
+        pib.short_address = 0xfffe;
+        assert_eq!(request.validate(&pib), Err(StartError::InvalidParameter));
+        pib.extended_address = Some([0x11; 8]);
+        assert_eq!(request.validate(&pib), Ok(()));
+
+        let request = StartRequest {
+            beacon_order: 14,
+            ..StartRequest::new(PanId::new_owned([0x34, 0x12]), Channel::_15, true)
+        };
+        assert_eq!(request.validate(&pib), Err(StartError::InvalidParameter));
 
-#[allow(dead_code)]
-impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, RadioDriverImpl> {
-    /// Used by PAN coordinator to initiate a new PAN or to begin using a new
-    /// configuration. Also used by a device already associated with an
-    /// existing PAN to begin using a new configuration.
-    async fn mlme_start_request(&mut self) -> Result<StartConfirm, ()> {
-        Err(())
+        let request = StartRequest::new(PanId::new_owned([0xff, 0xff]), Channel::_15, true);
+        assert_eq!(request.validate(&pib), Err(StartError::InvalidParameter));
     }
 }
@@ -0,0 +1,26 @@
+use crate::driver::{config::Channel, frame::PanId};
+
+/// The reason for a loss of synchronization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncLossReason {
+    // TODO: not supported
+    PanIdConflict,
+    // TODO: not supported
+    Realignment,
+    /// No beacon was received from the coordinator or, in TSCH mode, no frame
+    /// was exchanged with the time source within the TSCH desync timeout.
+    BeaconLost,
+}
+
+/// Represents an MLME-SYNC-LOSS.indication, see IEEE 802.15.4-2020, section
+/// 8.2.13.2.
+///
+/// TSCH devices leave TSCH mode once they lost synchronization. Frames waiting
+/// for a TSCH link are confirmed as expired.
+pub struct SyncLossIndication {
+    pub loss_reason: SyncLossReason,
+    /// The PAN ID with which the device lost synchronization.
+    pub pan_id: PanId<[u8; 2]>,
+    pub channel_number: Channel,
+    pub channel_page: u8,
+}
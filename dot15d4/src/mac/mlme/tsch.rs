@@ -0,0 +1,335 @@
+use rand_core::RngCore;
+
+use crate::{
+    driver::{constants::PHY_SHR_DURATION, time::Duration, DriverConfig},
+    mac::{
+        frame::fields::TschLinkOption,
+        neighbors::Neighbor,
+        tsch::{
+            join::TschJoinInfo,
+            schedule::{ScheduleError, TschLink, TschLinkType, TschSlotframe},
+        },
+        MacService,
+    },
+};
+
+use super::beacon::BeaconNotifyIndication;
+
+/// Represents an MLME-TSCH-MODE.request, see IEEE 802.15.4-2020, section
+/// 8.2.19.5.
+pub struct TschModeRequest {
+    /// Whether TSCH mode is entered or left.
+    pub tsch_mode: bool,
+    /// An Enhanced Beacon of the TSCH network to be joined when entering TSCH
+    /// mode, e.g. the beacon of the best time source indicated during a scan,
+    /// see [`PanDescriptor::is_preferred_time_source_over()`]. Coordinators
+    /// that start their own network don't need one.
+    ///
+    /// The beacon is consumed by the MAC sublayer.
+    ///
+    /// [`PanDescriptor::is_preferred_time_source_over()`]:
+    ///     super::scan::PanDescriptor::is_preferred_time_source_over
+    pub beacon: Option<BeaconNotifyIndication>,
+}
+
+impl TschModeRequest {
+    /// Enters or leaves TSCH mode without joining a network.
+    pub fn new(tsch_mode: bool) -> Self {
+        Self {
+            tsch_mode,
+            beacon: None,
+        }
+    }
+
+    /// Joins the TSCH network that sent the given Enhanced Beacon and enters
+    /// TSCH mode.
+    pub fn join(beacon: BeaconNotifyIndication) -> Self {
+        Self {
+            tsch_mode: true,
+            beacon: Some(beacon),
+        }
+    }
+}
+
+/// Represents an MLME-TSCH-MODE.confirm, see IEEE 802.15.4-2020, section
+/// 8.2.19.6.
+pub struct TschModeConfirm {
+    pub tsch_mode: bool,
+    pub status: Result<(), TschModeError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TschModeError {
+    /// The device is not synchronized to a TSCH network.
+    NoSync,
+}
+
+/// The operation requested by MLME-SET-SLOTFRAME or MLME-SET-LINK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleOperation {
+    Add,
+    Delete,
+    Modify,
+}
+
+/// Represents an MLME-SET-SLOTFRAME.request, see IEEE 802.15.4-2020, section
+/// 8.2.19.1.
+pub struct SetSlotframeRequest {
+    pub handle: u16,
+    pub operation: ScheduleOperation,
+    /// The number of timeslots of the slotframe. Ignored when deleting.
+    pub size: u16,
+}
+
+/// Represents an MLME-SET-SLOTFRAME.confirm, see IEEE 802.15.4-2020, section
+/// 8.2.19.2.
+pub struct SetSlotframeConfirm {
+    pub handle: u16,
+    pub status: Result<(), SetSlotframeError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetSlotframeError {
+    /// The slotframe already exists or its size is invalid.
+    InvalidParameter,
+    /// No slotframe with the given handle exists.
+    SlotframeNotFound,
+    /// The schedule cannot hold more slotframes.
+    MaxSlotframesExceeded,
+}
+
+/// Represents an MLME-SET-LINK.request, see IEEE 802.15.4-2020, section
+/// 8.2.19.3.
+pub struct SetLinkRequest {
+    pub operation: ScheduleOperation,
+    pub link_handle: u16,
+    pub slotframe_handle: u16,
+    pub timeslot: u16,
+    pub channel_offset: u16,
+    pub link_options: TschLinkOption,
+    pub link_type: TschLinkType,
+    /// The extended address of the neighbor of a dedicated link, `None` for
+    /// links to any device.
+    pub node_address: Option<[u8; 8]>,
+}
+
+/// Represents an MLME-SET-LINK.confirm, see IEEE 802.15.4-2020, section
+/// 8.2.19.4.
+pub struct SetLinkConfirm {
+    pub link_handle: u16,
+    pub slotframe_handle: u16,
+    pub status: Result<(), SetLinkError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetLinkError {
+    /// The slotframe does not exist, the link already exists or the timeslot
+    /// or channel offset are out of range.
+    InvalidParameter,
+    /// No link with the given handle exists.
+    UnknownLink,
+    /// The slotframe cannot hold more links.
+    MaxLinksExceeded,
+}
+
+impl From<ScheduleError> for SetSlotframeError {
+    fn from(value: ScheduleError) -> Self {
+        match value {
+            ScheduleError::UnknownHandle => SetSlotframeError::SlotframeNotFound,
+            ScheduleError::CapacityExceeded => SetSlotframeError::MaxSlotframesExceeded,
+            _ => SetSlotframeError::InvalidParameter,
+        }
+    }
+}
+
+impl From<ScheduleError> for SetLinkError {
+    fn from(value: ScheduleError) -> Self {
+        match value {
+            ScheduleError::UnknownHandle => SetLinkError::UnknownLink,
+            ScheduleError::CapacityExceeded => SetLinkError::MaxLinksExceeded,
+            _ => SetLinkError::InvalidParameter,
+        }
+    }
+}
+
+impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, RadioDriverImpl> {
+    /// Enters or leaves TSCH mode. Once entered, the TSCH slot task executes
+    /// the schedule with the timeslot timings of the schedule.
+    ///
+    /// Devices enter TSCH mode by joining the network of the given Enhanced
+    /// Beacon. Only coordinators may enter TSCH mode without beacon. The
+    /// beacon is ignored if TSCH mode was already entered.
+    ///
+    /// Queued frames must be confirmed by the caller once TSCH mode was left.
+    pub(crate) fn tsch_mode_request(&self, request: TschModeRequest) -> TschModeConfirm {
+        let is_synchronized = self.timeslot_timings.get().is_some();
+        let beacon = match request.beacon {
+            Some(beacon) if !request.tsch_mode || is_synchronized => {
+                self.drop_incoming_mpdu(beacon.mpdu);
+                None
+            }
+            beacon => beacon,
+        };
+
+        let status = if !request.tsch_mode {
+            self.timeslot_timings.set(None);
+            Ok(())
+        } else if let Some(beacon) = beacon {
+            self.join_tsch_network(beacon)
+        } else if self.pib.borrow().coordinator {
+            let mut schedule = self.tsch_schedule.borrow_mut();
+            if self.pib.borrow().pan_coordinator {
+                schedule.set_join_metric(0);
+            }
+            self.timeslot_timings.set(Some(schedule.timeslot_timings()));
+            Ok(())
+        } else if is_synchronized {
+            Ok(())
+        } else {
+            Err(TschModeError::NoSync)
+        };
+
+        TschModeConfirm {
+            tsch_mode: request.tsch_mode,
+            status,
+        }
+    }
+
+    /// Joins the TSCH network that sent the given Enhanced Beacon, see IEEE
+    /// 802.15.4-2020, section 6.3.6. The beacon's sender becomes the time
+    /// source.
+    ///
+    /// The PIB and the schedule remain unchanged if the beacon does not
+    /// announce a supported TSCH network.
+    fn join_tsch_network(&self, beacon: BeaconNotifyIndication) -> Result<(), TschModeError> {
+        let join_info =
+            TschJoinInfo::from_beacon(beacon.mpdu.pdu_ref_wo_fcs(), &beacon.pan_descriptor);
+        self.drop_incoming_mpdu(beacon.mpdu);
+        let join_info = join_info.ok_or(TschModeError::NoSync)?;
+
+        let mut pib = self.pib.borrow_mut();
+        let mut schedule = self.tsch_schedule.borrow_mut();
+        let hopping_sequence = join_info.hopping_sequence().unwrap_or(pib.hopping_sequence);
+        if join_info.slotframes.is_empty() {
+            // Keep the schedule set up by the upper layer.
+            schedule
+                .set_hopping_sequence(hopping_sequence)
+                .map_err(|_| TschModeError::NoSync)?;
+        } else {
+            let slotframes = join_info
+                .create_slotframes(hopping_sequence)
+                .map_err(|_| TschModeError::NoSync)?;
+            schedule.replace_slotframes(slotframes);
+        }
+
+        if let Some(channel_hopping_ie) = join_info.channel_hopping_ie() {
+            // The IE was validated while parsing the beacon.
+            let _ = pib.read_channel_hopping_ie(&channel_hopping_ie);
+        }
+        pib.pan_id = join_info.coord_pan_id;
+        self.frame_filter.set(pib.frame_filter());
+
+        // The beacon was timestamped with its RMARKER which passed the
+        // antenna the TX offset and SHR duration after the start of its slot.
+        let timings = join_info.timeslot_timings;
+        let tx_offset: Duration<RadioDriverImpl::Timer> =
+            timings.tx_offset().convert_into_rounding_up();
+        let shr_duration: Duration<RadioDriverImpl::Timer> =
+            PHY_SHR_DURATION.convert_into_rounding_up();
+        let slot_start = join_info
+            .timestamp
+            .saturating_sub((tx_offset.ticks() + shr_duration.ticks()) as u64);
+
+        schedule.set_timeslot_timings(timings);
+        schedule.synchronize(join_info.asn, slot_start);
+        schedule.set_join_metric(join_info.join_metric as u16 + 1);
+        schedule.set_time_source(Some(join_info.coord_address));
+        self.timeslot_timings.set(Some(timings));
+        Ok(())
+    }
+
+    /// Adds, deletes or modifies a slotframe of the TSCH schedule. Slotframes
+    /// hop over the hopping sequence of the PIB.
+    pub(crate) fn set_slotframe_request(
+        &self,
+        request: &SetSlotframeRequest,
+    ) -> SetSlotframeConfirm {
+        let mut schedule = self.tsch_schedule.borrow_mut();
+        let status = match request.operation {
+            ScheduleOperation::Add | ScheduleOperation::Modify if request.size == 0 => {
+                Err(SetSlotframeError::InvalidParameter)
+            }
+            ScheduleOperation::Add => schedule
+                .add_slotframe(TschSlotframe::new(
+                    request.handle,
+                    request.size,
+                    self.pib.borrow().hopping_sequence,
+                ))
+                .map_err(SetSlotframeError::from),
+            ScheduleOperation::Delete => schedule
+                .remove_slotframe(request.handle)
+                .map_err(SetSlotframeError::from),
+            ScheduleOperation::Modify => schedule
+                .slotframe_mut(request.handle)
+                .ok_or(SetSlotframeError::SlotframeNotFound)
+                .and_then(|slotframe| {
+                    slotframe
+                        .set_size(request.size)
+                        .map_err(SetSlotframeError::from)
+                }),
+        };
+
+        SetSlotframeConfirm {
+            handle: request.handle,
+            status,
+        }
+    }
+
+    /// Adds, deletes or modifies a link of the TSCH schedule. A modified link
+    /// is kept unchanged if the new parameters are invalid.
+    pub(crate) fn set_link_request(&self, request: &SetLinkRequest) -> SetLinkConfirm {
+        let mut schedule = self.tsch_schedule.borrow_mut();
+        let link = || {
+            TschLink::new(
+                request.link_handle,
+                request.timeslot,
+                request.channel_offset,
+                request.link_options,
+                request.link_type,
+                request.node_address.map(Neighbor::new),
+            )
+        };
+
+        let status = if schedule.slotframe_mut(request.slotframe_handle).is_none() {
+            Err(SetLinkError::InvalidParameter)
+        } else {
+            match request.operation {
+                ScheduleOperation::Add => schedule
+                    .add_link(request.slotframe_handle, link())
+                    .map_err(SetLinkError::from),
+                ScheduleOperation::Delete => schedule
+                    .remove_link(request.slotframe_handle, request.link_handle)
+                    .map(|_| ())
+                    .map_err(SetLinkError::from),
+                ScheduleOperation::Modify => schedule
+                    .remove_link(request.slotframe_handle, request.link_handle)
+                    .map_err(SetLinkError::from)
+                    .and_then(|previous_link| {
+                        schedule
+                            .add_link(request.slotframe_handle, link())
+                            .map_err(|err| {
+                                // Safety: We just removed the previous link.
+                                let _ = schedule.add_link(request.slotframe_handle, previous_link);
+                                SetLinkError::from(err)
+                            })
+                    }),
+            }
+        };
+
+        SetLinkConfirm {
+            link_handle: request.link_handle,
+            slotframe_handle: request.slotframe_handle,
+            status,
+        }
+    }
+}
@@ -1,26 +1,48 @@
+mod csma;
+mod devices;
+mod duplicates;
+mod indirect;
 mod mcps;
 mod mlme;
 mod neighbors;
 mod pib;
 pub mod primitives;
 mod task;
+mod timer;
 mod tsch;
 
 pub use dot15d4_frame as frame;
+pub use indirect::PendingTransactions;
 
-use core::{cell::RefCell, marker::PhantomData};
+use core::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    num::NonZero,
+};
 
 use paste::paste;
 use rand_core::RngCore;
 
 use crate::{
     driver::{
-        constants::PHY_MAX_PACKET_SIZE_127, frame::FrameType, DriverConfig, DriverRequestSender,
-        DRIVER_CHANNEL_CAPACITY, MAX_DRIVER_OVERHEAD,
+        alarm::Alarm,
+        config::Channel as RadioChannel,
+        constants::PHY_MAX_PACKET_SIZE_127,
+        frame::{
+            FrameFilter, FrameType, RadioFrame, RadioFrameRepr, RadioFrameSized, RadioFrameUnsized,
+        },
+        tasks::{AckMpdu, RxInfo},
+        time::Duration,
+        DriverConfig, DriverRequestSender, RadioTimerApi, DRIVER_CHANNEL_CAPACITY,
+        MAX_DRIVER_OVERHEAD,
+    },
+    mac::{
+        devices::{AssociatedDevices, ShortAddressAllocator},
+        mcps::data::{DataIndicationResult, DataRequestResult, PromiscuousIndication},
     },
-    mac::mcps::data::DataRequestResult,
     util::{
         allocator::{BufferAllocator, IntoBuffer},
+        frame::Frame,
         sync::{
             channel::{Channel, Receiver, Sender},
             mutex::Mutex,
@@ -30,11 +52,35 @@ use crate::{
 };
 
 use self::{
-    frame::mpdu::MpduFrame,
+    csma::BackoffRng,
+    duplicates::DuplicateCache,
+    frame::{
+        fields::TschTimeslotTimings,
+        mpdu::{CommandId, MpduFrame},
+    },
+    indirect::IndirectTxTask,
     mcps::data::{DataIndication, DataIndicationTask, DataRequestTask},
+    mlme::{
+        associate::{AssociateResponseTask, AssociateTask, AssociationResponseSlot},
+        beacon::{BeaconRequest, BeaconTask, BeaconType, EnhancedBeaconTask},
+        disassociate::DisassociateTask,
+        parse_command,
+        poll::{PollSlot, PollTask},
+        scan::{PanDescriptor, PanDescriptorList, ScanTask},
+        start::StartTask,
+        sync_loss::{SyncLossIndication, SyncLossReason},
+    },
+    neighbors::{NeighborAddress, NeighborTable},
     pib::Pib,
-    primitives::{MacIndication, MacRequest},
+    primitives::{BeaconNotifyIndication, MacConfirm, MacIndication, MacRequest},
     task::*,
+    timer::MacTaskTimers,
+    tsch::{
+        queue::MacTschTxQueue,
+        schedule::MacTschSchedule,
+        slot::{TschSlotResult, TschSlotTask},
+        AbsoluteSlotNumber,
+    },
 };
 
 // TODO: Make allocator and channel capacities and the number of upper layer
@@ -45,7 +91,7 @@ const UL_MAX_RX_TOKENS: usize = 1;
 
 /// The max number of UL Tx tokens that may be handed out in parallel.
 /// Note: Each Rx token requires an accompanying Tx token to be allocated.
-const UL_MAX_TX_TOKENS: usize = 1 + UL_MAX_RX_TOKENS;
+pub const UL_MAX_TX_TOKENS: usize = 1 + UL_MAX_RX_TOKENS;
 
 /// The number of additional messages that may be pending.
 /// Note: 1 is currently the min number supported.
@@ -58,11 +104,12 @@ const UL_MSG_BACKLOG: usize = 1;
 ///       control channels towards applications directly.
 const UL_NUM_CLIENTS: usize = 1;
 
-pub type MacRequestChannel = Channel<(), MacRequest, (), UL_MAX_TX_TOKENS, UL_MSG_BACKLOG, 1>;
+pub type MacRequestChannel =
+    Channel<(), MacRequest, MacConfirm, UL_MAX_TX_TOKENS, UL_MSG_BACKLOG, 1>;
 pub type MacRequestReceiver<'channel> =
-    Receiver<'channel, (), MacRequest, (), UL_MAX_TX_TOKENS, UL_MSG_BACKLOG, 1>;
+    Receiver<'channel, (), MacRequest, MacConfirm, UL_MAX_TX_TOKENS, UL_MSG_BACKLOG, 1>;
 pub type MacRequestSender<'channel> =
-    Sender<'channel, (), MacRequest, (), UL_MAX_TX_TOKENS, UL_MSG_BACKLOG, 1>;
+    Sender<'channel, (), MacRequest, MacConfirm, UL_MAX_TX_TOKENS, UL_MSG_BACKLOG, 1>;
 
 pub type MacIndicationChannel =
     Channel<(), MacIndication, (), UL_MAX_RX_TOKENS, UL_MSG_BACKLOG, UL_NUM_CLIENTS>;
@@ -75,10 +122,22 @@ pub type MacIndicationSender<'channel> =
 /// use of the driver's pipelining capability.
 const MAC_NUM_PARALLEL_INDICATION_TASKS: usize = UL_MAX_RX_TOKENS + 1;
 const MAC_NUM_PARALLEL_REQUEST_TASKS: usize = UL_MAX_TX_TOKENS;
+/// The number of tasks the MAC service runs on its own behalf: One task
+/// answering Beacon Request commands, one task sending periodic Enhanced
+/// Beacons and one task executing the TSCH schedule.
+const MAC_NUM_INTERNAL_TASKS: usize = 3;
+const MAC_NUM_TASKS: usize =
+    MAC_NUM_PARALLEL_REQUEST_TASKS + MAC_NUM_PARALLEL_INDICATION_TASKS + MAC_NUM_INTERNAL_TASKS;
+/// The index of the internal task sending beacons.
+const MAC_BEACON_TASK_INDEX: usize =
+    MAC_NUM_PARALLEL_REQUEST_TASKS + MAC_NUM_PARALLEL_INDICATION_TASKS;
+/// The index of the internal task sending periodic Enhanced Beacons.
+const MAC_ENHANCED_BEACON_TASK_INDEX: usize = MAC_BEACON_TASK_INDEX + 1;
+/// The index of the internal task executing the TSCH schedule.
+const MAC_TSCH_TASK_INDEX: usize = MAC_ENHANCED_BEACON_TASK_INDEX + 1;
 const _: () = {
     assert!(
-        DRIVER_CHANNEL_CAPACITY
-            == MAC_NUM_PARALLEL_INDICATION_TASKS + MAC_NUM_PARALLEL_REQUEST_TASKS,
+        DRIVER_CHANNEL_CAPACITY == MAC_NUM_TASKS,
         "driver channel capacity does not match number of MAC tasks"
     )
 };
@@ -87,15 +146,18 @@ const _: () = {
 /// Buffers are allocated by:
 /// - tx token
 /// - indication task
-/// - driver service (2 pre-allocated buffers for RX/TX ACKs)
+/// - internal task
+/// - driver service (3 pre-allocated buffers for RX/TX ACKs and TX Enh-ACKs)
 ///
 /// Required buffers:
 /// - one buffer per max outstanding upper layer tx token (= max request tasks)
 /// - one buffer per indication task
+/// - one buffer per internal task
 /// - one pre-allocated buffer for outgoing ACKs
 /// - one pre-allocated buffer for incoming ACKs
+/// - one pre-allocated buffer for outgoing Enh-ACKs
 pub const MAC_NUM_REQUIRED_BUFFERS: usize =
-    UL_MAX_TX_TOKENS + MAC_NUM_PARALLEL_INDICATION_TASKS + 2;
+    UL_MAX_TX_TOKENS + MAC_NUM_PARALLEL_INDICATION_TASKS + MAC_NUM_INTERNAL_TASKS + 3;
 pub const MAC_BUFFER_SIZE: usize = PHY_MAX_PACKET_SIZE_127 + MAX_DRIVER_OVERHEAD;
 
 pub type MacBufferAllocator = BufferAllocator;
@@ -108,6 +170,7 @@ macro_rules! mac_svc_tasks {
                 $($mac_task([<$mac_task Task>]<'task, RadioDriverImpl>)),*
             }
 
+            #[allow(clippy::large_enum_variant)]
             enum MacSvcTaskResult<'task, RadioDriverImpl: DriverConfig> {
                 $($mac_task(<[<$mac_task Task>]<'task, RadioDriverImpl> as MacTask<RadioDriverImpl::Timer>>::Result)),*
             }
@@ -137,6 +200,9 @@ macro_rules! mac_svc_tasks {
                             let task_result = task_result.map(|task_result| MacSvcTaskResult::$mac_task(task_result)) ;
                             MacTaskTransition::DrvSvcRequest(updated_task, driver_request, task_result)
                         },
+                        MacTaskTransition::TimerRequest(updated_task, expiry) => {
+                            MacTaskTransition::TimerRequest(MacSvcTask::$mac_task(updated_task), expiry)
+                        },
                         MacTaskTransition::Terminated(task_result) => {
                             let task_result = MacSvcTaskResult::$mac_task(task_result);
                             MacTaskTransition::Terminated(task_result.into())
@@ -148,7 +214,20 @@ macro_rules! mac_svc_tasks {
     }
 }
 
-mac_svc_tasks!(DataRequest, DataIndication);
+mac_svc_tasks!(
+    DataRequest,
+    DataIndication,
+    Scan,
+    Associate,
+    AssociateResponse,
+    Disassociate,
+    Start,
+    Beacon,
+    EnhancedBeacon,
+    IndirectTx,
+    Poll,
+    TschSlot
+);
 
 #[allow(dead_code)]
 /// A structure exposing MAC sublayer services such as MLME and MCPS. This runs
@@ -169,17 +248,74 @@ pub struct MacService<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> {
     driver_request_sender: DriverRequestSender<'svc>,
     /// PAN Information Base
     pib: RefCell<Pib>,
+    /// Incoming frame filter shared with the driver service. Derived from the
+    /// PIB.
+    frame_filter: &'svc Cell<FrameFilter>,
+    /// The radio channel shared with the driver service. Follows the PIB.
+    channel: &'svc Cell<RadioChannel>,
+    /// The channel scanned by an ongoing scan. Indication tasks receive on it
+    /// instead of the default channel.
+    scan_channel: Cell<Option<RadioChannel>>,
+    /// The TX power shared with the driver service. Follows the PIB.
+    tx_power: &'svc Cell<i8>,
+    /// PAN descriptors collected from incoming beacons while a scan is in
+    /// progress.
+    pan_descriptors: RefCell<Option<PanDescriptorList>>,
+    /// Hands the Association Response over to an ongoing association.
+    association_response: Cell<AssociationResponseSlot>,
+    /// Devices associated with this coordinator.
+    associated_devices: RefCell<AssociatedDevices>,
+    /// Allocates short addresses to associating devices.
+    short_address_allocator: RefCell<&'svc mut dyn ShortAddressAllocator>,
+    /// Frames waiting to be polled by devices, shared with the driver
+    /// service.
+    pending_transactions: &'svc RefCell<PendingTransactions>,
+    /// Hands data polled from a coordinator over to an ongoing poll.
+    poll_slot: Cell<PollSlot>,
+    /// Recently received frames, to recognize retransmissions.
+    duplicate_cache: RefCell<DuplicateCache>,
+    /// Neighbors with the link statistics of frames exchanged with them.
+    neighbors: RefCell<NeighborTable>,
+    /// The recipients of ongoing data requests, indexed by request task.
+    tx_neighbors: [Cell<Option<NeighborAddress>>; MAC_NUM_PARALLEL_REQUEST_TASKS],
+    /// Set when a Beacon Request command was received and a beacon is yet to
+    /// be sent.
+    beacon_requested: Cell<bool>,
+    /// The TSCH schedule, executed while TSCH mode is on.
+    tsch_schedule: RefCell<MacTschSchedule>,
+    /// Frames waiting for a TSCH link.
+    tsch_tx_queue: RefCell<MacTschTxQueue>,
+    /// The timeslot timings shared with the driver service. Set while TSCH
+    /// mode is on.
+    timeslot_timings: &'svc Cell<Option<TschTimeslotTimings>>,
+    /// The MAC service's share of the radio timer alarm, see
+    /// [`MacTaskTimers`].
+    alarm: Alarm<'svc, RadioDriverImpl::Timer>,
 }
 
 impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, RadioDriverImpl> {
     /// Creates a new [`MacService<Rng, U, Timer, R>`].
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         rng: &'svc mut Mutex<Rng>,
         buffer_allocator: MacBufferAllocator,
         request_receiver: MacRequestReceiver<'svc>,
         indication_sender: MacIndicationSender<'svc>,
         driver_request_sender: DriverRequestSender<'svc>,
+        frame_filter: &'svc Cell<FrameFilter>,
+        channel: &'svc Cell<RadioChannel>,
+        tx_power: &'svc Cell<i8>,
+        pending_transactions: &'svc RefCell<PendingTransactions>,
+        timeslot_timings: &'svc Cell<Option<TschTimeslotTimings>>,
+        alarm: Alarm<'svc, RadioDriverImpl::Timer>,
+        short_address_allocator: &'svc mut dyn ShortAddressAllocator,
     ) -> Self {
+        let mut pib = Pib::default();
+        pib.randomize_sequence_numbers(rng.next_u32());
+        frame_filter.set(pib.frame_filter());
+        channel.set(pib.current_channel);
+        tx_power.set(pib.tx_power);
+        let neighbors = NeighborTable::new(pib.neighbor_eviction_policy);
         Self {
             radio: PhantomData,
             rng,
@@ -187,14 +323,33 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
             request_receiver,
             indication_sender,
             driver_request_sender,
-            pib: RefCell::new(Pib::default()),
+            pib: RefCell::new(pib),
+            frame_filter,
+            channel,
+            tx_power,
+            scan_channel: Cell::new(None),
+            pan_descriptors: RefCell::new(None),
+            association_response: Cell::new(AssociationResponseSlot::Idle),
+            associated_devices: RefCell::new(AssociatedDevices::new()),
+            short_address_allocator: RefCell::new(short_address_allocator),
+            pending_transactions,
+            poll_slot: Cell::new(PollSlot::Idle),
+            duplicate_cache: RefCell::new(DuplicateCache::new()),
+            neighbors: RefCell::new(neighbors),
+            tx_neighbors: [const { Cell::new(None) }; MAC_NUM_PARALLEL_REQUEST_TASKS],
+            beacon_requested: Cell::new(false),
+            tsch_schedule: RefCell::new(MacTschSchedule::new()),
+            tsch_tx_queue: RefCell::new(MacTschTxQueue::new()),
+            timeslot_timings,
+            alarm,
         }
     }
 
     /// Run the main event loop used by the MAC sublayer for its operation.
     ///
-    /// The loop waits until receiving a MCPS-DATA request from the upper layer.
-    /// It will then instantiate the corresponding state machine and start
+    /// The loop waits until receiving a request from the upper layer.
+    /// MLME-GET and MLME-SET requests are confirmed immediately. For other
+    /// requests it will instantiate the corresponding state machine and start
     /// driving it. The state machine will produce driver service requests which
     /// will be passed on to the driver service. Whenever the driver service
     /// returns a response it will be used to drive the corresponding state
@@ -209,9 +364,11 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
         //
         // We need an additional indication background tasks so that we can
         // efficiently use the driver service's pipelining capability.
-        let mut mac_svc_tasks: [Option<MacSvcTask<RadioDriverImpl>>;
-            MAC_NUM_PARALLEL_REQUEST_TASKS + MAC_NUM_PARALLEL_INDICATION_TASKS] =
-            [const { None }; MAC_NUM_PARALLEL_REQUEST_TASKS + MAC_NUM_PARALLEL_INDICATION_TASKS];
+        //
+        // Internal MAC tasks, e.g. to answer Beacon Request commands, use the
+        // remaining indices.
+        let mut mac_svc_tasks: [Option<MacSvcTask<RadioDriverImpl>>; MAC_NUM_TASKS] =
+            [const { None }; MAC_NUM_TASKS];
 
         // Outstanding driver requests will be pushed to this vector and polled
         // for responses.
@@ -229,13 +386,15 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
         let mut outstanding_mac_requests: [Option<ResponseToken>; MAC_NUM_PARALLEL_REQUEST_TASKS] =
             [const { None }; MAC_NUM_PARALLEL_REQUEST_TASKS];
 
-        let first_mac_indication_task_index =
-            mac_svc_tasks.len() - MAC_NUM_PARALLEL_INDICATION_TASKS;
+        // Timers requested by MAC tasks, e.g. for CSMA-CA backoffs.
+        let mut task_timers: MacTaskTimers<RadioDriverImpl::Timer, MAC_NUM_TASKS> =
+            MacTaskTimers::new(&self.alarm);
+
         self.create_indication_tasks(
-            first_mac_indication_task_index,
             &mut mac_svc_tasks,
             &mut driver_msg_slot_to_task_index,
             &mut outstanding_driver_requests,
+            &mut task_timers,
         );
 
         let mut consumer_token = self
@@ -247,21 +406,136 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
             match select(
                 self.request_receiver
                     .wait_for_request(&mut consumer_token, &()),
-                self.driver_request_sender
-                    .wait_for_response(&mut outstanding_driver_requests),
+                select(
+                    self.driver_request_sender
+                        .wait_for_response(&mut outstanding_driver_requests),
+                    task_timers.wait_for_expiry(),
+                ),
             )
             .await
             {
                 // Upper layer: A MAC request was received. Create the corresponding task and kick it off.
                 Either::First((mac_request_response_token, mac_request)) => {
+                    // PIB requests don't involve the driver and are confirmed
+                    // immediately.
+                    let mac_request = match mac_request {
+                        MacRequest::MlmeGetRequest(attribute) => {
+                            let result = self.mlme_get_request(&attribute).await;
+                            self.request_receiver
+                                .received(mac_request_response_token, MacConfirm::MlmeGet(result));
+                            continue;
+                        }
+                        MacRequest::MlmeSetRequest(attribute) => {
+                            let result = self.mlme_set_request(&attribute).await;
+                            self.request_receiver
+                                .received(mac_request_response_token, MacConfirm::MlmeSet(result));
+                            // The new attribute value may enable periodic
+                            // Enhanced Beacons.
+                            self.schedule_internal_tasks(
+                                &mut mac_svc_tasks,
+                                &mut driver_msg_slot_to_task_index,
+                                &mut outstanding_driver_requests,
+                                &mut task_timers,
+                            );
+                            continue;
+                        }
+                        MacRequest::NeighborsRequest => {
+                            let neighbors = self.neighbors.borrow().clone();
+                            self.request_receiver.received(
+                                mac_request_response_token,
+                                MacConfirm::Neighbors(neighbors),
+                            );
+                            continue;
+                        }
+                        MacRequest::McpsPurgeRequest(purge_request) => {
+                            let (confirm, purged_task_index) = self.purge_request(&purge_request);
+                            // The purged task confirms its data request.
+                            if let Some(purged_task_index) = purged_task_index {
+                                task_timers.expire(purged_task_index);
+                            }
+                            self.request_receiver.received(
+                                mac_request_response_token,
+                                MacConfirm::McpsPurge(confirm),
+                            );
+                            continue;
+                        }
+                        MacRequest::MlmeSetSlotframeRequest(set_slotframe_request) => {
+                            let confirm = self.set_slotframe_request(&set_slotframe_request);
+                            self.request_receiver.received(
+                                mac_request_response_token,
+                                MacConfirm::MlmeSetSlotframe(confirm),
+                            );
+                            // The next active slot may have changed.
+                            task_timers.expire(MAC_TSCH_TASK_INDEX);
+                            continue;
+                        }
+                        MacRequest::MlmeSetLinkRequest(set_link_request) => {
+                            let confirm = self.set_link_request(&set_link_request);
+                            self.request_receiver.received(
+                                mac_request_response_token,
+                                MacConfirm::MlmeSetLink(confirm),
+                            );
+                            task_timers.expire(MAC_TSCH_TASK_INDEX);
+                            continue;
+                        }
+                        MacRequest::MlmeTschModeRequest(tsch_mode_request) => {
+                            let confirm = self.tsch_mode_request(tsch_mode_request);
+                            self.request_receiver.received(
+                                mac_request_response_token,
+                                MacConfirm::MlmeTschMode(confirm),
+                            );
+                            if self.timeslot_timings.get().is_none() {
+                                // The TSCH task terminates once woken.
+                                task_timers.expire(MAC_TSCH_TASK_INDEX);
+                                for (task_index, result) in self.expire_tsch_tx_queue() {
+                                    self.handle_request_task_result(
+                                        MacSvcTaskResult::DataRequest(result),
+                                        task_index,
+                                        outstanding_mac_requests[task_index].take().unwrap(),
+                                    );
+                                }
+                            }
+                            self.schedule_internal_tasks(
+                                &mut mac_svc_tasks,
+                                &mut driver_msg_slot_to_task_index,
+                                &mut outstanding_driver_requests,
+                                &mut task_timers,
+                            );
+                            continue;
+                        }
+                        // While TSCH mode is on, frames are sent in the TX
+                        // links of the TSCH schedule.
+                        MacRequest::McpsDataRequest(data_request)
+                            if self.timeslot_timings.get().is_some() =>
+                        {
+                            let task_index = mac_request_response_token.message_slot() as usize;
+                            if let Err(result) =
+                                self.enqueue_tsch_data_request(data_request, task_index)
+                            {
+                                self.handle_request_task_result(
+                                    MacSvcTaskResult::DataRequest(result),
+                                    task_index,
+                                    mac_request_response_token,
+                                );
+                            } else {
+                                outstanding_mac_requests[task_index] =
+                                    Some(mac_request_response_token);
+                            }
+                            continue;
+                        }
+                        mac_request => mac_request,
+                    };
+
                     let mac_request_task_index = mac_request_response_token.message_slot() as usize;
                     outstanding_mac_requests[mac_request_task_index] =
                         Some(mac_request_response_token);
-                    let mac_request_task = self.create_request_task(mac_request);
+                    let mac_request_task =
+                        self.create_request_task(mac_request, mac_request_task_index);
                     self.step_task(
                         &mut mac_svc_tasks,
                         &mut driver_msg_slot_to_task_index,
                         &mut outstanding_driver_requests,
+                        &mut task_timers,
                         Some(&mut outstanding_mac_requests),
                         mac_request_task_index,
                         mac_request_task,
@@ -269,10 +543,10 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
                     );
                 }
                 // Driver response
-                Either::Second(MatchingResponse {
+                Either::Second(Either::First(MatchingResponse {
                     response: driver_response,
                     msg_slot: driver_msg_slot,
-                }) => {
+                })) => {
                     let mac_svc_task_index =
                         driver_msg_slot_to_task_index[driver_msg_slot as usize];
                     let mac_task_event = MacTaskEvent::DrvSvcResponse(driver_response);
@@ -282,32 +556,49 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
                         &mut mac_svc_tasks,
                         &mut driver_msg_slot_to_task_index,
                         &mut outstanding_driver_requests,
+                        &mut task_timers,
                         Some(&mut outstanding_mac_requests),
                         mac_svc_task_index,
                         mac_svc_task,
                         mac_task_event,
                     );
                 }
+                // Timer expired
+                Either::Second(Either::Second(mac_svc_task_index)) => {
+                    let mac_svc_task = mac_svc_tasks[mac_svc_task_index].take().unwrap();
+
+                    self.step_task(
+                        &mut mac_svc_tasks,
+                        &mut driver_msg_slot_to_task_index,
+                        &mut outstanding_driver_requests,
+                        &mut task_timers,
+                        Some(&mut outstanding_mac_requests),
+                        mac_svc_task_index,
+                        mac_svc_task,
+                        MacTaskEvent::TimerExpired,
+                    );
+                }
             };
         }
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn step_task<'tasks>(
-        &self,
+    fn step_task<'tasks, const NUM_TASKS: usize>(
+        &'tasks self,
         mac_svc_tasks: &mut [Option<MacSvcTask<'tasks, RadioDriverImpl>>],
         driver_msg_slot_to_task_index: &mut [usize],
         outstanding_driver_requests: &mut heapless::Vec<
             PollingResponseToken,
             DRIVER_CHANNEL_CAPACITY,
         >,
+        task_timers: &mut MacTaskTimers<RadioDriverImpl::Timer, NUM_TASKS>,
         outstanding_mac_requests: Option<&mut [Option<ResponseToken>]>,
         mac_svc_task_index: usize,
         mac_svc_task: MacSvcTask<'tasks, RadioDriverImpl>,
         event: MacTaskEvent,
     ) {
         let is_mac_request = mac_svc_task_index < MAC_NUM_PARALLEL_REQUEST_TASKS;
-        let is_mac_indication = !is_mac_request;
+        let is_mac_indication = !is_mac_request && mac_svc_task_index < MAC_BEACON_TASK_INDEX;
 
         let task_result = match mac_svc_task.step(event) {
             MacTaskTransition::DrvSvcRequest(updated_task, driver_request, intermediate_result) => {
@@ -327,20 +618,26 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
                 mac_svc_tasks[mac_svc_task_index] = Some(updated_task);
                 debug_assert!({
                     if intermediate_result.is_some() {
-                        // Only indications may produce intermediate results.
-                        is_mac_indication
+                        // Only indications and the TSCH task may produce
+                        // intermediate results.
+                        is_mac_indication || mac_svc_task_index == MAC_TSCH_TASK_INDEX
                     } else {
                         true
                     }
                 });
                 intermediate_result
             }
+            MacTaskTransition::TimerRequest(updated_task, expiry) => {
+                task_timers.start(mac_svc_task_index, expiry);
+                mac_svc_tasks[mac_svc_task_index] = Some(updated_task);
+                None
+            }
             MacTaskTransition::Terminated(task_result) => {
                 #[cfg(feature = "rtos-trace")]
                 rtos_trace::trace::task_exec_end();
 
-                // Only MAC requests may terminate.
-                debug_assert!(is_mac_request);
+                // Indication tasks never terminate.
+                debug_assert!(!is_mac_indication);
 
                 Some(task_result)
             }
@@ -350,37 +647,134 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
             if is_mac_request {
                 self.handle_request_task_result(
                     task_result,
+                    mac_svc_task_index,
                     outstanding_mac_requests.unwrap()[mac_svc_task_index]
                         .take()
                         .unwrap(),
                 );
-            } else {
-                self.handle_indication_task_result(task_result);
+            } else if is_mac_indication {
+                if let Some(woken_task_index) = self.handle_indication_task_result(task_result) {
+                    task_timers.expire(woken_task_index);
+                }
+            } else if let Some(woken_task_index) =
+                self.handle_internal_task_result(task_result, outstanding_mac_requests)
+            {
+                task_timers.expire(woken_task_index);
             }
         }
+
+        self.schedule_internal_tasks(
+            mac_svc_tasks,
+            driver_msg_slot_to_task_index,
+            outstanding_driver_requests,
+            task_timers,
+        );
     }
 
-    fn create_indication_tasks<'tasks>(
-        &self,
-        first_mac_indication_task_index: usize,
+    /// Starts sending a beacon if one was requested and no other beacon is
+    /// currently being sent. Starts sending periodic Enhanced Beacons if they
+    /// are enabled and not yet being sent. Starts executing the TSCH schedule
+    /// once TSCH mode is on.
+    ///
+    /// While TSCH mode is on, Enhanced Beacons are only sent on advertising
+    /// links and Beacon Request commands are not answered.
+    fn schedule_internal_tasks<'tasks, const NUM_TASKS: usize>(
+        &'tasks self,
         mac_svc_tasks: &mut [Option<MacSvcTask<'tasks, RadioDriverImpl>>],
         driver_msg_slot_to_task_index: &mut [usize],
         outstanding_driver_requests: &mut heapless::Vec<
             PollingResponseToken,
             DRIVER_CHANNEL_CAPACITY,
         >,
-    ) where
-        'svc: 'tasks,
-    {
-        for mac_indication_task_index in first_mac_indication_task_index..mac_svc_tasks.len() {
+        task_timers: &mut MacTaskTimers<RadioDriverImpl::Timer, NUM_TASKS>,
+    ) {
+        if self.timeslot_timings.get().is_some() {
+            self.beacon_requested.set(false);
+            if mac_svc_tasks[MAC_TSCH_TASK_INDEX].is_none() {
+                let tsch_task = MacSvcTask::TschSlot(TschSlotTask::new(
+                    &self.pib,
+                    &self.tsch_schedule,
+                    &self.tsch_tx_queue,
+                    self.timeslot_timings,
+                    self.buffer_allocator,
+                ));
+                self.step_task(
+                    mac_svc_tasks,
+                    driver_msg_slot_to_task_index,
+                    outstanding_driver_requests,
+                    task_timers,
+                    None,
+                    MAC_TSCH_TASK_INDEX,
+                    tsch_task,
+                    MacTaskEvent::Entry,
+                );
+            }
+            return;
+        }
+
+        if mac_svc_tasks[MAC_BEACON_TASK_INDEX].is_none() && self.beacon_requested.take() {
+            let beacon_task = MacSvcTask::Beacon(BeaconTask::new(
+                BeaconRequest::new(BeaconType::Beacon),
+                &self.pib,
+                &*self.rng,
+                self.buffer_allocator,
+            ));
+            self.step_task(
+                mac_svc_tasks,
+                driver_msg_slot_to_task_index,
+                outstanding_driver_requests,
+                task_timers,
+                None,
+                MAC_BEACON_TASK_INDEX,
+                beacon_task,
+                MacTaskEvent::Entry,
+            );
+        }
+
+        let enhanced_beacons_enabled = {
+            let pib = self.pib.borrow();
+            pib.coordinator && pib.enhanced_beacon_interval().is_some()
+        };
+        if mac_svc_tasks[MAC_ENHANCED_BEACON_TASK_INDEX].is_none() && enhanced_beacons_enabled {
+            let enhanced_beacon_task = MacSvcTask::EnhancedBeacon(EnhancedBeaconTask::new(
+                &self.pib,
+                &*self.rng,
+                self.buffer_allocator,
+            ));
+            self.step_task(
+                mac_svc_tasks,
+                driver_msg_slot_to_task_index,
+                outstanding_driver_requests,
+                task_timers,
+                None,
+                MAC_ENHANCED_BEACON_TASK_INDEX,
+                enhanced_beacon_task,
+                MacTaskEvent::Entry,
+            );
+        }
+    }
+
+    fn create_indication_tasks<'tasks, const NUM_TASKS: usize>(
+        &'tasks self,
+        mac_svc_tasks: &mut [Option<MacSvcTask<'tasks, RadioDriverImpl>>],
+        driver_msg_slot_to_task_index: &mut [usize],
+        outstanding_driver_requests: &mut heapless::Vec<
+            PollingResponseToken,
+            DRIVER_CHANNEL_CAPACITY,
+        >,
+        task_timers: &mut MacTaskTimers<RadioDriverImpl::Timer, NUM_TASKS>,
+    ) {
+        for mac_indication_task_index in MAC_NUM_PARALLEL_REQUEST_TASKS..MAC_BEACON_TASK_INDEX {
             let mac_indication_task =
                 MacSvcTask::DataIndication(DataIndicationTask::<'tasks, RadioDriverImpl>::new(
+                    &self.scan_channel,
                     self.buffer_allocator,
                 ));
             self.step_task(
                 mac_svc_tasks,
                 driver_msg_slot_to_task_index,
                 outstanding_driver_requests,
+                task_timers,
                 None,
                 mac_indication_task_index,
                 mac_indication_task,
@@ -389,98 +783,667 @@ impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, Ra
         }
     }
 
-    fn create_request_task(&self, mac_request: MacRequest) -> MacSvcTask<'_, RadioDriverImpl> {
+    fn create_request_task(
+        &self,
+        mac_request: MacRequest,
+        task_index: usize,
+    ) -> MacSvcTask<'_, RadioDriverImpl> {
         match mac_request {
+            // Only coordinators send frames indirectly, see IEEE
+            // 802.15.4-2020, section 8.3.2.
+            MacRequest::McpsDataRequest(data_request)
+                if data_request.is_indirect_tx() && self.pib.borrow().coordinator =>
+            {
+                self.tx_neighbors[task_index].set(self.data_request_neighbor(&data_request));
+                let (device_address, short_address) = self.indirect_tx_addresses(&data_request);
+                MacSvcTask::IndirectTx(
+                    IndirectTxTask::new(
+                        data_request,
+                        device_address,
+                        short_address,
+                        task_index,
+                        &self.pib,
+                        self.pending_transactions,
+                        &*self.rng,
+                    )
+                    .purgeable(),
+                )
+            }
             MacRequest::McpsDataRequest(data_request) => {
-                MacSvcTask::DataRequest(DataRequestTask::new(data_request))
+                self.tx_neighbors[task_index].set(self.data_request_neighbor(&data_request));
+                MacSvcTask::DataRequest(DataRequestTask::new(
+                    data_request,
+                    &mut self.pib.borrow_mut(),
+                    &*self.rng,
+                ))
             }
-            MacRequest::MlmeBeaconRequest(_) => todo!(),
-            MacRequest::MlmeSetRequest(_) => todo!(),
+            MacRequest::MlmeScanRequest(scan_request) => MacSvcTask::Scan(ScanTask::new(
+                scan_request,
+                &self.pib,
+                &self.scan_channel,
+                &self.pan_descriptors,
+                &*self.rng,
+                self.buffer_allocator,
+            )),
+            MacRequest::MlmeAssociateRequest(associate_request) => {
+                MacSvcTask::Associate(AssociateTask::new(
+                    associate_request,
+                    &self.pib,
+                    self.frame_filter,
+                    self.channel,
+                    &self.association_response,
+                    &*self.rng,
+                    self.buffer_allocator,
+                ))
+            }
+            MacRequest::MlmeAssociateResponse(associate_response) => {
+                MacSvcTask::AssociateResponse(AssociateResponseTask::new(
+                    associate_response,
+                    task_index,
+                    &self.pib,
+                    &self.associated_devices,
+                    self.pending_transactions,
+                    &*self.rng,
+                    self.buffer_allocator,
+                ))
+            }
+            MacRequest::MlmeDisassociateRequest(disassociate_request) => {
+                MacSvcTask::Disassociate(DisassociateTask::new(
+                    disassociate_request,
+                    task_index,
+                    &self.pib,
+                    self.frame_filter,
+                    &self.associated_devices,
+                    self.pending_transactions,
+                    &*self.rng,
+                    self.buffer_allocator,
+                ))
+            }
+            MacRequest::MlmeStartRequest(start_request) => MacSvcTask::Start(StartTask::new(
+                start_request,
+                &self.pib,
+                self.frame_filter,
+                self.channel,
+                &*self.rng,
+                self.buffer_allocator,
+            )),
+            MacRequest::MlmeBeaconRequest(beacon_request) => MacSvcTask::Beacon(BeaconTask::new(
+                beacon_request,
+                &self.pib,
+                &*self.rng,
+                self.buffer_allocator,
+            )),
+            MacRequest::MlmePollRequest(poll_request) => MacSvcTask::Poll(PollTask::new(
+                poll_request,
+                task_index,
+                &self.pib,
+                &self.poll_slot,
+                &*self.rng,
+                self.buffer_allocator,
+            )),
+            // Safety: PIB, purge, neighbor and TSCH requests are handled
+            //         synchronously.
+            MacRequest::MlmeGetRequest(_)
+            | MacRequest::MlmeSetRequest(_)
+            | MacRequest::McpsPurgeRequest(_)
+            | MacRequest::NeighborsRequest
+            | MacRequest::MlmeSetSlotframeRequest(_)
+            | MacRequest::MlmeSetLinkRequest(_)
+            | MacRequest::MlmeTschModeRequest(_) => unreachable!(),
         }
     }
 
+    /// Returns the received ACK frame in the buffer of the sent frame, which
+    /// is no longer needed. De-allocates the buffer if there is no ACK frame or
+    /// if it doesn't fit into the buffer.
+    fn recycle_as_ack_frame(
+        &self,
+        radio_frame: RadioFrame<RadioFrameUnsized>,
+        ack_mpdu: Option<AckMpdu>,
+    ) -> Option<MpduFrame> {
+        let buffer = radio_frame.into_buffer();
+        let ack_mpdu = ack_mpdu.and_then(|ack_mpdu| {
+            let ack_length = NonZero::new(ack_mpdu.as_bytes().len() as u16)?;
+            let ack_repr = RadioFrameRepr::<RadioDriverImpl, RadioFrameSized>::new(ack_length);
+            (ack_repr.pdu_length() as usize <= buffer.len()).then_some((ack_mpdu, ack_length))
+        });
+        let Some((ack_mpdu, ack_length)) = ack_mpdu else {
+            // Safety: Clients must allocate buffers from the MAC's allocator.
+            unsafe {
+                self.buffer_allocator.deallocate_buffer(buffer);
+            }
+            return None;
+        };
+
+        let mut ack_frame =
+            RadioFrame::<RadioFrameUnsized>::new::<RadioDriverImpl>(buffer).with_size(ack_length);
+        ack_frame.sdu_mut().copy_from_slice(ack_mpdu.as_bytes());
+        Some(MpduFrame::from_radio_frame(ack_frame))
+    }
+
     fn handle_request_task_result(
         &self,
         result: MacSvcTaskResult<RadioDriverImpl>,
+        task_index: usize,
         response_token: ResponseToken,
     ) {
         match result {
-            MacSvcTaskResult::DataRequest(task_result) => {
-                let recovered_radio_frame = match task_result {
-                    DataRequestResult::Sent(recovered_radio_frame) => recovered_radio_frame,
-                    DataRequestResult::CcaBusy(unsent_radio_frame)
-                    | DataRequestResult::Nack(unsent_radio_frame) => {
-                        // TODO: CSMA/CA or Retry.
-                        unsent_radio_frame.forget_size::<RadioDriverImpl>()
-                    }
-                };
-
-                // Safety: Clients must allocate buffers from the MAC's
-                //         allocator.
-                unsafe {
-                    self.buffer_allocator
-                        .deallocate_buffer(recovered_radio_frame.into_buffer());
+            MacSvcTaskResult::DataRequest(DataRequestResult {
+                mut confirm,
+                radio_frame,
+                ack_mpdu,
+            })
+            | MacSvcTaskResult::IndirectTx(DataRequestResult {
+                mut confirm,
+                radio_frame,
+                ack_mpdu,
+            }) => {
+                if let Some(neighbor) = self.tx_neighbors[task_index].take() {
+                    self.record_neighbor_tx(neighbor, &confirm);
                 }
 
+                confirm.ack_frame = self.recycle_as_ack_frame(radio_frame, ack_mpdu);
+
                 // Safety: We signal reception _after_ de-allocating the buffer
                 //         so that clients can use the reception signal to
-                //         safely manage bounded buffer resources. We may even
-                //         return the buffer at some time so that it doesn't
-                //         have to be re-allocated. We just don't do that
-                //         currently as the smoltcp driver is synchronous and
-                //         cannot handle any response.
-                self.request_receiver.received(response_token, ());
-            }
-            // The rest are indications
+                //         safely manage bounded buffer resources. A buffer
+                //         returned with the ACK frame is owned by the client.
+                self.request_receiver
+                    .received(response_token, MacConfirm::McpsData(confirm));
+            }
+            MacSvcTaskResult::Scan(confirm) => {
+                self.request_receiver
+                    .received(response_token, MacConfirm::MlmeScan(confirm));
+            }
+            MacSvcTaskResult::Associate(confirm) => {
+                self.request_receiver
+                    .received(response_token, MacConfirm::MlmeAssociate(confirm));
+            }
+            MacSvcTaskResult::AssociateResponse(indication) => {
+                self.request_receiver
+                    .received(response_token, MacConfirm::MlmeCommStatus(indication));
+            }
+            MacSvcTaskResult::Disassociate(confirm) => {
+                self.request_receiver
+                    .received(response_token, MacConfirm::MlmeDisassociate(confirm));
+            }
+            MacSvcTaskResult::Start(confirm) => {
+                self.request_receiver
+                    .received(response_token, MacConfirm::MlmeStart(confirm));
+            }
+            MacSvcTaskResult::Beacon(confirm) => {
+                self.request_receiver
+                    .received(response_token, MacConfirm::MlmeBeacon(confirm));
+            }
+            MacSvcTaskResult::Poll(confirm) => {
+                self.request_receiver
+                    .received(response_token, MacConfirm::MlmePoll(confirm));
+            }
+            // The rest are indications or internal tasks
             _ => unreachable!(),
         }
     }
 
-    fn handle_indication_task_result(&self, result: MacSvcTaskResult<RadioDriverImpl>) {
+    /// Returns the index of a MAC task to be woken, if any.
+    fn handle_indication_task_result(
+        &self,
+        result: MacSvcTaskResult<RadioDriverImpl>,
+    ) -> Option<usize> {
         match result {
-            MacSvcTaskResult::DataIndication(DataIndication { mpdu, .. }) => {
-                self.handle_incoming_mpdu(mpdu);
+            MacSvcTaskResult::DataIndication(data_indication) => {
+                self.handle_incoming_mpdu(data_indication)
             }
-            // The rest are requests
+            // The rest are requests or internal tasks
             _ => unreachable!(),
         }
     }
 
-    fn handle_incoming_mpdu(&self, mpdu: MpduFrame) {
-        // TODO: Implement proper handling of incoming frames.
-        match mpdu.frame_control().frame_type() {
-            FrameType::Data => {
-                if let Some(request_token) = self.indication_sender.try_allocate_request_token() {
-                    let indication = MacIndication::McpsData(DataIndication {
-                        mpdu,
-                        timestamp: None,
-                    });
-
-                    // TODO: Poll response, once we work with MAC response
-                    //       primitives.
-                    self.indication_sender
-                        .send_request_no_response(request_token, indication);
-                } else {
-                    // To avoid DoS we drop incoming packets if the upper layer
-                    // is not able to ingest them fast enough.
-
-                    // Safety: Incoming frames are allocated by the
-                    //         MAC service itself.
-                    unsafe {
-                        self.buffer_allocator.deallocate_buffer(mpdu.into_buffer());
+    /// Returns the index of a MAC task to be woken, if any.
+    fn handle_internal_task_result(
+        &self,
+        result: MacSvcTaskResult<RadioDriverImpl>,
+        outstanding_mac_requests: Option<&mut [Option<ResponseToken>]>,
+    ) -> Option<usize> {
+        match result {
+            // Beacons that could not be sent are not retried: The requesting
+            // device will scan again.
+            MacSvcTaskResult::Beacon(_) => None,
+            // Enhanced Beacons stop once they are disabled.
+            MacSvcTaskResult::EnhancedBeacon(()) => None,
+            MacSvcTaskResult::TschSlot(TschSlotResult::Received(data_indication)) => {
+                self.handle_incoming_mpdu(data_indication)
+            }
+            MacSvcTaskResult::TschSlot(TschSlotResult::Transmitted { task_index, result }) => {
+                // Safety: Driver responses are only handled from the main
+                //         event loop which passes the outstanding requests.
+                let response_token = outstanding_mac_requests.unwrap()[task_index]
+                    .take()
+                    .unwrap();
+                self.handle_request_task_result(
+                    MacSvcTaskResult::DataRequest(result),
+                    task_index,
+                    response_token,
+                );
+                None
+            }
+            // The TSCH task stops once TSCH mode was left.
+            MacSvcTaskResult::TschSlot(TschSlotResult::Stopped) => None,
+            // The TSCH task left TSCH mode on its own, see IEEE 802.15.4-2020,
+            // section 6.5.4.3.
+            MacSvcTaskResult::TschSlot(TschSlotResult::SyncLost) => {
+                self.tsch_schedule.borrow_mut().set_time_source(None);
+
+                // Safety: Driver responses and timers are only handled from
+                //         the main event loop which passes the outstanding
+                //         requests.
+                let outstanding_mac_requests = outstanding_mac_requests.unwrap();
+                for (task_index, result) in self.expire_tsch_tx_queue() {
+                    self.handle_request_task_result(
+                        MacSvcTaskResult::DataRequest(result),
+                        task_index,
+                        outstanding_mac_requests[task_index].take().unwrap(),
+                    );
+                }
+
+                let indication = {
+                    let pib = self.pib.borrow();
+                    SyncLossIndication {
+                        loss_reason: SyncLossReason::BeaconLost,
+                        pan_id: pib.pan_id,
+                        channel_number: pib.current_channel,
+                        channel_page: pib.channel_page,
                     }
+                };
+                self.try_indicate(MacIndication::MlmeSyncLoss(indication));
+                None
+            }
+            // The rest are requests or indications
+            _ => unreachable!(),
+        }
+    }
+
+    /// Passes the given frame to the upper layer or consumes it.
+    ///
+    /// Returns the index of a MAC task to be woken by the frame, if any.
+    fn handle_incoming_mpdu(&self, data_indication: DataIndicationResult) -> Option<usize> {
+        let DataIndicationResult {
+            mpdu,
+            rx_info,
+            crc_ok,
+            channel,
+            asn,
+        } = data_indication;
+        // Frames received outside of TSCH and scans were received on the
+        // current channel.
+        let rx_channel = channel.unwrap_or(self.channel.get());
+
+        // While scanning, all frames other than beacons are discarded, see
+        // IEEE 802.15.4-2020, section 6.3.1. Beacons are accepted from any PAN.
+        // PAN descriptors are only collected if macAutoRequest is set.
+        if let Some(pan_descriptors) = self.pan_descriptors.borrow_mut().as_mut() {
+            if crc_ok {
+                if self.pib.borrow().auto_request {
+                    Self::collect_pan_descriptor(pan_descriptors, &mpdu, &rx_info, rx_channel);
                 }
+                self.receive_beacon(mpdu, &rx_info, rx_channel);
+            } else {
+                self.drop_incoming_mpdu(mpdu);
+            }
+            return None;
+        }
+
+        // In promiscuous mode, all frames are passed to the upper layer
+        // without further processing.
+        let pib = self.pib.borrow();
+        if pib.promiscuous_mode {
+            if crc_ok || pib.promiscuous_crc_errors {
+                let indication = MacIndication::Promiscuous(PromiscuousIndication {
+                    mpdu,
+                    crc_ok,
+                    channel: channel.unwrap_or(pib.current_channel),
+                    lqi: rx_info.lqi,
+                    rssi: rx_info.rssi,
+                    timestamp: rx_info.timestamp,
+                });
+                self.try_indicate(indication);
+            } else {
+                self.drop_incoming_mpdu(mpdu);
+            }
+            return None;
+        }
+        drop(pib);
+
+        // The driver service has already filtered incoming frames before
+        // acknowledging them.
+        let frame_type = crc_ok.then(|| mpdu.frame_control().frame_type());
+
+        // Retransmissions have already been acknowledged by the driver service
+        // and are not indicated again.
+        if matches!(frame_type, Some(FrameType::Data | FrameType::MacCommand))
+            && self.is_duplicate(&mpdu, asn)
+        {
+            self.drop_incoming_mpdu(mpdu);
+            return None;
+        }
+
+        if frame_type.is_some() {
+            self.record_neighbor_rx(&mpdu, &rx_info);
+        }
+
+        // TODO: Implement proper handling of incoming frames.
+        match frame_type {
+            Some(FrameType::Data) => {
+                let woken_task_index = self.receive_polled_data(&mpdu);
+                self.try_indicate(MacIndication::McpsData(DataIndication {
+                    mpdu,
+                    link_quality: rx_info.lqi,
+                    rssi: rx_info.rssi,
+                    timestamp: rx_info.timestamp,
+                }));
 
                 #[cfg(feature = "rtos-trace")]
                 rtos_trace::trace::task_exec_end();
+
+                woken_task_index
+            }
+            Some(FrameType::Beacon) => {
+                self.receive_beacon(mpdu, &rx_info, rx_channel);
+                None
+            }
+            Some(FrameType::MacCommand) => {
+                // MAC commands are consumed by the MAC sublayer.
+                let woken_task_index = self.receive_command(&mpdu, &rx_info);
+                self.drop_incoming_mpdu(mpdu);
+                woken_task_index
             }
             _ => {
-                // Safety: Incoming frames are allocated by the
-                //         MAC service itself.
-                unsafe {
-                    self.buffer_allocator.deallocate_buffer(mpdu.into_buffer());
-                }
+                self.drop_incoming_mpdu(mpdu);
+                None
             }
         }
     }
+
+    /// Whether the given frame was already received, see [`DuplicateCache`].
+    ///
+    /// In TSCH mode, frames are keyed by the ASN of the slot in which they
+    /// were received, otherwise by their DSN.
+    fn is_duplicate(&self, mpdu: &MpduFrame, asn: Option<AbsoluteSlotNumber>) -> bool {
+        let timeout = self.pib.borrow().duplicate_detection_timeout;
+        let sequence_number = if self.timeslot_timings.get().is_some() {
+            asn.map(AbsoluteSlotNumber::as_u64)
+        } else {
+            mpdu.sequence_number().map(u64::from)
+        };
+        let Some(sequence_number) = sequence_number else {
+            return false;
+        };
+        if timeout.ticks() == 0 {
+            return false;
+        }
+
+        let addressing_fields = mpdu
+            .reader()
+            .parse_addressing()
+            .and_then(|reader| reader.into_addressing_fields());
+        let Ok(Some(addressing_fields)) = addressing_fields else {
+            return false;
+        };
+        let Some(src_address) = addressing_fields.src_address() else {
+            return false;
+        };
+
+        let timeout: Duration<RadioDriverImpl::Timer> = timeout.convert_into_rounding_up();
+        self.duplicate_cache.borrow_mut().is_duplicate(
+            &src_address,
+            sequence_number,
+            RadioDriverImpl::Timer::now().tick(),
+            timeout.ticks() as u64,
+        )
+    }
+
+    /// Dispatches the given MAC command to its handler.
+    ///
+    /// Returns the index of a MAC task to be woken by the command, if any.
+    fn receive_command(&self, mpdu: &MpduFrame, rx_info: &RxInfo) -> Option<usize> {
+        let (addressing_fields, command_id, payload) = parse_command(mpdu.pdu_ref_wo_fcs())?;
+        match command_id {
+            CommandId::AssociationRequest => {
+                self.receive_association_request(&addressing_fields, payload, rx_info);
+                None
+            }
+            CommandId::AssociationResponse => {
+                self.receive_association_response(&addressing_fields, payload);
+                None
+            }
+            CommandId::DisassociationNotification => {
+                self.receive_disassociation_notification(&addressing_fields, payload);
+                None
+            }
+            CommandId::DataRequest => self.receive_data_request(&addressing_fields),
+            CommandId::BeaconRequest => {
+                self.receive_beacon_request();
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Adds the PAN descriptor of the given beacon to the list unless the list
+    /// is full or the coordinator is already listed.
+    fn collect_pan_descriptor(
+        pan_descriptors: &mut PanDescriptorList,
+        mpdu: &MpduFrame,
+        rx_info: &RxInfo,
+        channel: RadioChannel,
+    ) {
+        let Some(pan_descriptor) =
+            PanDescriptor::from_beacon(mpdu.pdu_ref_wo_fcs(), channel, rx_info)
+        else {
+            return;
+        };
+
+        if pan_descriptors
+            .iter()
+            .any(|listed| listed.is_same_coordinator(&pan_descriptor))
+        {
+            return;
+        }
+
+        let _ = pan_descriptors.push(pan_descriptor);
+    }
+
+    /// Passes the given indication to the upper layer.
+    fn try_indicate(&self, indication: MacIndication) {
+        if let Some(request_token) = self.indication_sender.try_allocate_request_token() {
+            // TODO: Poll response, once we work with MAC response
+            //       primitives.
+            self.indication_sender
+                .send_request_no_response(request_token, indication);
+        } else {
+            // To avoid DoS we drop incoming packets if the upper layer
+            // is not able to ingest them fast enough.
+            let mpdu = match indication {
+                MacIndication::McpsData(DataIndication { mpdu, .. })
+                | MacIndication::MlmeBeaconNotify(BeaconNotifyIndication { mpdu, .. })
+                | MacIndication::Promiscuous(PromiscuousIndication { mpdu, .. }) => mpdu,
+                MacIndication::MlmeAssociate(_)
+                | MacIndication::MlmeDisassociate(_)
+                | MacIndication::MlmeSyncLoss(_) => return,
+            };
+            self.drop_incoming_mpdu(mpdu);
+        }
+    }
+
+    fn drop_incoming_mpdu(&self, mpdu: MpduFrame) {
+        // Safety: Incoming frames are allocated by the MAC service itself.
+        unsafe {
+            self.buffer_allocator.deallocate_buffer(mpdu.into_buffer());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ops::RangeInclusive;
+
+    use rand_core::{impls, Error};
+
+    use crate::driver::{
+        alarm::AlarmMultiplexer,
+        export::U,
+        time::{Frequency, Instant},
+        DriverRequestChannel, FcsTwoBytes,
+    };
+
+    use super::{devices::SequentialShortAddressAllocator, *};
+
+    struct FakeTimer;
+
+    impl Frequency for FakeTimer {
+        const FREQUENCY: u32 = 1_000_000;
+    }
+
+    impl RadioTimerApi for FakeTimer {
+        fn now() -> Instant<Self> {
+            Instant::new(0)
+        }
+
+        fn schedule_alarm(_at: Instant<Self>) {
+            unreachable!()
+        }
+
+        async fn wait_for_alarm() -> Instant<Self> {
+            unreachable!()
+        }
+    }
+
+    struct FakeDriverConfig;
+
+    impl DriverConfig for FakeDriverConfig {
+        type Headroom = U<0>;
+        type Tailroom = U<2>;
+        type MaxSduLength = U<PHY_MAX_PACKET_SIZE_127>;
+        type Fcs = FcsTwoBytes;
+        type Timer = FakeTimer;
+        const TX_POWER_RANGE: RangeInclusive<i8> = -20..=8;
+        const ACCURATE_RX_TIMESTAMPS: bool = false;
+    }
+
+    struct FakeRng;
+
+    impl RngCore for FakeRng {
+        fn next_u32(&mut self) -> u32 {
+            0
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    /// Data frame with PAN ID compression and short addresses.
+    fn data_frame(buffer_allocator: MacBufferAllocator, sequence_number: u8) -> MpduFrame {
+        #[rustfmt::skip]
+        let frame = [
+            // Frame control: data, PAN ID compression, short addresses
+            0x41, 0x88,
+            // Sequence number
+            sequence_number,
+            // Destination PAN ID
+            0x34, 0x12,
+            // Destination address
+            0x01, 0x00,
+            // Source address
+            0x02, 0x00,
+            // Payload
+            0xaa,
+        ];
+        let repr = RadioFrameRepr::<FakeDriverConfig, RadioFrameUnsized>::new();
+        let mut buffer = buffer_allocator
+            .try_allocate_buffer(repr.max_buffer_length() as usize)
+            .unwrap();
+        buffer[..frame.len()].copy_from_slice(&frame);
+        let radio_frame = RadioFrame::new::<FakeDriverConfig>(buffer)
+            .with_size(NonZero::new(frame.len() as u16).unwrap());
+        MpduFrame::from_radio_frame(radio_frame)
+    }
+
+    #[test]
+    fn indicate_retransmissions_once() {
+        let buffer_allocator = dot15d4_util::buffer_allocator!(MAC_BUFFER_SIZE, 4);
+        let mut rng = Mutex::new(FakeRng);
+        let request_channel = MacRequestChannel::new();
+        let indication_channel = MacIndicationChannel::new();
+        let indication_receiver = indication_channel.receiver();
+        let driver_request_channel = DriverRequestChannel::new();
+        let frame_filter = Cell::new(FrameFilter::default());
+        let channel = Cell::new(RadioChannel::default());
+        let tx_power = Cell::new(0);
+        let pending_transactions = RefCell::new(PendingTransactions::new());
+        let timeslot_timings = Cell::new(None);
+        let alarms = AlarmMultiplexer::<FakeTimer, 1>::new();
+        let [alarm] = alarms.alarms();
+        let mut short_address_allocator = SequentialShortAddressAllocator::default();
+        let mac_service = MacService::<'_, FakeRng, FakeDriverConfig>::new(
+            &mut rng,
+            buffer_allocator,
+            request_channel.receiver(),
+            indication_channel.sender(),
+            driver_request_channel.sender(),
+            &frame_filter,
+            &channel,
+            &tx_power,
+            &pending_transactions,
+            &timeslot_timings,
+            alarm,
+            &mut short_address_allocator,
+        );
+
+        // Receives the frame and returns whether it was indicated.
+        let receive = |sequence_number: u8, asn: Option<AbsoluteSlotNumber>| {
+            mac_service.handle_incoming_mpdu(DataIndicationResult {
+                mpdu: data_frame(buffer_allocator, sequence_number),
+                rx_info: RxInfo {
+                    timestamp: None,
+                    lqi: 0,
+                    rssi: None,
+                },
+                crc_ok: true,
+                channel: None,
+                asn,
+            });
+            let Some((response_token, indication)) = indication_receiver.try_receive_request(&())
+            else {
+                return false;
+            };
+            let MacIndication::McpsData(DataIndication { mpdu, .. }) = indication else {
+                panic!("unexpected indication");
+            };
+            // Safety: The frame was allocated from the test's allocator.
+            unsafe { buffer_allocator.deallocate_buffer(mpdu.into_buffer()) };
+            indication_receiver.received(response_token, ());
+            true
+        };
+
+        // Outside of TSCH mode, frames are keyed by their DSN.
+        assert!(receive(1, None));
+        assert!(!receive(1, None));
+        assert!(receive(2, None));
+
+        // In TSCH mode, frames are keyed by the ASN of their slot.
+        timeslot_timings.set(Some(TschTimeslotTimings::default()));
+        let asn = AbsoluteSlotNumber::default();
+        assert!(receive(3, Some(asn)));
+        assert!(!receive(4, Some(asn)));
+        assert!(receive(3, Some(asn.wrapping_add(1))));
+    }
 }
@@ -1,7 +1,20 @@
-#![allow(dead_code)]
+use rand_core::RngCore;
 
-use crate::driver::time::{Instant, Microseconds};
+use crate::{
+    driver::{
+        frame::{Address, ShortAddress},
+        tasks::RxInfo,
+        time::{Instant, Microseconds},
+        DriverConfig, RadioTimerApi,
+    },
+    mac::{
+        frame::mpdu::MpduFrame,
+        mcps::data::{DataConfirm, DataError, DataRequest},
+        MacService,
+    },
+};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TableError {
     Full,
 }
@@ -45,11 +58,417 @@ pub trait MacNeighbor {
     fn set_num_rx(&mut self, num_rx: u32);
 }
 
+/// The max number of neighbors kept in the neighbor table.
+pub const MAC_MAX_NEIGHBORS: usize = 16;
+
+/// The fixed-point divisor of [`MacNeighbor::etx`], i.e. an ETX of
+/// `ETX_DIVISOR` denotes a perfect link.
+pub const ETX_DIVISOR: u32 = 128;
+
+/// The ETX assumed for a new neighbor.
+const ETX_INITIAL: u32 = 2 * ETX_DIVISOR;
+
+/// The ETX sample taken if a frame was not acknowledged at all.
+const ETX_NOACK_PENALTY: u32 = 12 * ETX_DIVISOR;
+
+/// The weight of the current ETX when smoothing in a new sample, in percent.
+const ETX_ALPHA: u32 = 90;
+
+/// Determines which neighbor is replaced once the neighbor table is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Replace the neighbor that was least recently heard from or sent to.
+    #[default]
+    LeastRecentlyUsed,
+    /// Replace the neighbor with the lowest link quality indicator.
+    LowestLinkQuality,
+}
+
+/// A neighbor with the link statistics maintained by the MAC service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Neighbor {
+    address: [u8; 8],
+    /// The short address of the neighbor, if known.
+    short_address: Option<[u8; 2]>,
+    last_tx: Instant<Microseconds>,
+    /// The time at which the last frame was received from the neighbor.
+    last_rx: Instant<Microseconds>,
+    etx: u32,
+    lqi: u32,
+    num_tx: u32,
+    num_rx: u32,
+    /// Incremented on every use of the neighbor, for LRU eviction.
+    last_used: u32,
+}
+
+impl Neighbor {
+    pub fn new(address: [u8; 8]) -> Self {
+        Self {
+            address,
+            short_address: None,
+            last_tx: Instant::new(0),
+            last_rx: Instant::new(0),
+            etx: ETX_INITIAL,
+            lqi: 0,
+            num_tx: 0,
+            num_rx: 0,
+            last_used: 0,
+        }
+    }
+
+    /// The short address of the neighbor, if it was learned from a frame or
+    /// an association.
+    pub fn short_address(&self) -> Option<[u8; 2]> {
+        self.short_address
+    }
+
+    pub(crate) fn set_short_address(&mut self, short_address: [u8; 2]) {
+        self.short_address = Some(short_address);
+    }
+
+    /// The time at which the last frame was received from the neighbor.
+    pub fn last_rx(&self) -> Instant<Microseconds> {
+        self.last_rx
+    }
+
+    /// Records a received frame.
+    pub(crate) fn record_rx(&mut self, lqi: u8, timestamp: Instant<Microseconds>) {
+        self.num_rx = self.num_rx.wrapping_add(1);
+        self.lqi = lqi as u32;
+        self.last_rx = timestamp;
+    }
+
+    /// Records the outcome of a unicast transmission with the given number of
+    /// attempts that ended at the given time. Smoothes the ETX if an ACK was
+    /// requested.
+    pub(crate) fn record_tx(
+        &mut self,
+        attempts: u8,
+        acked: Option<bool>,
+        ack_lqi: Option<u8>,
+        timestamp: Instant<Microseconds>,
+    ) {
+        self.num_tx = self.num_tx.wrapping_add(attempts as u32);
+        self.last_tx = timestamp;
+        if let Some(lqi) = ack_lqi {
+            self.lqi = lqi as u32;
+        }
+
+        let sample = match acked {
+            Some(true) => attempts as u32 * ETX_DIVISOR,
+            Some(false) => ETX_NOACK_PENALTY,
+            None => return,
+        };
+        self.etx = (self.etx * ETX_ALPHA + sample * (100 - ETX_ALPHA)) / 100;
+    }
+}
+
+impl MacNeighbor for Neighbor {
+    fn address(&self) -> [u8; 8] {
+        self.address
+    }
+
+    fn last_tx(&self) -> Instant<Microseconds> {
+        self.last_tx
+    }
+
+    fn etx(&self) -> u32 {
+        self.etx
+    }
+
+    fn lqi(&self) -> u32 {
+        self.lqi
+    }
+
+    fn num_tx(&self) -> u32 {
+        self.num_tx
+    }
+
+    fn num_rx(&self) -> u32 {
+        self.num_rx
+    }
+
+    fn set_last_tx(&mut self, instant: Instant<Microseconds>) {
+        self.last_tx = instant;
+    }
+
+    fn set_etx(&mut self, etx: u32) {
+        self.etx = etx;
+    }
+
+    fn set_lqi(&mut self, lqi: u32) {
+        self.lqi = lqi;
+    }
+
+    fn set_num_tx(&mut self, num_tx: u32) {
+        self.num_tx = num_tx;
+    }
+
+    fn set_num_rx(&mut self, num_rx: u32) {
+        self.num_rx = num_rx;
+    }
+}
+
+/// A fixed-capacity neighbor table, keyed by extended address.
+///
+/// A new neighbor replaces an existing one according to the eviction policy
+/// once the table is full.
+#[derive(Debug, Clone, Default)]
+pub struct NeighborTable {
+    neighbors: heapless::Vec<Neighbor, MAC_MAX_NEIGHBORS>,
+    eviction_policy: EvictionPolicy,
+    /// The use counter of the most recently used neighbor.
+    use_counter: u32,
+}
+
+impl NeighborTable {
+    pub const fn new(eviction_policy: EvictionPolicy) -> Self {
+        Self {
+            neighbors: heapless::Vec::new(),
+            eviction_policy,
+            use_counter: 0,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Neighbor> {
+        self.neighbors.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neighbors.is_empty()
+    }
+
+    pub fn eviction_policy(&self) -> EvictionPolicy {
+        self.eviction_policy
+    }
+
+    pub(crate) fn set_eviction_policy(&mut self, eviction_policy: EvictionPolicy) {
+        self.eviction_policy = eviction_policy;
+    }
+
+    /// Looks up a neighbor by its short address.
+    pub fn get_by_short_address(&self, short_address: [u8; 2]) -> Option<&Neighbor> {
+        self.neighbors
+            .iter()
+            .find(|neighbor| neighbor.short_address == Some(short_address))
+    }
+
+    /// Looks up the given neighbor for an update and marks it as used. Adds
+    /// the neighbor, possibly evicting another one, if it is not yet known.
+    pub(crate) fn touch(&mut self, address: [u8; 8]) -> Option<&mut Neighbor> {
+        let index = match self.position(address) {
+            Some(index) => index,
+            None => {
+                self.insert(Neighbor::new(address)).ok()?;
+                self.neighbors.len() - 1
+            }
+        };
+        self.use_counter = self.use_counter.wrapping_add(1);
+        let neighbor = &mut self.neighbors[index];
+        neighbor.last_used = self.use_counter;
+        Some(neighbor)
+    }
+
+    fn position(&self, address: [u8; 8]) -> Option<usize> {
+        self.neighbors
+            .iter()
+            .position(|neighbor| neighbor.address == address)
+    }
+
+    /// Appends the given neighbor, evicting another one if the table is
+    /// full.
+    fn insert(&mut self, neighbor: Neighbor) -> Result<(), TableError> {
+        if self.neighbors.is_full() {
+            let use_counter = self.use_counter;
+            let evicted = match self.eviction_policy {
+                // Use counters wrap around: The neighbor with the largest
+                // distance to the current counter is the least recently used.
+                EvictionPolicy::LeastRecentlyUsed => self
+                    .neighbors
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, neighbor)| use_counter.wrapping_sub(neighbor.last_used)),
+                EvictionPolicy::LowestLinkQuality => self
+                    .neighbors
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, neighbor)| neighbor.lqi),
+            }
+            .map(|(index, _)| index)
+            .ok_or(TableError::Full)?;
+            self.neighbors.remove(evicted);
+        }
+        self.neighbors.push(neighbor).map_err(|_| TableError::Full)
+    }
+}
+
+impl NeighborsTable<Neighbor, [u8; 8]> for NeighborTable {
+    fn add_neighbor(&mut self, address: [u8; 8]) -> Result<&Neighbor, TableError> {
+        if self.position(address).is_none() {
+            self.insert(Neighbor::new(address))?;
+        }
+        // Safety: The neighbor was either present or has just been added.
+        Ok(self.get_neighbor(address).unwrap())
+    }
+
+    fn get_neighbor(&self, address: [u8; 8]) -> Option<&Neighbor> {
+        self.neighbors
+            .iter()
+            .find(|neighbor| neighbor.address == address)
+    }
+
+    fn remove_neighbor(&mut self, address: [u8; 8]) {
+        if let Some(index) = self.position(address) {
+            self.neighbors.remove(index);
+        }
+    }
+}
+
+/// The addresses of a neighbor that a frame was exchanged with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct NeighborAddress {
+    address: [u8; 8],
+    short_address: Option<[u8; 2]>,
+}
+
+impl NeighborAddress {
+    /// The extended address of the neighbor.
+    pub fn address(&self) -> [u8; 8] {
+        self.address
+    }
+}
+
+impl<Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'_, Rng, RadioDriverImpl> {
+    /// Resolves the given frame address to the neighbor's extended address.
+    /// Short addresses are looked up in the neighbor table, among the
+    /// associated devices and in the coordinator's PIB attributes.
+    ///
+    /// Returns `None` for broadcasts and unknown short addresses.
+    fn resolve_neighbor(&self, address: &Address<&[u8]>) -> Option<NeighborAddress> {
+        let short_address: [u8; 2] = match address {
+            Address::Extended(address) => {
+                return Some(NeighborAddress {
+                    address: address.as_ref().try_into().ok()?,
+                    short_address: None,
+                })
+            }
+            Address::Short(address) if address.into_u16() < 0xfffe => {
+                address.as_ref().try_into().ok()?
+            }
+            _ => return None,
+        };
+
+        let known_neighbor = self
+            .neighbors
+            .borrow()
+            .get_by_short_address(short_address)
+            .map(|neighbor| neighbor.address());
+        let associated_device = || {
+            self.associated_devices
+                .borrow()
+                .get_by_short_address(&ShortAddress::new_owned(short_address))
+                .and_then(|device| device.extended_address.as_ref().try_into().ok())
+        };
+        let coordinator = || {
+            let pib = self.pib.borrow();
+            pib.coord_extended_address
+                .filter(|_| pib.coord_short_address.to_le_bytes() == short_address)
+        };
+
+        let address = known_neighbor
+            .or_else(associated_device)
+            .or_else(coordinator)?;
+        Some(NeighborAddress {
+            address,
+            short_address: Some(short_address),
+        })
+    }
+
+    /// Looks up the recipient of the given data request, if it is a known
+    /// neighbor.
+    pub(crate) fn data_request_neighbor(
+        &self,
+        data_request: &DataRequest,
+    ) -> Option<NeighborAddress> {
+        self.resolve_neighbor(&data_request.dst_addr().ok()?)
+    }
+
+    /// Updates the link statistics of the sender of the given frame.
+    pub(crate) fn record_neighbor_rx(&self, mpdu: &MpduFrame, rx_info: &RxInfo) {
+        let Ok(Some(addressing_fields)) = mpdu
+            .reader()
+            .parse_addressing()
+            .and_then(|reader| reader.into_addressing_fields())
+        else {
+            return;
+        };
+        let Some(neighbor_address) = addressing_fields
+            .src_address()
+            .and_then(|src_address| self.resolve_neighbor(&src_address))
+        else {
+            return;
+        };
+
+        let tick = rx_info
+            .timestamp
+            .unwrap_or_else(|| RadioDriverImpl::Timer::now().tick());
+        let timestamp = Instant::<RadioDriverImpl::Timer>::new(tick)
+            .convert_into_rounding_down::<Microseconds>();
+
+        let mut neighbors = self.neighbors.borrow_mut();
+        if let Some(neighbor) = neighbors.touch(neighbor_address.address) {
+            if let Some(short_address) = neighbor_address.short_address {
+                neighbor.set_short_address(short_address);
+            }
+            neighbor.record_rx(rx_info.lqi, timestamp);
+        }
+    }
+
+    /// Updates the link statistics of the recipient of a data frame from its
+    /// confirm. Frames that could not be sent at all leave the statistics
+    /// unchanged.
+    pub(crate) fn record_neighbor_tx(
+        &self,
+        neighbor_address: NeighborAddress,
+        confirm: &DataConfirm,
+    ) {
+        let acked = match confirm.status {
+            Ok(()) => confirm.acked.then_some(true),
+            Err(DataError::NoAck) => Some(false),
+            Err(_) => return,
+        };
+
+        let tick = confirm
+            .timestamp
+            .unwrap_or_else(|| RadioDriverImpl::Timer::now().tick());
+        let timestamp = Instant::<RadioDriverImpl::Timer>::new(tick)
+            .convert_into_rounding_down::<Microseconds>();
+
+        let mut neighbors = self.neighbors.borrow_mut();
+        if let Some(neighbor) = neighbors.touch(neighbor_address.address) {
+            if let Some(short_address) = neighbor_address.short_address {
+                neighbor.set_short_address(short_address);
+            }
+            neighbor.record_tx(
+                confirm.retries + 1,
+                acked,
+                confirm.ack_link_quality,
+                timestamp,
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::driver::time::{Instant, Microseconds};
 
-    use super::MacNeighbor;
+    use super::*;
+
     pub(crate) struct TestNeighbor {
         address: [u8; 8],
         last_tx: Instant<Microseconds>,
@@ -127,49 +546,90 @@ pub mod tests {
         }
     }
 
-    // pub(crate) struct TestNeighborTable<const N: usize> {
-    //     neighbors: heapless::Vec<TestNeighbor, N>,
-    // }
-
-    // impl<const N: usize> TestNeighborTable<N> {
-    //     pub fn new() -> Self {
-    //         Self {
-    //             neighbors: heapless::Vec::new(),
-    //         }
-    //     }
-    // }
-
-    // impl<const N: usize> NeighborsTable<TestNeighbor, [u8; 8]> for TestNeighborTable<N> {
-    //     fn add_neighbor(&mut self, address: [u8; 8]) -> Result<&TestNeighbor, TableError> {
-    //         if let Some(neighbor) = self.neighbors.iter().find(|nbr| nbr.address == address) {
-    //             return Ok(neighbor);
-    //         }
-    //         let neighbor = TestNeighbor::new(address);
-    //         match self.neighbors.push(neighbor) {
-    //             Ok(_) => Ok(self
-    //                 .neighbors
-    //                 .iter()
-    //                 .find(|nbr| nbr.address == address)
-    //                 .unwrap()),
-    //             Err(_) => Err(TableError::Full),
-    //         }
-    //     }
-
-    //     fn get_neighbor(&self, address: [u8; 8]) -> Option<&TestNeighbor> {
-    //         self.neighbors.iter().find(|nbr| nbr.address == address)
-    //     }
-
-    //     fn remove_neighbor(&mut self, address: [u8; 8]) {
-    //         todo!()
-    //     }
-    // }
-
-    // #[test]
-    // fn test1() {
-    //     let table = TestNeighborTable::<3>::new();
-    //     let nbr1 = TestNeighbor::new([0, 0, 0, 0, 0, 0, 0, 1]);
-    //     let nbr2 = TestNeighbor::new([0, 0, 0, 0, 0, 0, 0, 2]);
-    //     let nbr3 = TestNeighbor::new([0, 0, 0, 0, 0, 0, 0, 3]);
-    //     //
-    // }
+    #[test]
+    fn smooth_etx() {
+        let mut neighbor = Neighbor::new([0x01; 8]);
+        assert_eq!(neighbor.etx(), 2 * ETX_DIVISOR);
+
+        neighbor.record_tx(1, Some(true), Some(200), Instant::new(10));
+        assert_eq!(neighbor.etx(), (2 * 90 + 10) * ETX_DIVISOR / 100);
+        assert_eq!(neighbor.num_tx(), 1);
+        assert_eq!(neighbor.lqi(), 200);
+        assert_eq!(neighbor.last_tx(), Instant::new(10));
+
+        let etx = neighbor.etx();
+        neighbor.record_tx(4, Some(false), None, Instant::new(20));
+        assert!(neighbor.etx() > etx);
+        assert_eq!(neighbor.num_tx(), 5);
+        assert_eq!(neighbor.lqi(), 200);
+
+        // Broadcasts don't tell anything about the link.
+        let etx = neighbor.etx();
+        neighbor.record_tx(1, None, None, Instant::new(30));
+        assert_eq!(neighbor.etx(), etx);
+        assert_eq!(neighbor.num_tx(), 6);
+
+        // Received frames leave the time of the last transmission untouched.
+        neighbor.record_rx(100, Instant::new(42));
+        assert_eq!(neighbor.num_rx(), 1);
+        assert_eq!(neighbor.lqi(), 100);
+        assert_eq!(neighbor.last_rx(), Instant::new(42));
+        assert_eq!(neighbor.last_tx(), Instant::new(30));
+    }
+
+    #[test]
+    fn add_and_remove_neighbors() {
+        let mut table = NeighborTable::default();
+        assert!(table.is_empty());
+
+        assert_eq!(table.add_neighbor([0x01; 8]).unwrap().address(), [0x01; 8]);
+        assert!(table.add_neighbor([0x01; 8]).is_ok());
+        assert_eq!(table.len(), 1);
+
+        table
+            .touch([0x02; 8])
+            .unwrap()
+            .set_short_address([0x02, 0x00]);
+        assert_eq!(
+            table.get_by_short_address([0x02, 0x00]).unwrap().address(),
+            [0x02; 8]
+        );
+
+        table.remove_neighbor([0x01; 8]);
+        assert!(table.get_neighbor([0x01; 8]).is_none());
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let mut table = NeighborTable::new(EvictionPolicy::LeastRecentlyUsed);
+        for index in 0..MAC_MAX_NEIGHBORS {
+            table.touch([index as u8; 8]).unwrap();
+        }
+        // Use the oldest neighbor again.
+        table.touch([0x00; 8]).unwrap();
+
+        table.touch([0xff; 8]).unwrap();
+        assert_eq!(table.len(), MAC_MAX_NEIGHBORS);
+        assert!(table.get_neighbor([0x00; 8]).is_some());
+        assert!(table.get_neighbor([0x01; 8]).is_none());
+        assert!(table.get_neighbor([0xff; 8]).is_some());
+    }
+
+    #[test]
+    fn evict_lowest_link_quality() {
+        let mut table = NeighborTable::new(EvictionPolicy::LowestLinkQuality);
+        for index in 0..MAC_MAX_NEIGHBORS {
+            let lqi = if index == 5 { 10 } else { 100 + index as u8 };
+            table
+                .touch([index as u8; 8])
+                .unwrap()
+                .record_rx(lqi, Instant::new(0));
+        }
+
+        table.touch([0xff; 8]).unwrap();
+        assert!(table.get_neighbor([0x05; 8]).is_none());
+        assert!(table.get_neighbor([0x00; 8]).is_some());
+        assert!(table.get_neighbor([0xff; 8]).is_some());
+    }
 }
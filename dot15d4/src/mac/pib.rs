@@ -1,4 +1,21 @@
-use crate::driver::frame::PanId;
+use crate::{
+    driver::{
+        config::Channel,
+        constants::{A_BASE_SUPERFRAME_DURATION, MAC_UNIT_BACKOFF_PERIOD, PHY_MAX_FRAME_DURATION},
+        frame::{FrameFilter, FrameType, FrameVersion, PanId, ShortAddress},
+        time::{Duration, Milliseconds, SymbolsOQpsk250kB},
+    },
+    mac::{
+        frame::{
+            fields::ChannelHoppingIe,
+            mpdu::MpduFrame,
+            repr::{IeRepr, IeReprList},
+        },
+        mlme::set::SetError,
+        neighbors::EvictionPolicy,
+        tsch::schedule::TschHoppingSequence,
+    },
+};
 
 /// PAN Information Base (PIB) specified by MAC sublayer
 #[allow(dead_code)]
@@ -12,6 +29,14 @@ pub struct Pib {
     /// Indication of whether a coordinator is currently allowing association.
     /// If `true`, association is permitted.
     pub(crate) association_permit: bool,
+    /// Indication of whether a device automatically sends a data request
+    /// command if its address is listed in the beacon frame. If `false`,
+    /// every received beacon is passed to the next higher layer via
+    /// MLME-BEACON-NOTIFY and PAN descriptors are not collected during scans.
+    /// Otherwise, only beacons with a beacon payload are passed on.
+    pub(crate) auto_request: bool,
+    /// The sequence number added to the transmitted beacon frame (macBsn).
+    pub(crate) bsn: u8,
     /// The address of the coordinator through which the device is associated.
     pub(crate) coord_extended_address: Option<[u8; 8]>,
     /// The short address assigned to the coordinator through which the device
@@ -19,6 +44,16 @@ pub struct Pib {
     /// only using its extended address. A value of 0xffff indicates that this
     /// value is unknown.
     pub(crate) coord_short_address: u16,
+    /// The sequence number added to the transmitted Data frame or MAC command
+    /// (macDsn).
+    pub(crate) dsn: u8,
+    /// The sequence number added to the transmitted Enhanced Beacon frame
+    /// (macEbsn).
+    pub(crate) ebsn: u8,
+    /// Indication of whether frames without a destination PAN ID and a
+    /// destination address are to be treated as though they are addressed to
+    /// the broadcast PAN ID and broadcast short address.
+    pub(crate) implicit_broadcast: bool,
     /// The maximum value of the backoff exponent, BE, in the CSMA-CA
     /// algorithm.
     pub(crate) max_be: u8,
@@ -37,6 +72,9 @@ pub struct Pib {
     /// all) mode. A value of `true` indicates that the MAC sublayer accepts
     /// all frames received from the PHY.
     pub(crate) promiscuous_mode: bool,
+    /// Not a PIB attribute: Indication of whether frames with an invalid FCS
+    /// are also passed to the next higher layer in promiscuous mode.
+    pub(crate) promiscuous_crc_errors: bool,
     /// Indication of whether the MAC sublayer is to enable its receiver
     /// during idle periods. For a beacon-enabled PAN, this attribute is
     /// relevant only during the CAP of the incoming superframe. For a
@@ -51,10 +89,62 @@ pub struct Pib {
     /// is started. Otherwise, the short address is allocated by a coordinator
     /// during association.
     pub(crate) short_address: u16,
+    /// Specification of how often the coordinator transmits its beacon. Value
+    /// ranges from 0 to 15. If value is 15, the coordinator will not transmit
+    /// a periodic beacon, i.e. the PAN is non-beacon-enabled.
+    pub(crate) beacon_order: u8,
     /// Specification of how often the coordinator transmits an Enhanced
     /// Beacon frame. Value ranges from 0 to 15. If value is 15, no periodic
     /// Enhanced Beacon frame will be transmitted.
     pub(crate) enhanced_beacon_order: u8,
+    /// Not a PIB attribute: The IEs carried by periodic Enhanced Beacon
+    /// frames.
+    pub(crate) enhanced_beacon_ies: IeReprList<'static, IeRepr<'static>>,
+    /// The maximum time (in unit periods) that a transaction is stored by a
+    /// coordinator and indicated in its beacon. The unit period is
+    /// aBaseSuperframeDuration in a non-beacon-enabled PAN.
+    pub(crate) transaction_persistence_time: u16,
+    /// The RF channel to use for all following transmissions and receptions
+    /// (phyCurrentChannel).
+    pub(crate) current_channel: Channel,
+    /// The transmit power of the device in dBm (phyTxPower).
+    pub(crate) tx_power: i8,
+    /// The identifier of the hopping sequence, 0 for the default sequence.
+    pub(crate) hopping_sequence_id: u8,
+    /// The channel page of the channels of the hopping sequence.
+    pub(crate) channel_page: u8,
+    /// The number of channels supported by the PHY on the channel page.
+    pub(crate) number_of_channels: u16,
+    /// The channels supported by the PHY on the channel page, one bit per
+    /// channel starting at channel 0.
+    pub(crate) phy_configuration: u32,
+    /// The channels over which the device hops in TSCH mode
+    /// (macHoppingSequenceList and macHoppingSequenceLength).
+    pub(crate) hopping_sequence: TschHoppingSequence,
+    /// The index of the current channel within the hopping sequence.
+    pub(crate) current_hop: u16,
+    /// Not a PIB attribute: The time during which a frame with the same source
+    /// address and sequence number as a previously received frame is
+    /// considered a retransmission and not indicated again. Duplicate
+    /// detection is disabled if zero.
+    pub(crate) duplicate_detection_timeout: Duration<Milliseconds>,
+    /// Not a PIB attribute: The time without frames exchanged with the TSCH
+    /// time source after which a keep-alive is sent to it. Keep-alives are
+    /// disabled if zero.
+    pub(crate) tsch_keep_alive_period: Duration<Milliseconds>,
+    /// Not a PIB attribute: The time without frames exchanged with the TSCH
+    /// time source after which synchronization to the network is lost.
+    pub(crate) tsch_desync_timeout: Duration<Milliseconds>,
+    /// Not a PIB attribute: Which neighbor is replaced once the neighbor table
+    /// is full.
+    pub(crate) neighbor_eviction_policy: EvictionPolicy,
+    /// Not a PIB attribute: Indication of whether the device has been started
+    /// as PAN coordinator.
+    pub(crate) pan_coordinator: bool,
+    /// Not a PIB attribute: Indication of whether the device has been started
+    /// as a coordinator via MLME-START and therefore answers Beacon Request
+    /// commands.
+    pub(crate) coordinator: bool,
 }
 
 impl Default for Pib {
@@ -65,18 +155,305 @@ impl Default for Pib {
             extended_address: None,
             associated_pan_coord: false,
             association_permit: false,
+            auto_request: true,
+            bsn: 0,
             coord_extended_address: None,
             coord_short_address: 0xffff,
+            dsn: 0,
+            ebsn: 0,
+            implicit_broadcast: MAC_IMPLICIT_BROADCAST,
             max_be: MAC_MAX_BE,
             min_be: MAC_MIN_BE,
             max_frame_retries: MAC_MAX_FRAME_RETRIES,
             max_csma_backoffs: MAC_MAX_CSMA_BACKOFFS,
             pan_id: MAC_PAN_ID,
             promiscuous_mode: false,
+            promiscuous_crc_errors: false,
             rx_on_when_idle: false,
             security_enabled: false,
             short_address: 0xffff,
-            enhanced_beacon_order: 0,
+            beacon_order: 15,
+            enhanced_beacon_order: 15,
+            enhanced_beacon_ies: IeReprList::new(&[]),
+            transaction_persistence_time: 0x01f4,
+            current_channel: Channel::default(),
+            tx_power: 0,
+            hopping_sequence_id: 0,
+            channel_page: 0,
+            number_of_channels: 16,
+            // Channels 11 to 26 of the 2.4 GHz O-QPSK PHY.
+            phy_configuration: 0x07ff_f800,
+            hopping_sequence: TschHoppingSequence::default(),
+            current_hop: 0,
+            duplicate_detection_timeout: Duration::new(1000),
+            tsch_keep_alive_period: Duration::new(10_000),
+            tsch_desync_timeout: Duration::new(30_000),
+            neighbor_eviction_policy: EvictionPolicy::LeastRecentlyUsed,
+            pan_coordinator: false,
+            coordinator: false,
         }
     }
 }
+
+impl Pib {
+    /// Derives the incoming frame filter from the current PIB.
+    pub(crate) fn frame_filter(&self) -> FrameFilter {
+        FrameFilter {
+            pan_id: self.pan_id,
+            short_address: ShortAddress::new_owned(self.short_address.to_le_bytes()),
+            extended_address: self.extended_address,
+            implicit_broadcast: self.implicit_broadcast,
+            promiscuous_mode: self.promiscuous_mode,
+            accept_crc_errors: self.promiscuous_crc_errors,
+            pan_coordinator: self.pan_coordinator,
+        }
+    }
+
+    /// Leaves the PAN, e.g. after a failed association or once the device
+    /// disassociated, see IEEE 802.15.4-2020, section 6.4.2.
+    pub(crate) fn reset_association(&mut self) {
+        self.pan_id = PanId::new_owned([0xff, 0xff]);
+        self.short_address = 0xffff;
+        self.associated_pan_coord = false;
+        self.coord_short_address = 0xffff;
+        self.coord_extended_address = None;
+    }
+
+    /// Initializes the sequence numbers to random values as required by IEEE
+    /// 802.15.4-2020, section 8.4.3.1, table 8-94.
+    pub(crate) fn randomize_sequence_numbers(&mut self, random: u32) {
+        let [dsn, bsn, ebsn, _] = random.to_le_bytes();
+        self.dsn = dsn;
+        self.bsn = bsn;
+        self.ebsn = ebsn;
+    }
+
+    /// Returns the sequence number of the next outgoing frame of the given
+    /// type and advances macDsn, macBsn or macEbsn accordingly, see IEEE
+    /// 802.15.4-2020, section 7.2.2. Enhanced Beacons are distinguished from
+    /// beacons by their frame version.
+    pub(crate) fn next_sequence_number(
+        &mut self,
+        frame_type: FrameType,
+        frame_version: FrameVersion,
+    ) -> u8 {
+        let sequence_number = match (frame_type, frame_version) {
+            (FrameType::Beacon, FrameVersion::Ieee802154) => &mut self.ebsn,
+            (FrameType::Beacon, _) => &mut self.bsn,
+            _ => &mut self.dsn,
+        };
+        let next = *sequence_number;
+        *sequence_number = next.wrapping_add(1);
+        next
+    }
+
+    /// Writes the next sequence number into the given outgoing frame unless
+    /// its sequence number is suppressed.
+    pub(crate) fn stamp_sequence_number(&mut self, mpdu: &mut MpduFrame) {
+        let frame_control = mpdu.frame_control();
+        if frame_control.sequence_number_suppression() {
+            return;
+        }
+
+        let sequence_number =
+            self.next_sequence_number(frame_control.frame_type(), frame_control.frame_version());
+        let _ = mpdu.set_sequence_number(sequence_number);
+    }
+
+    /// The interval between periodic Enhanced Beacon frames or `None` if no
+    /// periodic Enhanced Beacons are to be sent, see IEEE 802.15.4-2020,
+    /// section 6.3.
+    pub(crate) fn enhanced_beacon_interval(&self) -> Option<Duration<SymbolsOQpsk250kB>> {
+        if self.enhanced_beacon_order >= 15 {
+            return None;
+        }
+        Some(A_BASE_SUPERFRAME_DURATION * (1_usize << self.enhanced_beacon_order))
+    }
+
+    /// The max time to wait for a frame announced as pending by a coordinator
+    /// (macMaxFrameTotalWaitTime), derived from the CSMA-CA attributes, see
+    /// IEEE 802.15.4-2020, section 8.4.3.1.
+    pub(crate) fn max_frame_total_wait_time(&self) -> Duration<SymbolsOQpsk250kB> {
+        let m = (self.max_be - self.min_be).min(self.max_csma_backoffs);
+        let backoff_periods = (0..m).map(|k| 1_usize << (self.min_be + k)).sum::<usize>()
+            + ((1_usize << self.max_be) - 1) * (self.max_csma_backoffs - m) as usize;
+        MAC_UNIT_BACKOFF_PERIOD * backoff_periods + PHY_MAX_FRAME_DURATION
+    }
+}
+
+#[allow(dead_code)]
+impl Pib {
+    /// The representation of a full Channel Hopping IE carrying the hopping
+    /// sequence, see IEEE 802.15.4-2020, section 7.4.4.31.
+    pub(crate) fn channel_hopping_ie_repr(&self) -> IeRepr<'static> {
+        IeRepr::FullChannelHoppingNestedIe(
+            self.number_of_channels as u8,
+            self.hopping_sequence.len() as u8,
+            false,
+        )
+    }
+
+    /// Writes the hopping sequence attributes into a Channel Hopping IE built
+    /// from [`Self::channel_hopping_ie_repr()`].
+    pub(crate) fn write_channel_hopping_ie<Bytes: AsRef<[u8]> + AsMut<[u8]>>(
+        &self,
+        ie: &mut ChannelHoppingIe<Bytes>,
+    ) {
+        ie.set_hopping_sequence_id(self.hopping_sequence_id);
+        if ie.is_reduced() {
+            return;
+        }
+
+        ie.set_channel_page(self.channel_page);
+        ie.set_phy_configuration(self.phy_configuration);
+        let _ = ie.set_hopping_sequence(
+            self.hopping_sequence
+                .channels()
+                .iter()
+                .map(|channel| *channel as u16),
+        );
+        ie.set_current_hop(self.current_hop);
+    }
+
+    /// Adopts the hopping sequence announced by a Channel Hopping IE. A
+    /// reduced IE only selects the hopping sequence ID.
+    ///
+    /// The PIB remains unchanged if the announced hopping sequence is not
+    /// supported.
+    pub(crate) fn read_channel_hopping_ie<Bytes: AsRef<[u8]>>(
+        &mut self,
+        ie: &ChannelHoppingIe<Bytes>,
+    ) -> Result<(), SetError> {
+        if ie.is_reduced() {
+            self.hopping_sequence_id = ie.hopping_sequence_id();
+            return Ok(());
+        }
+
+        let hopping_sequence =
+            TschHoppingSequence::from_channel_hopping_ie(ie).ok_or(SetError::InvalidParameter)?;
+        if ie.current_hop() as usize >= hopping_sequence.len() {
+            return Err(SetError::InvalidParameter);
+        }
+
+        self.hopping_sequence_id = ie.hopping_sequence_id();
+        self.channel_page = ie.channel_page();
+        self.number_of_channels = ie.number_of_channels();
+        self.phy_configuration = ie.phy_configuration();
+        self.hopping_sequence = hopping_sequence;
+        self.current_hop = ie.current_hop();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_frame_total_wait_time() {
+        let pib = Pib {
+            min_be: 3,
+            max_be: 5,
+            max_csma_backoffs: 4,
+            ..Default::default()
+        };
+        // (2^3 + 2^4 + (2^5 - 1) * 2) * 20 + 266 symbols
+        assert_eq!(pib.max_frame_total_wait_time(), Duration::new(1986));
+    }
+
+    #[test]
+    fn enhanced_beacon_interval() {
+        let mut pib = Pib::default();
+        assert_eq!(pib.enhanced_beacon_interval(), None);
+
+        pib.enhanced_beacon_order = 0;
+        assert_eq!(pib.enhanced_beacon_interval(), Some(Duration::new(960)));
+        pib.enhanced_beacon_order = 6;
+        assert_eq!(
+            pib.enhanced_beacon_interval(),
+            Some(Duration::new(960 * 64))
+        );
+    }
+
+    #[test]
+    fn next_sequence_number() {
+        let mut pib = Pib::default();
+        pib.randomize_sequence_numbers(0x00_30_20_ff);
+        assert_eq!((pib.dsn, pib.bsn, pib.ebsn), (0xff, 0x20, 0x30));
+
+        assert_eq!(
+            pib.next_sequence_number(FrameType::Data, FrameVersion::Ieee802154_2006),
+            0xff
+        );
+        // macDsn is shared by data frames and MAC commands and wraps around.
+        assert_eq!(
+            pib.next_sequence_number(FrameType::MacCommand, FrameVersion::Ieee802154_2003),
+            0x00
+        );
+        assert_eq!(
+            pib.next_sequence_number(FrameType::Beacon, FrameVersion::Ieee802154_2006),
+            0x20
+        );
+        assert_eq!(
+            pib.next_sequence_number(FrameType::Beacon, FrameVersion::Ieee802154),
+            0x30
+        );
+        assert_eq!((pib.dsn, pib.bsn, pib.ebsn), (0x01, 0x21, 0x31));
+    }
+
+    #[test]
+    fn reset_association() {
+        let mut pib = Pib {
+            pan_id: PanId::new_owned([0x34, 0x12]),
+            short_address: 0x0001,
+            associated_pan_coord: true,
+            coord_short_address: 0x0000,
+            coord_extended_address: Some([1; 8]),
+            ..Default::default()
+        };
+        pib.reset_association();
+
+        assert_eq!(pib.pan_id.into_u16(), 0xffff);
+        assert_eq!(pib.short_address, 0xffff);
+        assert!(!pib.associated_pan_coord);
+        assert_eq!(pib.coord_short_address, 0xffff);
+        assert_eq!(pib.coord_extended_address, None);
+    }
+
+    #[test]
+    fn channel_hopping_ie() {
+        let pib = Pib {
+            hopping_sequence_id: 1,
+            hopping_sequence: TschHoppingSequence::new(&[15, 25, 26, 20]).unwrap(),
+            current_hop: 3,
+            ..Default::default()
+        };
+
+        let ies = [pib.channel_hopping_ie_repr()];
+        let ies = IeReprList::new(&ies);
+        let mut buffer = [0; 26];
+        ies.write_ies(&mut buffer, false);
+        // Skip the Header Termination, MLME and Channel Hopping IE descriptors.
+        let mut ie = ChannelHoppingIe::new(&mut buffer[6..], false).unwrap();
+        pib.write_channel_hopping_ie(&mut ie);
+
+        let ie = ChannelHoppingIe::new(&buffer[6..], false).unwrap();
+        let mut joined_pib = Pib::default();
+        assert_eq!(joined_pib.read_channel_hopping_ie(&ie), Ok(()));
+        assert_eq!(joined_pib.hopping_sequence_id, 1);
+        assert_eq!(joined_pib.number_of_channels, 16);
+        assert_eq!(joined_pib.phy_configuration, 0x07ff_f800);
+        assert_eq!(joined_pib.hopping_sequence, pib.hopping_sequence);
+        assert_eq!(joined_pib.current_hop, 3);
+
+        // The current hop must be part of the hopping sequence.
+        buffer[24] = 4;
+        let ie = ChannelHoppingIe::new(&buffer[6..], false).unwrap();
+        let mut joined_pib = Pib::default();
+        assert_eq!(
+            joined_pib.read_channel_hopping_ie(&ie),
+            Err(SetError::InvalidParameter)
+        );
+        assert_eq!(joined_pib.hopping_sequence, TschHoppingSequence::default());
+    }
+}
@@ -1,21 +1,90 @@
 use crate::util::sync::HasAddress;
 
 pub use super::{
-    mcps::data::{DataIndication, DataRequest},
+    devices::{
+        AssociatedDevice, AssociatedDevices, SequentialShortAddressAllocator,
+        ShortAddressAllocator, MAC_MAX_ASSOCIATED_DEVICES,
+    },
+    mcps::{
+        data::{DataConfirm, DataError, DataIndication, DataRequest, PromiscuousIndication},
+        purge::{PurgeConfirm, PurgeError, PurgeRequest},
+    },
     mlme::{
-        beacon::{BeaconNotifyIndication, BeaconRequest},
-        set::SetRequestAttribute,
+        associate::{
+            AssociateConfirm, AssociateError, AssociateIndication, AssociateRequest,
+            AssociateResponse, AssociationStatus, CapabilityInformation,
+        },
+        beacon::{
+            BeaconConfirm, BeaconError, BeaconNotifyIndication, BeaconRequest, BeaconType,
+            SuperframeSpecification,
+        },
+        comm_status::{CommStatusError, CommStatusIndication},
+        disassociate::{
+            DisassociateConfirm, DisassociateError, DisassociateIndication, DisassociateRequest,
+            DisassociationReason,
+        },
+        get::{GetError, GetRequestAttribute},
+        poll::{PollConfirm, PollError, PollRequest},
+        scan::{
+            CoordAddress, EnergyDetectList, PanDescriptor, PanDescriptorList, ScanChannelIter,
+            ScanChannels, ScanConfirm, ScanError, ScanRequest, ScanType, TschSync,
+            MAC_MAX_ED_CHANNELS, MAC_MAX_PAN_DESCRIPTORS,
+        },
+        set::{SetError, SetRequestAttribute},
+        start::{StartConfirm, StartError, StartRequest},
+        sync_loss::{SyncLossIndication, SyncLossReason},
+        tsch::{
+            ScheduleOperation, SetLinkConfirm, SetLinkError, SetLinkRequest, SetSlotframeConfirm,
+            SetSlotframeError, SetSlotframeRequest, TschModeConfirm, TschModeError,
+            TschModeRequest,
+        },
+    },
+    neighbors::{
+        EvictionPolicy, MacNeighbor, Neighbor, NeighborTable, NeighborsTable, TableError,
+        ETX_DIVISOR, MAC_MAX_NEIGHBORS,
+    },
+    tsch::{
+        queue::MAC_TSCH_TX_QUEUE_CAPACITY,
+        schedule::{
+            TschHoppingSequence, TschLinkType, MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH,
+            MAC_TSCH_MAX_LINKS, MAC_TSCH_MAX_SLOTFRAMES, TSCH_DEFAULT_HOPPING_SEQUENCE,
+        },
     },
 };
 
 /// Enum representing all (currently) supported MAC services request primitives
 pub enum MacRequest {
+    /// IEEE 802.15.4-2020, section 8.2.6.1
+    MlmeGetRequest(GetRequestAttribute),
     /// IEEE 802.15.4-2020, section 8.2.6.4
     MlmeSetRequest(SetRequestAttribute),
+    /// IEEE 802.15.4-2020, section 8.2.3.1
+    MlmeAssociateRequest(AssociateRequest),
+    /// IEEE 802.15.4-2020, section 8.2.3.3
+    MlmeAssociateResponse(AssociateResponse),
+    /// IEEE 802.15.4-2020, section 8.2.4.1
+    MlmeDisassociateRequest(DisassociateRequest),
+    /// IEEE 802.15.4-2020, section 8.2.10.1
+    MlmeScanRequest(ScanRequest),
+    /// IEEE 802.15.4-2020, section 8.2.12.1
+    MlmeStartRequest(StartRequest),
     /// IEEE 802.15.4-2020, section 8.2.18.1
     MlmeBeaconRequest(BeaconRequest),
+    /// IEEE 802.15.4-2020, section 8.2.14.1
+    MlmePollRequest(PollRequest),
+    /// IEEE 802.15.4-2020, section 8.2.19.1
+    MlmeSetSlotframeRequest(SetSlotframeRequest),
+    /// IEEE 802.15.4-2020, section 8.2.19.3
+    MlmeSetLinkRequest(SetLinkRequest),
+    /// IEEE 802.15.4-2020, section 8.2.19.5
+    MlmeTschModeRequest(TschModeRequest),
     /// IEEE 802.15.4-2020, section 8.3.2
     McpsDataRequest(DataRequest),
+    /// IEEE 802.15.4-2020, section 8.3.5
+    McpsPurgeRequest(PurgeRequest),
+    /// Not part of the standard: Reads the neighbor table with the link
+    /// statistics maintained by the MAC sublayer.
+    NeighborsRequest,
 }
 
 /// Fake implementation to satisfy the generic channel.
@@ -28,9 +97,56 @@ impl HasAddress<()> for MacRequest {
     }
 }
 
+/// Enum representing all (currently) supported MAC services confirm primitives
+///
+/// Confirms are returned as responses to the corresponding [`MacRequest`].
+#[allow(clippy::large_enum_variant)]
+pub enum MacConfirm {
+    /// IEEE 802.15.4-2020, section 8.2.6.2
+    MlmeGet(Result<SetRequestAttribute, GetError>),
+    /// IEEE 802.15.4-2020, section 8.2.6.5
+    MlmeSet(Result<(), SetError>),
+    /// IEEE 802.15.4-2020, section 8.2.3.4
+    MlmeAssociate(AssociateConfirm),
+    /// IEEE 802.15.4-2020, section 8.2.5.2: Reports the outcome of an
+    /// MLME-ASSOCIATE.response.
+    MlmeCommStatus(CommStatusIndication),
+    /// IEEE 802.15.4-2020, section 8.2.4.3
+    MlmeDisassociate(DisassociateConfirm),
+    /// IEEE 802.15.4-2020, section 8.2.10.2
+    MlmeScan(ScanConfirm),
+    /// IEEE 802.15.4-2020, section 8.2.12.2
+    MlmeStart(StartConfirm),
+    /// IEEE 802.15.4-2020, section 8.2.18.2
+    MlmeBeacon(BeaconConfirm),
+    /// IEEE 802.15.4-2020, section 8.2.14.2
+    MlmePoll(PollConfirm),
+    /// IEEE 802.15.4-2020, section 8.2.19.2
+    MlmeSetSlotframe(SetSlotframeConfirm),
+    /// IEEE 802.15.4-2020, section 8.2.19.4
+    MlmeSetLink(SetLinkConfirm),
+    /// IEEE 802.15.4-2020, section 8.2.19.6
+    MlmeTschMode(TschModeConfirm),
+    /// IEEE 802.15.4-2020, section 8.3.3
+    McpsData(DataConfirm),
+    /// IEEE 802.15.4-2020, section 8.3.6
+    McpsPurge(PurgeConfirm),
+    /// Not part of the standard: A snapshot of the neighbor table.
+    Neighbors(NeighborTable),
+}
+
 pub enum MacIndication {
     McpsData(DataIndication),
+    /// IEEE 802.15.4-2020, section 8.2.3.2
+    MlmeAssociate(AssociateIndication),
+    /// IEEE 802.15.4-2020, section 8.2.4.2
+    MlmeDisassociate(DisassociateIndication),
     MlmeBeaconNotify(BeaconNotifyIndication),
+    /// IEEE 802.15.4-2020, section 8.2.13.2
+    MlmeSyncLoss(SyncLossIndication),
+    /// Any frame received in promiscuous mode, see IEEE 802.15.4-2020, section
+    /// 6.7.2
+    Promiscuous(PromiscuousIndication),
 }
 
 /// Fake implementation to satisfy the generic channel.
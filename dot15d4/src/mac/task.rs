@@ -1,4 +1,7 @@
-use crate::driver::{time::Frequency, DrvSvcRequest, DrvSvcResponse};
+use crate::driver::{
+    time::{Frequency, Instant},
+    DrvSvcRequest, DrvSvcResponse,
+};
 
 /// A MAC task represents a - possibly infinite - stream of driver
 /// request/response exchanges each of which MAY time out.
@@ -24,6 +27,8 @@ pub trait MacTask<F: Frequency> {
     /// - [`MacTaskEvent::Entry`]: The task has just been initialized.
     /// - [`MacTaskEvent::DrvSvcResponse`]: The driver service returned a
     ///   response to a pending request from the state machine.
+    /// - [`MacTaskEvent::TimerExpired`]: A timer previously requested by the
+    ///   task expired.
     /// - [`MacTaskEvent::Timeout`]: The transition timed out.
     /// - [`MacTaskEvent::Poll`]: The task previously returned
     fn step(self, event: MacTaskEvent) -> MacTaskTransition<Self, F>
@@ -41,6 +46,10 @@ pub enum MacTaskEvent {
     /// The driver service has produced a response to the driver service request
     /// previously produced by the state machine's request.
     DrvSvcResponse(DrvSvcResponse),
+
+    /// The timer previously requested by the state machine (see
+    /// [`MacTaskTransition::TimerRequest`]) expired.
+    TimerExpired,
 }
 
 /// Represents the transition triggered by a MAC task step.
@@ -70,6 +79,19 @@ pub enum MacTaskTransition<Task: MacTask<F>, F: Frequency> {
         Option<Task::Result>,
     ),
 
+    /// Signals to the executor that the state machine needs to wait until the
+    /// given instant before it can proceed, e.g. to back off before accessing
+    /// the channel.
+    ///
+    /// The executor will step the task with [`MacTaskEvent::TimerExpired`] once
+    /// the given instant has passed.
+    TimerRequest(
+        /// The task's next state.
+        Task,
+        /// The radio timer instant at which the task will be resumed.
+        Instant<F>,
+    ),
+
     /// Signals to the executor that the state machine exited, possibly with a
     /// final result.
     ///
@@ -0,0 +1,64 @@
+use core::future::pending;
+
+use crate::driver::{alarm::Alarm, time::Instant, RadioTimerApi};
+
+/// Timers requested by MAC tasks, indexed by MAC task.
+///
+/// Tasks request timers via [`super::task::MacTaskTransition::TimerRequest`].
+/// The MAC service waits for the earliest timer to expire and then steps the
+/// corresponding task with [`super::task::MacTaskEvent::TimerExpired`].
+pub(crate) struct MacTaskTimers<'alarm, Timer: RadioTimerApi, const NUM_TASKS: usize> {
+    /// Expiry (in radio timer ticks) of the timer requested by each task, if
+    /// any.
+    expiries: [Option<u64>; NUM_TASKS],
+    /// The MAC service's share of the radio timer alarm.
+    alarm: &'alarm Alarm<'alarm, Timer>,
+}
+
+impl<'alarm, Timer: RadioTimerApi, const NUM_TASKS: usize> MacTaskTimers<'alarm, Timer, NUM_TASKS> {
+    pub fn new(alarm: &'alarm Alarm<'alarm, Timer>) -> Self {
+        Self {
+            expiries: [None; NUM_TASKS],
+            alarm,
+        }
+    }
+
+    /// Starts a timer for the given task. A task may only wait for a single
+    /// timer at a time.
+    pub fn start(&mut self, task_index: usize, expiry: Instant<Timer>) {
+        let previous = self.expiries[task_index].replace(expiry.tick());
+        debug_assert!(previous.is_none());
+    }
+
+    /// Lets the timer of the given task expire immediately, e.g. to wake a
+    /// task waiting for an incoming frame. Does nothing if the task is not
+    /// waiting for a timer.
+    pub fn expire(&mut self, task_index: usize) {
+        if let Some(expiry) = self.expiries[task_index].as_mut() {
+            *expiry = 0;
+        }
+    }
+
+    fn next_expiry(&self) -> Option<(usize, u64)> {
+        self.expiries
+            .iter()
+            .enumerate()
+            .filter_map(|(task_index, expiry)| expiry.map(|expiry| (task_index, expiry)))
+            .min_by_key(|(_, expiry)| *expiry)
+    }
+
+    /// Waits until the next timer expires and returns the index of the
+    /// corresponding task.
+    ///
+    /// Pends forever if no timer is running. Cancellable: Timers started or
+    /// expired in the meantime are taken into account by the next call.
+    pub async fn wait_for_expiry(&mut self) -> usize {
+        let Some((task_index, expiry)) = self.next_expiry() else {
+            return pending().await;
+        };
+
+        self.alarm.wait_until(Instant::new(expiry)).await;
+        self.expiries[task_index] = None;
+        task_index
+    }
+}
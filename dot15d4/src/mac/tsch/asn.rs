@@ -1,9 +1,19 @@
 #![allow(dead_code)]
+use core::cmp::Ordering;
+
+use crate::{
+    driver::time::{Duration, Microseconds},
+    mac::frame::fields::TschTimeslotTimings,
+};
+
 /// The absolute slot number represents the total number of timeslots that has
 /// elapsed since the start of the network or an arbitrary start time
 /// determined by the PAN coordinator. It is stored as a 5-byte unsigned
 /// integer.
-#[derive(Default, Clone, Copy)]
+///
+/// Arithmetic operators wrap around at 2^40 slots. Use the `checked_*`
+/// methods to detect overflows.
+#[derive(Debug, Default, Clone, Copy)]
 pub struct AbsoluteSlotNumber {
     /// least significant 4 bytes of the absolute slot number
     ls4b: u32,
@@ -12,21 +22,121 @@ pub struct AbsoluteSlotNumber {
 }
 
 impl AbsoluteSlotNumber {
+    /// The number of bytes of an encoded ASN, e.g. in the TSCH
+    /// Synchronization IE or the CCM* nonce.
+    pub const LEN: usize = 5;
+
+    /// The largest ASN, 2^40 - 1.
+    pub const MAX: Self = Self {
+        ls4b: u32::MAX,
+        ms1b: u8::MAX,
+    };
+
+    /// Creates an ASN from the lower 40 bits of the given value.
+    const fn from_u64_wrapping(value: u64) -> Self {
+        Self {
+            ls4b: value as u32,
+            ms1b: (value >> 32) as u8,
+        }
+    }
+
+    /// Return the ASN as an integer.
+    pub const fn as_u64(self) -> u64 {
+        self.ls4b as u64 | ((self.ms1b as u64) << 32)
+    }
+
+    /// Decodes an ASN from its little-endian encoding.
+    pub const fn from_le_bytes(bytes: [u8; Self::LEN]) -> Self {
+        Self {
+            ls4b: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            ms1b: bytes[4],
+        }
+    }
+
+    /// Encodes the ASN in little-endian byte order.
+    pub const fn to_le_bytes(self) -> [u8; Self::LEN] {
+        let [b0, b1, b2, b3] = self.ls4b.to_le_bytes();
+        [b0, b1, b2, b3, self.ms1b]
+    }
+
+    /// Adds the given number of slots. Returns `None` if the result exceeds
+    /// [`Self::MAX`].
+    pub fn checked_add(self, slots: u64) -> Option<Self> {
+        let asn = self.as_u64().checked_add(slots)?;
+        if asn > Self::MAX.as_u64() {
+            return None;
+        }
+        Some(Self::from_u64_wrapping(asn))
+    }
+
+    /// Adds the given number of slots, wrapping around at 2^40.
+    pub fn wrapping_add(self, slots: u64) -> Self {
+        Self::from_u64_wrapping(self.as_u64().wrapping_add(slots))
+    }
+
+    /// Subtracts the given number of slots. Returns `None` if the result
+    /// would be negative.
+    pub fn checked_sub(self, slots: u64) -> Option<Self> {
+        self.as_u64()
+            .checked_sub(slots)
+            .map(Self::from_u64_wrapping)
+    }
+
+    /// Subtracts the given number of slots, wrapping around at 2^40.
+    pub fn wrapping_sub(self, slots: u64) -> Self {
+        Self::from_u64_wrapping(self.as_u64().wrapping_sub(slots))
+    }
+
+    /// The number of slots from the given earlier slot to this slot. Returns
+    /// `None` if the given slot is later.
+    pub fn checked_slots_since(self, earlier: Self) -> Option<u64> {
+        self.as_u64().checked_sub(earlier.as_u64())
+    }
+
+    /// The number of slots from the given slot to this slot, assuming that
+    /// the ASN wrapped around in between if the given slot is later.
+    pub fn wrapping_slots_since(self, earlier: Self) -> u64 {
+        self.wrapping_sub(earlier.as_u64()).as_u64()
+    }
+
+    /// The time from the start of the given earlier slot to the start of
+    /// this slot. Returns `None` if the given slot is later or the time
+    /// cannot be represented.
+    ///
+    /// * `earlier` - Absolute slot number of the earlier slot
+    /// * `timings` - Timings defining the timeslot length
+    pub fn duration_since(
+        self,
+        earlier: Self,
+        timings: &TschTimeslotTimings,
+    ) -> Option<Duration<Microseconds>> {
+        let slots = i64::try_from(self.checked_slots_since(earlier)?).ok()?;
+        let timeslot_length = timings.timeslot_length().ticks();
+        Some(Duration::new(slots.checked_mul(timeslot_length)?))
+    }
+
+    /// The ASN of the slot ongoing at the given time after the start of this
+    /// slot, wrapping around at 2^40. Negative durations refer to this slot.
+    ///
+    /// * `elapsed` - Time since the start of this slot
+    /// * `timings` - Timings defining the timeslot length
+    pub fn slot_after(
+        self,
+        elapsed: Duration<Microseconds>,
+        timings: &TschTimeslotTimings,
+    ) -> Self {
+        let timeslot_length = timings.timeslot_length().ticks().max(1);
+        let slots = elapsed.ticks().max(0) / timeslot_length;
+        self.wrapping_add(slots as u64)
+    }
+
     /// Increments the ASN by one slot
     pub fn increment(&mut self) {
-        let ls4b = self.ls4b;
-        self.ls4b += 1;
-        if ls4b > self.ls4b {
-            self.ms1b += 1;
-        }
+        *self = self.wrapping_add(1);
     }
     /// Decrements the ASN by one slot
     pub fn decrement(&mut self) {
-        let ls4b = self.ls4b;
-        self.ls4b -= 1;
-        if ls4b < self.ls4b {
-            self.ms1b -= 1;
-        }
+        *self = self.wrapping_sub(1);
     }
 }
 
@@ -36,6 +146,8 @@ impl PartialEq for AbsoluteSlotNumber {
     }
 }
 
+impl Eq for AbsoluteSlotNumber {}
+
 impl PartialEq<i64> for AbsoluteSlotNumber {
     fn eq(&self, other: &i64) -> bool {
         match Self::try_from(*other) {
@@ -46,14 +158,22 @@ impl PartialEq<i64> for AbsoluteSlotNumber {
 }
 
 impl PartialOrd for AbsoluteSlotNumber {
-    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        if self.ms1b < other.ms1b || self.ls4b < other.ls4b {
-            Some(core::cmp::Ordering::Less)
-        } else if self.ls4b > other.ls4b {
-            Some(core::cmp::Ordering::Greater)
-        } else {
-            Some(core::cmp::Ordering::Equal)
-        }
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AbsoluteSlotNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ms1b.cmp(&other.ms1b).then(self.ls4b.cmp(&other.ls4b))
+    }
+}
+
+impl core::ops::Add<u64> for AbsoluteSlotNumber {
+    type Output = Self;
+
+    fn add(self, rhs: u64) -> Self::Output {
+        self.wrapping_add(rhs)
     }
 }
 
@@ -61,12 +181,7 @@ impl core::ops::Add<u32> for AbsoluteSlotNumber {
     type Output = Self;
 
     fn add(self, rhs: u32) -> Self::Output {
-        let ls4b = self.ls4b + rhs;
-        let mut ms1b = self.ms1b;
-        if ls4b < self.ls4b {
-            ms1b += 1;
-        }
-        Self { ls4b, ms1b }
+        self.wrapping_add(rhs as u64)
     }
 }
 
@@ -74,12 +189,7 @@ impl core::ops::Add<u16> for AbsoluteSlotNumber {
     type Output = Self;
 
     fn add(self, rhs: u16) -> Self::Output {
-        let ls4b = self.ls4b + rhs as u32;
-        let mut ms1b = self.ms1b;
-        if ls4b < self.ls4b {
-            ms1b += 1;
-        }
-        Self { ls4b, ms1b }
+        self.wrapping_add(rhs as u64)
     }
 }
 
@@ -87,12 +197,7 @@ impl core::ops::Add<i32> for AbsoluteSlotNumber {
     type Output = Self;
 
     fn add(self, rhs: i32) -> Self::Output {
-        let ls4b = self.ls4b + rhs as u32;
-        let mut ms1b = self.ms1b;
-        if ls4b < self.ls4b {
-            ms1b += 1;
-        }
-        Self { ls4b, ms1b }
+        Self::from_u64_wrapping(self.as_u64().wrapping_add_signed(rhs as i64))
     }
 }
 
@@ -100,20 +205,16 @@ impl core::ops::Sub<u32> for AbsoluteSlotNumber {
     type Output = Self;
 
     fn sub(self, rhs: u32) -> Self::Output {
-        let ls4b = self.ls4b - rhs;
-        let mut ms1b = self.ms1b;
-        if ls4b > self.ls4b {
-            ms1b -= 1;
-        }
-        Self { ls4b, ms1b }
+        self.wrapping_sub(rhs as u64)
     }
 }
 
 impl core::ops::Sub<AbsoluteSlotNumber> for AbsoluteSlotNumber {
-    type Output = u32;
+    type Output = u64;
 
+    /// See [`AbsoluteSlotNumber::wrapping_slots_since()`].
     fn sub(self, rhs: AbsoluteSlotNumber) -> Self::Output {
-        self.ls4b - rhs.ls4b
+        self.wrapping_slots_since(rhs)
     }
 }
 
@@ -121,25 +222,28 @@ impl core::ops::Rem<u16> for AbsoluteSlotNumber {
     type Output = u16;
 
     fn rem(self, rhs: u16) -> u16 {
-        let remainder = ((0xffffffff % (rhs as u32)) + 1) % (rhs as u32);
-        let mut result = self.ls4b % rhs as u32;
-        result += self.ms1b as u32 * remainder % rhs as u32;
-        result as u16
+        (self.as_u64() % rhs as u64) as u16
     }
 }
 
-impl TryFrom<i64> for AbsoluteSlotNumber {
+impl TryFrom<u64> for AbsoluteSlotNumber {
     type Error = ();
 
-    fn try_from(value: i64) -> Result<Self, Self::Error> {
-        const MAX_VALUE: i64 = 0xffffffffff;
-        if !(0..=MAX_VALUE).contains(&value) {
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        if value > Self::MAX.as_u64() {
             return Err(());
         }
-        Ok(Self {
-            ls4b: (value & 0xffffffff) as u32,
-            ms1b: ((value & 0xff00000000) >> 32) as u8,
-        })
+        Ok(Self::from_u64_wrapping(value))
+    }
+}
+
+impl TryFrom<i64> for AbsoluteSlotNumber {
+    type Error = ();
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        u64::try_from(value)
+            .map_err(|_| ())
+            .and_then(Self::try_from)
     }
 }
 
@@ -147,14 +251,7 @@ impl TryFrom<i32> for AbsoluteSlotNumber {
     type Error = ();
 
     fn try_from(value: i32) -> Result<Self, Self::Error> {
-        if value < 0 {
-            Err(())
-        } else {
-            Ok(Self {
-                ls4b: value as u32,
-                ms1b: 0,
-            })
-        }
+        i64::from(value).try_into()
     }
 }
 
@@ -169,11 +266,17 @@ impl TryFrom<u32> for AbsoluteSlotNumber {
     }
 }
 
+impl From<AbsoluteSlotNumber> for u64 {
+    fn from(value: AbsoluteSlotNumber) -> Self {
+        value.as_u64()
+    }
+}
+
 impl TryInto<i64> for AbsoluteSlotNumber {
     type Error = ();
 
     fn try_into(self) -> Result<i64, Self::Error> {
-        Ok(self.ls4b as i64 + ((self.ms1b as i64) << 32))
+        Ok(self.as_u64() as i64)
     }
 }
 
@@ -191,7 +294,7 @@ impl TryInto<u32> for AbsoluteSlotNumber {
 #[cfg(test)]
 pub mod tests {
     const MAX_VALUE: i64 = 0xffffffffff;
-    use super::AbsoluteSlotNumber;
+    use super::*;
 
     #[test]
     fn asn_conversions() {
@@ -222,4 +325,116 @@ pub mod tests {
         asn1.decrement();
         assert!(asn1 == 42);
     }
+
+    #[test]
+    fn asn_encoding() {
+        let asn = AbsoluteSlotNumber::from_le_bytes([0x78, 0x56, 0x34, 0x12, 0xab]);
+        assert_eq!(asn.as_u64(), 0xab_1234_5678);
+        assert_eq!(asn.to_le_bytes(), [0x78, 0x56, 0x34, 0x12, 0xab]);
+        assert_eq!(
+            AbsoluteSlotNumber::MAX.to_le_bytes(),
+            [0xff; AbsoluteSlotNumber::LEN]
+        );
+    }
+
+    #[test]
+    fn asn_wraparound() {
+        let mut asn = AbsoluteSlotNumber::MAX;
+        assert_eq!(asn.checked_add(1), None);
+        assert!(asn + 1u32 == 0);
+        asn.increment();
+        assert!(asn == 0);
+        assert_eq!(asn.checked_sub(1), None);
+        asn.decrement();
+        assert_eq!(asn, AbsoluteSlotNumber::MAX);
+
+        // Crossing the boundary between the lower and the upper bytes.
+        let asn = AbsoluteSlotNumber::try_from(u32::MAX).unwrap();
+        assert!(asn + 1u16 == 0x1_0000_0000);
+        assert!(asn > AbsoluteSlotNumber::try_from(0x1_0000_0000_u64 - 2).unwrap());
+        assert!(asn < AbsoluteSlotNumber::try_from(0x1_0000_0000_u64).unwrap());
+
+        // The distance across a wraparound.
+        let later = AbsoluteSlotNumber::MAX + 3u32;
+        assert_eq!(later.checked_slots_since(AbsoluteSlotNumber::MAX), None);
+        assert_eq!(later - AbsoluteSlotNumber::MAX, 3);
+        assert!(later + -3 == AbsoluteSlotNumber::MAX.as_u64() as i64);
+    }
+
+    #[test]
+    fn asn_to_time() {
+        let timings = TschTimeslotTimings::default();
+        let timeslot_length = timings.timeslot_length().ticks();
+        let asn = AbsoluteSlotNumber::try_from(1000).unwrap();
+
+        let later = asn + 5u32;
+        assert_eq!(
+            later.duration_since(asn, &timings),
+            Some(Duration::new(5 * timeslot_length))
+        );
+        assert_eq!(asn.duration_since(later, &timings), None);
+
+        assert_eq!(asn.slot_after(Duration::new(-1), &timings), asn);
+        assert_eq!(
+            asn.slot_after(Duration::new(timeslot_length - 1), &timings),
+            asn
+        );
+        assert_eq!(
+            asn.slot_after(Duration::new(5 * timeslot_length), &timings),
+            later
+        );
+    }
+
+    /// Generates pseudo-random ASNs that are biased towards the boundaries of
+    /// the lower and upper bytes.
+    fn random_asns() -> impl Iterator<Item = u64> {
+        const MODULUS: u64 = 1 << 40;
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        (0..10_000).map(move |i| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let offset = state % 16;
+            match i % 4 {
+                0 => offset,
+                1 => MODULUS - 1 - offset,
+                2 => (1 << 32) - 8 + offset,
+                _ => state % MODULUS,
+            }
+        })
+    }
+
+    #[test]
+    fn asn_arithmetic_matches_u64() {
+        const MODULUS: u64 = 1 << 40;
+        let asns = random_asns().zip(random_asns().skip(1));
+
+        for (a, b) in asns {
+            let asn_a = AbsoluteSlotNumber::try_from(a).unwrap();
+            let asn_b = AbsoluteSlotNumber::try_from(b).unwrap();
+
+            assert_eq!(
+                AbsoluteSlotNumber::from_le_bytes(asn_a.to_le_bytes()),
+                asn_a
+            );
+            assert_eq!(asn_a.cmp(&asn_b), a.cmp(&b));
+            assert_eq!(asn_a == asn_b, a == b);
+
+            let sum = a + b;
+            assert_eq!(asn_a.wrapping_add(b).as_u64(), sum % MODULUS);
+            assert_eq!(
+                asn_a.checked_add(b).map(u64::from),
+                Some(sum).filter(|sum| *sum < MODULUS)
+            );
+            assert_eq!(asn_a.wrapping_sub(b).as_u64(), (a + MODULUS - b) % MODULUS);
+            assert_eq!(asn_a.checked_sub(b).map(u64::from), a.checked_sub(b));
+            assert_eq!(asn_a.checked_slots_since(asn_b), a.checked_sub(b));
+            assert_eq!(asn_a - asn_b, (a + MODULUS - b) % MODULUS);
+            assert_eq!(asn_b.wrapping_add(asn_a - asn_b), asn_a);
+
+            let divisor = (b % u16::MAX as u64) as u16 + 1;
+            assert_eq!((asn_a % divisor) as u64, a % divisor as u64);
+        }
+    }
 }
@@ -0,0 +1,576 @@
+//! Joining a TSCH network, see IEEE 802.15.4-2020, section 6.3.6.
+//!
+//! Coordinators advertise the network in Enhanced Beacons carrying the TSCH
+//! Synchronization, TSCH Timeslot, Channel Hopping and TSCH Slotframe and Link
+//! IEs. A joining device adopts the ASN, timeslot timings, hopping sequence
+//! and schedule announced by these IEs.
+
+use crate::{
+    driver::{
+        frame::PanId,
+        time::{Duration, Microseconds},
+    },
+    mac::{
+        frame::{
+            fields::{
+                ChannelHoppingIe, NestedIe, TschLinkOption, TschSynchronizationIe,
+                TschTimeslotTimings,
+            },
+            repr::{
+                CHANNEL_HOPPING_SUB_ID, TSCH_SLOTFRAME_AND_LINK_SUB_ID,
+                TSCH_SYNCHRONIZATION_SUB_ID, TSCH_TIMESLOT_SUB_ID,
+            },
+        },
+        mlme::scan::{parse_beacon_ies, CoordAddress, PanDescriptor},
+        neighbors::Neighbor,
+        pib::Pib,
+    },
+};
+
+use super::{
+    asn::AbsoluteSlotNumber,
+    schedule::{
+        MacTschSchedule, ScheduleError, TschHoppingSequence, TschLink, TschLinkType, TschSlotframe,
+        MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH, MAC_TSCH_MAX_LINKS, MAC_TSCH_MAX_SLOTFRAMES,
+    },
+};
+
+/// The length of the full TSCH Timeslot IE content: the Timeslot ID followed
+/// by twelve 2-byte timings.
+const TSCH_TIMESLOT_IE_LEN: usize = 25;
+/// The max length of a supported Channel Hopping IE content.
+const MAX_CHANNEL_HOPPING_IE_LEN: usize = 12 + 2 * MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH;
+/// Slotframe Handle, Slotframe Size and Number of Links fields.
+const SLOTFRAME_DESCRIPTOR_HDR_LEN: usize = 4;
+/// Timeslot, Channel Offset and Link Options fields.
+const LINK_INFO_LEN: usize = 5;
+
+/// A link advertised in a TSCH Slotframe and Link IE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TschLinkDescriptor {
+    pub timeslot: u16,
+    pub channel_offset: u16,
+    pub link_options: TschLinkOption,
+}
+
+/// A slotframe advertised in a TSCH Slotframe and Link IE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TschSlotframeDescriptor {
+    pub handle: u8,
+    pub size: u16,
+    pub links: heapless::Vec<TschLinkDescriptor, MAC_TSCH_MAX_LINKS>,
+}
+
+/// The network parameters announced by an Enhanced Beacon of a TSCH network.
+#[derive(Debug)]
+pub(crate) struct TschJoinInfo {
+    /// The sender of the beacon, i.e. the time source of the joining device.
+    pub coord_address: CoordAddress,
+    pub coord_pan_id: PanId<[u8; 2]>,
+    /// Radio clock tick at which the RMARKER of the beacon passed the local
+    /// antenna.
+    pub timestamp: u64,
+    /// The ASN of the slot in which the beacon was sent.
+    pub asn: AbsoluteSlotNumber,
+    /// The join metric of the sender.
+    pub join_metric: u8,
+    /// The announced timeslot timings, the default timings if the beacon has
+    /// no TSCH Timeslot IE.
+    pub timeslot_timings: TschTimeslotTimings,
+    /// The content of the Channel Hopping IE, if present.
+    channel_hopping_ie: Option<heapless::Vec<u8, MAX_CHANNEL_HOPPING_IE_LEN>>,
+    /// The announced slotframes, empty if the beacon has no TSCH Slotframe
+    /// and Link IE.
+    pub slotframes: heapless::Vec<TschSlotframeDescriptor, MAC_TSCH_MAX_SLOTFRAMES>,
+}
+
+impl TschJoinInfo {
+    /// Extracts the network parameters from the given Enhanced Beacon MPDU
+    /// (without FCS) and the PAN descriptor parsed from it.
+    ///
+    /// Returns `None` if the beacon has no TSCH Synchronization IE or no
+    /// timestamp, or if it announces parameters that are not supported, e.g.
+    /// a schedule that exceeds the capacity of the MAC's TSCH schedule.
+    pub fn from_beacon(mpdu: &[u8], pan_descriptor: &PanDescriptor) -> Option<Self> {
+        let timestamp = pan_descriptor.timestamp?;
+        let tsch_sync = pan_descriptor.tsch_sync?;
+
+        let mut timeslot_timings = TschTimeslotTimings::default();
+        let mut channel_hopping_ie = None;
+        let mut slotframes = heapless::Vec::new();
+        let mut is_supported = true;
+        for nested_ie in parse_beacon_ies(mpdu)?.nested_ies() {
+            let content = &mpdu[nested_ie.content];
+            let parsed = match (nested_ie.is_long, nested_ie.sub_id) {
+                (false, TSCH_TIMESLOT_SUB_ID) => {
+                    parse_timeslot_ie(content).map(|timings| timeslot_timings = timings)
+                }
+                (false, TSCH_SLOTFRAME_AND_LINK_SUB_ID) => {
+                    parse_slotframe_and_link_ie(content).map(|descriptors| slotframes = descriptors)
+                }
+                (true, CHANNEL_HOPPING_SUB_ID) => {
+                    parse_channel_hopping_ie(content).map(|ie| channel_hopping_ie = Some(ie))
+                }
+                _ => Some(()),
+            };
+            is_supported &= parsed.is_some();
+        }
+
+        is_supported.then_some(Self {
+            coord_address: pan_descriptor.coord_address,
+            coord_pan_id: pan_descriptor.coord_pan_id,
+            timestamp,
+            asn: AbsoluteSlotNumber::try_from(tsch_sync.asn).ok()?,
+            join_metric: tsch_sync.join_metric,
+            timeslot_timings,
+            channel_hopping_ie,
+            slotframes,
+        })
+    }
+
+    /// The Channel Hopping IE of the beacon, if present. Its hopping sequence
+    /// is supported.
+    pub fn channel_hopping_ie(&self) -> Option<ChannelHoppingIe<&[u8]>> {
+        // Safety: The IE was validated while parsing the beacon.
+        self.channel_hopping_ie
+            .as_ref()
+            .map(|ie| ChannelHoppingIe::new(ie.as_slice(), false).unwrap())
+    }
+
+    /// The hopping sequence announced by the Channel Hopping IE, if the
+    /// beacon carries a full one.
+    pub fn hopping_sequence(&self) -> Option<TschHoppingSequence> {
+        TschHoppingSequence::from_channel_hopping_ie(&self.channel_hopping_ie()?)
+    }
+
+    /// Creates the announced slotframes. Their links are normal links to any
+    /// neighbor.
+    ///
+    /// * `hopping_sequence` - Hopping sequence of the slotframes
+    pub fn create_slotframes(
+        &self,
+        hopping_sequence: TschHoppingSequence,
+    ) -> Result<
+        heapless::Vec<TschSlotframe<MAC_TSCH_MAX_LINKS, Neighbor>, MAC_TSCH_MAX_SLOTFRAMES>,
+        ScheduleError,
+    > {
+        let mut slotframes = heapless::Vec::<_, MAC_TSCH_MAX_SLOTFRAMES>::new();
+        let mut link_handle = 0;
+        for descriptor in &self.slotframes {
+            if descriptor.size == 0 {
+                return Err(ScheduleError::InvalidTimeslot);
+            }
+            let mut slotframe =
+                TschSlotframe::new(descriptor.handle as u16, descriptor.size, hopping_sequence);
+            for link in &descriptor.links {
+                slotframe.add_link(TschLink::new(
+                    link_handle,
+                    link.timeslot,
+                    link.channel_offset,
+                    link.link_options,
+                    TschLinkType::Normal,
+                    None,
+                ))?;
+                link_handle += 1;
+            }
+            if slotframes
+                .iter()
+                .any(|s: &TschSlotframe<_, _>| s.handle() == slotframe.handle())
+            {
+                return Err(ScheduleError::HandleDuplicate);
+            }
+            // Safety: There are at most MAC_TSCH_MAX_SLOTFRAMES descriptors.
+            let _ = slotframes.push(slotframe);
+        }
+        Ok(slotframes)
+    }
+}
+
+/// Parses the content of a TSCH Timeslot IE, see IEEE 802.15.4-2020, section
+/// 7.4.4.4. A reduced IE refers to the default timings.
+fn parse_timeslot_ie(content: &[u8]) -> Option<TschTimeslotTimings> {
+    match content.len() {
+        1 if content[0] == 0 => Some(TschTimeslotTimings::default()),
+        TSCH_TIMESLOT_IE_LEN => {
+            let field = |index: usize| {
+                let offset = 1 + 2 * index;
+                Duration::<Microseconds>::new(u16::from_le_bytes([
+                    content[offset],
+                    content[offset + 1],
+                ]) as i64)
+            };
+            let mut timings =
+                TschTimeslotTimings::new(content[0], TschTimeslotTimings::DEFAULT_GUARD_TIME);
+            timings.set_cca_offset(field(0));
+            timings.set_cca(field(1));
+            timings.set_tx_offset(field(2));
+            timings.set_rx_offset(field(3));
+            timings.set_rx_ack_delay(field(4));
+            timings.set_tx_ack_delay(field(5));
+            timings.set_rx_wait(field(6));
+            timings.set_ack_wait(field(7));
+            timings.set_rx_tx(field(8));
+            timings.set_max_ack(field(9));
+            timings.set_max_tx(field(10));
+            timings.set_timeslot_length(field(11));
+            Some(timings)
+        }
+        // Other timeslot IDs are unknown.
+        _ => None,
+    }
+}
+
+/// Writes the given timings into the content of a TSCH Timeslot IE. A
+/// reduced IE only carries the Timeslot ID.
+fn write_timeslot_ie(content: &mut [u8], timings: &TschTimeslotTimings) {
+    content[0] = timings.id();
+    if content.len() != TSCH_TIMESLOT_IE_LEN {
+        return;
+    }
+
+    let fields = [
+        timings.cca_offset(),
+        timings.cca(),
+        timings.tx_offset(),
+        timings.rx_offset(),
+        timings.rx_ack_delay(),
+        timings.tx_ack_delay(),
+        timings.rx_wait(),
+        timings.ack_wait(),
+        timings.rx_tx(),
+        timings.max_ack(),
+        timings.max_tx(),
+        timings.timeslot_length(),
+    ];
+    for (field, bytes) in fields.iter().zip(content[1..].chunks_exact_mut(2)) {
+        bytes.copy_from_slice(&(field.ticks() as u16).to_le_bytes());
+    }
+}
+
+/// Parses the content of a TSCH Slotframe and Link IE, see IEEE
+/// 802.15.4-2020, section 7.4.4.3.
+fn parse_slotframe_and_link_ie(
+    content: &[u8],
+) -> Option<heapless::Vec<TschSlotframeDescriptor, MAC_TSCH_MAX_SLOTFRAMES>> {
+    let (&num_slotframes, mut remaining) = content.split_first()?;
+
+    let mut slotframes = heapless::Vec::new();
+    for _ in 0..num_slotframes {
+        let header = remaining.get(..SLOTFRAME_DESCRIPTOR_HDR_LEN)?;
+        let num_links = header[3] as usize;
+        let link_infos = remaining.get(
+            SLOTFRAME_DESCRIPTOR_HDR_LEN..SLOTFRAME_DESCRIPTOR_HDR_LEN + num_links * LINK_INFO_LEN,
+        )?;
+
+        let mut links = heapless::Vec::new();
+        for link_info in link_infos.chunks_exact(LINK_INFO_LEN) {
+            let link = TschLinkDescriptor {
+                timeslot: u16::from_le_bytes([link_info[0], link_info[1]]),
+                channel_offset: u16::from_le_bytes([link_info[2], link_info[3]]),
+                link_options: TschLinkOption::from_bits_truncate(link_info[4]),
+            };
+            links.push(link).ok()?;
+        }
+        let slotframe = TschSlotframeDescriptor {
+            handle: header[0],
+            size: u16::from_le_bytes([header[1], header[2]]),
+            links,
+        };
+        slotframes.push(slotframe).ok()?;
+        remaining = &remaining[SLOTFRAME_DESCRIPTOR_HDR_LEN + link_infos.len()..];
+    }
+
+    remaining.is_empty().then_some(slotframes)
+}
+
+/// Writes the slotframes of the given schedule into the content of a TSCH
+/// Slotframe and Link IE. The number of slotframes and links is given by the
+/// IE. Fields without matching slotframe or link remain unchanged.
+fn write_slotframe_and_link_ie(content: &mut [u8], schedule: &MacTschSchedule) {
+    let num_slotframes = content[0] as usize;
+    let mut offset = 1;
+    for index in 0..num_slotframes {
+        let Some(header) = content.get_mut(offset..offset + SLOTFRAME_DESCRIPTOR_HDR_LEN) else {
+            return;
+        };
+        let num_links = header[3] as usize;
+        let slotframe = schedule.slotframes().get(index);
+        if let Some(slotframe) = slotframe {
+            header[0] = slotframe.handle() as u8;
+            header[1..3].copy_from_slice(&slotframe.size().to_le_bytes());
+        }
+        offset += SLOTFRAME_DESCRIPTOR_HDR_LEN;
+
+        let links = slotframe
+            .map(|slotframe| slotframe.links())
+            .unwrap_or_default();
+        for link_index in 0..num_links {
+            let Some(link_info) = content.get_mut(offset..offset + LINK_INFO_LEN) else {
+                return;
+            };
+            if let Some(link) = links.get(link_index) {
+                link_info[..2].copy_from_slice(&link.timeslot().to_le_bytes());
+                link_info[2..4].copy_from_slice(&link.channel_offset().to_le_bytes());
+                link_info[4] = link.link_options().bits();
+            }
+            offset += LINK_INFO_LEN;
+        }
+    }
+}
+
+/// Copies the content of a Channel Hopping IE if it announces a supported
+/// hopping sequence.
+fn parse_channel_hopping_ie(
+    content: &[u8],
+) -> Option<heapless::Vec<u8, MAX_CHANNEL_HOPPING_IE_LEN>> {
+    let ie = ChannelHoppingIe::new(content, false).ok()?;
+    if !ie.is_reduced() {
+        let hopping_sequence = TschHoppingSequence::from_channel_hopping_ie(&ie)?;
+        if ie.current_hop() as usize >= hopping_sequence.len() {
+            return None;
+        }
+    }
+    heapless::Vec::from_slice(content).ok()
+}
+
+/// Fills in the TSCH IEs of an Enhanced Beacon (MPDU without FCS) sent in the
+/// slot with the given ASN, i.e. the TSCH Synchronization, TSCH Timeslot,
+/// Channel Hopping and TSCH Slotframe and Link IEs. Other IEs remain
+/// unchanged.
+pub(crate) fn write_tsch_ies(
+    mpdu: &mut [u8],
+    asn: AbsoluteSlotNumber,
+    schedule: &MacTschSchedule,
+    pib: &Pib,
+) {
+    let mut nested_ies = heapless::Vec::<NestedIe, 4>::new();
+    if let Some(ies) = parse_beacon_ies(mpdu) {
+        for nested_ie in ies.nested_ies().filter(|nested_ie| {
+            matches!(
+                (nested_ie.is_long, nested_ie.sub_id),
+                (
+                    false,
+                    TSCH_SYNCHRONIZATION_SUB_ID
+                        | TSCH_TIMESLOT_SUB_ID
+                        | TSCH_SLOTFRAME_AND_LINK_SUB_ID
+                ) | (true, CHANNEL_HOPPING_SUB_ID)
+            )
+        }) {
+            let _ = nested_ies.push(nested_ie);
+        }
+    }
+
+    for nested_ie in nested_ies {
+        let content = &mut mpdu[nested_ie.content];
+        match (nested_ie.is_long, nested_ie.sub_id) {
+            (false, TSCH_SYNCHRONIZATION_SUB_ID) => {
+                if let Ok(mut ie) = TschSynchronizationIe::new(content) {
+                    ie.set_asn(asn.as_u64());
+                    ie.set_join_metric(schedule.join_metric().min(u8::MAX as u16) as u8);
+                }
+            }
+            (false, TSCH_TIMESLOT_SUB_ID) if !content.is_empty() => {
+                write_timeslot_ie(content, &schedule.timeslot_timings());
+            }
+            (false, TSCH_SLOTFRAME_AND_LINK_SUB_ID) if !content.is_empty() => {
+                write_slotframe_and_link_ie(content, schedule);
+            }
+            (true, CHANNEL_HOPPING_SUB_ID) => {
+                if let Ok(mut ie) = ChannelHoppingIe::new(content, false) {
+                    pib.write_channel_hopping_ie(&mut ie);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::driver::{config::Channel, tasks::RxInfo};
+
+    const RX_INFO: RxInfo = RxInfo {
+        timestamp: Some(1234),
+        lqi: 200,
+        rssi: Some(-60),
+    };
+
+    /// An Enhanced Beacon with zeroed TSCH IEs as built from the IE
+    /// representations: a TSCH Synchronization IE, a full TSCH Timeslot IE, a
+    /// Channel Hopping IE with 4 channels and a TSCH Slotframe and Link IE
+    /// with one slotframe of two links.
+    #[rustfmt::skip]
+    fn enhanced_beacon() -> heapless::Vec<u8, 127> {
+        let mut mpdu = heapless::Vec::new();
+        mpdu.extend_from_slice(&[
+            // Frame control: beacon, 2015, PAN ID compression, IEs present,
+            // short destination address, extended source address
+            0x40, 0xea,
+            // Sequence number
+            0x01,
+            // Destination PAN ID and address
+            0xcd, 0xab, 0xff, 0xff,
+            // Source address
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            // Header Termination 1 IE
+            0x00, 0x3f,
+            // MLME IE
+            0x4a, 0x88,
+            // TSCH Synchronization IE
+            0x06, 0x1a,
+        ]).unwrap();
+        mpdu.extend_from_slice(&[0; 6]).unwrap();
+        // TSCH Timeslot IE
+        mpdu.extend_from_slice(&[0x19, 0x1c]).unwrap();
+        mpdu.extend_from_slice(&[0; 25]).unwrap();
+        mpdu.extend_from_slice(&[
+            // Channel Hopping IE: number of channels, hopping sequence length
+            0x14, 0xc8,
+            0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00,
+        ]).unwrap();
+        mpdu.extend_from_slice(&[0; 10]).unwrap();
+        mpdu.extend_from_slice(&[
+            // TSCH Slotframe and Link IE: one slotframe with two links
+            0x0f, 0x1b,
+            0x01, 0x00, 0x00, 0x00, 0x02,
+        ]).unwrap();
+        mpdu.extend_from_slice(&[0; 10]).unwrap();
+        mpdu
+    }
+
+    #[test]
+    fn advertise_and_join() {
+        let pib = Pib {
+            hopping_sequence_id: 1,
+            hopping_sequence: TschHoppingSequence::new(&[15, 25, 26, 20]).unwrap(),
+            current_hop: 1,
+            ..Default::default()
+        };
+        let mut timings = TschTimeslotTimings::new(1, Duration::new(2000));
+        timings.set_timeslot_length(Duration::new(15000));
+
+        let mut schedule = MacTschSchedule::new();
+        schedule.set_timeslot_timings(timings);
+        schedule.set_join_metric(2);
+        schedule
+            .add_slotframe(TschSlotframe::new(3, 7, pib.hopping_sequence))
+            .unwrap();
+        let shared = TschLinkOption::Tx | TschLinkOption::Rx | TschLinkOption::Shared;
+        schedule
+            .add_link(
+                3,
+                TschLink::new(0, 0, 0, shared, TschLinkType::Advertising, None),
+            )
+            .unwrap();
+        schedule
+            .add_link(
+                3,
+                TschLink::new(1, 4, 3, TschLinkOption::Rx, TschLinkType::Normal, None),
+            )
+            .unwrap();
+
+        let mut mpdu = enhanced_beacon();
+        let asn = AbsoluteSlotNumber::try_from(0x0102030405_u64).unwrap();
+        write_tsch_ies(&mut mpdu, asn, &schedule, &pib);
+
+        let pan_descriptor = PanDescriptor::from_beacon(&mpdu, Channel::_20, &RX_INFO).unwrap();
+        let join_info = TschJoinInfo::from_beacon(&mpdu, &pan_descriptor).unwrap();
+        assert_eq!(join_info.coord_address, pan_descriptor.coord_address);
+        assert_eq!(join_info.coord_pan_id.into_u16(), 0xabcd);
+        assert_eq!(join_info.timestamp, 1234);
+        assert_eq!(join_info.asn, asn);
+        assert_eq!(join_info.join_metric, 2);
+        assert_eq!(join_info.timeslot_timings, timings);
+
+        let hopping_sequence = join_info.hopping_sequence().unwrap();
+        assert_eq!(hopping_sequence, pib.hopping_sequence);
+        let mut joined_pib = Pib::default();
+        assert_eq!(
+            joined_pib.read_channel_hopping_ie(&join_info.channel_hopping_ie().unwrap()),
+            Ok(())
+        );
+        assert_eq!(joined_pib.hopping_sequence_id, 1);
+        assert_eq!(joined_pib.current_hop, 1);
+
+        assert_eq!(join_info.slotframes.len(), 1);
+        let slotframes = join_info.create_slotframes(hopping_sequence).unwrap();
+        let slotframe = &slotframes[0];
+        assert_eq!((slotframe.handle(), slotframe.size()), (3, 7));
+        let links: heapless::Vec<_, 2> = slotframe
+            .links()
+            .iter()
+            .map(|link| {
+                (
+                    link.handle(),
+                    link.timeslot(),
+                    link.channel_offset(),
+                    link.link_options(),
+                    link.link_type(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (0, 0, 0, shared, TschLinkType::Normal),
+                (1, 4, 3, TschLinkOption::Rx, TschLinkType::Normal)
+            ]
+        );
+
+        // Channel offsets must be part of the hopping sequence.
+        assert_eq!(
+            join_info
+                .create_slotframes(TschHoppingSequence::new(&[11, 12]).unwrap())
+                .err(),
+            Some(ScheduleError::InvalidChannelOffset)
+        );
+    }
+
+    #[test]
+    fn reject_unsupported_beacons() {
+        #[rustfmt::skip]
+        let mut mpdu = [
+            // Frame control: beacon, 2015, PAN ID compression, IEs present,
+            // short destination address, short source address
+            0x40, 0xaa,
+            // Sequence number
+            0x01,
+            // Destination PAN ID and address
+            0xcd, 0xab, 0xff, 0xff,
+            // Source address
+            0x01, 0x00,
+            // Header Termination 1 IE
+            0x00, 0x3f,
+            // MLME IE
+            0x0b, 0x88,
+            // TSCH Synchronization IE
+            0x06, 0x1a, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Reduced TSCH Timeslot IE: default timings
+            0x01, 0x1c, 0x00,
+        ];
+
+        let pan_descriptor = PanDescriptor::from_beacon(&mpdu, Channel::_20, &RX_INFO).unwrap();
+        let join_info = TschJoinInfo::from_beacon(&mpdu, &pan_descriptor).unwrap();
+        assert_eq!(join_info.timeslot_timings, TschTimeslotTimings::default());
+        assert!(join_info.channel_hopping_ie().is_none());
+        assert!(join_info.slotframes.is_empty());
+
+        // The timestamp is required to synchronize.
+        let mut untimed = pan_descriptor;
+        untimed.timestamp = None;
+        assert!(TschJoinInfo::from_beacon(&mpdu, &untimed).is_none());
+
+        // Unknown timeslot timings
+        *mpdu.last_mut().unwrap() = 1;
+        let pan_descriptor = PanDescriptor::from_beacon(&mpdu, Channel::_20, &RX_INFO).unwrap();
+        assert!(TschJoinInfo::from_beacon(&mpdu, &pan_descriptor).is_none());
+
+        // Not a TSCH network
+        let mut beacon = enhanced_beacon();
+        beacon[20] = 0x1d;
+        let pan_descriptor = PanDescriptor::from_beacon(&beacon, Channel::_20, &RX_INFO).unwrap();
+        assert!(TschJoinInfo::from_beacon(&beacon, &pan_descriptor).is_none());
+    }
+}
@@ -1,6 +1,10 @@
 #![allow(unused_imports)]
 pub mod asn;
+pub mod join;
+pub mod queue;
 pub mod schedule;
+pub mod slot;
+pub mod sync;
 
 pub use asn::AbsoluteSlotNumber;
 pub use schedule::{TschHoppingSequence, TschLink, TschSchedule, TschSlotframe};
@@ -0,0 +1,211 @@
+//! Frames waiting for a TSCH link.
+//!
+//! While TSCH is enabled, data requests are not sent by CSMA-CA but queued
+//! until the schedule reaches a TX link to the recipient, see IEEE
+//! 802.15.4-2020, section 6.2.5.1.
+
+use rand_core::RngCore;
+
+use crate::{
+    driver::{
+        constants::{FCS_LEN, PHY_MAX_PACKET_SIZE_127},
+        DriverConfig,
+    },
+    mac::{
+        frame::mpdu::MpduFrame,
+        mcps::data::{DataError, DataRequest, DataRequestResult},
+        MacService,
+    },
+};
+
+use super::slot::TschSlotTask;
+
+/// The max number of frames that may be queued at the same time.
+pub const MAC_TSCH_TX_QUEUE_CAPACITY: usize = 2;
+
+/// The queue of frames sent by the MAC's TSCH schedule.
+pub(crate) type MacTschTxQueue = TschTxQueue<MpduFrame, MAC_TSCH_TX_QUEUE_CAPACITY>;
+
+/// A queued frame together with the data request it belongs to.
+#[derive(Debug)]
+pub(crate) struct TschTxEntry<Frame> {
+    /// The frame to be sent.
+    pub frame: Frame,
+    /// The handle of the MSDU being sent.
+    pub msdu_handle: u8,
+    /// The extended address of the recipient, if it is a known neighbor.
+    /// Frames without neighbor are only sent on shared links.
+    pub neighbor: Option<[u8; 8]>,
+    /// The index of the MAC request task slot of the data request.
+    pub task_index: usize,
+    /// The number of retransmissions so far.
+    pub retries: u8,
+}
+
+/// A bounded FIFO queue of frames waiting for a TSCH link.
+#[derive(Debug)]
+pub(crate) struct TschTxQueue<Frame, const N: usize>(heapless::Deque<TschTxEntry<Frame>, N>);
+
+impl<Frame, const N: usize> TschTxQueue<Frame, N> {
+    pub const fn new() -> Self {
+        Self(heapless::Deque::new())
+    }
+
+    /// Queues the given frame behind all other frames.
+    ///
+    /// Returns the entry if the queue is full.
+    pub fn enqueue(&mut self, entry: TschTxEntry<Frame>) -> Result<(), TschTxEntry<Frame>> {
+        self.0.push_back(entry)
+    }
+
+    /// Puts a frame that was not acknowledged back to the front of the queue
+    /// so that it is retransmitted on the next matching link.
+    ///
+    /// Returns the entry if the queue is full.
+    pub fn requeue(&mut self, entry: TschTxEntry<Frame>) -> Result<(), TschTxEntry<Frame>> {
+        self.0.push_front(entry)
+    }
+
+    /// Removes the oldest frame that may be sent on a link to the given
+    /// neighbor. Dedicated links only carry frames to their neighbor while
+    /// links without neighbor carry any frame.
+    pub fn dequeue_for_link(&mut self, neighbor: Option<[u8; 8]>) -> Option<TschTxEntry<Frame>> {
+        let index = match neighbor {
+            Some(neighbor) => self
+                .0
+                .iter()
+                .position(|entry| entry.neighbor == Some(neighbor))?,
+            None if self.0.is_empty() => return None,
+            None => 0,
+        };
+
+        // Rotate the entry to the front to keep the order of the other
+        // entries.
+        for _ in 0..index {
+            let entry = self.0.pop_front()?;
+            let _ = self.0.push_back(entry);
+        }
+        let entry = self.0.pop_front();
+        for _ in 0..self.0.len() - index {
+            let entry = self.0.pop_front()?;
+            let _ = self.0.push_back(entry);
+        }
+        entry
+    }
+
+    /// Removes all queued frames, e.g. when TSCH is disabled.
+    pub fn drain(&mut self) -> impl Iterator<Item = TschTxEntry<Frame>> + '_ {
+        core::iter::from_fn(|| self.0.pop_front())
+    }
+}
+
+impl<'svc, Rng: RngCore, RadioDriverImpl: DriverConfig> MacService<'svc, Rng, RadioDriverImpl> {
+    /// Queues the frame of the given data request until the TSCH schedule
+    /// reaches a matching TX link. The frame is stamped with the next sequence
+    /// number.
+    ///
+    /// Returns the result to be confirmed right away if the frame cannot be
+    /// queued.
+    pub(crate) fn enqueue_tsch_data_request(
+        &self,
+        data_request: DataRequest,
+        task_index: usize,
+    ) -> Result<(), DataRequestResult> {
+        let msdu_handle = data_request.msdu_handle();
+        let neighbor = self.data_request_neighbor(&data_request);
+        let mut frame = data_request.into_mpdu();
+
+        let status = if frame.pdu_length_wo_fcs() as usize + FCS_LEN > PHY_MAX_PACKET_SIZE_127 {
+            DataError::FrameTooLong
+        } else {
+            self.pib.borrow_mut().stamp_sequence_number(&mut frame);
+            let entry = TschTxEntry {
+                frame,
+                msdu_handle,
+                neighbor: neighbor.map(|neighbor| neighbor.address()),
+                task_index,
+                retries: 0,
+            };
+            match self.tsch_tx_queue.borrow_mut().enqueue(entry) {
+                Ok(()) => {
+                    self.tx_neighbors[task_index].set(neighbor);
+                    return Ok(());
+                }
+                Err(entry) => {
+                    frame = entry.frame;
+                    DataError::TransactionOverflow
+                }
+            }
+        };
+
+        Err(TschSlotTask::<RadioDriverImpl>::unsent_data_request_result(
+            msdu_handle,
+            status,
+            frame,
+        ))
+    }
+
+    /// Removes all queued frames once TSCH mode was left.
+    ///
+    /// Returns the MAC request task slots of the frames together with the
+    /// results to be confirmed.
+    pub(crate) fn expire_tsch_tx_queue(
+        &self,
+    ) -> heapless::Vec<(usize, DataRequestResult), MAC_TSCH_TX_QUEUE_CAPACITY> {
+        self.tsch_tx_queue
+            .borrow_mut()
+            .drain()
+            .map(|entry| {
+                let result = TschSlotTask::<RadioDriverImpl>::unsent_data_request_result(
+                    entry.msdu_handle,
+                    DataError::TransactionExpired,
+                    entry.frame,
+                );
+                (entry.task_index, result)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(frame: u8, neighbor: Option<[u8; 8]>) -> TschTxEntry<u8> {
+        TschTxEntry {
+            frame,
+            msdu_handle: frame,
+            neighbor,
+            task_index: frame as usize,
+            retries: 0,
+        }
+    }
+
+    #[test]
+    fn dequeue_for_link() {
+        let mut queue = TschTxQueue::<u8, 3>::new();
+        assert!(queue.enqueue(entry(0, Some([1; 8]))).is_ok());
+        assert!(queue.enqueue(entry(1, Some([2; 8]))).is_ok());
+        assert!(queue.enqueue(entry(2, None)).is_ok());
+        assert!(queue.enqueue(entry(3, None)).is_err());
+
+        // Dedicated links only carry frames to their neighbor.
+        assert!(queue.dequeue_for_link(Some([3; 8])).is_none());
+        assert_eq!(queue.dequeue_for_link(Some([2; 8])).unwrap().frame, 1);
+
+        // A frame that was not acknowledged is retransmitted first.
+        let mut nacked = queue.dequeue_for_link(None).unwrap();
+        assert_eq!(nacked.frame, 0);
+        nacked.retries += 1;
+        assert!(queue.requeue(nacked).is_ok());
+        let retransmitted = queue.dequeue_for_link(Some([1; 8])).unwrap();
+        assert_eq!((retransmitted.frame, retransmitted.retries), (0, 1));
+
+        assert!(queue.enqueue(entry(4, Some([1; 8]))).is_ok());
+        assert!(queue.enqueue(entry(5, None)).is_ok());
+        assert_eq!(queue.dequeue_for_link(Some([1; 8])).unwrap().frame, 4);
+        let drained: heapless::Vec<u8, 3> = queue.drain().map(|entry| entry.frame).collect();
+        assert_eq!(drained, [2, 5]);
+        assert!(queue.dequeue_for_link(None).is_none());
+    }
+}
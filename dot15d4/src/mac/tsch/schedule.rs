@@ -1,16 +1,42 @@
 #![allow(dead_code)]
 use crate::mac::{
-    frame::fields::{TschLinkOption, TschTimeslotTimings},
-    neighbors::MacNeighbor,
+    frame::fields::{ChannelHoppingIe, TschLinkOption, TschTimeslotTimings},
+    mlme::scan::CoordAddress,
+    neighbors::{MacNeighbor, Neighbor},
 };
 
 use super::asn::AbsoluteSlotNumber;
 
+/// The max number of slotframes of the MAC's TSCH schedule.
+pub const MAC_TSCH_MAX_SLOTFRAMES: usize = 2;
+
+/// The max number of links per slotframe of the MAC's TSCH schedule.
+pub const MAC_TSCH_MAX_LINKS: usize = 8;
+
+/// The TSCH schedule maintained by the MAC service.
+pub(crate) type MacTschSchedule =
+    TschSchedule<MAC_TSCH_MAX_SLOTFRAMES, MAC_TSCH_MAX_LINKS, Neighbor>;
+
+/// The max number of channels of a hopping sequence.
+pub const MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH: usize = 16;
+
+/// The default hopping sequence of the 2.4 GHz O-QPSK PHY using all 16
+/// channels, see IEEE 802.15.4-2020, section 6.2.10.
+pub const TSCH_DEFAULT_HOPPING_SEQUENCE: TschHoppingSequence = TschHoppingSequence {
+    channels: [
+        16, 17, 23, 18, 26, 15, 25, 22, 19, 11, 12, 13, 24, 14, 20, 21,
+    ],
+    len: 16,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScheduleError {
     InvalidTimeslot,
     InvalidChannelOffset,
     CapacityExceeded,
     HandleDuplicate,
+    /// No slotframe or link with the given handle exists.
+    UnknownHandle,
 }
 
 /// A TSCH link is a pairwise assignment of a directed communication between
@@ -32,14 +58,158 @@ pub struct TschLink<T: MacNeighbor> {
     neighbor: Option<T>,
 }
 
+impl<T: MacNeighbor> TschLink<T> {
+    /// Creates a new [`TschLink`].
+    pub fn new(
+        handle: u16,
+        timeslot: u16,
+        channel_offset: u16,
+        link_options: TschLinkOption,
+        link_type: TschLinkType,
+        neighbor: Option<T>,
+    ) -> Self {
+        Self {
+            handle,
+            timeslot,
+            channel_offset,
+            link_options,
+            link_type,
+            neighbor,
+        }
+    }
+
+    /// Return the link identifier.
+    pub fn handle(&self) -> u16 {
+        self.handle
+    }
+
+    /// Return the timeslot of the link within its slotframe.
+    pub fn timeslot(&self) -> u16 {
+        self.timeslot
+    }
+
+    /// Return the channel offset of the link.
+    pub fn channel_offset(&self) -> u16 {
+        self.channel_offset
+    }
+
+    /// Return the communication options of the link.
+    pub fn link_options(&self) -> TschLinkOption {
+        self.link_options
+    }
+
+    /// Return the type of the link.
+    pub fn link_type(&self) -> TschLinkType {
+        self.link_type
+    }
+
+    /// Return the neighbor assigned to the link, if any.
+    pub fn neighbor(&self) -> Option<&T> {
+        self.neighbor.as_ref()
+    }
+}
+
 /// Type of link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TschLinkType {
     Advertising,
     Normal,
 }
 
-/// Represents a channel hopping sequence
-pub type TschHoppingSequence = [u8; 4];
+/// Represents a channel hopping sequence, i.e. macHoppingSequenceList
+/// together with macHoppingSequenceLength.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TschHoppingSequence {
+    channels: [u8; MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH],
+    len: u8,
+}
+
+impl TschHoppingSequence {
+    /// Creates a hopping sequence from the given channels.
+    ///
+    /// Returns `None` if the sequence is empty or longer than
+    /// [`MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH`].
+    pub fn new(channels: &[u8]) -> Option<Self> {
+        if channels.is_empty() || channels.len() > MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH {
+            return None;
+        }
+
+        let mut hopping_sequence = Self {
+            channels: [0; MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH],
+            len: channels.len() as u8,
+        };
+        hopping_sequence.channels[..channels.len()].copy_from_slice(channels);
+        Some(hopping_sequence)
+    }
+
+    /// Creates the hopping sequence announced by a full Channel Hopping IE.
+    ///
+    /// Returns `None` if the IE is reduced or the announced sequence is not
+    /// supported.
+    pub fn from_channel_hopping_ie<Bytes: AsRef<[u8]>>(
+        ie: &ChannelHoppingIe<Bytes>,
+    ) -> Option<Self> {
+        if ie.is_reduced() {
+            return None;
+        }
+
+        let mut channels = heapless::Vec::<u8, MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH>::new();
+        for channel in ie.hopping_sequence() {
+            channels.push(u8::try_from(channel).ok()?).ok()?;
+        }
+        Self::new(&channels)
+    }
+
+    /// Return the channels of the sequence.
+    pub fn channels(&self) -> &[u8] {
+        &self.channels[..self.len as usize]
+    }
+
+    /// Return the number of channels of the sequence.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Always `false` as hopping sequences contain at least one channel.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the channel used by a link with the given channel offset at the
+    /// given ASN, see IEEE 802.15.4-2020, section 6.2.6.3:
+    ///
+    /// `macHoppingSequenceList[(ASN + channelOffset) % macHoppingSequenceLength]`
+    ///
+    /// * `asn` - Absolute slot number
+    /// * `channel_offset` - Channel offset of the link
+    pub fn channel(&self, asn: AbsoluteSlotNumber, channel_offset: u16) -> u8 {
+        let index = (asn + channel_offset) % self.len as u16;
+        self.channels[index as usize]
+    }
+}
+
+impl Default for TschHoppingSequence {
+    fn default() -> Self {
+        TSCH_DEFAULT_HOPPING_SEQUENCE
+    }
+}
+
+/// An active slot of the schedule, i.e. a timeslot with a link, resolved to
+/// the channel used at its ASN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TschSlot {
+    /// The absolute slot number of the slot.
+    pub asn: AbsoluteSlotNumber,
+    /// The channel derived from the ASN and the link's channel offset.
+    pub channel: u8,
+    /// Communication options of the link.
+    pub link_options: TschLinkOption,
+    /// Type of the link.
+    pub link_type: TschLinkType,
+    /// The extended address of the neighbor assigned to the link, if it is a
+    /// dedicated link.
+    pub neighbor: Option<[u8; 8]>,
+}
 
 /// A TSCH slotframe collection of timeslots repeating in time, analogous to a
 /// superframe in that it defines periods of communication opportunities.
@@ -86,6 +256,41 @@ impl<const N: usize, T: MacNeighbor> TschSlotframe<N, T> {
         }
     }
 
+    /// Return the slotframe identifier.
+    pub fn handle(&self) -> u16 {
+        self.handle
+    }
+
+    /// Return the number of timeslots of the slotframe.
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+
+    /// Change the number of timeslots of the slotframe. Fails if a link would
+    /// no longer fit into the slotframe.
+    ///
+    /// * `size` - New number of timeslots
+    pub fn set_size(&mut self, size: u16) -> Result<(), ScheduleError> {
+        if size == 0 || self.links.iter().any(|l| l.timeslot >= size) {
+            Err(ScheduleError::InvalidTimeslot)
+        } else {
+            self.size = size;
+            Ok(())
+        }
+    }
+
+    /// Remove the link with the given handle from the slotframe.
+    ///
+    /// * `handle` - Handle of the link to remove
+    pub fn remove_link(&mut self, handle: u16) -> Result<TschLink<T>, ScheduleError> {
+        let index = self
+            .links
+            .iter()
+            .position(|l| l.handle == handle)
+            .ok_or(ScheduleError::UnknownHandle)?;
+        Ok(self.links.remove(index))
+    }
+
     /// Return the link associated to the given ASN, if any.
     ///
     /// * `asn` - Absolute slot number
@@ -101,11 +306,41 @@ impl<const N: usize, T: MacNeighbor> TschSlotframe<N, T> {
         asn % self.size
     }
 
-    /// Return the channel offset for a given link at a given ASN
+    /// Return the links of the slotframe.
+    pub fn links(&self) -> &[TschLink<T>] {
+        &self.links
+    }
+
+    /// Return the hopping sequence of the slotframe.
+    pub fn hopping_sequence(&self) -> &TschHoppingSequence {
+        &self.hopping_sequence
+    }
+
+    /// Change the hopping sequence of the slotframe. Fails if the channel
+    /// offset of a link would exceed the new sequence.
+    ///
+    /// * `hopping_sequence` - New hopping sequence
+    pub fn set_hopping_sequence(
+        &mut self,
+        hopping_sequence: TschHoppingSequence,
+    ) -> Result<(), ScheduleError> {
+        if self
+            .links
+            .iter()
+            .any(|l| l.channel_offset as usize >= hopping_sequence.len())
+        {
+            Err(ScheduleError::InvalidChannelOffset)
+        } else {
+            self.hopping_sequence = hopping_sequence;
+            Ok(())
+        }
+    }
+
+    /// Return the channel used by a given link at a given ASN
     /// * `asn` - Absolute slot number
     /// * `link` - Link to consider
-    fn channel_offset(&self, asn: AbsoluteSlotNumber, link: TschLink<T>) -> u16 {
-        (asn + link.channel_offset) % self.size
+    fn channel(&self, asn: AbsoluteSlotNumber, link: &TschLink<T>) -> u8 {
+        self.hopping_sequence.channel(asn, link.channel_offset)
     }
 }
 
@@ -123,6 +358,14 @@ pub struct TschSchedule<const S: usize, const L: usize, T: MacNeighbor> {
     join_metric: u16,
     /// Timings used for communication inside a timeslot
     timeslot_timings: TschTimeslotTimings,
+    /// The neighbor the device synchronizes to, `None` on the PAN coordinator
+    /// and before joining a network.
+    time_source: Option<CoordAddress>,
+    /// A slot of the network and the radio clock tick at its start, as
+    /// learned when joining the network. Taken by the slot task.
+    sync_reference: Option<(AbsoluteSlotNumber, u64)>,
+    /// The number of active slots skipped as no buffer was available.
+    skipped_slots: u32,
 }
 
 #[allow(dead_code)]
@@ -186,6 +429,192 @@ impl<const S: usize, const L: usize, T: MacNeighbor> TschSchedule<S, L, T> {
     pub(crate) fn set_asn(&mut self, asn: AbsoluteSlotNumber) {
         self.asn = asn;
     }
+
+    /// Return the absolute slot number of the last executed slot.
+    pub(crate) fn asn(&self) -> AbsoluteSlotNumber {
+        self.asn
+    }
+
+    /// Return the timings used for communication inside a timeslot.
+    pub(crate) fn timeslot_timings(&self) -> TschTimeslotTimings {
+        self.timeslot_timings
+    }
+
+    /// Set the timings used for communication inside a timeslot.
+    pub(crate) fn set_timeslot_timings(&mut self, timeslot_timings: TschTimeslotTimings) {
+        self.timeslot_timings = timeslot_timings;
+    }
+
+    /// Return the metric advertised in the TSCH Synchronization IE, i.e. the
+    /// distance to the PAN coordinator.
+    pub(crate) fn join_metric(&self) -> u16 {
+        self.join_metric
+    }
+
+    /// Set the metric advertised in the TSCH Synchronization IE.
+    pub(crate) fn set_join_metric(&mut self, join_metric: u16) {
+        self.join_metric = join_metric;
+    }
+
+    /// Return the neighbor the device synchronizes to, if any.
+    pub(crate) fn time_source(&self) -> Option<CoordAddress> {
+        self.time_source
+    }
+
+    /// Set the neighbor the device synchronizes to.
+    pub(crate) fn set_time_source(&mut self, time_source: Option<CoordAddress>) {
+        self.time_source = time_source;
+    }
+
+    /// Synchronize the schedule to the network, i.e. the slot with the given
+    /// ASN started at the given radio clock tick.
+    ///
+    /// * `asn` - Absolute slot number of the reference slot
+    /// * `slot_start` - Tick at the start of the reference slot
+    pub(crate) fn synchronize(&mut self, asn: AbsoluteSlotNumber, slot_start: u64) {
+        self.asn = asn;
+        self.sync_reference = Some((asn, slot_start));
+    }
+
+    /// Take the reference slot set by [`Self::synchronize()`], if any.
+    pub(crate) fn take_sync_reference(&mut self) -> Option<(AbsoluteSlotNumber, u64)> {
+        self.sync_reference.take()
+    }
+
+    /// Count an active slot that was skipped as no buffer was available.
+    pub(crate) fn count_skipped_slot(&mut self) {
+        self.skipped_slots = self.skipped_slots.wrapping_add(1);
+    }
+
+    /// Return the number of active slots skipped as no buffer was available.
+    pub(crate) fn skipped_slots(&self) -> u32 {
+        self.skipped_slots
+    }
+
+    /// Return the slotframes of the schedule.
+    pub(crate) fn slotframes(&self) -> &[TschSlotframe<L, T>] {
+        &self.slotframes
+    }
+
+    /// Replace all slotframes of the schedule, e.g. by the slotframes
+    /// advertised by the network the device joined.
+    ///
+    /// * `slotframes` - New slotframes
+    pub(crate) fn replace_slotframes(&mut self, slotframes: heapless::Vec<TschSlotframe<L, T>, S>) {
+        self.slotframes = slotframes;
+    }
+
+    /// Return the slotframe with the given handle, if any.
+    pub(crate) fn slotframe_mut(&mut self, handle: u16) -> Option<&mut TschSlotframe<L, T>> {
+        self.slotframes.iter_mut().find(|s| s.handle == handle)
+    }
+
+    /// Remove the slotframe with the given handle including its links.
+    ///
+    /// * `handle` - Handle of the slotframe to remove
+    pub(crate) fn remove_slotframe(&mut self, handle: u16) -> Result<(), ScheduleError> {
+        let index = self
+            .slotframes
+            .iter()
+            .position(|s| s.handle == handle)
+            .ok_or(ScheduleError::UnknownHandle)?;
+        self.slotframes.remove(index);
+        Ok(())
+    }
+
+    /// Add the given link to the slotframe with the given handle. Link handles
+    /// are unique across slotframes.
+    ///
+    /// * `slotframe_handle` - Handle of the slotframe
+    /// * `link` - Link to add
+    pub(crate) fn add_link(
+        &mut self,
+        slotframe_handle: u16,
+        link: TschLink<T>,
+    ) -> Result<(), ScheduleError> {
+        if self
+            .slotframes
+            .iter()
+            .any(|s| s.links.iter().any(|l| l.handle == link.handle))
+        {
+            return Err(ScheduleError::HandleDuplicate);
+        }
+        self.slotframe_mut(slotframe_handle)
+            .ok_or(ScheduleError::UnknownHandle)?
+            .add_link(link)
+    }
+
+    /// Remove the link with the given handle from the slotframe with the
+    /// given handle.
+    ///
+    /// * `slotframe_handle` - Handle of the slotframe
+    /// * `link_handle` - Handle of the link to remove
+    pub(crate) fn remove_link(
+        &mut self,
+        slotframe_handle: u16,
+        link_handle: u16,
+    ) -> Result<TschLink<T>, ScheduleError> {
+        self.slotframe_mut(slotframe_handle)
+            .ok_or(ScheduleError::UnknownHandle)?
+            .remove_link(link_handle)
+    }
+
+    /// Change the hopping sequence of all slotframes. No slotframe is changed
+    /// if the channel offset of any link would exceed the new sequence.
+    ///
+    /// * `hopping_sequence` - New hopping sequence
+    pub(crate) fn set_hopping_sequence(
+        &mut self,
+        hopping_sequence: TschHoppingSequence,
+    ) -> Result<(), ScheduleError> {
+        if self.slotframes.iter().any(|s| {
+            s.links
+                .iter()
+                .any(|l| l.channel_offset as usize >= hopping_sequence.len())
+        }) {
+            return Err(ScheduleError::InvalidChannelOffset);
+        }
+        for slotframe in self.slotframes.iter_mut() {
+            slotframe.set_hopping_sequence(hopping_sequence)?;
+        }
+        Ok(())
+    }
+
+    /// Whether any slotframe has at least one link.
+    pub(crate) fn has_links(&self) -> bool {
+        self.slotframes.iter().any(|s| !s.links.is_empty())
+    }
+
+    /// Return the active slot at the given ASN, if any. Slotframes added first
+    /// take precedence over slotframes added later.
+    ///
+    /// * `asn` - Absolute slot number
+    pub(crate) fn slot(&self, asn: AbsoluteSlotNumber) -> Option<TschSlot> {
+        self.slotframes.iter().find_map(|slotframe| {
+            slotframe.get_link(asn).map(|link| TschSlot {
+                asn,
+                channel: slotframe.channel(asn, link),
+                link_options: link.link_options,
+                link_type: link.link_type,
+                neighbor: link.neighbor.as_ref().map(|neighbor| neighbor.address()),
+            })
+        })
+    }
+
+    /// Return the first active slot after the given ASN without changing the
+    /// ASN of the schedule. Every link recurs within the size of its
+    /// slotframe, so the search is bounded by the largest slotframe.
+    ///
+    /// * `asn` - Absolute slot number
+    pub(crate) fn next_active_slot_after(&self, asn: AbsoluteSlotNumber) -> Option<TschSlot> {
+        let max_size = self
+            .slotframes
+            .iter()
+            .filter(|s| !s.links.is_empty())
+            .map(|s| s.size)
+            .max()?;
+        (1..=max_size).find_map(|offset| self.slot(asn + offset))
+    }
 }
 
 impl<const S: usize, const L: usize, T: MacNeighbor> Default for TschSchedule<S, L, T> {
@@ -195,19 +624,28 @@ impl<const S: usize, const L: usize, T: MacNeighbor> Default for TschSchedule<S,
             join_metric: 1,
             asn: AbsoluteSlotNumber::try_from(0).unwrap(),
             timeslot_timings: TschTimeslotTimings::default(),
+            time_source: None,
+            sync_reference: None,
+            skipped_slots: 0,
         }
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use crate::mac::{frame::fields::TschLinkOption, neighbors::tests::TestNeighbor};
+    use crate::mac::{
+        frame::fields::TschLinkOption, neighbors::tests::TestNeighbor,
+        tsch::asn::AbsoluteSlotNumber,
+    };
 
-    use super::{ScheduleError, TschLink, TschLinkType, TschSchedule, TschSlotframe};
+    use super::{
+        ScheduleError, TschHoppingSequence, TschLink, TschLinkType, TschSchedule, TschSlotframe,
+        MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH, TSCH_DEFAULT_HOPPING_SEQUENCE,
+    };
 
     #[test]
     fn schedule() {
-        let hopping_sequence = [15, 25, 26, 20];
+        let hopping_sequence = TschHoppingSequence::new(&[15, 25, 26, 20]).unwrap();
         let nbr1 = TestNeighbor::new([0, 0, 0, 0, 0, 0, 0, 1]);
         let nbr2 = TestNeighbor::new([0, 0, 0, 0, 0, 0, 0, 2]);
         let mut sf = TschSlotframe::new(1, 3, hopping_sequence);
@@ -279,7 +717,7 @@ pub mod tests {
 
     #[test]
     fn invalid_links() {
-        let hopping_sequence = [15, 25, 26, 20];
+        let hopping_sequence = TschHoppingSequence::new(&[15, 25, 26, 20]).unwrap();
         let mut sf = TschSlotframe::<2, TestNeighbor>::new(1, 11, hopping_sequence);
 
         let res = sf.add_link(TschLink {
@@ -331,9 +769,69 @@ pub mod tests {
             _ => panic!(),
         };
     }
+    #[test]
+    fn active_slots() {
+        let hopping_sequence = TschHoppingSequence::new(&[15, 25, 26, 20]).unwrap();
+        let mut schedule = TschSchedule::<2, 2, TestNeighbor>::new();
+        assert!(schedule
+            .add_slotframe(TschSlotframe::new(1, 5, hopping_sequence))
+            .is_ok());
+        let asn = AbsoluteSlotNumber::try_from(0).unwrap();
+        assert!(schedule.next_active_slot_after(asn).is_none());
+
+        let link = TschLink::new(
+            1,
+            3,
+            0,
+            TschLinkOption::Tx,
+            TschLinkType::Normal,
+            Some(TestNeighbor::new([1; 8])),
+        );
+        assert!(schedule.add_link(1, link).is_ok());
+        let link = TschLink::new(1, 4, 0, TschLinkOption::Rx, TschLinkType::Normal, None);
+        assert_eq!(
+            schedule.add_link(1, link),
+            Err(ScheduleError::HandleDuplicate)
+        );
+        let link = TschLink::new(2, 4, 0, TschLinkOption::Rx, TschLinkType::Normal, None);
+        assert_eq!(
+            schedule.add_link(2, link),
+            Err(ScheduleError::UnknownHandle)
+        );
+
+        // The search does not change the ASN of the schedule.
+        let slot = schedule.next_active_slot_after(asn).unwrap();
+        assert!(slot.asn == 3);
+        assert_eq!(slot.link_options, TschLinkOption::Tx);
+        assert_eq!(slot.neighbor, Some([1; 8]));
+        assert!(schedule.asn() == 0);
+        let slot = schedule.next_active_slot_after(slot.asn).unwrap();
+        assert!(slot.asn == 8);
+        assert!(schedule.slot(asn).is_none());
+
+        // Links must still fit into a resized slotframe.
+        let slotframe = schedule.slotframe_mut(1).unwrap();
+        assert_eq!(slotframe.set_size(3), Err(ScheduleError::InvalidTimeslot));
+        assert_eq!(slotframe.set_size(4), Ok(()));
+        assert!(schedule.next_active_slot_after(asn + 4u32).unwrap().asn == 7);
+
+        assert!(schedule.remove_link(1, 1).is_ok());
+        assert_eq!(
+            schedule.remove_link(1, 1).err(),
+            Some(ScheduleError::UnknownHandle)
+        );
+        assert!(schedule.next_active_slot_after(asn).is_none());
+
+        assert_eq!(schedule.remove_slotframe(1), Ok(()));
+        assert_eq!(
+            schedule.remove_slotframe(1),
+            Err(ScheduleError::UnknownHandle)
+        );
+    }
+
     #[test]
     fn multiple_slotframes() {
-        let hopping_sequence = [15, 25, 26, 20];
+        let hopping_sequence = TschHoppingSequence::new(&[15, 25, 26, 20]).unwrap();
         let mut sf1 = TschSlotframe::new(1, 3, hopping_sequence);
         let mut sf2 = TschSlotframe::new(2, 2, hopping_sequence);
 
@@ -396,4 +894,43 @@ pub mod tests {
             _ => panic!(),
         };
     }
+
+    #[test]
+    fn channel_hopping() {
+        assert!(TschHoppingSequence::new(&[]).is_none());
+        assert!(
+            TschHoppingSequence::new(&[11; MAC_TSCH_MAX_HOPPING_SEQUENCE_LENGTH + 1]).is_none()
+        );
+        assert_eq!(TSCH_DEFAULT_HOPPING_SEQUENCE.len(), 16);
+
+        let hopping_sequence = TschHoppingSequence::new(&[15, 25, 26, 20]).unwrap();
+        assert_eq!(hopping_sequence.channels(), [15, 25, 26, 20]);
+        let asn = AbsoluteSlotNumber::try_from(0).unwrap();
+        assert_eq!(hopping_sequence.channel(asn, 0), 15);
+        assert_eq!(hopping_sequence.channel(asn + 5u32, 2), 20);
+
+        // A link recurring in a slotframe that is shorter than the hopping
+        // sequence visits all channels of the sequence.
+        let mut schedule = TschSchedule::<1, 1, TestNeighbor>::new();
+        assert!(schedule
+            .add_slotframe(TschSlotframe::new(1, 3, hopping_sequence))
+            .is_ok());
+        let link = TschLink::new(1, 0, 1, TschLinkOption::Tx, TschLinkType::Normal, None);
+        assert!(schedule.add_link(1, link).is_ok());
+        let channels: heapless::Vec<u8, 4> = (0..4u32)
+            .map(|i| schedule.slot(asn + 3 * i).unwrap().channel)
+            .collect();
+        assert_eq!(channels, [25, 15, 20, 26]);
+
+        // The channel offset of the link must stay within the sequence.
+        let short_sequence = TschHoppingSequence::new(&[26]).unwrap();
+        assert_eq!(
+            schedule.set_hopping_sequence(short_sequence),
+            Err(ScheduleError::InvalidChannelOffset)
+        );
+        assert!(schedule
+            .set_hopping_sequence(TSCH_DEFAULT_HOPPING_SEQUENCE)
+            .is_ok());
+        assert_eq!(schedule.slot(asn).unwrap().channel, 17);
+    }
 }
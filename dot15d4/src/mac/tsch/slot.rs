@@ -0,0 +1,737 @@
+//! Execution of the TSCH schedule, see IEEE 802.15.4-2020, section 6.2.6.
+
+use core::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    mem,
+};
+
+use crate::{
+    driver::{
+        config::Channel as RadioChannel,
+        constants::PHY_SHR_DURATION,
+        frame::{Address, ExtendedAddress, RadioFrame, RadioFrameUnsized, ShortAddress},
+        tasks::{Timestamp, TxError, TxInfo, TxResult},
+        time::{Duration, Instant, Microseconds},
+        DriverConfig, DrvSvcRequest, DrvSvcResponse, DrvSvcTaskError, DrvSvcTaskOff, DrvSvcTaskRx,
+        DrvSvcTaskTx, RadioTimerApi,
+    },
+    mac::{
+        frame::{
+            fields::{TschLinkOption, TschTimeslotTimings},
+            mpdu::{keep_alive_frame, keep_alive_frame_repr, MpduFrame},
+        },
+        mcps::data::{
+            DataConfirm, DataError, DataIndicationResult, DataIndicationTask, DataRequestResult,
+        },
+        mlme::beacon::{build_beacon_frame, BeaconError, BeaconType},
+        pib::Pib,
+        task::*,
+        MacBufferAllocator,
+    },
+    util::allocator::{export::AllocError, IntoBuffer},
+};
+
+use super::{
+    asn::AbsoluteSlotNumber,
+    join::write_tsch_ies,
+    queue::{MacTschTxQueue, TschTxEntry},
+    schedule::{MacTschSchedule, TschLinkType, TschSlot},
+    sync::TschClock,
+};
+
+/// The number of slots after which the schedule is checked again for links
+/// if it has none.
+const TSCH_IDLE_SLOTS: u32 = 100;
+
+/// The shortest RX window opened for frames from the time source. It covers
+/// the timestamp jitter of both devices.
+const TSCH_MIN_GUARD_TIME: Duration<Microseconds> = Duration::new(300);
+
+/// Intermediate and final results of the TSCH slot task.
+pub(crate) enum TschSlotResult {
+    /// A frame was received in an RX slot.
+    Received(DataIndicationResult),
+    /// A queued frame was sent or could not be sent.
+    Transmitted {
+        /// The index of the MAC request task slot of the data request.
+        task_index: usize,
+        result: DataRequestResult,
+    },
+    /// TSCH was disabled.
+    Stopped,
+    /// The device did not hear from its time source within the desync
+    /// timeout and left TSCH mode.
+    SyncLost,
+}
+
+/// Executes the active slots of the TSCH schedule while TSCH is enabled.
+///
+/// The task wakes at the start of every active slot and issues a timed TX or
+/// RX task on the channel derived from the ASN and the channel offset of the
+/// link, at the offsets given by the timeslot timings. The driver service
+/// exchanges (Enh-)ACKs within the slot and switches the radio off between
+/// slots.
+///
+/// Frames are taken from the TSCH TX queue or, on advertising links, are
+/// Enhanced Beacons. Slots that are missed, e.g. because the previous slot
+/// overran, are skipped. So are slots for which no buffer is available, these
+/// are counted by the schedule.
+///
+/// Devices with a time source resynchronize to every frame received from it
+/// and to every Enh-ACK it sends, see IEEE 802.15.4-2020, section 6.5.4. Frames
+/// are only used if the driver provides accurate RX timestamps.
+/// Keep-alives are sent to it if no such frame was exchanged within the
+/// keep-alive period. TSCH mode is left once the desync timeout elapses
+/// without synchronization.
+pub(crate) struct TschSlotTask<'task, RadioDriverImpl: DriverConfig> {
+    pib: &'task RefCell<Pib>,
+    schedule: &'task RefCell<MacTschSchedule>,
+    tx_queue: &'task RefCell<MacTschTxQueue>,
+    /// The timeslot timings, `None` once TSCH is disabled.
+    timeslot_timings: &'task Cell<Option<TschTimeslotTimings>>,
+    buffer_allocator: MacBufferAllocator,
+    /// The local clock from which the start of all slots is derived.
+    clock: TschClock,
+    /// The RX frame kept across RX slots in which no frame was received.
+    rx_radio_frame: Option<RadioFrame<RadioFrameUnsized>>,
+    state: TschSlotState<RadioDriverImpl>,
+}
+
+enum TschSlotState<RadioDriverImpl: DriverConfig> {
+    Initial(PhantomData<RadioDriverImpl>),
+    /// Switching the radio off after the given slot.
+    SwitchingOff(AbsoluteSlotNumber),
+    /// Waiting for the start of the given slot.
+    Waiting(AbsoluteSlotNumber),
+    /// Sending a frame in the given slot.
+    Sending(AbsoluteSlotNumber, TschTxFrame),
+    /// Receiving in the given slot.
+    Receiving(AbsoluteSlotNumber),
+}
+
+/// The frame being sent in a TX slot.
+enum TschTxFrame {
+    /// An Enhanced Beacon on an advertising link.
+    Beacon,
+    /// A frame from the TSCH TX queue.
+    Data {
+        msdu_handle: u8,
+        neighbor: Option<[u8; 8]>,
+        task_index: usize,
+        retries: u8,
+        ack_requested: bool,
+        /// Whether the frame is acknowledged by the time source.
+        to_time_source: bool,
+    },
+    /// A keep-alive to the time source.
+    KeepAlive,
+}
+
+impl<'task, RadioDriverImpl: DriverConfig> TschSlotTask<'task, RadioDriverImpl> {
+    pub fn new(
+        pib: &'task RefCell<Pib>,
+        schedule: &'task RefCell<MacTschSchedule>,
+        tx_queue: &'task RefCell<MacTschTxQueue>,
+        timeslot_timings: &'task Cell<Option<TschTimeslotTimings>>,
+        buffer_allocator: MacBufferAllocator,
+    ) -> Self {
+        // Devices that joined a network start from the slot of the beacon.
+        let (reference_asn, reference_start) = {
+            let mut schedule = schedule.borrow_mut();
+            schedule
+                .take_sync_reference()
+                .unwrap_or_else(|| (schedule.asn(), RadioDriverImpl::Timer::now().tick()))
+        };
+        Self {
+            pib,
+            schedule,
+            tx_queue,
+            timeslot_timings,
+            buffer_allocator,
+            clock: TschClock::new(reference_asn, reference_start),
+            rx_radio_frame: None,
+            state: TschSlotState::Initial(PhantomData),
+        }
+    }
+
+    /// The length of a timeslot in timer ticks.
+    fn timeslot_length(timings: &TschTimeslotTimings) -> u64 {
+        let timeslot_length: Duration<RadioDriverImpl::Timer> =
+            timings.timeslot_length().convert_into_rounding_up();
+        timeslot_length.ticks() as u64
+    }
+
+    /// The tick at which the RMARKER of a frame sent in the slot with the
+    /// given ASN passes the antenna: Frames are timestamped with their
+    /// RMARKER, i.e. the SHR duration after the start of the frame.
+    fn rmarker(&self, asn: AbsoluteSlotNumber, timings: &TschTimeslotTimings) -> u64 {
+        let slot_start = self.clock.slot_start(asn, Self::timeslot_length(timings));
+        let rmarker = Instant::<RadioDriverImpl::Timer>::new(slot_start)
+            + timings.tx_offset().convert_into_rounding_up()
+            + PHY_SHR_DURATION.convert_into_rounding_up();
+        rmarker.tick()
+    }
+
+    /// Whether the given address belongs to the time source.
+    fn is_time_source(&self, address: &Address<&[u8]>) -> bool {
+        self.schedule
+            .borrow()
+            .time_source()
+            .is_some_and(|time_source| {
+                time_source.as_address().as_le_bytes() == address.as_le_bytes()
+            })
+    }
+
+    /// Whether the given link is dedicated to the time source.
+    fn is_time_source_link(&self, slot: &TschSlot) -> bool {
+        slot.neighbor.is_some_and(|neighbor| {
+            self.is_time_source(&Address::Extended(ExtendedAddress::new(&neighbor[..])))
+        })
+    }
+
+    /// Whether the source or destination address of the given frame belongs
+    /// to the time source.
+    fn is_time_source_frame(&self, mpdu: &MpduFrame, source: bool) -> bool {
+        let Ok(Some(addressing_fields)) = mpdu
+            .reader()
+            .parse_addressing()
+            .and_then(|reader| reader.into_addressing_fields())
+        else {
+            return false;
+        };
+        let address = if source {
+            addressing_fields.src_address()
+        } else {
+            addressing_fields.dst_address()
+        };
+        address.is_some_and(|address| self.is_time_source(&address))
+    }
+
+    /// Synchronizes the local clock to the time source: The slot with the
+    /// given ASN started the given number of ticks after its expected start.
+    fn synchronize(&mut self, asn: AbsoluteSlotNumber, correction: i64) {
+        let Some(timings) = self.timeslot_timings.get() else {
+            return;
+        };
+        self.clock
+            .synchronize(asn, Self::timeslot_length(&timings), correction);
+    }
+
+    /// Whether the device has not synchronized to its time source for longer
+    /// than the desync timeout. Devices without time source, e.g. the PAN
+    /// coordinator, never lose synchronization.
+    fn is_desynchronized(&self, now: u64) -> bool {
+        if self.schedule.borrow().time_source().is_none() {
+            return false;
+        }
+        let desync_timeout: Duration<RadioDriverImpl::Timer> = self
+            .pib
+            .borrow()
+            .tsch_desync_timeout
+            .convert_into_rounding_up();
+        now.saturating_sub(self.clock.last_sync()) > desync_timeout.ticks() as u64
+    }
+
+    /// Waits for the first active slot after the given slot that has not yet
+    /// started. Terminates if TSCH was disabled.
+    fn wait_for_next_slot(
+        mut self,
+        asn: AbsoluteSlotNumber,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let Some(timings) = self.timeslot_timings.get() else {
+            return self.stop(TschSlotResult::Stopped);
+        };
+        let timeslot_length = Self::timeslot_length(&timings);
+        let now = RadioDriverImpl::Timer::now().tick();
+
+        if self.is_desynchronized(now) {
+            self.timeslot_timings.set(None);
+            return self.stop(TschSlotResult::SyncLost);
+        }
+
+        let ongoing_asn = self.clock.asn_at(now, timeslot_length);
+        let asn = if self.clock.slots_since_reference(ongoing_asn)
+            > self.clock.slots_since_reference(asn)
+        {
+            ongoing_asn
+        } else {
+            asn
+        };
+        let next_asn = match self.schedule.borrow().next_active_slot_after(asn) {
+            Some(slot) => slot.asn,
+            // Links may be added later.
+            None => asn + TSCH_IDLE_SLOTS,
+        };
+
+        self.state = TschSlotState::Waiting(next_asn);
+        let slot_start = self.clock.slot_start(next_asn, timeslot_length);
+        MacTaskTransition::TimerRequest(self, Instant::new(slot_start))
+    }
+
+    /// Executes the slot with the given ASN if it is still active and if
+    /// there is something to do in it. Otherwise waits for the next slot.
+    fn start_slot(
+        mut self,
+        asn: AbsoluteSlotNumber,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let Some(mut timings) = self.timeslot_timings.get() else {
+            return self.stop(TschSlotResult::Stopped);
+        };
+        let timeslot_length = Self::timeslot_length(&timings);
+        let slot_start = self.clock.slot_start(asn, timeslot_length);
+        let now = RadioDriverImpl::Timer::now().tick();
+
+        // We were woken early, e.g. because the schedule changed.
+        if now < slot_start {
+            let ongoing_asn = self.clock.asn_at(now, timeslot_length);
+            return self.wait_for_next_slot(ongoing_asn);
+        }
+
+        // The link may have been removed in the meantime.
+        let Some(slot) = self.schedule.borrow().slot(asn) else {
+            return self.wait_for_next_slot(asn);
+        };
+        let Ok(channel) = RadioChannel::try_from(slot.channel) else {
+            return self.wait_for_next_slot(asn);
+        };
+        // The RX window opens rx_offset after the start of the slot. It is too
+        // late to execute the slot once it should already be open.
+        let rx_offset: Duration<RadioDriverImpl::Timer> =
+            timings.rx_offset().convert_into_rounding_up();
+        if now >= slot_start + rx_offset.ticks() as u64 {
+            return self.wait_for_next_slot(asn);
+        }
+        self.schedule.borrow_mut().set_asn(asn);
+
+        let rmarker = self.rmarker(asn, &timings);
+
+        match self.frame_for_slot(&slot, now) {
+            Ok(Some((tx_mpdu, tx_frame))) => {
+                self.state = TschSlotState::Sending(asn, tx_frame);
+                let tx_task = DrvSvcTaskTx {
+                    at: Timestamp::Scheduled(rmarker),
+                    radio_frame: tx_mpdu.into_radio_frame::<RadioDriverImpl>(),
+                    cca: false,
+                    channel: Some(channel),
+                };
+                return MacTaskTransition::DrvSvcRequest(self, tx_task.into(), None);
+            }
+            Ok(None) => {}
+            Err(AllocError) => return self.skip_slot(asn),
+        }
+
+        if !slot.link_options.contains(TschLinkOption::Rx) {
+            return self.wait_for_next_slot(asn);
+        }
+
+        // The time source's frames arrive within the drift that may have
+        // accumulated since the last synchronization. Other neighbors may be
+        // synchronized to a different time source.
+        let max_rx_wait = self.schedule.borrow().timeslot_timings().rx_wait();
+        let rx_wait = if self.is_time_source_link(&slot) {
+            let max_guard_time: Duration<RadioDriverImpl::Timer> =
+                max_rx_wait.convert_into_rounding_down();
+            let min_guard_time: Duration<RadioDriverImpl::Timer> =
+                TSCH_MIN_GUARD_TIME.convert_into_rounding_up();
+            let guard_time = self.clock.guard_time(
+                asn,
+                timeslot_length,
+                min_guard_time.ticks() as u64,
+                max_guard_time.ticks() as u64,
+            );
+            Duration::<RadioDriverImpl::Timer>::new(guard_time as i64)
+                .convert_into_rounding_up()
+                .min(max_rx_wait)
+        } else {
+            max_rx_wait
+        };
+        timings.set_rx_wait(rx_wait);
+        self.timeslot_timings.set(Some(timings));
+        let Some(rx_radio_frame) = self.rx_radio_frame.take().or_else(|| {
+            DataIndicationTask::<RadioDriverImpl>::allocate_rx_radio_frame(&self.buffer_allocator)
+        }) else {
+            return self.skip_slot(asn);
+        };
+        self.state = TschSlotState::Receiving(asn);
+        let rx_task = DrvSvcTaskRx {
+            start: Timestamp::Scheduled(rmarker),
+            radio_frame: rx_radio_frame,
+            channel: Some(channel),
+        };
+        MacTaskTransition::DrvSvcRequest(self, rx_task.into(), None)
+    }
+
+    /// Skips the given slot as no buffer is available for it.
+    fn skip_slot(self, asn: AbsoluteSlotNumber) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        self.schedule.borrow_mut().count_skipped_slot();
+        self.wait_for_next_slot(asn)
+    }
+
+    /// Takes the frame to be sent in the given slot, if any: An Enhanced
+    /// Beacon on advertising links, otherwise a queued frame matching the
+    /// link or a keep-alive if one is due on the link.
+    ///
+    /// Returns an error if a keep-alive or an Enhanced Beacon is due but no
+    /// buffer is available.
+    fn frame_for_slot(
+        &mut self,
+        slot: &TschSlot,
+        now: u64,
+    ) -> Result<Option<(MpduFrame, TschTxFrame)>, AllocError> {
+        if !slot.link_options.contains(TschLinkOption::Tx) {
+            return Ok(None);
+        }
+
+        if slot.link_type == TschLinkType::Advertising {
+            // The task owns a single buffer at a time.
+            self.release_rx_radio_frame();
+
+            let mut pib = self.pib.borrow_mut();
+            let ies = pib.enhanced_beacon_ies;
+            let beacon = build_beacon_frame::<RadioDriverImpl>(
+                &pib,
+                BeaconType::EnhancedBeacon,
+                ies,
+                self.buffer_allocator,
+            );
+            let mut beacon = match beacon {
+                Ok(beacon) => beacon,
+                Err(BeaconError::NoBuffer) => return Err(AllocError),
+                // No Enhanced Beacon can be built from the PIB.
+                Err(_) => return Ok(None),
+            };
+            write_tsch_ies(
+                beacon.pdu_mut_wo_fcs(),
+                slot.asn,
+                &self.schedule.borrow(),
+                &pib,
+            );
+            pib.stamp_sequence_number(&mut beacon);
+            return Ok(Some((beacon, TschTxFrame::Beacon)));
+        }
+
+        let entry = self.tx_queue.borrow_mut().dequeue_for_link(slot.neighbor);
+        let Some(TschTxEntry {
+            frame,
+            msdu_handle,
+            neighbor,
+            task_index,
+            retries,
+        }) = entry
+        else {
+            if slot.neighbor.is_some() && !self.is_time_source_link(slot) {
+                return Ok(None);
+            }
+            let keep_alive = self.keep_alive_frame(now)?;
+            return Ok(keep_alive.map(|keep_alive| (keep_alive, TschTxFrame::KeepAlive)));
+        };
+        let ack_requested = frame.frame_control().ack_request();
+        let to_time_source = ack_requested && self.is_time_source_frame(&frame, false);
+        Ok(Some((
+            frame,
+            TschTxFrame::Data {
+                msdu_handle,
+                neighbor,
+                task_index,
+                retries,
+                ack_requested,
+                to_time_source,
+            },
+        )))
+    }
+
+    /// Builds a keep-alive to the time source if the device did not
+    /// synchronize to it within the keep-alive period, see IEEE
+    /// 802.15.4-2020, section 6.5.4.3.
+    ///
+    /// Returns `None` if no keep-alive is due, if the device has no time
+    /// source or no address to send from and an error if no buffer is
+    /// available.
+    fn keep_alive_frame(&mut self, now: u64) -> Result<Option<MpduFrame>, AllocError> {
+        let Some(time_source) = self.schedule.borrow().time_source() else {
+            return Ok(None);
+        };
+        let keep_alive_period: Duration<RadioDriverImpl::Timer> = self
+            .pib
+            .borrow()
+            .tsch_keep_alive_period
+            .convert_into_rounding_up();
+        if keep_alive_period.ticks() == 0
+            || now.saturating_sub(self.clock.last_sync()) < keep_alive_period.ticks() as u64
+        {
+            return Ok(None);
+        }
+
+        // The task owns a single buffer at a time.
+        self.release_rx_radio_frame();
+
+        let mut pib = self.pib.borrow_mut();
+        let short_address;
+        let extended_address;
+        let src_address = if pib.short_address < 0xfffe {
+            short_address = pib.short_address.to_le_bytes();
+            Address::Short(ShortAddress::new(&short_address[..]))
+        } else {
+            let Some(pib_extended_address) = pib.extended_address else {
+                return Ok(None);
+            };
+            extended_address = pib_extended_address;
+            Address::Extended(ExtendedAddress::new(&extended_address[..]))
+        };
+        let dst_address = time_source.as_address();
+
+        let buffer_size = keep_alive_frame_repr(dst_address.into(), src_address.into())
+            .min_buffer_size::<RadioDriverImpl>(0)
+            .unwrap();
+        let buffer = self.buffer_allocator.try_allocate_buffer(buffer_size)?;
+        let mut keep_alive =
+            keep_alive_frame::<RadioDriverImpl>(&dst_address, pib.pan_id, &src_address, 0, buffer)
+                .into_mpdu_frame();
+        pib.stamp_sequence_number(&mut keep_alive);
+        Ok(Some(keep_alive))
+    }
+
+    fn handle_tx_driver_response(
+        mut self,
+        asn: AbsoluteSlotNumber,
+        tx_frame: TschTxFrame,
+        response: DrvSvcResponse,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        let (status, radio_frame, tx_info) = match response {
+            DrvSvcResponse::Tx(Ok(TxResult::Sent(radio_frame, tx_info))) => {
+                (Ok(()), radio_frame, Some(tx_info))
+            }
+            DrvSvcResponse::Tx(Ok(TxResult::Nack(radio_frame, tx_info))) => {
+                (Err(DataError::NoAck), radio_frame, Some(tx_info))
+            }
+            DrvSvcResponse::Tx(Err(DrvSvcTaskError::Task(TxError::CcaBusy(radio_frame)))) => {
+                (Err(DataError::ChannelAccessFailure), radio_frame, None)
+            }
+            // TODO: Implement if required by a driver implementation.
+            DrvSvcResponse::Tx(Err(_)) => unreachable!(),
+            // Safety: We issued a Tx task and therefore expect a Tx result.
+            _ => unreachable!(),
+        };
+
+        // The time source reports the deviation of our slots from its own in
+        // its Enh-ACK, see IEEE 802.15.4-2020, section 6.5.4.2.
+        let to_time_source = match tx_frame {
+            TschTxFrame::Beacon => false,
+            TschTxFrame::Data { to_time_source, .. } => to_time_source,
+            TschTxFrame::KeepAlive => true,
+        };
+        if let Some(time_correction) = tx_info
+            .and_then(|tx_info| tx_info.ack_time_correction)
+            .filter(|_| to_time_source)
+        {
+            let time_correction: Duration<RadioDriverImpl::Timer> =
+                time_correction.convert_into_rounding_down();
+            self.synchronize(asn, time_correction.ticks());
+        }
+
+        let TschTxFrame::Data {
+            msdu_handle,
+            neighbor,
+            task_index,
+            retries,
+            ack_requested,
+            ..
+        } = tx_frame
+        else {
+            // Safety: We allocated the buffer ourselves.
+            unsafe {
+                self.buffer_allocator
+                    .deallocate_buffer(radio_frame.into_buffer());
+            }
+            return self.wait_for_next_slot(asn);
+        };
+
+        // Frames that were not acknowledged are retransmitted on the next
+        // matching link unless TSCH mode was left in the meantime.
+        // TODO: Back off on shared links, see IEEE 802.15.4-2020, section
+        //       6.2.5.3.
+        let radio_frame = if status == Err(DataError::NoAck)
+            && retries < self.pib.borrow().max_frame_retries
+            && self.timeslot_timings.get().is_some()
+        {
+            let entry = TschTxEntry {
+                frame: MpduFrame::from_radio_frame(radio_frame),
+                msdu_handle,
+                neighbor,
+                task_index,
+                retries: retries + 1,
+            };
+            match self.tx_queue.borrow_mut().requeue(entry) {
+                Ok(()) => return self.wait_for_next_slot(asn),
+                Err(entry) => entry.frame.into_radio_frame::<RadioDriverImpl>(),
+            }
+        } else {
+            radio_frame
+        };
+
+        let result = Self::data_request_result(
+            msdu_handle,
+            status,
+            retries,
+            ack_requested,
+            radio_frame.forget_size::<RadioDriverImpl>(),
+            tx_info,
+        );
+        self.report(asn, TschSlotResult::Transmitted { task_index, result })
+    }
+
+    fn handle_rx_driver_response(
+        mut self,
+        asn: AbsoluteSlotNumber,
+        response: DrvSvcResponse,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match DataIndicationTask::<RadioDriverImpl>::handle_rx_driver_response(response) {
+            Ok(mut data_indication) => {
+                data_indication.asn = Some(asn);
+                // Frames from the time source arrive at the start of its slot
+                // plus the TX offset, see IEEE 802.15.4-2020, section 6.5.4.1.
+                // Devices with inaccurate timestamps synchronize to Enh-Acks
+                // only.
+                if RadioDriverImpl::ACCURATE_RX_TIMESTAMPS
+                    && data_indication.crc_ok
+                    && self.is_time_source_frame(&data_indication.mpdu, true)
+                {
+                    if let (Some(timestamp), Some(timings)) = (
+                        data_indication.rx_info.timestamp,
+                        self.timeslot_timings.get(),
+                    ) {
+                        let correction = timestamp as i64 - self.rmarker(asn, &timings) as i64;
+                        self.synchronize(asn, correction);
+                    }
+                }
+                self.report(asn, TschSlotResult::Received(data_indication))
+            }
+            // The RX window ended without a frame being received.
+            Err(rx_radio_frame) => {
+                self.rx_radio_frame = Some(rx_radio_frame);
+                self.wait_for_next_slot(asn)
+            }
+        }
+    }
+
+    /// The result of a data request whose frame was never sent.
+    pub(crate) fn unsent_data_request_result(
+        msdu_handle: u8,
+        status: DataError,
+        frame: MpduFrame,
+    ) -> DataRequestResult {
+        let radio_frame = frame
+            .into_radio_frame::<RadioDriverImpl>()
+            .forget_size::<RadioDriverImpl>();
+        Self::data_request_result(msdu_handle, Err(status), 0, false, radio_frame, None)
+    }
+
+    fn data_request_result(
+        msdu_handle: u8,
+        status: Result<(), DataError>,
+        retries: u8,
+        ack_requested: bool,
+        radio_frame: RadioFrame<RadioFrameUnsized>,
+        tx_info: Option<TxInfo>,
+    ) -> DataRequestResult {
+        let ack_rx_info = tx_info.and_then(|tx_info| tx_info.ack);
+        let confirm = DataConfirm {
+            msdu_handle,
+            acked: status.is_ok() && ack_requested,
+            status,
+            timestamp: tx_info.and_then(|tx_info| tx_info.timestamp),
+            retries,
+            ack_link_quality: ack_rx_info.map(|rx_info| rx_info.lqi),
+            ack_rssi: ack_rx_info.and_then(|rx_info| rx_info.rssi),
+            ack_frame: None,
+            frame_pending: tx_info.is_some_and(|tx_info| tx_info.ack_frame_pending),
+        };
+        DataRequestResult {
+            confirm,
+            radio_frame,
+            ack_mpdu: tx_info.and_then(|tx_info| tx_info.ack_mpdu),
+        }
+    }
+
+    /// Hands the given result over to the MAC service. Only driver requests
+    /// carry intermediate results, so the radio is switched off, which it
+    /// already is after the slot.
+    fn report(
+        mut self,
+        asn: AbsoluteSlotNumber,
+        result: TschSlotResult,
+    ) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        self.state = TschSlotState::SwitchingOff(asn);
+        MacTaskTransition::DrvSvcRequest(self, Self::off_task(), Some(result))
+    }
+
+    /// Switches the radio off right away. Untimed requests are not served
+    /// while TSCH is enabled.
+    fn off_task() -> DrvSvcRequest {
+        DrvSvcTaskOff {
+            at: Timestamp::Scheduled(RadioDriverImpl::Timer::now().tick()),
+        }
+        .into()
+    }
+
+    fn release_rx_radio_frame(&mut self) {
+        if let Some(rx_radio_frame) = self.rx_radio_frame.take() {
+            // Safety: We allocated the buffer ourselves.
+            unsafe {
+                self.buffer_allocator
+                    .deallocate_buffer(rx_radio_frame.into_buffer());
+            }
+        }
+    }
+
+    fn stop(mut self, result: TschSlotResult) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        self.release_rx_radio_frame();
+        MacTaskTransition::Terminated(result)
+    }
+}
+
+impl<RadioDriverImpl: DriverConfig> MacTask<RadioDriverImpl::Timer>
+    for TschSlotTask<'_, RadioDriverImpl>
+{
+    type Result = TschSlotResult;
+
+    fn step(mut self, event: MacTaskEvent) -> MacTaskTransition<Self, RadioDriverImpl::Timer> {
+        match mem::replace(&mut self.state, TschSlotState::Initial(PhantomData)) {
+            TschSlotState::Initial(_) => {
+                debug_assert!(matches!(event, MacTaskEvent::Entry));
+
+                // End any untimed RX window before the first slot.
+                let asn = self.clock.reference_asn();
+                self.state = TschSlotState::SwitchingOff(asn);
+                MacTaskTransition::DrvSvcRequest(self, Self::off_task(), None)
+            }
+            TschSlotState::SwitchingOff(asn) => {
+                debug_assert!(matches!(
+                    event,
+                    MacTaskEvent::DrvSvcResponse(DrvSvcResponse::Off(_))
+                ));
+                self.wait_for_next_slot(asn)
+            }
+            TschSlotState::Waiting(asn) => {
+                debug_assert!(matches!(event, MacTaskEvent::TimerExpired));
+                self.start_slot(asn)
+            }
+            TschSlotState::Sending(asn, tx_frame) => match event {
+                MacTaskEvent::DrvSvcResponse(response) => {
+                    self.handle_tx_driver_response(asn, tx_frame, response)
+                }
+                // Safety: We issued a Tx task and therefore expect a Tx result.
+                _ => unreachable!(),
+            },
+            TschSlotState::Receiving(asn) => match event {
+                MacTaskEvent::DrvSvcResponse(response) => {
+                    self.handle_rx_driver_response(asn, response)
+                }
+                // Safety: We issued an Rx task and therefore expect an Rx
+                //         result.
+                _ => unreachable!(),
+            },
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Synchronization of TSCH devices to their time source, see IEEE
+//! 802.15.4-2020, section 6.5.4.
+
+use super::asn::AbsoluteSlotNumber;
+
+/// One part per billion.
+const PPB: i64 = 1_000_000_000;
+
+/// The largest drift between the clocks of two devices in parts per billion:
+/// The O-QPSK PHY tolerates clocks deviating by 40 ppm in either direction.
+const TSCH_MAX_DRIFT_PPB: i64 = 80_000;
+
+/// The drift that remains once the drift estimate has settled, e.g. due to
+/// temperature changes, in parts per billion.
+const TSCH_RESIDUAL_DRIFT_PPB: i64 = 10_000;
+
+/// The number of drift samples after which the drift estimate is considered
+/// settled.
+const TSCH_SETTLED_DRIFT_SAMPLES: u8 = 3;
+
+/// The number of slots that have to elapse between two synchronizations to
+/// sample the drift. Shorter intervals are dominated by timestamp jitter.
+const TSCH_MIN_DRIFT_SLOTS: u64 = 100;
+
+/// The local radio clock of a TSCH device relative to the network.
+///
+/// The start of all slots is derived from a reference slot, i.e. the slot in
+/// which the device last synchronized to its time source. The drift of the
+/// local clock is estimated from consecutive synchronizations and compensated
+/// in between. Devices without time source, e.g. the PAN coordinator, keep
+/// their initial reference slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TschClock {
+    /// The ASN of the reference slot.
+    reference_asn: AbsoluteSlotNumber,
+    /// The tick at the start of the reference slot.
+    reference_start: u64,
+    /// The estimated rate at which the slots of the time source lag behind
+    /// the local clock in parts per billion, negative if they are ahead.
+    drift_ppb: i64,
+    /// The number of synchronizations the drift estimate is based on.
+    drift_samples: u8,
+}
+
+impl TschClock {
+    /// Creates a clock on which the slot with the given ASN starts at the
+    /// given tick.
+    pub fn new(reference_asn: AbsoluteSlotNumber, reference_start: u64) -> Self {
+        Self {
+            reference_asn,
+            reference_start,
+            drift_ppb: 0,
+            drift_samples: 0,
+        }
+    }
+
+    /// The ASN of the slot in which the device last synchronized.
+    pub fn reference_asn(&self) -> AbsoluteSlotNumber {
+        self.reference_asn
+    }
+
+    /// The tick at the start of the slot in which the device last
+    /// synchronized.
+    pub fn last_sync(&self) -> u64 {
+        self.reference_start
+    }
+
+    /// The number of slots from the reference slot to the given slot. Slots
+    /// executed by the slot task never precede the reference slot.
+    pub fn slots_since_reference(&self, asn: AbsoluteSlotNumber) -> u64 {
+        asn - self.reference_asn
+    }
+
+    /// The given number of ticks elapsed on the local clock, corrected by the
+    /// estimated drift.
+    fn compensate(&self, elapsed: u64) -> u64 {
+        let drift = (elapsed as i64).saturating_mul(self.drift_ppb) / PPB;
+        elapsed.saturating_add_signed(drift)
+    }
+
+    /// The tick at the start of the slot with the given ASN.
+    pub fn slot_start(&self, asn: AbsoluteSlotNumber, timeslot_length: u64) -> u64 {
+        let elapsed = timeslot_length.saturating_mul(self.slots_since_reference(asn));
+        self.reference_start + self.compensate(elapsed)
+    }
+
+    /// The ASN of the slot ongoing at the given tick.
+    pub fn asn_at(&self, tick: u64, timeslot_length: u64) -> AbsoluteSlotNumber {
+        let elapsed = tick.saturating_sub(self.reference_start) as i128;
+        let uncompensated = elapsed * PPB as i128 / (PPB + self.drift_ppb) as i128;
+        self.reference_asn + uncompensated as u64 / timeslot_length
+    }
+
+    /// Synchronizes the clock to the time source: The slot with the given ASN
+    /// started the given number of ticks after its expected start on the
+    /// local clock. The slot becomes the new reference slot.
+    ///
+    /// Corrections accumulated over sufficiently long intervals update the
+    /// drift estimate, an exponentially weighted moving average.
+    pub fn synchronize(&mut self, asn: AbsoluteSlotNumber, timeslot_length: u64, correction: i64) {
+        let expected_start = self.slot_start(asn, timeslot_length);
+        let elapsed = (expected_start - self.reference_start) as i64;
+        if self.slots_since_reference(asn) >= TSCH_MIN_DRIFT_SLOTS && elapsed > 0 {
+            // The correction is the drift that was not compensated yet.
+            let residual_drift_ppb = correction.saturating_mul(PPB) / elapsed;
+            let weighted_drift_ppb = if self.drift_samples == 0 {
+                residual_drift_ppb
+            } else {
+                residual_drift_ppb / 4
+            };
+            self.drift_ppb = (self.drift_ppb + weighted_drift_ppb)
+                .clamp(-TSCH_MAX_DRIFT_PPB, TSCH_MAX_DRIFT_PPB);
+            self.drift_samples = self.drift_samples.saturating_add(1);
+        }
+
+        self.reference_asn = asn;
+        self.reference_start = expected_start.saturating_add_signed(correction);
+    }
+
+    /// The RX window, in ticks, required to receive a frame sent by the time
+    /// source in the slot with the given ASN: The clocks may have drifted
+    /// apart in either direction since the last synchronization.
+    ///
+    /// The window shrinks with the uncertainty of the drift estimate but never
+    /// drops below the given minimum nor exceeds the given maximum.
+    pub fn guard_time(
+        &self,
+        asn: AbsoluteSlotNumber,
+        timeslot_length: u64,
+        min_guard_time: u64,
+        max_guard_time: u64,
+    ) -> u64 {
+        let uncertainty_ppb = if self.drift_samples >= TSCH_SETTLED_DRIFT_SAMPLES {
+            TSCH_RESIDUAL_DRIFT_PPB
+        } else {
+            TSCH_MAX_DRIFT_PPB
+        };
+        let elapsed = timeslot_length.saturating_mul(self.slots_since_reference(asn));
+        let max_deviation = elapsed.saturating_mul(uncertainty_ppb as u64) / PPB as u64;
+        min_guard_time
+            .saturating_add(2 * max_deviation)
+            .min(max_guard_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10 ms timeslots of a 1 MHz clock.
+    const TIMESLOT_LENGTH: u64 = 10_000;
+
+    #[test]
+    fn slot_boundaries() {
+        let asn = AbsoluteSlotNumber::try_from(1000).unwrap();
+        let clock = TschClock::new(asn, 5_000);
+        assert_eq!(clock.slot_start(asn, TIMESLOT_LENGTH), 5_000);
+        assert_eq!(clock.slot_start(asn + 3, TIMESLOT_LENGTH), 35_000);
+        assert_eq!(clock.asn_at(34_999, TIMESLOT_LENGTH), asn + 2);
+        assert_eq!(clock.asn_at(35_000, TIMESLOT_LENGTH), asn + 3);
+        // Ticks before the reference slot belong to it.
+        assert_eq!(clock.asn_at(0, TIMESLOT_LENGTH), asn);
+    }
+
+    #[test]
+    fn drift_compensation() {
+        let asn = AbsoluteSlotNumber::default();
+        let mut clock = TschClock::new(asn, 0);
+
+        // Short intervals only shift the slots.
+        clock.synchronize(asn + 10, TIMESLOT_LENGTH, 3);
+        assert_eq!(clock.reference_asn(), asn + 10);
+        assert_eq!(clock.last_sync(), 100_003);
+        assert_eq!(clock.drift_ppb, 0);
+
+        // The time source lags 20 ppm behind: 200 ticks over 1000 slots.
+        clock.synchronize(asn + 1010, TIMESLOT_LENGTH, 200);
+        assert_eq!(clock.drift_ppb, 20_000);
+        assert_eq!(clock.last_sync(), 10_100_203);
+
+        // The drift is compensated from now on.
+        assert_eq!(
+            clock.slot_start(asn + 2010, TIMESLOT_LENGTH),
+            10_100_203 + 10_000_000 + 200
+        );
+        assert_eq!(clock.asn_at(20_100_402, TIMESLOT_LENGTH), asn + 2009);
+        assert_eq!(clock.asn_at(20_100_403, TIMESLOT_LENGTH), asn + 2010);
+
+        // Further samples are averaged.
+        clock.synchronize(asn + 2010, TIMESLOT_LENGTH, 40);
+        assert_eq!(clock.drift_ppb, 20_999);
+        clock.synchronize(asn + 3010, TIMESLOT_LENGTH, -40);
+        assert_eq!(clock.drift_ppb, 20_000);
+    }
+
+    #[test]
+    fn guard_time() {
+        let asn = AbsoluteSlotNumber::default();
+        let mut clock = TschClock::new(asn, 0);
+
+        // 80 ppm over 1000 slots in either direction.
+        assert_eq!(
+            clock.guard_time(asn + 1000, TIMESLOT_LENGTH, 100, 2200),
+            1700
+        );
+        assert_eq!(
+            clock.guard_time(asn + 2000, TIMESLOT_LENGTH, 100, 2200),
+            2200
+        );
+        assert_eq!(clock.guard_time(asn, TIMESLOT_LENGTH, 100, 2200), 100);
+
+        // A settled drift estimate shrinks the guard time.
+        for sample in 1..=TSCH_SETTLED_DRIFT_SAMPLES as u64 {
+            clock.synchronize(asn + 1000 * sample, TIMESLOT_LENGTH, 0);
+        }
+        let asn = clock.reference_asn();
+        assert_eq!(
+            clock.guard_time(asn + 1000, TIMESLOT_LENGTH, 100, 2200),
+            300
+        );
+    }
+}